@@ -1,3 +1,12 @@
+use std::{
+  collections::HashMap,
+  sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+  },
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
 use serde::{
   de::{Deserializer, Visitor},
   Deserialize, Serialize,
@@ -5,10 +14,81 @@ use serde::{
 use strum::Display;
 
 use crate::{
-  db::{Feed, FeedItem, Group},
+  catalog::CatalogEntry,
+  db::{
+    Feed, FeedHealth, FeedInfo, FeedItem, FeedPosition, FeedStats, Group, Highlight, SaveSummary,
+    SmartView,
+  },
+  export::{ExportFormat, ExportedArticle},
+  extract::Extracted,
+  fetcher::FetchedFeed,
+  media::Enclosure,
   mode::Mode,
+  refresh::RefreshError,
+  switcher::Destination,
 };
 
+/// The feeds a refresh fetches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RefreshScope {
+  /// Every feed that is not dead.
+  All,
+  /// The feeds in the group with this id and the groups nested in it, leaving out dead ones.
+  Group(i32),
+  /// The feed at this URL, revived if it was dead.
+  Feed(String),
+  /// The feeds not refreshed successfully within their refresh interval, leaving out dead ones.
+  Stale,
+}
+
+impl RefreshScope {
+  /// The scope refreshing `group`, everything for All Feeds and the smart views.
+  pub fn group(group: &Group) -> Self {
+    if group.id < 0 {
+      RefreshScope::All
+    } else {
+      RefreshScope::Group(group.id)
+    }
+  }
+}
+
+/// Identifies a tab for as long as it is open. Unlike its position in the tab bar, it stays the
+/// same as other tabs are opened and closed, so replies to a tab's requests can be addressed to
+/// it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TabId(u32);
+
+impl TabId {
+  /// An id no other tab has had.
+  pub fn next() -> Self {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+    TabId(NEXT.fetch_add(1, Ordering::Relaxed))
+  }
+}
+
+/// An entry of a context menu. Choosing it presses `key` for the view that opened the menu, so
+/// the menu does exactly what the key would.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MenuItem {
+  pub label: String,
+  pub key: KeyEvent,
+}
+
+impl MenuItem {
+  pub fn new(label: &str, code: KeyCode) -> Self {
+    Self { label: label.to_string(), key: KeyEvent::from(code) }
+  }
+
+  /// How the key is shown next to the label.
+  pub fn key_label(&self) -> String {
+    match self.key.code {
+      KeyCode::Char(' ') => "Space".to_string(),
+      KeyCode::Char(c) => c.to_string(),
+      code => format!("{code:?}"),
+    }
+  }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Display, Deserialize)]
 pub enum Action {
   Tick,
@@ -16,27 +96,142 @@ pub enum Action {
   Resize(u16, u16),
   Suspend,
   Resume,
-  ConfirmQuit,
+  /// Asks the question and sends the action if the answer is yes.
+  Confirm(String, Box<Action>),
   Quit,
-  ChangeTab(usize),
-  RemoveTab(usize),
-  RequestRefresh,
+  ChangeTab(TabId),
+  RemoveTab(TabId),
+  RequestRefresh(RefreshScope),
+  CancelRefresh,
+  RefreshStarted,
+  FeedFetched(i32, FetchedFeed),
+  FeedSaved(SaveSummary),
+  FeedFailed(String, RefreshError),
+  RefreshFinished,
+  UpdateUnreadCount(usize),
+  UpdateFeedUnreadCounts(HashMap<i32, usize>),
+  UpdateGroupUnreadCounts(HashMap<i32, usize>),
   Refresh(Vec<Group>),
   NewTabFeedView(Group),
   NewTabArticleViewAll,
+  NewTabArticleViewSmart(SmartView),
   NewTabArticleViewGroup(Group),
-  NewTabArticleViewFeed(Feed),
-  RequestUpdateFeedView(usize, Group),
-  RequestUpdateArticleViewAll(usize),
-  RequestUpdateArticleViewGroup(usize, Group),
-  RequestUpdateArticleViewFeed(usize, Feed),
-  UpdateFeedView(usize, Vec<Feed>),
-  UpdateArticleView(usize, Vec<FeedItem>),
+  NewTabArticleViewFeed(Group, Feed),
+  NewTabStats,
+  NewTabHealth,
+  NewTabHighlights,
+  RequestUpdateFeedView(TabId, Group),
+  RequestUpdateArticleViewAll(TabId),
+  RequestUpdateArticleViewSmart(TabId, SmartView),
+  RequestUpdateArticleViewGroup(TabId, Group),
+  RequestUpdateArticleViewFeed(TabId, Feed),
+  RequestUpdateStats(TabId),
+  RequestUpdateHealth(TabId),
+  RequestUpdateHighlights(TabId),
+  RequestFeedTree,
+  UpdateFeedView(TabId, Vec<Feed>),
+  UpdateFeedTree(Vec<(Group, Vec<Feed>)>),
+  UpdateArticleView(TabId, Arc<[FeedItem]>),
+  UpdateStats(TabId, Vec<FeedStats>),
+  UpdateHealth(TabId, Vec<FeedHealth>),
+  UpdateHighlights(TabId, Vec<Highlight>),
+  SaveFeedPosition(i32, FeedPosition),
+  MarkRead(Vec<i32>),
+  /// Marks every article listed in the tab read.
+  MarkAllRead(TabId),
+  MarkReadFailed(Vec<i32>),
+  RequestUnreadCounts,
+  SetFeedMuted(i32, bool),
+  DeleteFeed(i32),
+  /// Deletes read articles older than `fetch.prune_read_after_days`.
+  PruneArticles,
+  RestoreFeedPosition(TabId, FeedPosition),
   ModeChange(Mode),
-  RequestUpdateReader(usize, FeedItem),
-  UpdateReader(usize, String),
-  ActivateReader,
-  ActivateFeedList,
+  RequestUpdateReader(TabId, FeedItem),
+  /// The text of an article, found in the background for the reader tab or, without one, for
+  /// the viewer waiting on it. `None` when no text could be found.
+  ArticleLoaded(Option<TabId>, FeedItem, Option<Extracted>),
+  UpdateReader(TabId, String),
+  /// Articles related to the one with the given id, for the reader's footer.
+  UpdateRelated(TabId, i32, Vec<FeedItem>),
+  /// Files attached to the article with the given id, for the reader's media pane.
+  UpdateEnclosures(TabId, i32, Vec<Enclosure>),
+  DownloadEnclosure(Enclosure),
+  RequestRevisionDiff(TabId, i32),
+  ShowRevisionDiff(TabId, String, String),
+  ShowExportDialog(ExportedArticle),
+  ExportArticle(ExportedArticle, ExportFormat, String),
+  EmailArticle(ExportedArticle),
+  RequestCatalog,
+  ShowCatalog(Vec<String>),
+  /// Fetches a feed to show what it holds before subscribing to it.
+  PreviewFeed(CatalogEntry),
+  /// The feed fetched for a preview, or why it could not be.
+  ShowFeedPreview(CatalogEntry, Result<FetchedFeed, String>),
+  Subscribe(CatalogEntry),
+  RequestSwitcher,
+  ShowSwitcher(Vec<Destination>),
+  /// Focuses the tab showing the destination, opening one if there is none.
+  OpenDestination(Destination),
+  OpenUrl(String),
+  OpenInViewer(FeedItem),
+  CaptureTask(FeedItem),
+  ShowShareMenu(FeedItem),
+  /// Lists what can be done with a row, titled after it.
+  ShowContextMenu(String, Vec<MenuItem>),
+  /// Handles a key as if it had been pressed, for a context menu entry.
+  PressKey(KeyEvent),
+  /// Asks for what is known about the feed with this id, to show in the info popup.
+  RequestFeedInfo(i32),
+  ShowFeedInfo(FeedInfo),
+  /// Asks for everything stored about the article with this id, to show in the info popup.
+  RequestArticleInfo(i32),
+  ShowArticleInfo(FeedItem),
+  /// Shares the article with the share target at this position in the config.
+  Share(usize, FeedItem),
+  ShowNoteEditor(FeedItem),
+  SaveNote(i32, String),
+  /// The note of an article changed, or was removed when `None`.
+  NoteSaved(i32, Option<String>),
+  /// Summarizes an article from its extracted text.
+  Summarize(FeedItem, String),
+  SaveSummary(i32, String),
+  /// The summary of the article with the given id, stored or just made.
+  SummaryReady(i32, String),
+  SummaryFailed(i32),
+  SaveHighlight(i32, String),
+  DeleteHighlight(i32),
+  ToggleTheme,
+  /// Moves on to the next of normal, high contrast and no colours.
+  CycleColors,
+  ReadAloud(String),
+  StopReadAloud,
+  ReadAloudStarted,
+  ReadAloudFinished,
+  /// Names what is on screen in the terminal's title, or just the app when `None`.
+  SetTerminalTitle(Option<String>),
   Error(String),
+  Notify(String),
   Help,
 }
+
+impl Action {
+  /// The tab the action is addressed to. Such actions are delivered to that tab alone, rather
+  /// than to every component.
+  pub fn tab(&self) -> Option<TabId> {
+    match self {
+      Action::UpdateFeedView(tab, _)
+      | Action::UpdateArticleView(tab, _)
+      | Action::UpdateStats(tab, _)
+      | Action::UpdateHealth(tab, _)
+      | Action::UpdateHighlights(tab, _)
+      | Action::MarkAllRead(tab)
+      | Action::RestoreFeedPosition(tab, _)
+      | Action::UpdateReader(tab, _)
+      | Action::UpdateRelated(tab, ..)
+      | Action::UpdateEnclosures(tab, ..)
+      | Action::ShowRevisionDiff(tab, ..) => Some(*tab),
+      _ => None,
+    }
+  }
+}
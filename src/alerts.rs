@@ -0,0 +1,13 @@
+use crate::db::FeedItem;
+
+/// The first of the watch `keywords` found in the article's title or description, ignoring case.
+pub fn matching<'k>(keywords: &'k [String], item: &FeedItem) -> Option<&'k str> {
+  let title = item.title.to_lowercase();
+  let desc = item.desc.to_lowercase();
+  keywords.iter().map(|keyword| keyword.trim()).filter(|keyword| !keyword.is_empty()).find(
+    |keyword| {
+      let keyword = keyword.to_lowercase();
+      title.contains(&keyword) || desc.contains(&keyword)
+    },
+  )
+}
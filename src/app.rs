@@ -1,34 +1,43 @@
-use std::ops::Index;
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
-use color_eyre::eyre::Result;
-use crossterm::event::KeyEvent;
-use ratatui::{
-  layout::{Constraint, Direction, Layout},
-  prelude::Rect,
-};
-use readability::extractor;
-use reqwest::{Client, Url};
+use color_eyre::eyre::{eyre, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
 use rss::Channel;
-use scraper::{Html, Selector};
-use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use tokio::{
+  sync::mpsc::{self, UnboundedSender},
+  task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-  action::Action,
-  components::{
-    article_list::ArticleList, article_reader::ArticleReader, article_view::ArticleView,
-    info_bar::InfoBar, popup_quit::QuitPopup, tab_bar::TabBar, tab_viewer::TabViewer, Component,
-  },
-  config::Config,
-  db::{Database, DbError},
+  action::{Action, RefreshScope, TabId},
+  cache::ArticleCache,
+  components::{self, Component},
+  config::{Config, StartupRefresh, ThemeVariant},
+  db::{Database, DbWrite, Group},
+  fetcher::Fetcher,
+  i18n::Message,
   mode::Mode,
-  tui,
+  switcher::Destination,
+  tui::{self, Frame},
   utils::get_data_dir,
 };
 
+mod lifecycle;
+mod queries;
+mod reader;
+mod refresh;
+#[cfg(test)]
+mod tests;
+
+/// Position of the tabs or panes among the components, ahead of the bars and popups drawn over it.
+const MAIN_VIEW: usize = 0;
+
 pub struct App {
   pub config: Config,
   pub db: Database,
+  pub fetcher: Arc<Fetcher>,
   pub tick_rate: f64,
   pub frame_rate: f64,
   pub components: Vec<Box<dyn Component>>,
@@ -37,6 +46,21 @@ pub struct App {
   pub mode: Mode,
   pub last_tick_key_events: Vec<KeyEvent>,
   pub feeds: Option<Vec<Channel>>,
+  pub refresh_token: Option<CancellationToken>,
+  pub speech: Option<(CancellationToken, JoinHandle<()>)>,
+  /// Theme variant chosen by hand, overriding the configured mode for the session.
+  pub theme_override: Option<ThemeVariant>,
+  /// Queue of writes made from the UI, applied on a second connection.
+  pub db_writer: Option<UnboundedSender<DbWrite>>,
+  pub article_cache: ArticleCache,
+  /// The article each reader tab last asked for, so text loaded for one it has since left is
+  /// not shown in its place.
+  pub reader_articles: HashMap<TabId, i32>,
+  /// What the terminal's title last named, so it can be named again after a suspend.
+  pub terminal_title: Option<String>,
+  /// Whether anything on screen may have changed since the last frame was drawn.
+  pub dirty: bool,
+  pub last_render: Instant,
 }
 
 impl App {
@@ -45,150 +69,339 @@ impl App {
     let mut db = Database::new(get_data_dir().to_str().unwrap()).await?;
     db.set_config(config.clone());
     db.init().await?;
-    db.refresh_feeds().await?;
-    let tabbar = TabBar::new();
-    let infobar = InfoBar::new();
-    let tab_viewer = TabViewer::new();
-    let quit_popup = QuitPopup::new();
+    let fetcher = Arc::new(Fetcher::new(config.fetch.clone())?);
+    let article_cache =
+      ArticleCache::new(get_data_dir().join("articles"), config.fetch.article_cache_mb * 1_000_000);
+    let mut app = Self::with_parts(config, db, fetcher, article_cache)?;
+    app.tick_rate = tick_rate;
+    app.frame_rate = frame_rate;
+    Ok(app)
+  }
+
+  /// Puts the app together from a database that is ready to use and the fetcher to refresh it
+  /// with, which is how tests hand it a database and feeds of their own.
+  pub fn with_parts(
+    config: Config,
+    db: Database,
+    fetcher: Arc<Fetcher>,
+    article_cache: ArticleCache,
+  ) -> Result<Self> {
+    let mut components = Vec::new();
+    let missing =
+      components::REQUIRED.into_iter().filter(|name| !config.components.iter().any(|c| c == name));
+    for name in [config.layout.main_view()]
+      .into_iter()
+      .chain(config.components.iter().map(String::as_str))
+      .chain(missing)
+    {
+      let component = components::build(name, &config);
+      components.push(component.ok_or_else(|| eyre!("There is no component named {name}"))?);
+    }
     let mode = Mode::Main;
     Ok(Self {
-      tick_rate,
-      frame_rate,
-      components: vec![Box::new(tab_viewer), Box::new(infobar), Box::new(quit_popup)],
+      tick_rate: 4.0,
+      frame_rate: 60.0,
+      components,
       should_quit: false,
       should_suspend: false,
       config,
       db,
+      fetcher,
       mode,
       last_tick_key_events: Vec::new(),
       feeds: None,
+      refresh_token: None,
+      speech: None,
+      theme_override: None,
+      terminal_title: None,
+      dirty: true,
+      last_render: Instant::now(),
+      db_writer: None,
+      article_cache,
+      reader_articles: HashMap::new(),
     })
   }
 
-  pub async fn run(&mut self) -> Result<()> {
-    let (action_tx, mut action_rx) = mpsc::unbounded_channel();
+  /// Opens the articles of the feed or group the config starts on, if any. Feeds are only known
+  /// once they have been fetched.
+  fn open_start_tab(&self, groups: &[Group], tx: &UnboundedSender<Action>) -> Result<()> {
+    if let Some(url) = &self.config.start_feed {
+      let feed = self.db.get_feeds()?.into_iter().find(|feed| &feed.url == url);
+      let group =
+        feed.as_ref().and_then(|feed| groups.iter().find(|group| group.id == feed.group_id));
+      match (group, feed) {
+        (Some(group), Some(feed)) => {
+          let destination = Destination { group: group.clone(), feed: Some(feed), unread: None };
+          tx.send(Action::OpenDestination(destination))?
+        },
+        _ => {
+          tx.send(Action::Error(format!("Cannot start on {url}, it has not been fetched yet")))?
+        },
+      }
+    } else if let Some(name) = &self.config.start_group {
+      match groups.iter().find(|group| &group.name == name) {
+        Some(group) => {
+          let destination = Destination { group: group.clone(), feed: None, unread: None };
+          tx.send(Action::OpenDestination(destination))?
+        },
+        None => {
+          tx.send(Action::Error(format!("Cannot start on {name}, there is no such group")))?
+        },
+      }
+    }
+    Ok(())
+  }
 
-    let mut tui =
-      tui::Tui::new()?.tick_rate(self.tick_rate).frame_rate(self.frame_rate).mouse(true);
-    tui.enter()?;
+  /// Draws every component into `f`. It only needs a frame, so the app can be drawn onto any
+  /// backend, such as ratatui's `TestBackend`, without entering the terminal.
+  pub fn render(&mut self, f: &mut Frame<'_>, action_tx: &UnboundedSender<Action>) {
+    for component in self.components.iter_mut() {
+      if let Err(e) = component.draw(f, f.size()) {
+        let _ = action_tx.send(Action::Error(format!("Failed to draw: {:?}", e)));
+      }
+    }
+  }
+
+  /// Carries out an action that the app itself is responsible for, before the components see it,
+  /// by handing it to the handler for its family.
+  async fn handle_action(
+    &mut self,
+    action: Action,
+    tui: Option<&mut tui::Tui>,
+    action_tx: &UnboundedSender<Action>,
+  ) -> Result<()> {
+    match action {
+      Action::Tick
+      | Action::ToggleTheme
+      | Action::CycleColors
+      | Action::Quit
+      | Action::Suspend
+      | Action::Resume
+      | Action::Resize(..)
+      | Action::Render
+      | Action::OpenUrl(_)
+      | Action::PressKey(_)
+      | Action::SetTerminalTitle(_) => self.handle_lifecycle(action, tui, action_tx),
+      Action::RequestRefresh(_)
+      | Action::CancelRefresh
+      | Action::FeedFetched(..)
+      | Action::FeedFailed(..)
+      | Action::RefreshFinished
+      | Action::PreviewFeed(_)
+      | Action::Subscribe(_) => self.handle_refresh(action, action_tx),
+      Action::RequestUpdateFeedView(..)
+      | Action::RequestFeedTree
+      | Action::RequestUpdateArticleViewSmart(..)
+      | Action::RequestUpdateArticleViewAll(_)
+      | Action::RequestUpdateArticleViewFeed(..)
+      | Action::RequestUpdateArticleViewGroup(..)
+      | Action::RequestUpdateHealth(_)
+      | Action::RequestUpdateStats(_)
+      | Action::RequestUpdateHighlights(_)
+      | Action::RequestRevisionDiff(..)
+      | Action::MarkRead(_)
+      | Action::RequestUnreadCounts
+      | Action::SetFeedMuted(..)
+      | Action::DeleteFeed(_)
+      | Action::PruneArticles
+      | Action::SaveFeedPosition(..)
+      | Action::SaveNote(..)
+      | Action::SaveSummary(..)
+      | Action::SaveHighlight(..)
+      | Action::DeleteHighlight(_)
+      | Action::RequestCatalog
+      | Action::RequestSwitcher
+      | Action::RequestFeedInfo(_)
+      | Action::RequestArticleInfo(_) => self.handle_query(action, action_tx),
+      Action::RequestUpdateReader(..)
+      | Action::ArticleLoaded(..)
+      | Action::OpenInViewer(_)
+      | Action::CaptureTask(_)
+      | Action::Share(..)
+      | Action::DownloadEnclosure(_)
+      | Action::Summarize(..)
+      | Action::ExportArticle(..)
+      | Action::EmailArticle(_)
+      | Action::ReadAloud(_)
+      | Action::StopReadAloud => self.handle_reader(action, tui, action_tx).await,
+      _ => Ok(()),
+    }
+  }
 
+  /// Hands the components the action sender and the config, then sizes them to the screen.
+  pub fn register_components(
+    &mut self,
+    action_tx: &UnboundedSender<Action>,
+    size: Rect,
+  ) -> Result<()> {
     for component in self.components.iter_mut() {
       component.register_action_handler(action_tx.clone())?;
     }
 
+    self.config.theme.variant = self.config.theme.preferred_variant(chrono::Local::now().time());
     for component in self.components.iter_mut() {
       component.register_config_handler(self.config.clone())?;
     }
 
     for component in self.components.iter_mut() {
-      component.init(tui.size()?)?;
+      component.init(size)?;
     }
+    Ok(())
+  }
 
+  /// Applies the writes made from the UI on `writer`, a second connection to the database.
+  pub fn start_db_writer(&mut self, writer: Database, action_tx: &UnboundedSender<Action>) {
+    let writer_tx = action_tx.clone();
+    self.db_writer = Some(writer.spawn_writer(move |write, result| {
+      let actions = match (write, result) {
+        (_, Ok(())) => vec![Action::RequestUnreadCounts],
+        (DbWrite::MarkRead(item_ids), Err(error)) => {
+          vec![
+            Action::MarkReadFailed(item_ids),
+            Action::Error(format!("Failed to mark articles read: {error}")),
+          ]
+        },
+      };
+      for action in actions {
+        let _ = writer_tx.send(action);
+      }
+    }));
+  }
+
+  /// Shows the stored groups and starts the refresh and tab the config asks for at launch.
+  pub fn start(&mut self, action_tx: &UnboundedSender<Action>) -> Result<()> {
+    // Feeds taken out of the config go before anything is shown, even without a refresh.
+    self.sync_config(action_tx)?;
     let groups = self.db.get_groups()?;
-    action_tx.send(Action::Refresh(groups))?;
+    action_tx.send(Action::Refresh(groups.clone()))?;
+    self.send_unread_counts(action_tx)?;
+    match self.config.fetch.on_startup {
+      StartupRefresh::Never => {},
+      StartupRefresh::Always => self.start_refresh(action_tx.clone(), RefreshScope::All)?,
+      StartupRefresh::StaleOnly => self.start_refresh(action_tx.clone(), RefreshScope::Stale)?,
+    }
+    self.open_start_tab(&groups, action_tx)
+  }
 
-    loop {
-      if let Some(e) = tui.next().await {
-        match e {
-          tui::Event::Quit => action_tx.send(Action::Quit)?,
-          tui::Event::Tick => action_tx.send(Action::Tick)?,
-          tui::Event::Render => action_tx.send(Action::Render)?,
-          tui::Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
-          tui::Event::Key(key) => {
-            if key.code == crossterm::event::KeyCode::Char('q') {
-              action_tx.send(Action::ConfirmQuit)?;
-            }
-          },
-
-          _ => {},
-        }
-        for component in self.components.iter_mut() {
-          if let Some(action) = component.handle_events(Some(e.clone()))? {
-            action_tx.send(action)?;
+  /// Hands `action` to the app and then to every component, queueing what they send back. Without
+  /// a terminal, as in tests, whatever needs one is left undone.
+  pub async fn dispatch(
+    &mut self,
+    action: Action,
+    tui: Option<&mut tui::Tui>,
+    action_tx: &UnboundedSender<Action>,
+  ) -> Result<()> {
+    if action != Action::Tick && action != Action::Render {
+      log::debug!("{action:?}");
+      self.dirty = true;
+    }
+    if action.tab().is_some() {
+      // Only the main view holds tabs, so it alone is handed what is addressed to one.
+      if let Some(action) = self.components[MAIN_VIEW].update(action)? {
+        action_tx.send(action)?
+      }
+      return Ok(());
+    }
+    if let Err(error) = self.handle_action(action.clone(), tui, action_tx).await {
+      log::error!("Failed to handle {action}: {error:?}");
+      action_tx.send(Action::Error(error.to_string()))?;
+    }
+    for component in self.components.iter_mut() {
+      if let Some(action) = component.update(action.clone())? {
+        action_tx.send(action)?
+      };
+    }
+    Ok(())
+  }
+
+  /// Turns a terminal event into actions, first the app-wide keys and then whatever the
+  /// components make of it. A popup taking input gets the event to itself.
+  pub fn handle_event(&mut self, e: tui::Event, action_tx: &UnboundedSender<Action>) -> Result<()> {
+    if !matches!(e, tui::Event::Tick | tui::Event::Render) {
+      // Components change state on keys and the mouse without always sending an action.
+      self.dirty = true;
+    }
+    let capturing_input = self.components.iter().any(|component| component.captures_input());
+    match e {
+      tui::Event::Quit => action_tx.send(Action::Quit)?,
+      tui::Event::Tick => action_tx.send(Action::Tick)?,
+      tui::Event::Render => action_tx.send(Action::Render)?,
+      tui::Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
+      tui::Event::Key(key) if !capturing_input => {
+        if key.code == KeyCode::Char('q') {
+          if self.config.confirm_quit {
+            let prompt = self.config.text(Message::QuitPrompt).to_string();
+            action_tx.send(Action::Confirm(prompt, Box::new(Action::Quit)))?;
+          } else {
+            action_tx.send(Action::Quit)?;
           }
+        } else if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+          action_tx.send(Action::CancelRefresh)?;
+        } else if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+          action_tx.send(Action::Suspend)?;
+        } else if key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL) {
+          action_tx.send(Action::RequestSwitcher)?;
+        } else if key.code == KeyCode::Char('R') {
+          action_tx.send(Action::RequestRefresh(RefreshScope::All))?;
+        } else if key.code == KeyCode::Char('C') {
+          action_tx.send(Action::RequestCatalog)?;
+        } else if key.code == KeyCode::Char('T') {
+          action_tx.send(Action::ToggleTheme)?;
+        } else if key.code == KeyCode::Char('t') && key.modifiers.contains(KeyModifiers::CONTROL) {
+          action_tx.send(Action::CycleColors)?;
         }
+      },
+
+      _ => {},
+    }
+    for component in self.components.iter_mut() {
+      if capturing_input && !component.captures_input() {
+        continue;
+      }
+      if let Some(action) = component.handle_events(Some(e.clone()))? {
+        action_tx.send(action)?;
+      }
+    }
+    Ok(())
+  }
+
+  pub async fn run(&mut self) -> Result<()> {
+    let (action_tx, mut action_rx) = mpsc::unbounded_channel();
+
+    let mut tui = tui::Tui::new()?
+      .tick_rate(self.tick_rate)
+      .frame_rate(self.frame_rate)
+      .mouse(true)
+      .paste(true);
+    tui.enter()?;
+    self.save_terminal_title()?;
+
+    self.register_components(&action_tx, tui.size()?)?;
+    self.start_db_writer(Database::new(get_data_dir().to_str().unwrap()).await?, &action_tx);
+    self.start(&action_tx)?;
+
+    loop {
+      if let Some(e) = tui.next().await {
+        self.handle_event(e, &action_tx)?;
       }
 
       while let Ok(action) = action_rx.try_recv() {
-        if action != Action::Tick && action != Action::Render {
-          log::debug!("{action:?}");
-        }
-        match action {
-          Action::Tick => {
-            self.last_tick_key_events.drain(..);
-          },
-          Action::Quit => self.should_quit = true,
-          Action::Suspend => self.should_suspend = true,
-          Action::Resume => self.should_suspend = false,
-          Action::Resize(w, h) => {
-            tui.resize(Rect::new(0, 0, w, h))?;
-            tui.draw(|f| {
-              for component in self.components.iter_mut() {
-                let r = component.draw(f, f.size());
-                if let Err(e) = r {
-                  action_tx.send(Action::Error(format!("Failed to draw: {:?}", e))).unwrap();
-                }
-              }
-            })?;
-          },
-          Action::Render => {
-            tui.draw(|f| {
-              for component in self.components.iter_mut() {
-                let r = component.draw(f, f.size());
-                if let Err(e) = r {
-                  action_tx.send(Action::Error(format!("Failed to draw: {:?}", e))).unwrap();
-                }
-              }
-            })?;
-          },
-          Action::RequestUpdateFeedView(idx, ref group) => {
-            let feeds = self.db.get_feeds_from_group(group.id)?;
-            action_tx.send(Action::UpdateFeedView(idx, feeds))?;
-          },
-          Action::RequestUpdateArticleViewAll(idx) => {
-            let feed_items = self.db.get_feed_items()?;
-            action_tx.send(Action::UpdateArticleView(idx, feed_items))?;
-          },
-          Action::RequestUpdateArticleViewFeed(idx, ref feed) => {
-            let feed_items = self.db.get_feed_items_from_feed(feed.id)?;
-            log::info!("Sending UpdateArticleViewFeed");
-            action_tx.send(Action::UpdateArticleView(idx, feed_items))?;
-          },
-          Action::RequestUpdateArticleViewGroup(idx, ref group) => {
-            let feed_items = self.db.get_feed_items_from_group(group.id)?;
-            action_tx.send(Action::UpdateArticleView(idx, feed_items))?;
-          },
-          Action::Refresh(_) => {},
-          Action::RequestUpdateReader(idx, ref feed_item) => {
-            let link = feed_item.url.clone();
-            let result = tokio::task::spawn_blocking(move || extractor::scrape(&link)).await?;
-
-            match result {
-              Ok(product) => {
-                action_tx.send(Action::UpdateReader(idx, product.content))?;
-              },
-              Err(_) => log::error!("Failed to display post."),
-            }
-          },
-          _ => {},
-        }
-        for component in self.components.iter_mut() {
-          if let Some(action) = component.update(action.clone())? {
-            action_tx.send(action)?
-          };
-        }
+        self.dispatch(action, Some(&mut tui), &action_tx).await?;
       }
       if self.should_suspend {
+        // Refreshes and other tasks keep their handles and channels, so they carry on after the
+        // stop, and the same Tui comes back with the mouse and rates it was built with.
+        self.restore_terminal_title()?;
         tui.suspend()?;
+        tui.resume()?;
+        self.save_terminal_title()?;
         action_tx.send(Action::Resume)?;
-        tui = tui::Tui::new()?.tick_rate(self.tick_rate).frame_rate(self.frame_rate);
-        // tui.mouse(true);
-        tui.enter()?;
       } else if self.should_quit {
         tui.stop()?;
         break;
       }
     }
+    self.restore_terminal_title()?;
     tui.exit()?;
     Ok(())
   }
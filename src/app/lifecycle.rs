@@ -0,0 +1,213 @@
+use std::{
+  io::Write,
+  time::{Duration, Instant},
+};
+
+use color_eyre::eyre::Result;
+use crossterm::terminal::SetTitle;
+use ratatui::{
+  backend::Backend,
+  buffer::{Buffer, Cell},
+  prelude::Rect,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::App;
+use crate::{action::Action, browser, i18n::Message, tui};
+
+/// Longest the screen goes without a redraw while idle, so relative dates keep up.
+const IDLE_REDRAW: Duration = Duration::from_secs(60);
+
+impl App {
+  /// Handles the actions that drive the app itself: ticks, drawing, suspending and quitting.
+  pub(super) fn handle_lifecycle(
+    &mut self,
+    action: Action,
+    tui: Option<&mut tui::Tui>,
+    tx: &UnboundedSender<Action>,
+  ) -> Result<()> {
+    match action {
+      Action::Tick => {
+        self.last_tick_key_events.drain(..);
+        self.update_theme()?;
+        if self.last_render.elapsed() >= IDLE_REDRAW
+          || self.components.iter().any(|component| component.is_dirty())
+        {
+          self.dirty = true;
+        }
+      },
+      Action::ToggleTheme => {
+        self.theme_override = Some(self.config.theme.variant.toggled());
+        self.update_theme()?;
+      },
+      Action::CycleColors => {
+        self.config.theme.colors = self.config.theme.colors.next();
+        self.share_config()?;
+      },
+      Action::Quit => self.should_quit = true,
+      Action::Suspend => self.should_suspend = true,
+      Action::Resume => {
+        self.should_suspend = false;
+        self.set_terminal_title(self.terminal_title.clone().as_deref())?;
+        // The shell drew over the screen, so it is painted whole rather than diffed against the
+        // last frame.
+        if let Some(tui) = tui {
+          tui.clear()?;
+          self.draw(tui, tx)?;
+        }
+      },
+      Action::Resize(w, h) => {
+        if let Some(tui) = tui {
+          tui.resize(Rect::new(0, 0, w, h))?;
+          self.draw(tui, tx)?;
+        }
+      },
+      // Frames only come at the frame rate while something changes, so an idle app stays asleep.
+      Action::Render if self.dirty => {
+        if let Some(tui) = tui {
+          self.draw(tui, tx)?;
+        }
+      },
+      Action::OpenUrl(url) => {
+        if let Err(error) = browser::open(&url) {
+          tx.send(Action::Error(self.config.format(Message::OpenFailed, &[&url, &error])))?;
+        }
+      },
+      Action::PressKey(key) => {
+        for component in self.components.iter_mut() {
+          if let Some(action) = component.handle_events(Some(tui::Event::Key(key)))? {
+            tx.send(action)?;
+          }
+        }
+      },
+      Action::SetTerminalTitle(context) => {
+        self.set_terminal_title(context.as_deref())?;
+        self.terminal_title = context;
+      },
+      _ => {},
+    }
+    Ok(())
+  }
+
+  fn draw(&mut self, tui: &mut tui::Tui, tx: &UnboundedSender<Action>) -> Result<()> {
+    let frame = tui.draw(|f| self.render(f, tx))?;
+    // Popups may cover the links, so they are only marked with none open.
+    let hyperlinks = self.config.display.hyperlinks == Some(true);
+    if hyperlinks && !self.components.iter().any(|c| c.captures_input()) {
+      let links: Vec<_> = self.components.iter().flat_map(|c| c.hyperlinks()).collect();
+      let cells = hyperlink_cells(frame.buffer, &links);
+      if !cells.is_empty() {
+        let backend = tui.backend_mut();
+        backend.draw(cells.iter().map(|(x, y, cell)| (*x, *y, cell)))?;
+        Backend::flush(backend)?;
+      }
+    }
+    self.dirty = false;
+    self.last_render = Instant::now();
+    Ok(())
+  }
+
+  /// Names what is on screen in the title of the terminal window, followed by the app's name.
+  fn set_terminal_title(&self, context: Option<&str>) -> Result<()> {
+    if !self.config.display.terminal_title {
+      return Ok(());
+    }
+    crossterm::execute!(tui::io(), SetTitle(terminal_title(context)))?;
+    Ok(())
+  }
+
+  /// Pushes the terminal's own title onto the xterm title stack, so it can be put back on exit.
+  /// Terminals without a title stack ignore the sequence.
+  pub(super) fn save_terminal_title(&self) -> Result<()> {
+    if self.config.display.terminal_title {
+      write!(tui::io(), "\x1b[22;0t")?;
+      self.set_terminal_title(None)?;
+    }
+    Ok(())
+  }
+
+  pub(super) fn restore_terminal_title(&self) -> Result<()> {
+    if self.config.display.terminal_title {
+      let mut io = tui::io();
+      write!(io, "\x1b[23;0t")?;
+      io.flush()?;
+    }
+    Ok(())
+  }
+
+  /// Switches to the theme variant the config asks for now, unless it was toggled by hand, and
+  /// hands the components the config with the new palette.
+  fn update_theme(&mut self) -> Result<()> {
+    let variant = self
+      .theme_override
+      .unwrap_or_else(|| self.config.theme.preferred_variant(chrono::Local::now().time()));
+    if variant != self.config.theme.variant {
+      self.config.theme.variant = variant;
+      self.share_config()?;
+    }
+    Ok(())
+  }
+
+  /// Hands the changed config to every component and redraws with it.
+  fn share_config(&mut self) -> Result<()> {
+    self.dirty = true;
+    for component in self.components.iter_mut() {
+      component.register_config_handler(self.config.clone())?;
+    }
+    Ok(())
+  }
+}
+
+/// The cells of `links` as drawn in `buffer`, each wrapped in the OSC 8 escapes that make it part
+/// of a terminal hyperlink. Drawing them again over the frame turns the links on.
+fn hyperlink_cells(buffer: &Buffer, links: &[(Rect, String)]) -> Vec<(u16, u16, Cell)> {
+  let mut cells = Vec::new();
+  // The cells of a link share an id, so terminals underline it whole under the mouse.
+  for (id, (area, url)) in links.iter().enumerate() {
+    let area = area.intersection(buffer.area);
+    for y in area.top()..area.bottom() {
+      for x in area.left()..area.right() {
+        let mut cell = buffer.get(x, y).clone();
+        // The second half of a wide character is drawn along with the first.
+        if cell.symbol().is_empty() {
+          continue;
+        }
+        let symbol = format!("\x1b]8;id={id};{url}\x1b\\{}\x1b]8;;\x1b\\", cell.symbol());
+        cell.set_symbol(&symbol);
+        cells.push((x, y, cell));
+      }
+    }
+  }
+  cells
+}
+
+/// The title naming `context`, which comes from feed and article names, followed by the app's
+/// name. Control characters are left out, as an escape or bell in a feed's title would end the
+/// title sequence early and have the rest run as terminal commands.
+fn terminal_title(context: Option<&str>) -> String {
+  let app = env!("CARGO_PKG_NAME");
+  match context {
+    Some(context) => {
+      let context: String = context.chars().filter(|c| !c.is_control()).collect();
+      format!("{context} — {app}")
+    },
+    None => app.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn leaves_control_characters_out_of_the_title() {
+    let app = env!("CARGO_PKG_NAME");
+    assert_eq!(terminal_title(None), app);
+    assert_eq!(
+      terminal_title(Some("News\x07\x1b]0;pwned\x1b\\\n")),
+      format!("News]0;pwned\\ — {app}")
+    );
+  }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+
+use color_eyre::eyre::Result;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::App;
+use crate::{
+  action::{Action, TabId},
+  components::stats_view::STATS_WEEKS,
+  db::{DbWrite, FeedItem, SmartView},
+  switcher, utils,
+};
+
+impl App {
+  /// Handles the actions that read from or write to the database on behalf of a view.
+  pub(super) fn handle_query(
+    &mut self,
+    action: Action,
+    tx: &UnboundedSender<Action>,
+  ) -> Result<()> {
+    match action {
+      Action::RequestUpdateFeedView(tab, group) => {
+        let feeds = self.db.get_feeds_from_group(group.id)?;
+        tx.send(Action::UpdateFeedView(tab, feeds))?;
+      },
+      Action::RequestFeedTree => {
+        let mut tree = Vec::new();
+        for group in self.db.get_groups()? {
+          let feeds = if group.id < 0 {
+            Vec::new()
+          } else {
+            // Leave out the "All Feeds" entry, the group itself stands for it.
+            let mut feeds = self.db.get_feeds_from_group(group.id)?;
+            feeds.retain(|feed| feed.id != -1);
+            feeds
+          };
+          tree.push((group, feeds));
+        }
+        tx.send(Action::UpdateFeedTree(tree))?;
+      },
+      Action::RequestUpdateArticleViewSmart(tab, view) => {
+        let feed_items = match (view, view.bounds(chrono::Local::now())) {
+          (_, Some((start, end))) => self.db.get_feed_items_between(start, end)?,
+          (SmartView::Alerts, None) => self.db.get_alerts()?,
+          (SmartView::Archive, None) => self.db.get_archived()?,
+          (_, None) => self.db.get_history()?,
+        };
+        self.send_article_view(tab, feed_items, tx)?;
+      },
+      Action::RequestUpdateArticleViewAll(tab) => {
+        let feed_items = self.db.get_feed_items()?;
+        self.send_article_view(tab, feed_items, tx)?;
+      },
+      Action::RequestUpdateArticleViewFeed(tab, feed) => {
+        let feed_items = self.db.get_feed_items_from_feed(feed.id)?;
+        log::info!("Sending UpdateArticleViewFeed");
+        self.send_article_view(tab, feed_items, tx)?;
+        if let Some(position) = self.db.get_feed_position(feed.id)? {
+          tx.send(Action::RestoreFeedPosition(tab, position))?;
+        }
+      },
+      Action::RequestUpdateArticleViewGroup(tab, group) => {
+        let feed_items = self.db.get_feed_items_from_group(group.id)?;
+        self.send_article_view(tab, feed_items, tx)?;
+      },
+      Action::RequestUpdateHealth(tab) => {
+        tx.send(Action::UpdateHealth(tab, self.db.get_feed_health()?))?;
+      },
+      Action::RequestUpdateStats(tab) => {
+        let stats = self.db.get_feed_stats(STATS_WEEKS)?;
+        tx.send(Action::UpdateStats(tab, stats))?;
+      },
+      Action::RequestUpdateHighlights(tab) => {
+        tx.send(Action::UpdateHighlights(tab, self.db.get_highlights()?))?;
+      },
+      Action::RequestRevisionDiff(tab, item_id) => {
+        let revisions = self.db.get_item_revisions(item_id)?;
+        match revisions.as_slice() {
+          [current, previous, ..] => {
+            tx.send(Action::ShowRevisionDiff(
+              tab,
+              previous.body().to_string(),
+              current.body().to_string(),
+            ))?;
+          },
+          _ => tx.send(Action::Error("This article has not been revised".to_string()))?,
+        }
+      },
+      Action::MarkRead(item_ids) => {
+        // The list already shows the articles as read, and is told if the write fails.
+        if let Some(writer) = &self.db_writer {
+          writer.send(DbWrite::MarkRead(item_ids))?;
+        }
+      },
+      Action::RequestUnreadCounts => self.send_unread_counts(tx)?,
+      Action::SetFeedMuted(feed_id, muted) => {
+        self.db.set_feed_muted(feed_id, muted)?;
+        self.send_unread_counts(tx)?;
+      },
+      Action::DeleteFeed(feed_id) => {
+        self.db.delete_feed(feed_id)?;
+        tx.send(Action::Refresh(self.db.get_groups()?))?;
+        self.send_unread_counts(tx)?;
+      },
+      Action::PruneArticles => {
+        let days = self.config.fetch.prune_read_after_days;
+        let pruned = match utils::days_ago(days) {
+          Some(cutoff) => self.db.prune_read_items(cutoff)?,
+          None => 0,
+        };
+        tx.send(Action::Notify(format!("Deleted {pruned} read articles older than {days} days")))?;
+      },
+      Action::SaveFeedPosition(feed_id, position) => {
+        self.db.save_feed_position(feed_id, position)?;
+      },
+      Action::SaveNote(item_id, note) => {
+        self.db.save_note(item_id, &note)?;
+        let note = Some(note).filter(|note| !note.trim().is_empty());
+        tx.send(Action::NoteSaved(item_id, note))?;
+      },
+      Action::SaveSummary(item_id, summary) => {
+        self.db.save_summary(item_id, &summary)?;
+        tx.send(Action::SummaryReady(item_id, summary))?;
+      },
+      Action::SaveHighlight(item_id, text) => {
+        self.db.save_highlight(item_id, &text)?;
+        let lines = text.lines().count();
+        tx.send(Action::Notify(format!(
+          "Saved a highlight of {lines} line{}",
+          if lines == 1 { "" } else { "s" }
+        )))?;
+      },
+      Action::DeleteHighlight(id) => self.db.delete_highlight(id)?,
+      Action::RequestCatalog => {
+        let mut subscribed: Vec<String> = self
+          .config
+          .all_groups()
+          .into_iter()
+          .flat_map(|group| group.feeds.iter().map(|feed| feed.link.clone()))
+          .collect();
+        subscribed.extend(self.db.get_subscriptions()?.into_iter().map(|(_, feed)| feed.link));
+        tx.send(Action::ShowCatalog(subscribed))?;
+      },
+      Action::RequestSwitcher => {
+        tx.send(Action::ShowSwitcher(switcher::destinations(&self.db)?))?;
+      },
+      Action::RequestFeedInfo(feed_id) => {
+        if let Some(feed) = self.db.get_feeds()?.into_iter().find(|feed| feed.id == feed_id) {
+          tx.send(Action::ShowFeedInfo(self.db.get_feed_info(feed)?))?;
+        }
+      },
+      Action::RequestArticleInfo(item_id) => {
+        if let Some(item) = self.db.get_article_info(item_id)? {
+          tx.send(Action::ShowArticleInfo(item))?;
+        }
+      },
+      _ => {},
+    }
+    Ok(())
+  }
+
+  /// Sends the articles for `tab`. Their feeds count as viewed from now on, so the articles
+  /// marked new stay marked only until the next visit.
+  fn send_article_view(
+    &self,
+    tab: TabId,
+    feed_items: Vec<FeedItem>,
+    tx: &UnboundedSender<Action>,
+  ) -> Result<()> {
+    let feed_ids: HashSet<i32> = feed_items.iter().map(|item| item.feed_id).collect();
+    self.db.mark_feeds_viewed(&feed_ids)?;
+    tx.send(Action::UpdateArticleView(tab, feed_items.into()))?;
+    Ok(())
+  }
+
+  pub(super) fn send_unread_counts(&self, tx: &UnboundedSender<Action>) -> Result<()> {
+    tx.send(Action::UpdateUnreadCount(self.db.count_unread()?))?;
+    tx.send(Action::UpdateFeedUnreadCounts(self.db.count_unread_by_feed()?))?;
+    tx.send(Action::UpdateGroupUnreadCounts(self.db.count_unread_by_group()?))?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::Utc;
+  use pretty_assertions::assert_eq;
+  use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+  use super::*;
+  use crate::{
+    db::{Database, Feed, FeedPosition, Group},
+    testing,
+  };
+
+  /// The app around an in-memory database holding a feed with two unread articles, along with
+  /// the ids of the feed and of its articles.
+  async fn app() -> (App, i32, Vec<i32>) {
+    let db = Database::in_memory().await.unwrap();
+    let group = Group { id: 0, name: "News".to_string(), desc: String::new(), parent_id: None };
+    let group_id = db.upsert_group(group).unwrap();
+    let feed = Feed {
+      id: 0,
+      group_id,
+      name: "Feed".to_string(),
+      desc: String::new(),
+      url: "https://example.com/feed".to_string(),
+      moved_from: None,
+      updated_at: Utc::now(),
+      muted: false,
+      dead: false,
+      cadence: None,
+    };
+    let (feed_id, _) = db.upsert_feed(feed).unwrap();
+    for slug in ["first", "second"] {
+      db.upsert_feed_item(FeedItem {
+        feed_id,
+        title: slug.to_string(),
+        url: format!("https://example.com/{slug}"),
+        pub_date: Utc::now(),
+        ..Default::default()
+      })
+      .unwrap();
+    }
+    let item_ids =
+      db.get_feed_items_from_feed(feed_id).unwrap().iter().map(|item| item.id).collect();
+    (testing::app(db), feed_id, item_ids)
+  }
+
+  fn sent(rx: &mut UnboundedReceiver<Action>) -> Vec<Action> {
+    std::iter::from_fn(|| rx.try_recv().ok()).collect()
+  }
+
+  #[tokio::test]
+  async fn muting_a_feed_takes_it_out_of_the_unread_counts() {
+    let (mut app, feed_id, _) = app().await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    app.handle_query(Action::RequestUnreadCounts, &tx).unwrap();
+    assert_eq!(sent(&mut rx)[0], Action::UpdateUnreadCount(2));
+
+    app.handle_query(Action::SetFeedMuted(feed_id, true), &tx).unwrap();
+    assert_eq!(sent(&mut rx)[0], Action::UpdateUnreadCount(0));
+    assert!(app.db.get_feeds().unwrap()[0].muted);
+  }
+
+  #[tokio::test]
+  async fn reopening_a_feed_restores_where_its_list_was_left() {
+    let (mut app, feed_id, item_ids) = app().await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let position = FeedPosition { item_id: item_ids[1], list_offset: 1 };
+    app.handle_query(Action::SaveFeedPosition(feed_id, position), &tx).unwrap();
+    let feed = app.db.get_feeds().unwrap().remove(0);
+    let tab = TabId::next();
+    app.handle_query(Action::RequestUpdateArticleViewFeed(tab, feed), &tx).unwrap();
+
+    let actions = sent(&mut rx);
+    assert_eq!(actions.len(), 2);
+    let Action::UpdateArticleView(view_tab, items) = &actions[0] else {
+      panic!("expected the articles, got {actions:?}");
+    };
+    assert_eq!(*view_tab, tab);
+    assert_eq!(items.iter().map(|item| item.id).collect::<Vec<_>>(), item_ids);
+    assert_eq!(actions[1], Action::RestoreFeedPosition(tab, position));
+  }
+
+  #[tokio::test]
+  async fn a_blank_note_is_reported_removed() {
+    let (mut app, _, item_ids) = app().await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    app.handle_query(Action::SaveNote(item_ids[0], "Worth a reread".to_string()), &tx).unwrap();
+    app.handle_query(Action::SaveNote(item_ids[0], "  ".to_string()), &tx).unwrap();
+    assert_eq!(sent(&mut rx), vec![
+      Action::NoteSaved(item_ids[0], Some("Worth a reread".to_string())),
+      Action::NoteSaved(item_ids[0], None),
+    ]);
+  }
+
+  #[tokio::test]
+  async fn deleting_a_feed_refreshes_the_groups_and_counts() {
+    let (mut app, feed_id, _) = app().await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    app.handle_query(Action::DeleteFeed(feed_id), &tx).unwrap();
+
+    assert!(app.db.get_feeds().unwrap().is_empty());
+    let actions = sent(&mut rx);
+    assert_eq!(actions[0], Action::Refresh(app.db.get_groups().unwrap()));
+    assert_eq!(actions[1], Action::UpdateUnreadCount(0));
+  }
+}
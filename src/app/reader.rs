@@ -0,0 +1,307 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre::Result;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
+
+use super::App;
+use crate::{
+  action::{Action, TabId},
+  browser,
+  components::article_view::RELATED_ITEMS,
+  db::FeedItem,
+  email,
+  export::{self, ExportedArticle},
+  extract, html,
+  i18n::Message,
+  media, speech, summary, task, tui,
+  utils::get_data_dir,
+  viewer,
+};
+
+impl App {
+  /// Handles the actions on a single article: fetching it for the reader and handing it to the
+  /// viewer, exports, email and speech.
+  pub(super) async fn handle_reader(
+    &mut self,
+    action: Action,
+    tui: Option<&mut tui::Tui>,
+    tx: &UnboundedSender<Action>,
+  ) -> Result<()> {
+    match action {
+      Action::RequestUpdateReader(tab, feed_item) => {
+        self.db.record_opened(feed_item.id)?;
+        let related = self.db.get_related_items(feed_item.id, RELATED_ITEMS)?;
+        tx.send(Action::UpdateRelated(tab, feed_item.id, related))?;
+        if let Some(summary) = self.db.get_summary(feed_item.id)? {
+          tx.send(Action::SummaryReady(feed_item.id, summary))?;
+        }
+        let enclosures = self.db.get_enclosures(feed_item.id)?;
+        tx.send(Action::UpdateEnclosures(tab, feed_item.id, enclosures))?;
+        self.reader_articles.insert(tab, feed_item.id);
+        match self.article_cache.get(&feed_item.url) {
+          Some(content) => tx.send(Action::UpdateReader(tab, content))?,
+          None => self.load_article(Some(tab), feed_item, tx)?,
+        }
+      },
+      Action::ArticleLoaded(tab, feed_item, extracted) => {
+        let Some(extracted) = extracted else {
+          tx.send(Action::Error("Failed to display post".to_string()))?;
+          return Ok(());
+        };
+        let link = self.config.feed_link(&feed_item.feed_url, feed_item.feed_moved_from.as_deref());
+        self.db.record_extraction(link, extracted.extraction)?;
+        if let Err(error) = self.article_cache.put(&feed_item.url, &extracted.content) {
+          log::warn!("Failed to cache {}: {}", feed_item.url, error);
+        }
+        match (tab, tui) {
+          (Some(tab), _) => {
+            if self.reader_articles.get(&tab) == Some(&feed_item.id) {
+              tx.send(Action::UpdateReader(tab, extracted.content))?;
+            }
+          },
+          (None, Some(tui)) => {
+            self.open_in_viewer(&feed_item, Some(extracted.content), tui, tx).await?
+          },
+          (None, None) => {},
+        }
+      },
+      Action::OpenInViewer(feed_item) => {
+        self.db.record_opened(feed_item.id)?;
+        // The viewer takes over the terminal, so there is nothing to run it in without one.
+        if let Some(tui) = tui {
+          self.open_in_viewer(&feed_item, None, tui, tx).await?;
+        }
+      },
+      Action::CaptureTask(feed_item) => {
+        let Some(template) = &self.config.task.command else {
+          tx.send(Action::Error("No task command configured, set `task.command`".to_string()))?;
+          return Ok(());
+        };
+        let command = task::command_line(template, &feed_item);
+        let tx = tx.clone();
+        tokio::spawn(async move {
+          match task::capture(&command).await {
+            Ok(()) => {
+              let _ = tx.send(Action::Notify(format!("Added a task for {}", feed_item.title)));
+            },
+            Err(error) => {
+              log::error!("Failed to add a task: {}", error);
+              let _ = tx.send(Action::Error(format!("Failed to add a task: {}", error)));
+            },
+          }
+        });
+      },
+      Action::Share(idx, feed_item) => {
+        let Some(target) = self.config.share.get(idx) else {
+          return Ok(());
+        };
+        if let Some(template) = &target.url {
+          let url = task::share_url(template, &feed_item);
+          if let Err(error) = browser::open(&url) {
+            tx.send(Action::Error(self.config.format(Message::OpenFailed, &[&url, &error])))?;
+          }
+        } else if let Some(template) = &target.command {
+          let command = task::command_line(template, &feed_item);
+          let name = target.name.clone();
+          let tx = tx.clone();
+          tokio::spawn(async move {
+            match task::capture(&command).await {
+              Ok(()) => {
+                let _ = tx.send(Action::Notify(format!("Shared {} to {name}", feed_item.title)));
+              },
+              Err(error) => {
+                log::error!("Failed to share to {}: {}", name, error);
+                let _ = tx.send(Action::Error(format!("Failed to share to {name}: {error}")));
+              },
+            }
+          });
+        } else if target.email {
+          let article =
+            ExportedArticle { title: feed_item.title, url: feed_item.url, content: feed_item.desc };
+          tx.send(Action::EmailArticle(article))?;
+        } else {
+          tx.send(Action::Error(format!(
+            "Share target {} needs a `url`, a `command` or `email = true`",
+            target.name
+          )))?;
+        }
+      },
+      Action::DownloadEnclosure(enclosure) => {
+        let dir = media::download_dir(&self.config.media);
+        tx.send(Action::Notify(format!("Downloading {}", enclosure.file_name())))?;
+        let fetcher = self.fetcher.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+          match media::download(&fetcher, &enclosure, &dir).await {
+            Ok(path) => {
+              let _ = tx.send(Action::Notify(format!("Saved {}", path.display())));
+            },
+            Err(error) => {
+              log::error!("Failed to download {}: {:?}", enclosure.url, error);
+              let _ = tx.send(Action::Error(format!("{error:#}")));
+            },
+          }
+        });
+      },
+      Action::Summarize(feed_item, text) => {
+        let config = self.config.summary.clone();
+        let fetcher = self.fetcher.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+          match summary::summarize(&config, &fetcher, &feed_item, &text).await {
+            Ok(summary) => {
+              let _ = tx.send(Action::SaveSummary(feed_item.id, summary));
+            },
+            Err(error) => {
+              log::error!("Failed to summarize {}: {}", feed_item.url, error);
+              let _ = tx.send(Action::SummaryFailed(feed_item.id));
+              let _ = tx.send(Action::Error(error.to_string()));
+            },
+          }
+        });
+      },
+      Action::ExportArticle(article, format, path) => {
+        let path = PathBuf::from(path);
+        let result =
+          tokio::task::spawn_blocking(move || export::export_article(&article, format, &path))
+            .await?;
+        if let Err(error) = result {
+          log::error!("Failed to export article: {}", error);
+          tx.send(Action::Error(format!("Failed to export article: {}", error)))?;
+        }
+      },
+      Action::EmailArticle(article) => {
+        let email = self.config.email.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+          match email::send_article(&email, &article).await {
+            Ok(()) => log::info!("Sent \"{}\" by email", article.title),
+            Err(error) => {
+              log::error!("Failed to email article: {}", error);
+              let _ = tx.send(Action::Error(format!("Failed to email article: {}", error)));
+            },
+          }
+        });
+      },
+      Action::ReadAloud(text) => {
+        self.stop_speech().await;
+        let token = CancellationToken::new();
+        let command = self.config.tts.command.clone();
+        let task_token = token.clone();
+        let tx = tx.clone();
+        tx.send(Action::ReadAloudStarted)?;
+        let handle = tokio::spawn(async move {
+          if let Err(error) = speech::speak(&command, &text, task_token).await {
+            log::error!("Failed to read aloud: {}", error);
+            let _ = tx.send(Action::Error(format!("Failed to read aloud: {}", error)));
+          }
+          let _ = tx.send(Action::ReadAloudFinished);
+        });
+        self.speech = Some((token, handle));
+      },
+      Action::StopReadAloud => self.stop_speech().await,
+      _ => {},
+    }
+    Ok(())
+  }
+
+  /// Finds the text of an article in the background, from its page or else from what the feed
+  /// carries, and sends it back in an [`Action::ArticleLoaded`] for `tab`.
+  fn load_article(
+    &self,
+    tab: Option<TabId>,
+    feed_item: FeedItem,
+    tx: &UnboundedSender<Action>,
+  ) -> Result<()> {
+    let feed_content = self.db.get_item_content(feed_item.id)?;
+    let link = self.config.feed_link(&feed_item.feed_url, feed_item.feed_moved_from.as_deref());
+    let selector = self.config.feed(link).and_then(|feed| feed.content_selector.clone());
+    let min_chars = self.config.fetch.min_article_chars;
+    let max_content_size = self.config.fetch.max_content_size;
+    let fetcher = self.fetcher.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+      let url = feed_item.url.clone();
+      // A page that fails to load still leaves the feed's own content to fall back on.
+      let page = fetcher.fetch_page(&url).await.unwrap_or_else(|error| {
+        log::warn!("Failed to load {url}: {error}");
+        String::new()
+      });
+      let extracted = tokio::task::spawn_blocking(move || {
+        let mut extracted =
+          extract::extract(&page, &url, &feed_content, selector.as_deref(), min_chars)?;
+        html::truncate(&mut extracted.content, max_content_size);
+        Some(extracted)
+      })
+      .await
+      .unwrap_or_else(|error| {
+        log::error!("Failed to extract {}: {}", feed_item.url, error);
+        None
+      });
+      let _ = tx.send(Action::ArticleLoaded(tab, feed_item, extracted));
+    });
+    Ok(())
+  }
+
+  /// Hands the terminal to the configured viewer until it exits, exporting the article first
+  /// when the command wants it as a file. An article that is neither given as `content` nor
+  /// cached is loaded first, and the viewer started once it is.
+  async fn open_in_viewer(
+    &mut self,
+    feed_item: &FeedItem,
+    content: Option<String>,
+    tui: &mut tui::Tui,
+    action_tx: &UnboundedSender<Action>,
+  ) -> Result<()> {
+    let Some(template) = self.config.viewer.command.clone() else {
+      action_tx.send(Action::Error("No viewer configured, set `viewer.command`".to_string()))?;
+      return Ok(());
+    };
+    let file = if template.contains("%file") {
+      let Some(content) = content.or_else(|| self.article_cache.get(&feed_item.url)) else {
+        return self.load_article(None, feed_item.clone(), action_tx);
+      };
+      let article =
+        ExportedArticle { title: feed_item.title.clone(), url: feed_item.url.clone(), content };
+      let format = self.config.viewer.format;
+      // Kept out of the shared temporary directory, where another user could plant a symlink
+      // under the predictable name.
+      let dir = get_data_dir().join("viewer");
+      std::fs::create_dir_all(&dir)?;
+      let path = dir.join(format!("{}.{}", article.file_stem(), format.extension()));
+      export::export_article(&article, format, &path)?;
+      Some(path)
+    } else {
+      None
+    };
+
+    let command = viewer::command_line(&template, &feed_item.url, file.as_deref());
+    tui.exit()?;
+    let status = viewer::run(&command).await;
+    tui.enter()?;
+    tui.clear()?;
+    if let Some(file) = &file {
+      if let Err(error) = std::fs::remove_file(file) {
+        log::warn!("Failed to remove {}: {}", file.display(), error);
+      }
+    }
+    match status {
+      Ok(status) if !status.success() => {
+        action_tx.send(Action::Error(format!("Viewer exited with {status}")))?;
+      },
+      Err(error) => action_tx.send(Action::Error(format!("Failed to run viewer: {error}")))?,
+      Ok(_) => {},
+    }
+    Ok(())
+  }
+
+  /// Stops any article being read aloud, waiting for it so its `ReadAloudFinished` is sent
+  /// before anything that follows.
+  async fn stop_speech(&mut self) {
+    if let Some((token, handle)) = self.speech.take() {
+      token.cancel();
+      let _ = handle.await;
+    }
+  }
+}
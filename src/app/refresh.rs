@@ -0,0 +1,292 @@
+use std::{
+  collections::{HashMap, HashSet, VecDeque},
+  time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use tokio::{sync::mpsc::UnboundedSender, task::JoinSet};
+use tokio_util::sync::CancellationToken;
+
+use super::App;
+use crate::{
+  action::{Action, RefreshScope},
+  config::{FeedConfig, RemovedFeeds},
+  db::{FeedItem, Group},
+  refresh::RefreshError,
+  task, utils,
+};
+
+impl App {
+  /// Handles the actions that fetch feeds and store what was fetched.
+  pub(super) fn handle_refresh(
+    &mut self,
+    action: Action,
+    tx: &UnboundedSender<Action>,
+  ) -> Result<()> {
+    match action {
+      Action::RequestRefresh(scope) => {
+        if self.refresh_token.is_some() {
+          tx.send(Action::Notify("A refresh is already running".to_string()))?;
+        }
+        self.start_refresh(tx.clone(), scope)?;
+      },
+      Action::CancelRefresh => {
+        if let Some(token) = &self.refresh_token {
+          token.cancel();
+        }
+      },
+      Action::FeedFetched(group_id, fetched) => {
+        if let Some(moved_to) = &fetched.moved_to {
+          log::info!("Feed {} moved permanently to {}", fetched.url, moved_to);
+          tx.send(Action::Notify(format!(
+            "{} has moved to {}, consider updating your config",
+            fetched.url, moved_to
+          )))?;
+        }
+        let url = fetched.url.clone();
+        match self.db.save_fetched_feed(group_id, fetched) {
+          Ok(summary) => {
+            self.db.record_fetch_success(&url)?;
+            self.announce_alerts(&summary.alerts, tx)?;
+            tx.send(Action::FeedSaved(summary))?;
+          },
+          Err(error) => {
+            log::error!("Failed to store feed {}: {:?}", url, error);
+            tx.send(Action::FeedFailed(url, RefreshError::from(&error)))?;
+          },
+        }
+      },
+      Action::FeedFailed(url, error) => {
+        self.db.record_fetch_failure(&url, &error, self.config.fetch.dead_after_failures)?;
+      },
+      Action::RefreshFinished => {
+        self.refresh_token = None;
+        self.archive_stale_unread(tx)?;
+        tx.send(Action::Refresh(self.db.get_groups()?))?;
+        self.send_unread_counts(tx)?;
+        self.maintain_db()?;
+      },
+      Action::PreviewFeed(entry) => {
+        let fetcher = self.fetcher.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+          let fetched = fetcher
+            .fetch_feed(&entry.feed_config())
+            .await
+            .map_err(|error| RefreshError::from(&error).explained());
+          let _ = tx.send(Action::ShowFeedPreview(entry, fetched));
+        });
+      },
+      Action::Subscribe(entry) => {
+        self.db.add_subscription(&entry.category, &entry.feed_config())?;
+        tx.send(Action::Notify(format!("Subscribed to {}", entry.name)))?;
+        self.start_refresh(tx.clone(), RefreshScope::Feed(entry.link))?;
+      },
+      _ => {},
+    }
+    Ok(())
+  }
+
+  /// Tells of new articles matching a watch keyword in the info bar and, if enabled, on the
+  /// desktop.
+  fn announce_alerts(&self, alerts: &[FeedItem], tx: &UnboundedSender<Action>) -> Result<()> {
+    let Some(first) = alerts.first() else {
+      return Ok(());
+    };
+    let notice = match alerts.len() {
+      1 => format!("Alert: {} ({})", first.title, first.alert.as_deref().unwrap_or_default()),
+      n => format!("{n} new alerts from {}, see Alerts", first.feed_name),
+    };
+    tx.send(Action::Notify(notice))?;
+    if !self.config.alerts.desktop {
+      return Ok(());
+    }
+    for alert in alerts {
+      let alert = alert.clone();
+      let command = self.config.alerts.notify_command.clone();
+      tokio::spawn(async move {
+        if let Err(error) = task::notify(&alert, command.as_deref()).await {
+          log::warn!("Failed to show a desktop notification: {error}");
+        }
+      });
+    }
+    Ok(())
+  }
+
+  /// Archives the unread articles older than their group's `archive_unread_after_days`.
+  fn archive_stale_unread(&self, tx: &UnboundedSender<Action>) -> Result<()> {
+    let mut archived = 0;
+    for group in self.config.all_groups() {
+      let Some(cutoff) = group.archive_unread_after_days.and_then(utils::days_ago) else {
+        continue;
+      };
+      let group_id = self.db.get_group_id(&group.name)?;
+      if group_id != -1 {
+        archived += self.db.archive_unread_before(group_id, cutoff)?;
+      }
+    }
+    if archived > 0 {
+      tx.send(Action::Notify(format!("Archived {archived} old unread articles")))?;
+    }
+    Ok(())
+  }
+
+  /// Runs database upkeep when `fetch.maintenance_interval_hours` have passed since it last did.
+  fn maintain_db(&self) -> Result<()> {
+    let hours = self.config.fetch.maintenance_interval_hours;
+    if hours == 0 {
+      return Ok(());
+    }
+    // An interval too long to represent means upkeep is only ever run once.
+    let interval = i64::try_from(hours)
+      .ok()
+      .and_then(chrono::Duration::try_hours)
+      .unwrap_or(chrono::Duration::max_value());
+    if self.db.maintenance_due(interval)? {
+      let maintenance = self.db.run_maintenance()?;
+      log::info!(
+        "Database upkeep removed {} orphaned articles and freed {} bytes",
+        maintenance.orphans,
+        maintenance.freed
+      );
+    }
+    Ok(())
+  }
+
+  /// Stores the configured groups and those of feeds subscribed to from the app, then archives or
+  /// deletes the stored feeds that are in neither, as `removed_feeds` says. Returns the feeds to
+  /// refresh with the ids of their groups.
+  pub fn sync_config(&self, tx: &UnboundedSender<Action>) -> Result<Vec<(i32, FeedConfig)>> {
+    let mut feeds = Vec::new();
+    let mut group_names = HashSet::new();
+    // Parents are stored before the groups nested in them, which need their ids.
+    let mut pending: VecDeque<_> = self.config.groups.iter().map(|group| (None, group)).collect();
+    while let Some((parent_id, group)) = pending.pop_front() {
+      group_names.insert(group.name.clone());
+      let new_group =
+        Group { id: 0, name: group.name.clone(), desc: group.desc.clone(), parent_id };
+      let group_id = match self.db.upsert_group(new_group) {
+        Ok(id) => id,
+        Err(error) => {
+          log::error!("Failed to upsert group: {:?}", error);
+          continue;
+        },
+      };
+      feeds.extend(group.feeds.iter().map(|feed| (group_id, feed.clone())));
+      pending.extend(group.groups.iter().map(|subgroup| (Some(group_id), subgroup)));
+    }
+    for (group_name, feed) in self.db.get_subscriptions()? {
+      if feeds.iter().any(|(_, configured)| configured.link == feed.link) {
+        continue;
+      }
+      group_names.insert(group_name.clone());
+      // Catalog groups may share a configured group's name, whose description is kept.
+      let group_id = match self.db.get_group_id(&group_name)? {
+        -1 => {
+          let group = Group { id: 0, name: group_name, desc: String::new(), parent_id: None };
+          self.db.upsert_group(group)?
+        },
+        id => id,
+      };
+      feeds.push((group_id, feed));
+    }
+
+    let feed_urls = feeds.iter().map(|(_, feed)| feed.link.clone()).collect();
+    // A config without groups is more likely broken or not yet written than meant to drop every
+    // feed, so nothing is removed for it.
+    let removed_feeds =
+      if self.config.groups.is_empty() { RemovedFeeds::Keep } else { self.config.removed_feeds };
+    let removed = self.db.remove_unconfigured(&feed_urls, &group_names, removed_feeds)?;
+    match removed.as_slice() {
+      [] => {},
+      [name] => {
+        tx.send(Action::Notify(format!("Removed {name}, it is no longer in the config")))?
+      },
+      names => {
+        tx.send(Action::Notify(format!("Removed {} feeds no longer in the config", names.len())))?
+      },
+    }
+    Ok(feeds)
+  }
+
+  /// Starts fetching the configured feeds in `scope` in the background. Each fetched feed is sent
+  /// back as an [`Action::FeedFetched`] so it can be stored from the main loop, followed by a
+  /// single [`Action::RefreshFinished`] once all feeds are done, the refresh deadline passes, or
+  /// the refresh is cancelled.
+  pub fn start_refresh(&mut self, tx: UnboundedSender<Action>, scope: RefreshScope) -> Result<()> {
+    if self.refresh_token.is_some() {
+      log::info!("Refresh already in progress");
+      return Ok(());
+    }
+
+    let mut feeds = self.sync_config(&tx)?;
+    match scope {
+      RefreshScope::All => {},
+      RefreshScope::Group(group_id) => {
+        let group_ids = self.db.get_subgroup_ids(group_id)?;
+        feeds.retain(|(group_id, _)| group_ids.contains(group_id));
+      },
+      RefreshScope::Feed(url) => {
+        self.db.revive_feed(&url)?;
+        feeds.retain(|(_, feed)| feed.link == url);
+      },
+      RefreshScope::Stale => {
+        let last_success: HashMap<String, DateTime<Utc>> = self
+          .db
+          .get_feed_health()?
+          .into_iter()
+          .filter_map(|health| Some((health.url, health.last_success?)))
+          .collect();
+        let now = Utc::now();
+        feeds.retain(|(_, feed)| {
+          match last_success.get(&feed.link) {
+            Some(&success) => now - success >= self.config.fetch.refresh_interval(feed),
+            None => true,
+          }
+        });
+      },
+    }
+    let dead = self.db.get_dead_feeds()?;
+    feeds.retain(|(_, feed)| !dead.contains(&feed.link));
+
+    let token = CancellationToken::new();
+    self.refresh_token = Some(token.clone());
+    tx.send(Action::RefreshStarted)?;
+    let fetcher = self.fetcher.clone();
+    let deadline = Duration::from_secs(self.config.fetch.refresh_deadline_secs);
+
+    tokio::spawn(async move {
+      let mut tasks = JoinSet::new();
+      for (group_id, feed) in feeds {
+        let fetcher = fetcher.clone();
+        tasks.spawn(async move { (group_id, fetcher.fetch_feed(&feed).await, feed.link) });
+      }
+
+      let fetch_all = async {
+        while let Some(result) = tasks.join_next().await {
+          match result {
+            Ok((group_id, Ok(fetched), _)) => {
+              let _ = tx.send(Action::FeedFetched(group_id, fetched));
+            },
+            Ok((_, Err(error), link)) => {
+              log::error!("Failed to fetch feed {}: {}", link, error);
+              let _ = tx.send(Action::FeedFailed(link, RefreshError::from(&error)));
+            },
+            Err(error) => log::error!("Fetch task failed: {:?}", error),
+          }
+        }
+      };
+
+      tokio::select! {
+        _ = token.cancelled() => log::info!("Refresh cancelled"),
+        _ = tokio::time::sleep(deadline) => log::warn!("Refresh deadline exceeded"),
+        _ = fetch_all => {},
+      }
+      tasks.abort_all();
+      let _ = tx.send(Action::RefreshFinished);
+    });
+
+    Ok(())
+  }
+}
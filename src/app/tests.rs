@@ -0,0 +1,175 @@
+use chrono::{TimeZone, Utc};
+use crossterm::event::KeyCode;
+use pretty_assertions::assert_eq;
+
+use crate::{
+  action::{Action, RefreshScope},
+  config::RemovedFeeds,
+  testing::{config, group, Harness, MockServer, Reply},
+};
+
+const NEWS: &str = include_str!("../../tests/fixtures/news.xml");
+
+/// Starts the app with a single group, News, holding the feeds at `paths` of `server`.
+async fn start(server: &MockServer, paths: &[&str]) -> Harness {
+  let mut config = config();
+  let links: Vec<String> = paths.iter().map(|path| server.url(path)).collect();
+  config.groups = vec![group("News", &links)];
+  Harness::start(config, 100, 20).await
+}
+
+async fn refresh(harness: &mut Harness) {
+  harness.send(Action::RequestRefresh(RefreshScope::All));
+  harness.run_until(|action| *action == Action::RefreshFinished).await;
+}
+
+#[tokio::test]
+async fn refreshing_stores_the_articles_of_a_feed() {
+  let server = MockServer::start(vec![("/news.xml", vec![Reply::ok(NEWS)])]).await;
+  let mut harness = start(&server, &["/news.xml"]).await;
+  refresh(&mut harness).await;
+
+  let mut items: Vec<_> = harness
+    .app
+    .db
+    .get_feed_items()
+    .unwrap()
+    .into_iter()
+    .map(|item| (item.title, item.pub_date))
+    .collect();
+  items.sort();
+  assert_eq!(items, vec![
+    (
+      "Harbour reopens after storm".to_string(),
+      Utc.with_ymd_and_hms(2024, 6, 3, 14, 30, 0).unwrap()
+    ),
+    (
+      "Library extends opening hours".to_string(),
+      Utc.with_ymd_and_hms(2024, 6, 2, 9, 0, 0).unwrap()
+    ),
+    ("Market moves to the square".to_string(), Utc.with_ymd_and_hms(2024, 6, 1, 8, 0, 0).unwrap()),
+  ]);
+  assert!(harness.render().contains("3 unread"));
+}
+
+#[tokio::test]
+async fn opening_all_feeds_lists_the_articles_newest_first() {
+  let server = MockServer::start(vec![("/news.xml", vec![Reply::ok(NEWS)])]).await;
+  let mut harness = start(&server, &["/news.xml"]).await;
+  refresh(&mut harness).await;
+
+  harness.press(KeyCode::Enter);
+  harness.settle().await;
+  let screen = harness.render();
+  let rows: Vec<_> = ["Harbour reopens", "Library extends", "Market moves"]
+    .into_iter()
+    .map(|title| screen.find(title).expect(title))
+    .collect();
+  assert!(rows.windows(2).all(|pair| pair[0] < pair[1]), "{screen}");
+  assert!(screen.contains("All Articles (3)"), "{screen}");
+}
+
+#[tokio::test]
+async fn opening_an_article_loads_its_page_in_the_background() {
+  let server = MockServer::start(vec![("/harbour", vec![Reply::ok(
+    "<html><body><p>The harbour master said the boats are back in the water.</p></body></html>",
+  )])])
+  .await;
+  server.route("/news.xml", vec![Reply::ok(
+    &NEWS.replace("https://news.example.com", &server.url("")),
+  )]);
+  let mut harness = start(&server, &["/news.xml"]).await;
+  refresh(&mut harness).await;
+
+  harness.press(KeyCode::Enter);
+  harness.settle().await;
+  harness.press(KeyCode::Enter);
+  harness.run_until(|action| matches!(action, Action::ArticleLoaded(..))).await;
+  assert!(server.requests().iter().any(|request| request.path == "/harbour"));
+  assert!(harness.render().contains("boats are back in the water"));
+}
+
+#[tokio::test]
+async fn a_failing_feed_is_recorded_in_its_health() {
+  let server = MockServer::start(vec![("/news.xml", vec![Reply::status(500)])]).await;
+  let mut harness = start(&server, &["/news.xml"]).await;
+  refresh(&mut harness).await;
+
+  let health = harness.app.db.get_feed_health().unwrap();
+  assert_eq!(health.len(), 1);
+  assert_eq!(health[0].url, server.url("/news.xml"));
+  assert_eq!(health[0].failures, 1);
+  assert!(health[0].last_success.is_none());
+  assert!(harness.app.db.get_feed_items().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn a_feed_that_moved_permanently_follows_it() {
+  let server = MockServer::start(vec![
+    ("/old.xml", vec![Reply::redirect(301, "/news.xml")]),
+    ("/news.xml", vec![Reply::ok(NEWS)]),
+  ])
+  .await;
+  let mut harness = start(&server, &["/old.xml"]).await;
+  refresh(&mut harness).await;
+
+  let feeds = harness.app.db.get_feeds().unwrap();
+  assert_eq!(feeds.iter().map(|feed| feed.url.clone()).collect::<Vec<_>>(), vec![
+    server.url("/news.xml")
+  ]);
+  let notice = format!("{} has moved to {}", server.url("/old.xml"), server.url("/news.xml"));
+  assert!(harness
+    .actions
+    .iter()
+    .any(|action| matches!(action, Action::Notify(message) if message.starts_with(&notice))));
+  assert_eq!(harness.app.db.get_feed_items().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn a_config_without_groups_removes_no_feeds() {
+  let server = MockServer::start(vec![("/news.xml", vec![Reply::ok(NEWS)])]).await;
+  let mut harness = start(&server, &["/news.xml"]).await;
+  refresh(&mut harness).await;
+
+  harness.app.config.groups.clear();
+  harness.app.config.removed_feeds = RemovedFeeds::Delete;
+  let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+  harness.app.sync_config(&tx).unwrap();
+  assert_eq!(harness.app.db.get_feeds().unwrap().len(), 1);
+  assert_eq!(harness.app.db.get_feed_items().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn q_quits_without_asking_when_told_not_to() {
+  let server = MockServer::start(vec![]).await;
+  let mut harness = start(&server, &[]).await;
+
+  harness.press(KeyCode::Char('q'));
+  harness.settle().await;
+  assert!(harness.actions.contains(&Action::Quit));
+  assert!(harness.app.should_quit);
+}
+
+#[tokio::test]
+async fn quitting_is_confirmed_even_with_no_components_configured() {
+  let mut config = config();
+  config.confirm_quit = true;
+  config.components.clear();
+  let mut harness = Harness::start(config, 100, 20).await;
+
+  harness.press(KeyCode::Char('q'));
+  harness.settle().await;
+  harness.press(KeyCode::Char('y'));
+  harness.settle().await;
+  assert!(harness.app.should_quit);
+}
+
+#[tokio::test]
+async fn the_tabs_open_at_launch_come_from_the_config() {
+  let mut config = config();
+  config.tabs = vec!["groups".to_string(), "stats".to_string()];
+  let mut harness = Harness::start(config, 100, 20).await;
+
+  assert!(harness.render().lines().next().unwrap().contains(" Groups │ Stats "));
+  assert!(harness.actions.iter().any(|action| matches!(action, Action::RequestUpdateStats(_))));
+}
@@ -0,0 +1,34 @@
+use std::{
+  io,
+  process::{Command, Stdio},
+};
+
+/// Escapes everything but the characters URLs leave unreserved.
+pub fn percent_encode(text: &str) -> String {
+  let mut encoded = String::new();
+  for byte in text.bytes() {
+    if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+      encoded.push(byte as char);
+    } else {
+      encoded.push_str(&format!("%{byte:02X}"));
+    }
+  }
+  encoded
+}
+
+/// Opens `url` with the program named by `$BROWSER`, or the platform's default handler.
+pub fn open(url: &str) -> io::Result<()> {
+  let mut command = match std::env::var("BROWSER") {
+    Ok(browser) if !browser.is_empty() => Command::new(browser),
+    _ if cfg!(target_os = "macos") => Command::new("open"),
+    _ if cfg!(target_os = "windows") => {
+      let mut command = Command::new("cmd");
+      command.args(["/C", "start", ""]);
+      command
+    },
+    _ => Command::new("xdg-open"),
+  };
+  // The browser outlives the request, so it is not waited on.
+  command.arg(url).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+  Ok(())
+}
@@ -0,0 +1,73 @@
+use std::{
+  fs::{self, File},
+  io,
+  path::PathBuf,
+  time::SystemTime,
+};
+
+use crate::utils::stable_hash;
+
+/// Extracted articles kept as files in the data directory, one per URL, so reopening an article
+/// needs neither the network nor readability. The least recently opened articles are removed
+/// once the files take up more than `max_bytes`.
+pub struct ArticleCache {
+  dir: PathBuf,
+  max_bytes: u64,
+}
+
+impl ArticleCache {
+  pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+    Self { dir, max_bytes }
+  }
+
+  fn path(&self, url: &str) -> PathBuf {
+    self.dir.join(format!("{:016x}.html", stable_hash(url)))
+  }
+
+  /// The cached article at `url`, marking it as recently used.
+  pub fn get(&self, url: &str) -> Option<String> {
+    if self.max_bytes == 0 {
+      return None;
+    }
+    let path = self.path(url);
+    let content = fs::read_to_string(&path).ok()?;
+    // The modification time doubles as the last use, which is what eviction goes by.
+    let touched =
+      File::options().write(true).open(&path).and_then(|file| file.set_modified(SystemTime::now()));
+    if let Err(error) = touched {
+      log::warn!("Failed to touch {}: {}", path.display(), error);
+    }
+    Some(content)
+  }
+
+  /// Caches the article at `url`, then evicts the least recently used ones over the size cap.
+  pub fn put(&self, url: &str, content: &str) -> io::Result<()> {
+    if self.max_bytes == 0 {
+      return Ok(());
+    }
+    fs::create_dir_all(&self.dir)?;
+    fs::write(self.path(url), content)?;
+    self.evict()
+  }
+
+  fn evict(&self) -> io::Result<()> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&self.dir)? {
+      let entry = entry?;
+      let metadata = entry.metadata()?;
+      if metadata.is_file() {
+        files.push((metadata.modified()?, metadata.len(), entry.path()));
+      }
+    }
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    files.sort();
+    for (_, len, path) in files {
+      if total <= self.max_bytes {
+        break;
+      }
+      fs::remove_file(path)?;
+      total -= len;
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::FeedConfig;
+
+/// A well-known feed offered for subscription without looking up its URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+  /// The group the feed is added to when subscribing.
+  pub category: String,
+  pub name: String,
+  pub desc: String,
+  pub link: String,
+}
+
+impl CatalogEntry {
+  pub fn feed_config(&self) -> FeedConfig {
+    FeedConfig { name: Some(self.name.clone()), link: self.link.clone(), ..Default::default() }
+  }
+
+  /// Whether every word of `query` appears in the name, description or category.
+  pub fn matches(&self, query: &str) -> bool {
+    let haystack = format!("{} {} {}", self.name, self.desc, self.category).to_lowercase();
+    query.to_lowercase().split_whitespace().all(|word| haystack.contains(word))
+  }
+}
+
+const CATALOG: &str = include_str!("catalog.json");
+
+/// Returns the bundled catalog of popular feeds.
+pub fn entries() -> Vec<CatalogEntry> {
+  serde_json::from_str(CATALOG).expect("bundled feed catalog is valid JSON")
+}
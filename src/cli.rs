@@ -1,8 +1,8 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
-use crate::utils::version;
+use crate::{config::ThemeVariant, digest::DigestFormat, utils::version};
 
 #[derive(Parser, Debug)]
 #[command(author, version = version(), about)]
@@ -24,4 +24,131 @@ pub struct Cli {
     default_value_t = 4.0
   )]
   pub frame_rate: f64,
+
+  #[arg(
+    short,
+    long,
+    value_name = "NAME",
+    help = "Group to open on launch, overriding `start_group` and `start_feed` in the config"
+  )]
+  pub group: Option<String>,
+
+  #[arg(
+    long,
+    help = "Move the database aside and start a new one from the config, fetching every feed again"
+  )]
+  pub rebuild_db: bool,
+
+  #[command(subcommand)]
+  pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+  /// Print sample screens drawn with a palette, to try out theme changes without opening the app
+  PreviewTheme {
+    #[arg(value_enum, help = "Palette to preview, the one the theme mode picks by default")]
+    variant: Option<ThemeVariant>,
+
+    #[arg(long, value_name = "COLUMNS", help = "Width of the preview", default_value_t = 100)]
+    width: u16,
+
+    #[arg(long, value_name = "ROWS", help = "Height of each sample screen", default_value_t = 16)]
+    height: u16,
+  },
+  /// Store passwords and tokens outside the config file
+  Auth {
+    #[command(subcommand)]
+    command: AuthCommand,
+  },
+  /// Save or restore which articles have been read, e.g. to move to another machine
+  State {
+    #[command(subcommand)]
+    command: StateCommand,
+  },
+  /// Summarize the articles fetched recently, grouped by group and feed, e.g. to email or archive
+  Digest {
+    #[arg(
+      long,
+      value_name = "AGE",
+      help = "How far back to go, e.g. `12h`, `7d` or `2w`",
+      default_value = "24h"
+    )]
+    since: String,
+
+    #[arg(short, long, value_name = "FILE", help = "File to write, printed when left out")]
+    output: Option<PathBuf>,
+
+    #[arg(
+      long,
+      value_enum,
+      help = "Format of the digest, going by the file extension when left out"
+    )]
+    format: Option<DigestFormat>,
+  },
+  /// Subscribe to feeds, finding the feed behind each link to a site or page
+  Add {
+    #[arg(value_name = "URL", help = "Links to feeds or to the sites they belong to")]
+    urls: Vec<String>,
+
+    #[arg(
+      long,
+      value_name = "FILE",
+      help = "File with a link on each line, `-` to read them from standard input"
+    )]
+    from_file: Option<PathBuf>,
+
+    #[arg(
+      short,
+      long,
+      value_name = "NAME",
+      help = "Group to add the feeds to, asked when left out"
+    )]
+    group: Option<String>,
+  },
+  /// Look after the database
+  Db {
+    #[command(subcommand)]
+    command: DbCommand,
+  },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AuthCommand {
+  /// Prompt for a password or token and store it in the system keyring
+  Set {
+    #[arg(value_name = "NAME", help = "Name the config refers to the secret by, e.g. `smtp`")]
+    name: String,
+  },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DbCommand {
+  /// Show how large the database is and which feeds and articles take up the most
+  Stats {
+    #[arg(
+      long,
+      value_name = "COUNT",
+      help = "How many of the largest articles to list",
+      default_value_t = 10
+    )]
+    largest: usize,
+  },
+  /// Remove orphaned articles and compact the database file, which upkeep after a refresh only
+  /// does bit by bit
+  Vacuum,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StateCommand {
+  /// Write the read state of every article to a JSON file
+  Export {
+    #[arg(value_name = "FILE", help = "File to write")]
+    path: PathBuf,
+  },
+  /// Mark the articles in a JSON file written by `state export` as read or unread
+  Import {
+    #[arg(value_name = "FILE", help = "File to read")]
+    path: PathBuf,
+  },
 }
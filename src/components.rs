@@ -1,10 +1,17 @@
 use color_eyre::eyre::Result;
 use crossterm::event::{KeyEvent, MouseEvent};
-use ratatui::layout::Rect;
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, Terminal};
 use tokio::sync::mpsc::UnboundedSender;
 
+use self::{
+  browser_view::BrowserView, group_view::GroupView, health_view::HealthView,
+  highlights_view::HighlightsView, info_bar::InfoBar, popup_catalog::CatalogPopup,
+  popup_confirm::ConfirmPopup, popup_export::ExportPopup, popup_info::InfoPopup,
+  popup_menu::MenuPopup, popup_note::NotePopup, popup_share::SharePopup,
+  popup_switcher::SwitcherPopup, stats_view::StatsView, tab_viewer::TabViewer,
+};
 use crate::{
-  action::Action,
+  action::{Action, TabId},
   config::Config,
   tui::{Event, Frame},
 };
@@ -12,12 +19,100 @@ use crate::{
 pub mod article_list;
 pub mod article_reader;
 pub mod article_view;
+pub mod browser_view;
+pub mod feed_tree;
 pub mod feed_view;
 pub mod group_view;
+pub mod health_view;
+pub mod highlights_view;
 pub mod info_bar;
-pub mod popup_quit;
+pub mod mouse;
+pub mod popup_catalog;
+pub mod popup_confirm;
+pub mod popup_export;
+pub mod popup_info;
+pub mod popup_menu;
+pub mod popup_note;
+pub mod popup_share;
+pub mod popup_switcher;
+pub mod scroll;
+pub mod stats_view;
 pub mod tab_bar;
 pub mod tab_viewer;
+pub mod text_input;
+
+/// Builds a component in the state it starts the app with `config` in.
+pub type Constructor = fn(&Config) -> Box<dyn Component>;
+
+/// The components the app can be put together from, by the name the config gives them. `tabs` and
+/// `panes` are the main views of the two layouts, the rest are bars and popups drawn over them.
+pub const REGISTRY: [(&str, Constructor); 11] = [
+  ("tabs", |config| Box::new(TabViewer::new(&config.tabs))),
+  ("panes", |_| Box::new(BrowserView::new())),
+  ("info_bar", |_| Box::new(InfoBar::new())),
+  ("export", |_| Box::new(ExportPopup::new())),
+  ("catalog", |_| Box::new(CatalogPopup::new())),
+  ("switcher", |_| Box::new(SwitcherPopup::new())),
+  ("note", |_| Box::new(NotePopup::new())),
+  ("share", |_| Box::new(SharePopup::new())),
+  ("info", |_| Box::new(InfoPopup::new())),
+  ("menu", |_| Box::new(MenuPopup::new())),
+  ("confirm", |_| Box::new(ConfirmPopup::new())),
+];
+
+/// Components built whether or not the config lists them: messages are shown in `info_bar` and
+/// quitting goes through `confirm`.
+pub const REQUIRED: [&str; 2] = ["info_bar", "confirm"];
+
+/// Builds the component registered as `name` for `config`, if there is one.
+pub fn build(name: &str, config: &Config) -> Option<Box<dyn Component>> {
+  REGISTRY
+    .iter()
+    .find(|(registered, _)| *registered == name)
+    .map(|(_, constructor)| constructor(config))
+}
+
+/// A view the tabs layout shows in a tab of its own.
+pub struct TabKind {
+  pub name: &'static str,
+  pub title: &'static str,
+  pub build: fn(TabId) -> Box<dyn Component>,
+  /// The request loading what the view shows, if it does not wait for a refresh.
+  pub request: fn(TabId) -> Option<Action>,
+}
+
+/// The views the tabs layout can open by name, such as those the config has open at launch.
+pub const TABS: [TabKind; 4] = [
+  TabKind {
+    name: "groups",
+    title: "Groups",
+    build: |id| Box::new(GroupView::new(id)),
+    request: |_| None,
+  },
+  TabKind {
+    name: "stats",
+    title: "Stats",
+    build: |id| Box::new(StatsView::new(id)),
+    request: |id| Some(Action::RequestUpdateStats(id)),
+  },
+  TabKind {
+    name: "health",
+    title: "Feed health",
+    build: |id| Box::new(HealthView::new(id)),
+    request: |id| Some(Action::RequestUpdateHealth(id)),
+  },
+  TabKind {
+    name: "highlights",
+    title: "Highlights",
+    build: |id| Box::new(HighlightsView::new(id)),
+    request: |id| Some(Action::RequestUpdateHighlights(id)),
+  },
+];
+
+/// The view registered in [`TABS`] as `name`, if there is one.
+pub fn tab_kind(name: &str) -> Option<&'static TabKind> {
+  TABS.iter().find(|kind| kind.name == name)
+}
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 /// Implementors of this trait can be registered with the main application loop and will be able to receive events,
@@ -74,6 +169,7 @@ pub trait Component {
     let r = match event {
       Some(Event::Key(key_event)) => self.handle_key_events(key_event)?,
       Some(Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event)?,
+      Some(Event::Paste(text)) => self.handle_paste(text)?,
       _ => None,
     };
     Ok(r)
@@ -104,6 +200,48 @@ pub trait Component {
   fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
     Ok(None)
   }
+  /// Handle text pasted into the terminal and produce actions if necessary.
+  ///
+  /// # Arguments
+  ///
+  /// * `text` - The pasted text, in one piece.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<Option<Action>>` - An action to be processed or none.
+  #[allow(unused_variables)]
+  fn handle_paste(&mut self, text: String) -> Result<Option<Action>> {
+    Ok(None)
+  }
+  /// Whether the component currently captures all input, e.g. a popup with a text field.
+  ///
+  /// While any component captures input, events are only delivered to that component and global
+  /// keybindings are disabled.
+  ///
+  /// # Returns
+  ///
+  /// * `bool` - True if input should only go to this component.
+  fn captures_input(&self) -> bool {
+    false
+  }
+  /// Whether the component changed on its own since it was last drawn, e.g. a clock ticking over.
+  ///
+  /// The app only redraws when actions or input arrive, and asks this on every tick otherwise.
+  ///
+  /// # Returns
+  ///
+  /// * `bool` - True if the component needs drawing again.
+  fn is_dirty(&self) -> bool {
+    false
+  }
+  /// Links drawn by the component in the last frame, for the app to mark as terminal hyperlinks.
+  ///
+  /// # Returns
+  ///
+  /// * `Vec<(Rect, String)>` - The cells each link covers, one row at most, and where it leads.
+  fn hyperlinks(&self) -> Vec<(Rect, String)> {
+    Vec::new()
+  }
   /// Update the state of the component based on a received action. (REQUIRED)
   ///
   /// # Arguments
@@ -128,4 +266,21 @@ pub trait Component {
   ///
   /// * `Result<()>` - An Ok result or an error.
   fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()>;
+  /// Render the component off screen, e.g. for previews or snapshots.
+  ///
+  /// # Arguments
+  ///
+  /// * `width` - The width of the buffer in cells.
+  /// * `height` - The height of the buffer in cells.
+  ///
+  /// # Returns
+  ///
+  /// * `Result<Buffer>` - The cells drawn by the component or an error.
+  fn render_to_buffer(&mut self, width: u16, height: u16) -> Result<Buffer> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    let mut result = Ok(());
+    terminal.draw(|f| result = self.draw(f, f.size()))?;
+    result?;
+    Ok(terminal.backend().buffer().clone())
+  }
 }
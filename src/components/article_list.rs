@@ -1,49 +1,313 @@
+use std::collections::HashMap;
+
 use color_eyre::eyre::Result;
 use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
 use ratatui::{
-  layout::{Margin, Rect},
-  prelude::{Color, Line, Modifier, Style, Text},
-  widgets::{
-    Block, Borders, List, ListItem, ListState, Scrollbar, ScrollbarOrientation, ScrollbarState,
-  },
+  layout::Rect,
+  prelude::{Color, Line, Modifier, Span, Style, Text},
+  widgets::{Block, Borders, List, ListItem, ListState},
 };
 use tokio::sync::mpsc::UnboundedSender;
 
-use super::Component;
-use crate::{action::Action, app, config::Config, db::FeedItem, mode::Mode};
+use super::{
+  mouse::{list_index_at, ClickTracker},
+  scroll, Component,
+};
+use crate::{
+  action::{Action, TabId},
+  app,
+  config::{Config, SortOrder},
+  db::{FeedItem, FeedPosition},
+  html,
+  mode::Mode,
+};
 
 #[derive(Default)]
 pub struct ArticleList {
   command_tx: Option<UnboundedSender<Action>>,
   config: Config,
   mode: Mode,
-  idx: usize,
+  tab: TabId,
   feed_items: Option<Vec<FeedItem>>,
+  /// Sorts the articles by date, or leaves them in the order they were loaded in.
+  sort: Option<SortOrder>,
+  /// Hides read articles, moving them to `hidden` until this is turned off again.
+  unread_only: bool,
+  hidden: Vec<FeedItem>,
+  /// Position of each article as loaded, to put them back in order when unsorted.
+  load_order: HashMap<i32, usize>,
+  /// Where visual selection started, the selection running from here to the selected article.
+  visual_anchor: Option<usize>,
+  /// The description of each item as shown, without HTML when `strip_html` is set.
+  descs: Vec<String>,
   selected: usize,
   state: ListState,
-  scrollbar_state: ScrollbarState,
   vertical_scroll: usize,
   active: bool,
+  show_feed_name: bool,
+  /// Badge color of each feed shown, looked up in the config once rather than on every draw.
+  feed_colors: HashMap<String, Color>,
+  area: Rect,
+  clicks: ClickTracker,
 }
 
 impl ArticleList {
-  pub fn new(idx: usize) -> Self {
+  pub fn new(tab: TabId) -> Self {
     Self {
       command_tx: None,
       config: Config::default(),
       mode: Mode::default(),
-      idx,
+      tab,
       feed_items: None,
+      sort: None,
+      unread_only: false,
+      hidden: Vec::new(),
+      load_order: HashMap::new(),
+      visual_anchor: None,
+      descs: Vec::new(),
       selected: 0,
       state: ListState::default().with_selected(Some(0)),
-      scrollbar_state: ScrollbarState::default(),
       vertical_scroll: 0,
       active: true,
+      show_feed_name: false,
+      feed_colors: HashMap::new(),
+      area: Rect::default(),
+      clicks: ClickTracker::default(),
+    }
+  }
+
+  /// Shows the source feed of each article, for lists that aggregate several feeds.
+  pub fn with_feed_names(mut self, show_feed_name: bool) -> Self {
+    self.show_feed_name = show_feed_name;
+    self
+  }
+
+  pub fn with_sort(mut self, sort: Option<SortOrder>) -> Self {
+    self.sort = sort;
+    self
+  }
+
+  pub fn with_unread_only(mut self, unread_only: bool) -> Self {
+    self.unread_only = unread_only;
+    self
+  }
+
+  /// Switches between newest and oldest first, starting with oldest first when unsorted.
+  pub fn toggle_sort(&mut self) {
+    self.sort = Some(self.sort.map_or(SortOrder::OldestFirst, SortOrder::toggled));
+    self.apply_view();
+  }
+
+  pub fn toggle_unread_only(&mut self) {
+    self.unread_only = !self.unread_only;
+    self.apply_view();
+  }
+
+  /// Sorts the articles and hides or brings back the read ones, keeping the selected article
+  /// selected when it is still shown.
+  fn apply_view(&mut self) {
+    let selected_id = self.selected_item().map(|item| item.id);
+    let Some(feed_items) = &mut self.feed_items else {
+      return;
+    };
+    if self.unread_only {
+      let (unread, read): (Vec<_>, Vec<_>) =
+        std::mem::take(feed_items).into_iter().partition(|item| !item.read);
+      *feed_items = unread;
+      self.hidden.extend(read);
+    } else {
+      feed_items.append(&mut self.hidden);
+    }
+    match self.sort {
+      Some(SortOrder::NewestFirst) => feed_items.sort_by(|a, b| b.pub_date.cmp(&a.pub_date)),
+      Some(SortOrder::OldestFirst) => feed_items.sort_by_key(|item| item.pub_date),
+      None => feed_items.sort_by_key(|item| self.load_order.get(&item.id).copied()),
+    }
+    let selected =
+      selected_id.and_then(|id| feed_items.iter().position(|item| item.id == id)).unwrap_or(0);
+    self.state.select(Some(selected));
+    self.visual_anchor = None;
+    self.update_descs();
+  }
+
+  pub fn position(&self) -> Option<FeedPosition> {
+    let item = self.selected_item()?;
+    Some(FeedPosition { item_id: item.id, list_offset: self.state.offset() })
+  }
+
+  /// Selects the remembered article again, if it is still in the list.
+  pub fn restore_position(&mut self, position: FeedPosition) {
+    let Some(feed_items) = &self.feed_items else {
+      return;
+    };
+    if let Some(i) = feed_items.iter().position(|item| item.id == position.item_id) {
+      self.state = ListState::default().with_selected(Some(i)).with_offset(position.list_offset);
+    }
+  }
+
+  /// Asks for the selected article to be loaded into the reader.
+  pub fn open_selected(&self) -> Result<()> {
+    if let (Some(tx), Some(selected_item)) = (&self.command_tx, self.selected_item()) {
+      tx.send(Action::RequestUpdateReader(self.tab, selected_item.clone()))?;
+    }
+    Ok(())
+  }
+
+  /// Selects the article under the mouse, returning whether the click was a double click.
+  pub fn click(&mut self, column: u16, row: u16) -> bool {
+    self.select_at(column, row).is_some_and(|idx| self.clicks.click(idx))
+  }
+
+  /// Selects the article under the mouse, returning its index if there is one.
+  pub fn select_at(&mut self, column: u16, row: u16) -> Option<usize> {
+    let len = self.feed_items.as_ref().map_or(0, Vec::len);
+    let idx = list_index_at(self.area, self.state.offset(), self.item_height(), len, column, row)?;
+    self.state.select(Some(idx));
+    Some(idx)
+  }
+
+  /// Marks the selected article as read, returning its id if it was unread.
+  pub fn mark_selected_read(&mut self) -> Option<i32> {
+    let selected = self.state.selected()?;
+    let item = self.feed_items.as_mut()?.get_mut(selected)?;
+    if item.read {
+      return None;
+    }
+    item.read = true;
+    Some(item.id)
+  }
+
+  /// Shows articles as unread again, after marking them read could not be saved.
+  pub fn mark_unread(&mut self, item_ids: &[i32]) {
+    for item in self.feed_items.iter_mut().flatten().chain(&mut self.hidden) {
+      if item_ids.contains(&item.id) {
+        item.read = false;
+      }
+    }
+  }
+
+  /// Shows the note saved for an article, or none once it was removed.
+  pub fn set_note(&mut self, item_id: i32, note: Option<String>) {
+    for item in self.feed_items.iter_mut().flatten().chain(&mut self.hidden) {
+      if item.id == item_id {
+        item.note = note.clone();
+      }
+    }
+  }
+
+  /// Starts selecting a range of articles from the selected one, or stops if already selecting.
+  pub fn toggle_visual(&mut self) {
+    self.visual_anchor = match self.visual_anchor {
+      Some(_) => None,
+      None => self.state.selected(),
+    };
+  }
+
+  pub fn visual(&self) -> bool {
+    self.visual_anchor.is_some()
+  }
+
+  /// Indices of the articles in the visual selection, or of the selected article outside it.
+  fn selection_range(&self) -> std::ops::RangeInclusive<usize> {
+    let selected = self.state.selected().unwrap_or(0);
+    let anchor = self.visual_anchor.unwrap_or(selected);
+    anchor.min(selected)..=anchor.max(selected)
+  }
+
+  /// The articles a bulk operation applies to, see [`Self::selection_range`].
+  pub fn selection(&self) -> &[FeedItem] {
+    let Some(feed_items) = &self.feed_items else {
+      return &[];
+    };
+    let range = self.selection_range();
+    let end = (*range.end() + 1).min(feed_items.len());
+    &feed_items[(*range.start()).min(end)..end]
+  }
+
+  /// Marks the selected articles as read and ends visual selection, returning the ids of those
+  /// that were unread.
+  pub fn mark_selection_read(&mut self) -> Vec<i32> {
+    let range = self.selection_range();
+    self.visual_anchor = None;
+    let Some(feed_items) = &mut self.feed_items else {
+      return Vec::new();
+    };
+    feed_items
+      .iter_mut()
+      .enumerate()
+      .filter(|(i, item)| range.contains(i) && !item.read)
+      .map(|(_, item)| {
+        item.read = true;
+        item.id
+      })
+      .collect()
+  }
+
+  /// Marks every article in the list read, those hidden by the unread filter too, returning the
+  /// ids of the ones that were unread.
+  pub fn mark_all_read(&mut self) -> Vec<i32> {
+    self.visual_anchor = None;
+    self
+      .feed_items
+      .iter_mut()
+      .flatten()
+      .chain(&mut self.hidden)
+      .filter(|item| !item.read)
+      .map(|item| {
+        item.read = true;
+        item.id
+      })
+      .collect()
+  }
+
+  pub fn unread_count(&self) -> usize {
+    self.feed_items.iter().flatten().chain(&self.hidden).filter(|item| !item.read).count()
+  }
+
+  /// Selects the next unread article below the current one, returning whether there was one.
+  pub fn select_next_unread(&mut self) -> bool {
+    let Some(feed_items) = &self.feed_items else {
+      return false;
+    };
+    let start = self.state.selected().map_or(0, |selected| selected + 1);
+    match feed_items.iter().skip(start).position(|item| !item.read) {
+      Some(offset) => {
+        self.state.select(Some(start + offset));
+        true
+      },
+      None => false,
     }
   }
 
-  pub fn set_feed_items(&mut self, feed_items: Vec<FeedItem>) {
-    self.feed_items = Some(feed_items);
+  pub fn selected_item(&self) -> Option<&FeedItem> {
+    self.feed_items.as_ref()?.get(self.state.selected()?)
+  }
+
+  /// Lists `feed_items`, copied since the list sorts them and marks them read in place.
+  pub fn set_feed_items(&mut self, feed_items: &[FeedItem]) {
+    self.load_order = feed_items.iter().enumerate().map(|(i, item)| (item.id, i)).collect();
+    self.hidden.clear();
+    self.feed_items = Some(feed_items.to_vec());
+    self.apply_view();
+  }
+
+  fn update_descs(&mut self) {
+    let strip_html = self.config.display.strip_html;
+    self.descs = self
+      .feed_items
+      .iter()
+      .flatten()
+      .map(|item| if strip_html { html::to_plain_text(&item.desc) } else { item.desc.clone() })
+      .collect();
+  }
+
+  /// Rows taken by each article, the title and its description lines.
+  fn item_height(&self) -> u16 {
+    1 + self.config.display.preview_lines() as u16
+  }
+
+  pub fn set_active(&mut self, active: bool) {
+    self.active = active;
   }
 }
 
@@ -55,32 +319,31 @@ impl Component for ArticleList {
 
   fn register_config_handler(&mut self, config: Config) -> Result<()> {
     self.config = config;
+    self.feed_colors.clear();
+    self.update_descs();
     Ok(())
   }
 
   fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
     if self.active {
-      if let Some(feed_items) = &self.feed_items {
-        let selected_idx = self.state.selected().unwrap_or(0);
+      match key.code {
+        KeyCode::Char('s') => self.toggle_sort(),
+        KeyCode::Char('u') => self.toggle_unread_only(),
+        _ => {},
+      }
+      if let Some(feed_items) = self.feed_items.as_ref().filter(|items| !items.is_empty()) {
+        let page = scroll::page_rows(self.area, self.item_height());
+        let wrap = self.config.display.wrap_lists;
+        if let Some(idx) =
+          scroll::navigate(self.state.selected(), feed_items.len(), key.code, page, wrap)
+        {
+          self.state.select(Some(idx));
+          return Ok(None);
+        }
         match key.code {
-          KeyCode::Char('j') | KeyCode::Down => {
-            self.state.select(Some((selected_idx + 1) % feed_items.len()));
-          },
-          KeyCode::Char('k') | KeyCode::Up => {
-            if selected_idx == 0 {
-              self.state.select(Some(feed_items.len() - 1));
-            } else {
-              self.state.select(Some(selected_idx - 1));
-            }
-          },
-          KeyCode::Char('l') | KeyCode::Enter => {
-            if let Some(tx) = &self.command_tx {
-              let selected_idx = self.state.selected().unwrap();
-              let selected_item = feed_items.get(selected_idx).unwrap().clone();
-              tx.send(Action::RequestUpdateReader(self.idx, selected_item))?;
-              tx.send(Action::ActivateReader)?;
-            }
-          },
+          KeyCode::Char('l') | KeyCode::Enter => self.open_selected()?,
+          KeyCode::Char('v') => self.toggle_visual(),
+          KeyCode::Esc => self.visual_anchor = None,
           _ => {},
         }
       }
@@ -91,19 +354,16 @@ impl Component for ArticleList {
   fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
     if self.active {
       match self.mode {
-        Mode::ViewArticles(_) => {
-          if let Some(feed_items) = &self.feed_items {
+        Mode::ViewArticles => {
+          if let Some(feed_items) = self.feed_items.as_ref().filter(|items| !items.is_empty()) {
             let selected_idx = self.state.selected().unwrap_or(0);
+            let (len, wrap) = (feed_items.len(), self.config.display.wrap_lists);
             match mouse.kind {
               MouseEventKind::ScrollUp => {
-                self.state.select(Some((selected_idx + 1) % feed_items.len()));
+                self.state.select(Some(scroll::step(selected_idx, len, true, wrap)));
               },
               MouseEventKind::ScrollDown => {
-                if selected_idx == 0 {
-                  self.state.select(Some(feed_items.len() - 1));
-                } else {
-                  self.state.select(Some(selected_idx - 1));
-                }
+                self.state.select(Some(scroll::step(selected_idx, len, false, wrap)));
               },
               _ => {},
             }
@@ -120,77 +380,128 @@ impl Component for ArticleList {
       // Action::Refresh(groups) => {
       //   self.groups = Some(groups);
       // },
-      Action::ModeChange(mode) => {
-        match mode {
-          Mode::ViewArticles(feed_items) => {
-            self.feed_items = Some(feed_items);
-          },
-          _ => {},
-        }
-      },
-      Action::ActivateFeedList => {
-        self.state.select(Some(0));
-        self.active = true;
-      },
-      Action::ActivateReader => {
-        self.state.select(None);
-        self.active = false;
-      },
+      Action::ModeChange(mode) => self.mode = mode,
       _ => {},
     }
     Ok(None)
   }
 
   fn draw(&mut self, f: &mut crate::tui::Frame<'_>, area: Rect) -> Result<()> {
+    let palette = *self.config.palette();
     if let Some(feed_items) = &self.feed_items {
-      let name_style = Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD);
-      let desc_style = Style::default().fg(Color::Gray);
-      let selected_name_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
-      let selected_desc_style = Style::default().fg(Color::Gray);
+      let name_style = Style::default().fg(palette.title).add_modifier(Modifier::BOLD);
+      let desc_style = Style::default().fg(palette.text);
+      let selected_name_style = self.config.selected_style();
+      let selected_desc_style = Style::default().fg(palette.text);
+
+      let preview_lines = self.config.display.preview_lines();
+      // Leaves out the borders and the highlight symbol.
+      let text_width = usize::from(area.width.saturating_sub(3));
 
+      let visual_range = self.visual_anchor.map(|_| self.selection_range());
       let items: Vec<ListItem> = feed_items
         .iter()
         .enumerate()
         .map(|(i, item)| {
-          if self.state.selected() == Some(i) {
-            let text = Text::from(vec![
-              Line::styled(&item.title, selected_name_style),
-              Line::styled(&item.desc, selected_desc_style),
-              // Line::styled("(0/0) read", selected_desc_style),
-            ]);
-            ListItem::new(text)
+          let (name_style, desc_style) = if self.state.selected() == Some(i) {
+            (selected_name_style, selected_desc_style)
           } else {
-            let text = Text::from(vec![
-              Line::styled(&item.title, name_style),
-              Line::styled(&item.desc, desc_style),
-              // Line::styled("(0/0) read", desc_style),
-            ]);
-            ListItem::new(text)
+            (name_style, desc_style)
+          };
+          let mut title_line = Line::styled(&item.title, name_style);
+          let mut desc_line = Line::styled(
+            format!("{} · ", self.config.display.format_date(item.pub_date)),
+            desc_style,
+          );
+          if item.new {
+            let new_style = self.config.unread_style(palette.accent).add_modifier(Modifier::BOLD);
+            title_line.spans.push(Span::styled("  NEW", new_style));
+          }
+          if item.note.is_some() {
+            title_line.spans.push(Span::styled("  ✎", Style::default().fg(palette.accent)));
+          }
+          if let Some(keyword) = &item.alert {
+            let alert_style = Style::default().fg(palette.error).add_modifier(Modifier::BOLD);
+            title_line.spans.push(Span::styled(format!("  ⚑ {keyword}"), alert_style));
+          }
+          if item.comments.is_some() {
+            let comments = match item.comments_count {
+              Some(count) => format!("  [{count} comments]"),
+              None => "  [comments]".to_string(),
+            };
+            title_line.spans.push(Span::styled(comments, Style::default().fg(palette.muted)));
+          }
+          if self.show_feed_name {
+            let link = self.config.feed_link(&item.feed_url, item.feed_moved_from.as_deref());
+            let color = *self
+              .feed_colors
+              .entry(link.to_string())
+              .or_insert_with(|| self.config.feed_color(link));
+            let badge_style = Style::default().fg(color);
+            title_line.spans.insert(0, Span::styled("● ", badge_style));
+            desc_line.spans.insert(0, Span::styled(format!("{} · ", item.feed_name), badge_style));
+            if let Some(group) = self.config.feed_group(link) {
+              if let Some(icon) = &group.icon {
+                let icon_style = group.color.map_or(desc_style, |color| desc_style.fg(color));
+                desc_line.spans.insert(0, Span::styled(format!("{icon} "), icon_style));
+              }
+            }
+          }
+          if preview_lines == 0 {
+            return ListItem::new(title_line);
+          }
+
+          let desc = self.descs.get(i).map_or("", String::as_str);
+          let first_width = text_width.saturating_sub(desc_line.width());
+          let mut desc_lines =
+            wrap_preview(desc, first_width, text_width, preview_lines).into_iter();
+          if let Some(first) = desc_lines.next() {
+            desc_line.spans.push(Span::styled(first, desc_style));
+          }
+          let mut lines = vec![title_line, desc_line];
+          lines.extend(desc_lines.map(|line| Line::styled(line, desc_style)));
+          // Keeps every row the same height, so mouse clicks map onto the right article.
+          lines.resize(1 + preview_lines, Line::default());
+          ListItem::new(Text::from(lines))
+        })
+        .enumerate()
+        .map(|(i, list_item)| {
+          match &visual_range {
+            Some(range) if range.contains(&i) => {
+              list_item.style(Style::default().add_modifier(Modifier::REVERSED))
+            },
+            _ => list_item,
           }
         })
         .collect();
 
+      let mut block = Block::default().borders(Borders::ALL);
+      let mut view = Vec::new();
+      if self.unread_only {
+        view.push("unread only");
+      }
+      match self.sort {
+        Some(SortOrder::NewestFirst) => view.push("newest first"),
+        Some(SortOrder::OldestFirst) => view.push("oldest first"),
+        None => {},
+      }
+      if let Some(range) = &visual_range {
+        block = block.title(format!(" VISUAL · {} selected ", range.clone().count()));
+      } else if !view.is_empty() {
+        block = block.title(format!(" {} ", view.join(" · ")));
+      }
+      if let Some(position) = scroll::position_title(&self.state, feed_items.len()) {
+        block = block.title(position);
+      }
       let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL))
+        .block(block)
         .highlight_symbol("┃")
         .repeat_highlight_symbol(true)
         .scroll_padding(1);
 
-      let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
-        .begin_symbol(None)
-        .end_symbol(None)
-        .track_symbol(None)
-        .thumb_symbol("▌");
-
-      self.scrollbar_state =
-        ScrollbarState::new(list.len()).position(self.state.selected().unwrap_or(0));
-
       f.render_stateful_widget(list, area, &mut self.state);
-      f.render_stateful_widget(
-        scrollbar,
-        area.inner(&Margin { vertical: 1, horizontal: 0 }),
-        &mut self.scrollbar_state,
-      );
+      self.area = area;
+      scroll::render_scrollbar(f, area, &self.state, feed_items.len());
     } else {
       let block = Block::new().borders(Borders::ALL).title("Feed List");
       f.render_widget(block, area);
@@ -199,3 +510,30 @@ impl Component for ArticleList {
     Ok(())
   }
 }
+
+/// Word-wraps `text` into at most `max_lines` lines, the first `first_width` columns wide and the
+/// rest `width` wide, ending in "…" when the text does not fit.
+fn wrap_preview(text: &str, first_width: usize, width: usize, max_lines: usize) -> Vec<String> {
+  let mut lines = vec![String::new()];
+  let mut line_width = 0;
+  for word in text.split_whitespace() {
+    let word_width = Span::raw(word).width();
+    let limit = if lines.len() == 1 { first_width } else { width };
+    if line_width > 0 && line_width + 1 + word_width > limit {
+      if lines.len() == max_lines {
+        lines.last_mut().unwrap().push_str(" …");
+        break;
+      }
+      lines.push(String::new());
+      line_width = 0;
+    }
+    let line = lines.last_mut().unwrap();
+    if line_width > 0 {
+      line.push(' ');
+      line_width += 1;
+    }
+    line.push_str(word);
+    line_width += word_width;
+  }
+  lines
+}
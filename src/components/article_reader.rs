@@ -1,128 +1,402 @@
-use std::default::Default;
+use std::{default::Default, sync::Arc};
 
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
-use html5ever::{parse_document, tendril::TendrilSink, tree_builder::TreeBuilderOpts, ParseOpts};
-use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 use ratatui::{
-  layout::Rect,
+  layout::{Position, Rect},
   style::{Color, Modifier, Style},
-  text::{Line, Span, Text},
-  widgets::{Block, Paragraph, Wrap},
+  text::{Line, Span, StyledGrapheme, Text},
+  widgets::{Block, Paragraph},
   Frame,
 };
+use similar::{ChangeTag, TextDiff};
 use tokio::sync::mpsc::UnboundedSender;
 
 use super::Component;
-use crate::action::Action;
+use crate::{
+  action::Action,
+  config::{Config, ReaderConfig},
+  render_html::{LinkRange, Rendered, Renderer},
+};
+
+/// Most blank lines between paragraphs that toggling the spacing cycles through.
+const MAX_PARAGRAPH_SPACING: u8 = 2;
+/// Fewest letters left on either side of a hyphen.
+const MIN_HYPHEN_PART: usize = 3;
+/// Columns the HTML source scrolls sideways per key press.
+const SOURCE_SCROLL_COLUMNS: u16 = 8;
 
 #[derive(Default)]
 pub struct ArticleReader<'a> {
   command_tx: Option<UnboundedSender<Action>>,
-  idx: usize,
+  config: Config,
+  /// The reader options from the config, as toggled while reading.
+  options: ReaderConfig,
+  /// Ignores `max_width` and runs the text across the whole reader.
+  full_width: bool,
+  /// Shows the extracted HTML as it is, for telling extraction bugs from rendering ones.
+  show_source: bool,
+  source_text: Option<Arc<Text<'a>>>,
   content: Option<String>,
   scroll_position: (u16, u16),
-  text: Option<Text<'a>>,
+  text: Option<Arc<Text<'a>>>,
+  /// Changes between two revisions of the article, shown instead of the text while set.
+  diff_text: Option<Arc<Text<'a>>>,
+  wrapped_text: Option<Arc<Text<'a>>>,
+  /// Whether each row of the wrapped text starts a line of the text, rather than continuing one.
+  row_starts: Vec<bool>,
+  /// Where each row of the wrapped text starts in the text, as given by [`row_positions`].
+  row_positions: Vec<(usize, usize)>,
+  wrap_width: u16,
+  /// Where visual selection started, the selection running from here to `cursor`. Both are rows
+  /// of the wrapped text.
+  visual_anchor: Option<usize>,
+  cursor: usize,
+  page_height: u16,
+  /// Where the links in the text lead.
+  links: Vec<String>,
+  /// Where the links are in the text.
+  link_ranges: Vec<LinkRange>,
+  /// Each piece of a link on screen as last drawn, with the index of the link.
+  drawn_links: Vec<(Rect, usize)>,
+  /// The link under the mouse, drawn reversed.
+  hovered_link: Option<usize>,
   active: bool,
 }
 
 impl<'a> ArticleReader<'a> {
-  pub fn new(idx: usize) -> Self {
+  pub fn new() -> Self {
     Self {
       command_tx: None,
-      idx,
+      config: Config::default(),
+      options: ReaderConfig::default(),
+      full_width: false,
+      show_source: false,
+      source_text: None,
       content: None,
       scroll_position: (0, 0),
       text: None,
+      diff_text: None,
+      wrapped_text: None,
+      row_starts: Vec::new(),
+      row_positions: Vec::new(),
+      wrap_width: 0,
+      visual_anchor: None,
+      cursor: 0,
+      page_height: 0,
+      links: Vec::new(),
+      link_ranges: Vec::new(),
+      drawn_links: Vec::new(),
+      hovered_link: None,
       active: false,
     }
   }
 
-  pub fn set_content(&mut self, content: String) {
-    self.content = Some(content);
+  pub fn content(&self) -> Option<&str> {
+    self.content.as_deref()
   }
 
-  pub fn build_text(&mut self) {
-    let opts = ParseOpts {
-      tree_builder: TreeBuilderOpts { drop_doctype: true, ..Default::default() },
-      ..Default::default()
+  /// The extracted article as plain text, one line per paragraph.
+  pub fn plain_text(&self) -> Option<String> {
+    self.text.as_deref().map(text_to_string)
+  }
+
+  /// Scrolls down by a page, returning `false` once the end of the article is already visible.
+  /// An article that is still loading counts as having more to show.
+  pub fn page_down(&mut self) -> bool {
+    let text = if self.show_source { &self.source_text } else { &self.wrapped_text };
+    let Some(text) = text else {
+      return true;
+    };
+    let bottom = self.scroll_position.0 as usize + self.page_height as usize;
+    if bottom >= text.lines.len() {
+      return false;
+    }
+    self.scroll_position.0 += self.page_height.saturating_sub(1).max(1);
+    true
+  }
+
+  /// Starts selecting rows from the top of the page, or stops if already selecting.
+  pub fn toggle_visual(&mut self) {
+    if self.visual_anchor.take().is_none() && !self.show_source && self.wrapped_text.is_some() {
+      self.cursor = self.scroll_position.0 as usize;
+      self.visual_anchor = Some(self.cursor);
+    }
+  }
+
+  pub fn visual(&self) -> bool {
+    self.visual_anchor.is_some()
+  }
+
+  fn selection_range(&self) -> Option<std::ops::RangeInclusive<usize>> {
+    let anchor = self.visual_anchor?;
+    Some(anchor.min(self.cursor)..=anchor.max(self.cursor))
+  }
+
+  /// Ends visual selection, returning the selected text. Rows wrapped from the same line are
+  /// joined back together, so a highlight does not depend on the width of the reader.
+  pub fn take_selection(&mut self) -> Option<String> {
+    let range = self.selection_range()?;
+    self.visual_anchor = None;
+    let text = self.wrapped_text.as_ref()?;
+    let mut selection = String::new();
+    for row in range.clone() {
+      let Some(line) = text.lines.get(row) else {
+        break;
+      };
+      let content: String = line.spans.iter().map(|span| span.content.as_ref()).collect();
+      if row > *range.start() {
+        selection.push(if self.row_starts.get(row) == Some(&false) { ' ' } else { '\n' });
+      }
+      // Justified rows have their spaces widened.
+      selection.push_str(&content.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    let selection = selection.trim_matches('\n').to_string();
+    (!selection.trim().is_empty()).then_some(selection)
+  }
+
+  /// Moves the end of the visual selection by `rows`, scrolling to keep it on the page.
+  fn move_cursor(&mut self, rows: isize) {
+    let Some(text) = &self.wrapped_text else {
+      return;
     };
+    let last = text.lines.len().saturating_sub(1);
+    self.cursor = self.cursor.saturating_add_signed(rows).min(last);
+    let top = self.scroll_position.0 as usize;
+    let page_height = (self.page_height as usize).max(1);
+    if self.cursor < top {
+      self.scroll_position.0 = self.cursor as u16;
+    } else if self.cursor >= top + page_height {
+      self.scroll_position.0 = (self.cursor + 1 - page_height) as u16;
+    }
+  }
 
-    let dom = parse_document(RcDom::default(), opts)
-      .from_utf8()
-      .read_from(&mut self.content.clone().unwrap().as_bytes())
-      .unwrap();
+  pub fn set_active(&mut self, active: bool) {
+    self.active = active;
+  }
 
-    self.text = Some(self.walk_dom(&dom.document));
+  /// Where the link drawn at (`column`, `row`) leads, if one is.
+  pub fn link_at(&self, column: u16, row: u16) -> Option<String> {
+    let position = Position { x: column, y: row };
+    let (_, link) = self.drawn_links.iter().find(|(area, _)| area.contains(position))?;
+    self.links.get(*link).cloned()
   }
 
-  fn walk_dom(&self, handle: &Handle) -> Text<'a> {
-    let mut text = Text::default();
-    self.walk_dom_recursive(handle, &mut text, &mut vec![]);
-    text
+  /// Highlights the link under the mouse at (`column`, `row`), if there is one.
+  pub fn hover(&mut self, column: u16, row: u16) {
+    let position = Position { x: column, y: row };
+    self.hovered_link =
+      self.drawn_links.iter().find(|(area, _)| area.contains(position)).map(|(_, link)| *link);
   }
 
-  fn walk_dom_recursive(&self, handle: &Handle, text: &mut Text<'a>, spans: &mut Vec<Span<'a>>) {
-    match &handle.data {
-      NodeData::Document => {
-        for child in handle.children.borrow().iter() {
-          self.walk_dom_recursive(child, text, spans);
+  /// Sets the article content, only rebuilding the text when it actually changed.
+  pub fn set_content(&mut self, content: String) {
+    self.hide_diff();
+    if self.content.as_ref() != Some(&content) {
+      self.content = Some(content);
+      self.source_text = None;
+      self.build_text();
+    }
+  }
+
+  /// Switches between the rendered article and the HTML it was rendered from.
+  pub fn toggle_source(&mut self) {
+    self.show_source = !self.show_source;
+    self.scroll_position = (0, 0);
+    self.visual_anchor = None;
+  }
+
+  /// The extracted HTML line by line, unwrapped.
+  fn source_text(&mut self) -> Option<Arc<Text<'a>>> {
+    if self.source_text.is_none() {
+      let lines: Vec<Line> =
+        self.content.as_ref()?.lines().map(|line| Line::raw(line.replace('\t', "    "))).collect();
+      self.source_text = Some(Arc::new(Text::from(lines)));
+    }
+    self.source_text.clone()
+  }
+
+  pub fn build_text(&mut self) {
+    let Rendered { text, links, link_ranges } =
+      self.html_to_text(self.content.as_deref().unwrap_or_default());
+    self.text = Some(Arc::new(text));
+    self.links = links;
+    self.link_ranges = link_ranges;
+    self.hovered_link = None;
+    self.wrapped_text = None;
+  }
+
+  fn html_to_text(&self, html: &str) -> Rendered<'a> {
+    Renderer::default()
+      .paragraph_spacing(self.options.paragraph_spacing)
+      .link_style(self.config.link_style())
+      .render(html)
+      .unwrap_or_else(|error| {
+        log::error!("Failed to render the article: {}", error);
+        Rendered { text: Text::raw(html.to_string()), links: Vec::new(), link_ranges: Vec::new() }
+      })
+  }
+
+  pub fn showing_diff(&self) -> bool {
+    self.diff_text.is_some()
+  }
+
+  /// Shows the words removed from and added to the article between two revisions of its HTML.
+  pub fn show_diff(&mut self, old: &str, new: &str) {
+    let old = text_to_string(&self.html_to_text(old).text);
+    let new = text_to_string(&self.html_to_text(new).text);
+
+    let mut lines = vec![Line::default()];
+    for change in TextDiff::from_words(&old, &new).iter_all_changes() {
+      let style = match change.tag() {
+        ChangeTag::Equal => Style::default(),
+        ChangeTag::Delete => {
+          Style::default().fg(self.config.color(Color::Red)).add_modifier(Modifier::CROSSED_OUT)
+        },
+        ChangeTag::Insert => {
+          Style::default().fg(self.config.color(Color::Green)).add_modifier(Modifier::BOLD)
+        },
+      };
+      for (i, part) in change.value().split('\n').enumerate() {
+        if i > 0 {
+          lines.push(Line::default());
         }
-      },
-      NodeData::Text { contents } => {
-        let content = contents.borrow();
-        spans.push(Span::raw(content.to_string()));
-      },
-      NodeData::Element { name, .. } => {
-        let tag_name = name.local.as_ref();
-
-        match tag_name {
-          "p" => {
-            // Push current spans as a new line if any
-            if !spans.is_empty() {
-              text.lines.push(Line::from(spans.clone()));
-              spans.clear();
-            }
-            // Process children of <p>
-            for child in handle.children.borrow().iter() {
-              self.walk_dom_recursive(child, text, spans);
-            }
-            // Push a new line after the paragraph
-            if !spans.is_empty() {
-              text.lines.push(Line::from(spans.clone()));
-              spans.clear();
-            }
-            text.lines.push(Line::from(vec![])); // Add an empty line
-          },
-          "h1" | "h2" | "h3" => {
-            for child in handle.children.borrow().iter() {
-              let mut heading_spans = vec![];
-              self.walk_dom_recursive(child, text, &mut heading_spans);
-              for span in heading_spans.iter_mut() {
-                span.style = Style::default().add_modifier(Modifier::BOLD);
-              }
-              spans.extend(heading_spans);
-            }
-          },
-          "a" => {
-            for child in handle.children.borrow().iter() {
-              let mut link_spans = vec![];
-              self.walk_dom_recursive(child, text, &mut link_spans);
-              for span in link_spans.iter_mut() {
-                span.style = Style::default().fg(Color::Blue);
-              }
-              spans.extend(link_spans);
-            }
-          },
-          _ => {
-            for child in handle.children.borrow().iter() {
-              self.walk_dom_recursive(child, text, spans);
+        if !part.is_empty() {
+          lines.last_mut().unwrap().spans.push(Span::styled(part.to_string(), style));
+        }
+      }
+    }
+
+    self.diff_text = Some(Arc::new(Text::from(lines)));
+    self.wrapped_text = None;
+    self.scroll_position = (0, 0);
+  }
+
+  pub fn hide_diff(&mut self) {
+    if self.diff_text.take().is_some() {
+      self.wrapped_text = None;
+    }
+  }
+
+  /// The part of `area` the text runs in, centred when it is wider than `max_width`.
+  fn column(&self, area: Rect) -> Rect {
+    let max_width = self.options.max_width;
+    if self.full_width || max_width == 0 || area.width <= max_width {
+      return area;
+    }
+    Rect { x: area.x + (area.width - max_width) / 2, width: max_width, ..area }
+  }
+
+  /// Returns the text wrapped to `width`, rewrapping only when the width or text changed.
+  fn wrapped_text(&mut self, width: u16) -> Option<Arc<Text<'a>>> {
+    let text = self.diff_text.as_ref().or(self.text.as_ref())?;
+    if self.wrapped_text.is_none() || self.wrap_width != width {
+      // Rows move when the text is wrapped again, so the page is kept at the same text instead.
+      let anchor = self.wrapped_text.as_ref().and_then(|wrapped| {
+        row_positions(&wrapped.lines, &self.row_starts).nth(self.scroll_position.0 as usize)
+      });
+      let ReaderConfig { hyphenate, justify, .. } = self.options;
+      let mut row_starts = Vec::new();
+      let lines: Vec<Line> = text
+        .lines
+        .iter()
+        .flat_map(|line| {
+          let mut rows = wrap_line(line, width as usize, hyphenate);
+          row_starts.extend((0..rows.len()).map(|i| i == 0));
+          // The last row of a paragraph stays ragged, as in print.
+          if justify {
+            let last = rows.len().saturating_sub(1);
+            for row in &mut rows[..last] {
+              *row = justify_line(row, width as usize);
             }
+          }
+          rows
+        })
+        .collect();
+      if let Some(anchor) = anchor {
+        let rows = row_positions(&lines, &row_starts).take_while(|&position| position <= anchor);
+        self.scroll_position.0 = rows.count().saturating_sub(1) as u16;
+      }
+      self.row_positions = row_positions(&lines, &row_starts).collect();
+      self.wrapped_text = Some(Arc::new(Text::from(lines)));
+      self.row_starts = row_starts;
+      self.wrap_width = width;
+      // The rows selected no longer hold the same text.
+      self.visual_anchor = None;
+    }
+    self.wrapped_text.clone()
+  }
+
+  /// Notes where the links in `row`, drawn from `origin` and starting `offset` characters into
+  /// `line` of the text, are on screen within `column`, and reverses the hovered one.
+  fn mark_links(
+    &mut self,
+    row: &mut Line<'a>,
+    origin: Position,
+    column: Rect,
+    line: usize,
+    offset: usize,
+  ) {
+    let ranges: Vec<LinkRange> =
+      self.link_ranges.iter().filter(|range| range.line == line).cloned().collect();
+    if ranges.is_empty() {
+      return;
+    }
+    let graphemes: Vec<StyledGrapheme> = row.styled_graphemes(Style::default()).collect();
+    let mut links: Vec<Option<usize>> = Vec::with_capacity(graphemes.len());
+    let mut position = offset;
+    for grapheme in &graphemes {
+      let counted = LinkRange::count(grapheme.symbol);
+      let link = (counted > 0)
+        .then(|| ranges.iter().find(|range| range.chars.contains(&position)))
+        .flatten()
+        .map(|range| range.link);
+      links.push(link);
+      position += counted;
+    }
+    // Spaces and hyphens are part of a link only between two of its characters.
+    let counted: Vec<usize> =
+      (0..graphemes.len()).filter(|&i| LinkRange::count(graphemes[i].symbol) > 0).collect();
+    for pair in counted.windows(2) {
+      if links[pair[0]].is_some() && links[pair[0]] == links[pair[1]] {
+        let link = links[pair[0]];
+        links[pair[0] + 1..pair[1]].fill(link);
+      }
+    }
+
+    let mut spans: Vec<Span<'a>> = Vec::new();
+    let mut x = origin.x;
+    for (grapheme, link) in graphemes.iter().zip(&links) {
+      let width = Span::raw(grapheme.symbol).width() as u16;
+      let mut style = grapheme.style;
+      if let Some(link) = *link {
+        let area = Rect::new(x, origin.y, width, 1).intersection(column);
+        match self.drawn_links.last_mut() {
+          Some((last, last_link))
+            if *last_link == link && last.y == area.y && last.right() == area.x =>
+          {
+            last.width += area.width;
           },
+          _ if !area.is_empty() => self.drawn_links.push((area, link)),
+          _ => {},
         }
-      },
-      _ => {},
+        if self.hovered_link == Some(link) {
+          style = style.add_modifier(Modifier::REVERSED);
+        }
+      }
+      match spans.last_mut() {
+        Some(span) if span.style == style => span.content.to_mut().push_str(grapheme.symbol),
+        _ => spans.push(Span::styled(grapheme.symbol.to_string(), style)),
+      }
+      x = x.saturating_add(width);
     }
+    row.spans = spans;
+  }
+
+  /// Wraps the text again on the next draw, keeping the page at the same place in it.
+  fn rewrap(&mut self) {
+    // No column is zero wide, so the width never matches.
+    self.wrap_width = 0;
   }
 }
 
@@ -132,9 +406,45 @@ impl Component for ArticleReader<'_> {
     Ok(())
   }
 
+  fn register_config_handler(&mut self, config: Config) -> Result<()> {
+    self.options = config.reader;
+    self.config = config;
+    Ok(())
+  }
+
   fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
     if self.active {
       match key.code {
+        KeyCode::Char('u') => self.toggle_source(),
+        KeyCode::Left if self.show_source => {
+          self.scroll_position.1 = self.scroll_position.1.saturating_sub(SOURCE_SCROLL_COLUMNS);
+        },
+        KeyCode::Right if self.show_source => {
+          self.scroll_position.1 = self.scroll_position.1.saturating_add(SOURCE_SCROLL_COLUMNS);
+        },
+        KeyCode::Char('w') => {
+          self.full_width = !self.full_width;
+        },
+        KeyCode::Char('J') => {
+          self.options.justify = !self.options.justify;
+          self.rewrap();
+        },
+        KeyCode::Char('-') => {
+          self.options.hyphenate = !self.options.hyphenate;
+          self.rewrap();
+        },
+        // Ctrl-P opens the quick switcher.
+        KeyCode::Char('p') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+          self.options.paragraph_spacing =
+            (self.options.paragraph_spacing + 1) % (MAX_PARAGRAPH_SPACING + 1);
+          if self.content.is_some() {
+            self.build_text();
+          }
+        },
+        KeyCode::Char('v') => self.toggle_visual(),
+        KeyCode::Esc => self.visual_anchor = None,
+        KeyCode::Char('k') | KeyCode::Up if self.visual() => self.move_cursor(-1),
+        KeyCode::Char('j') | KeyCode::Down if self.visual() => self.move_cursor(1),
         KeyCode::Char('k') => {
           if self.scroll_position.0 > 0 {
             self.scroll_position.0 = self.scroll_position.0 - 1;
@@ -143,12 +453,6 @@ impl Component for ArticleReader<'_> {
         KeyCode::Char('j') => {
           self.scroll_position.0 = self.scroll_position.0 + 1;
         },
-        KeyCode::Char('h') => {
-          if let Some(tx) = &self.command_tx {
-            tx.send(Action::ActivateFeedList)?;
-            self.active = false;
-          }
-        },
         _ => {},
       }
     }
@@ -174,38 +478,321 @@ impl Component for ArticleReader<'_> {
 
   fn update(&mut self, action: Action) -> Result<Option<Action>> {
     match action {
-      Action::UpdateReader(idx, content) => {
-        if self.idx == idx {
-          self.content = Some(content);
-          self.build_text();
-          self.scroll_position = (0, 0);
-        }
-      },
-      Action::ActivateFeedList => {
-        self.active = false;
-      },
-      Action::ActivateReader => {
-        self.active = true;
+      Action::UpdateReader(_, content) => {
+        self.set_content(content);
+        self.scroll_position = (0, 0);
       },
+      Action::ShowRevisionDiff(_, old, new) => self.show_diff(&old, &new),
       _ => {},
     }
     Ok(None)
   }
 
+  fn hyperlinks(&self) -> Vec<(Rect, String)> {
+    self
+      .drawn_links
+      .iter()
+      .filter_map(|(area, link)| Some((*area, self.links.get(*link)?.clone())))
+      .collect()
+  }
+
   fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-    if let Some(text) = self.text.clone() {
-      let paragraph = Paragraph::new(text).wrap(Wrap { trim: true }).scroll(self.scroll_position);
+    let palette = *self.config.palette();
+    let mut block = if self.active {
+      Block::bordered().style(Style::default().fg(palette.accent))
+    } else {
+      Block::bordered()
+    };
+    if self.show_source {
+      block = block.title(" HTML source ");
+    } else if let Some(range) = self.selection_range() {
+      block = block.title(format!(" VISUAL · {} lines · y saves a highlight ", range.count()));
+    }
+    let inner = block.inner(area);
+    let column = if self.show_source { inner } else { self.column(inner) };
+    self.page_height = inner.height;
+
+    let text = if self.show_source { self.source_text() } else { self.wrapped_text(column.width) };
+    self.drawn_links.clear();
+    if let Some(text) = text {
+      let max_scroll = text.lines.len().saturating_sub(inner.height as usize) as u16;
+      self.scroll_position.0 = self.scroll_position.0.min(max_scroll);
+
+      // Only the visible lines are handed to the paragraph, so the cost of a frame does not
+      // depend on the length of the article.
+      let selection = if self.show_source { None } else { self.selection_range() };
+      let mut visible: Vec<Line> = text
+        .lines
+        .iter()
+        .enumerate()
+        .skip(self.scroll_position.0 as usize)
+        .take(inner.height as usize)
+        .map(|(row, line)| {
+          match &selection {
+            Some(range) if range.contains(&row) => {
+              line.clone().patch_style(Style::default().add_modifier(Modifier::REVERSED))
+            },
+            _ => line.clone(),
+          }
+        })
+        .collect();
+      // The diff and the source have no links.
+      if !self.show_source && self.diff_text.is_none() {
+        let positions: Vec<(usize, usize)> =
+          self.row_positions.iter().skip(self.scroll_position.0 as usize).copied().collect();
+        for ((y, row), (line, offset)) in (column.y..).zip(visible.iter_mut()).zip(positions) {
+          self.mark_links(row, Position { x: column.x, y }, column, line, offset);
+        }
+      }
+      // Source lines are not wrapped, so they scroll sideways instead.
+      let mut paragraph = Paragraph::new(visible).scroll((0, self.scroll_position.1));
       if self.active {
-        let paragraph = paragraph
-          .block(Block::bordered().style(Style::default().fg(Color::Green)))
-          .style(Style::default().fg(Color::White));
-        f.render_widget(paragraph, area);
-      } else {
-        let paragraph = paragraph.block(Block::bordered());
-        f.render_widget(paragraph, area);
+        paragraph = paragraph.style(Style::default().fg(palette.foreground));
       }
+      f.render_widget(block, area);
+      f.render_widget(paragraph, column);
     }
 
     Ok(())
   }
 }
+
+/// Where each row of wrapped text starts in the text: the line it belongs to and how many
+/// characters of that line, spaces and hyphens aside, come before it. Unlike row numbers, these
+/// stay the same however the text is wrapped, justified or hyphenated.
+fn row_positions<'r>(
+  rows: &'r [Line],
+  row_starts: &'r [bool],
+) -> impl Iterator<Item = (usize, usize)> + 'r {
+  let mut position = (0, 0);
+  rows.iter().zip(row_starts).enumerate().map(move |(i, (row, &starts))| {
+    if i > 0 && starts {
+      position = (position.0 + 1, 0);
+    }
+    let start = position;
+    position.1 += row
+      .spans
+      .iter()
+      .flat_map(|span| span.content.chars())
+      .filter(|c| !c.is_whitespace() && *c != '-')
+      .count();
+    start
+  })
+}
+
+fn text_to_string(text: &Text) -> String {
+  let lines: Vec<String> = text
+    .lines
+    .iter()
+    .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect())
+    .collect();
+  lines.join("\n")
+}
+
+/// Word wraps a line to `width` columns, trimming whitespace at the wrap points and hard
+/// breaking words that are wider than a whole line. With `hyphenate`, words that do not fit are
+/// split between two letters instead of moving whole to the next row.
+fn wrap_line<'a>(line: &Line<'a>, width: usize, hyphenate: bool) -> Vec<Line<'a>> {
+  if width == 0 || line.width() <= width {
+    return vec![line.clone()];
+  }
+
+  let mut wrapper =
+    LineWrapper { width, hyphenate, lines: Vec::new(), row: Vec::new(), row_width: 0 };
+  let mut word: Vec<StyledGrapheme> = Vec::new();
+  for grapheme in line.styled_graphemes(Style::default()) {
+    if grapheme.symbol.chars().all(char::is_whitespace) {
+      wrapper.push_word(&word);
+      word.clear();
+      wrapper.push_space(grapheme);
+    } else {
+      word.push(grapheme);
+    }
+  }
+  wrapper.push_word(&word);
+  wrapper.finish()
+}
+
+/// Widens the spaces between words so that `line` fills `width` columns, the leftmost gaps
+/// taking any remainder.
+fn justify_line<'a>(line: &Line<'_>, width: usize) -> Line<'a> {
+  let is_space = |grapheme: &StyledGrapheme| grapheme.symbol.chars().all(char::is_whitespace);
+  let graphemes: Vec<StyledGrapheme> = line.styled_graphemes(Style::default()).collect();
+  let gaps = graphemes.iter().filter(|grapheme| is_space(grapheme)).count();
+  let extra = width.saturating_sub(line.width());
+  if gaps == 0 || extra == 0 {
+    return Line::from(
+      line
+        .spans
+        .iter()
+        .map(|span| Span::styled(span.content.to_string(), span.style))
+        .collect::<Vec<_>>(),
+    );
+  }
+
+  let mut spans: Vec<Span> = Vec::new();
+  let mut gap = 0;
+  for grapheme in &graphemes {
+    let mut symbol = grapheme.symbol.to_string();
+    if is_space(grapheme) {
+      let widen = extra / gaps + usize::from(gap < extra % gaps);
+      symbol.push_str(&" ".repeat(widen));
+      gap += 1;
+    }
+    match spans.last_mut() {
+      Some(span) if span.style == grapheme.style => span.content.to_mut().push_str(&symbol),
+      _ => spans.push(Span::styled(symbol, grapheme.style)),
+    }
+  }
+  Line::from(spans)
+}
+
+struct LineWrapper<'a> {
+  width: usize,
+  hyphenate: bool,
+  lines: Vec<Line<'a>>,
+  row: Vec<Span<'a>>,
+  row_width: usize,
+}
+
+impl<'a> LineWrapper<'a> {
+  fn push_grapheme(&mut self, grapheme: &StyledGrapheme) {
+    match self.row.last_mut() {
+      Some(span) if span.style == grapheme.style => span.content.to_mut().push_str(grapheme.symbol),
+      _ => self.row.push(Span::styled(grapheme.symbol.to_string(), grapheme.style)),
+    }
+    self.row_width += Span::raw(grapheme.symbol).width();
+  }
+
+  fn push_space(&mut self, grapheme: StyledGrapheme) {
+    if self.row_width == 0 {
+      return;
+    }
+    if self.row_width + Span::raw(grapheme.symbol).width() > self.width {
+      self.break_row();
+    } else {
+      self.push_grapheme(&grapheme);
+    }
+  }
+
+  fn push_word(&mut self, mut word: &[StyledGrapheme]) {
+    let word_width: usize = word.iter().map(|g| Span::raw(g.symbol).width()).sum();
+    if self.row_width > 0 && self.row_width + word_width > self.width {
+      if self.hyphenate {
+        word = self.push_hyphenated(word);
+      }
+      self.break_row();
+    }
+    for grapheme in word {
+      let grapheme_width = Span::raw(grapheme.symbol).width();
+      if self.row_width > 0 && self.row_width + grapheme_width > self.width {
+        self.break_row();
+      }
+      self.push_grapheme(grapheme);
+    }
+  }
+
+  /// Fits as much of `word` on the row as leaves room for a hyphen and returns the rest, or the
+  /// whole word when it cannot be split between two letters with enough on either side.
+  fn push_hyphenated<'w, 'g>(
+    &mut self,
+    word: &'w [StyledGrapheme<'g>],
+  ) -> &'w [StyledGrapheme<'g>] {
+    let room = self.width.saturating_sub(self.row_width + 1);
+    let mut split = 0;
+    let mut split_width = 0;
+    for grapheme in word {
+      split_width += Span::raw(grapheme.symbol).width();
+      if split_width > room {
+        break;
+      }
+      split += 1;
+    }
+
+    let is_letter = |grapheme: &StyledGrapheme| grapheme.symbol.chars().all(char::is_alphabetic);
+    while split >= MIN_HYPHEN_PART
+      && (word.len() - split < MIN_HYPHEN_PART
+        || !is_letter(&word[split - 1])
+        || !is_letter(&word[split]))
+    {
+      split -= 1;
+    }
+    if split < MIN_HYPHEN_PART {
+      return word;
+    }
+
+    for grapheme in &word[..split] {
+      self.push_grapheme(grapheme);
+    }
+    self.push_grapheme(&StyledGrapheme { symbol: "-", style: word[split - 1].style });
+    &word[split..]
+  }
+
+  fn break_row(&mut self) {
+    if let Some(span) = self.row.last_mut() {
+      let trimmed_len = span.content.trim_end().len();
+      span.content.to_mut().truncate(trimmed_len);
+    }
+    self.lines.push(Line::from(std::mem::take(&mut self.row)));
+    self.row_width = 0;
+  }
+
+  fn finish(mut self) -> Vec<Line<'a>> {
+    if !self.row.is_empty() {
+      self.break_row();
+    }
+    self.lines
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+  use ratatui::{backend::TestBackend, Terminal};
+
+  use super::*;
+
+  const LINKED: &str =
+    r#"<p>See <a href="https://example.com/a">the well-linked words</a> here</p>"#;
+
+  /// Draws `reader` on a screen `width` wide and five rows high, returning the screen as text.
+  fn draw(reader: &mut ArticleReader<'_>, width: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, 5)).unwrap();
+    let frame = terminal.draw(|f| reader.draw(f, f.size()).unwrap()).unwrap();
+    crate::testing::buffer_text(frame.buffer)
+  }
+
+  #[test]
+  fn a_link_wrapped_over_rows_is_found_on_each() {
+    let mut reader = ArticleReader::new();
+    reader.set_content(LINKED.to_string());
+    let screen = draw(&mut reader, 16);
+    assert!(screen.contains("│See the       │\n│well-linked   │\n│words here    │"), "{screen}");
+
+    let url = "https://example.com/a".to_string();
+    assert_eq!(reader.hyperlinks(), vec![
+      (Rect::new(5, 1, 3, 1), url.clone()),
+      (Rect::new(1, 2, 11, 1), url.clone()),
+      (Rect::new(1, 3, 5, 1), url.clone()),
+    ]);
+    assert_eq!(reader.link_at(1, 2), Some(url));
+    assert_eq!(reader.link_at(1, 1), None);
+    assert_eq!(reader.link_at(7, 3), None);
+  }
+
+  #[test]
+  fn a_hyphenated_and_justified_link_keeps_its_place() {
+    let mut reader = ArticleReader::new();
+    reader.options.hyphenate = true;
+    reader.options.justify = true;
+    reader.set_content(LINKED.to_string());
+    let screen = draw(&mut reader, 16);
+    assert!(screen.contains("│See  the  wel-│\n│l-linked words│"), "{screen}");
+
+    let url = "https://example.com/a".to_string();
+    assert_eq!(reader.hyperlinks(), vec![
+      (Rect::new(6, 1, 8, 1), url.clone()),
+      (Rect::new(1, 2, 14, 1), url.clone()),
+    ]);
+  }
+}
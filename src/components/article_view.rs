@@ -1,41 +1,548 @@
 use std::{ops::Index, usize};
 
+use chrono::{DateTime, Utc};
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyEvent, MouseEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
-  layout::{Constraint, Direction, Layout, Rect},
+  layout::{Constraint, Direction, Layout, Position, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span},
+  widgets::{Block, Paragraph, Wrap},
   Frame,
 };
 use tokio::sync::mpsc::UnboundedSender;
+use unicode_width::UnicodeWidthStr;
 
 use super::{article_list::ArticleList, article_reader::ArticleReader, Component};
-use crate::{action::Action, config::Config, tui::Event};
+use crate::{
+  action::{Action, MenuItem, TabId},
+  config::{Config, SortOrder},
+  db::{FeedItem, FeedPosition},
+  export::ExportedArticle,
+  media::{self, Enclosure},
+  summary,
+  tui::Event,
+};
+
+/// Lines of a note shown beneath the reader before it is cut off.
+const NOTE_MAX_LINES: usize = 6;
+/// Rows of a summary shown above the reader before it is cut off.
+const SUMMARY_MAX_ROWS: u16 = 8;
+/// Related articles listed beneath the reader.
+pub const RELATED_ITEMS: usize = 5;
+/// Attachments shown at once in the media pane, which scrolls through any more.
+const MEDIA_ROWS: usize = 4;
+
+/// Which half of the article view receives input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+  List,
+  Reader,
+}
 
 pub struct ArticleView<'a> {
-  idx: usize,
-  selected_idx: usize,
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  tab: TabId,
+  selected_tab: TabId,
+  focus: Focus,
+  breadcrumb: Vec<String>,
+  article_title: Option<String>,
+  article_date: Option<DateTime<Utc>>,
+  article_id: Option<i32>,
+  /// The note on the article in the reader, shown beneath it.
+  article_note: Option<String>,
+  /// The summary of the article in the reader, shown above it.
+  summary: Option<String>,
+  /// Whether the summary is shown, toggled with `z` and kept from one article to the next.
+  show_summary: bool,
+  /// A summary of the article in the reader is being made.
+  summarizing: bool,
+  /// Articles sharing categories or title words with the one in the reader.
+  related: Vec<FeedItem>,
+  /// The related article `]` and `[` moved to, opened with Enter.
+  selected_related: Option<usize>,
+  /// Files attached to the article in the reader, listed in the media pane.
+  enclosures: Vec<Enclosure>,
+  /// The attachment `}` and `{` moved to, the first one until then.
+  selected_enclosure: usize,
+  feed_id: Option<i32>,
+  list_area: Rect,
+  reader_area: Rect,
   article_list: ArticleList,
   article_reader: ArticleReader<'a>,
 }
 
 impl<'a> ArticleView<'a> {
-  pub fn new(idx: usize) -> Self {
-    let article_list = ArticleList::new(idx);
-    let article_reader = ArticleReader::new(idx);
-    Self { idx, selected_idx: idx, article_list, article_reader }
+  /// Creates an article view whose header starts with `breadcrumb`, e.g. the group and feed
+  /// names the articles belong to.
+  pub fn new(tab: TabId, breadcrumb: Vec<String>) -> Self {
+    let article_list = ArticleList::new(tab);
+    let article_reader = ArticleReader::new();
+    let mut article_view = Self {
+      command_tx: None,
+      config: Config::default(),
+      tab,
+      selected_tab: tab,
+      focus: Focus::List,
+      breadcrumb,
+      article_title: None,
+      article_date: None,
+      article_id: None,
+      article_note: None,
+      summary: None,
+      show_summary: true,
+      summarizing: false,
+      related: Vec::new(),
+      selected_related: None,
+      enclosures: Vec::new(),
+      selected_enclosure: 0,
+      feed_id: None,
+      list_area: Rect::default(),
+      reader_area: Rect::default(),
+      article_list,
+      article_reader,
+    };
+    article_view.set_focus(Focus::List);
+    article_view
+  }
+
+  pub fn tab(&self) -> TabId {
+    self.tab
+  }
+
+  pub fn with_feed_names(mut self, show_feed_name: bool) -> Self {
+    self.article_list = self.article_list.with_feed_names(show_feed_name);
+    self
+  }
+
+  /// Opens the list sorted and filtered as configured for its feed.
+  pub fn with_list_view(mut self, sort: Option<SortOrder>, unread_only: bool) -> Self {
+    self.article_list = self.article_list.with_sort(sort).with_unread_only(unread_only);
+    self
+  }
+
+  /// Remembers the list position under `feed_id` whenever the selection changes.
+  pub fn with_feed_id(mut self, feed_id: i32) -> Self {
+    self.feed_id = Some(feed_id);
+    self
+  }
+
+  fn save_position(&self, previous: Option<FeedPosition>) -> Result<()> {
+    let position = self.article_list.position();
+    if let (Some(feed_id), Some(position), Some(tx)) = (self.feed_id, position, &self.command_tx) {
+      if Some(position) != previous {
+        tx.send(Action::SaveFeedPosition(feed_id, position))?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Opens the selected article in the reader and moves focus there, or in the external viewer
+  /// when that replaces the reader.
+  fn open_selected(&mut self) -> Result<()> {
+    let viewer = &self.config.viewer;
+    if viewer.replace_reader && viewer.command.is_some() {
+      return self.open_in_viewer();
+    }
+    self.article_list.open_selected()?;
+    if let Some(item) = self.article_list.selected_item().cloned() {
+      self.show_article(&item);
+    }
+    Ok(())
+  }
+
+  /// Opens the related article picked with `]` and `[` in the reader. The list keeps its
+  /// selection, as the article need not be in it.
+  fn open_related(&mut self) -> Result<()> {
+    let Some(item) = self.selected_related.and_then(|idx| self.related.get(idx)).cloned() else {
+      return Ok(());
+    };
+    if let Some(tx) = &self.command_tx {
+      tx.send(Action::RequestUpdateReader(self.tab, item.clone()))?;
+    }
+    self.show_article(&item);
+    Ok(())
+  }
+
+  /// Takes over the header and footers of the article just sent to the reader.
+  fn show_article(&mut self, item: &FeedItem) {
+    self.article_title = Some(item.title.clone());
+    self.article_date = Some(item.pub_date);
+    self.article_id = Some(item.id);
+    self.article_note = item.note.clone();
+    self.summary = None;
+    self.summarizing = false;
+    self.related.clear();
+    self.selected_related = None;
+    self.enclosures.clear();
+    self.selected_enclosure = 0;
+    self.set_focus(Focus::Reader);
+  }
+
+  fn select_related(&mut self, forward: bool) {
+    let len = self.related.len();
+    if len == 0 {
+      return;
+    }
+    self.selected_related = Some(match (self.selected_related, forward) {
+      (None, true) => 0,
+      (None, false) => len - 1,
+      (Some(idx), true) => (idx + 1) % len,
+      (Some(idx), false) => idx.checked_sub(1).unwrap_or(len - 1),
+    });
+  }
+
+  fn select_enclosure(&mut self, forward: bool) {
+    let len = self.enclosures.len();
+    if len == 0 {
+      return;
+    }
+    self.selected_enclosure = if forward {
+      (self.selected_enclosure + 1) % len
+    } else {
+      self.selected_enclosure.checked_sub(1).unwrap_or(len - 1)
+    };
+  }
+
+  /// Opens the selected attachment with the browser, or downloads it.
+  fn use_enclosure(&self, download: bool) -> Result<()> {
+    let (Some(enclosure), Some(tx)) =
+      (self.enclosures.get(self.selected_enclosure), &self.command_tx)
+    else {
+      return Ok(());
+    };
+    if download {
+      tx.send(Action::DownloadEnclosure(enclosure.clone()))?;
+    } else {
+      tx.send(Action::OpenUrl(enclosure.url.clone()))?;
+    }
+    Ok(())
+  }
+
+  /// Shows or hides the summary of the article in the reader, asking the summarizer for one if
+  /// there is none yet.
+  fn toggle_summary(&mut self) -> Result<()> {
+    if self.summary.is_some() || self.summarizing {
+      self.show_summary = !self.show_summary;
+      return Ok(());
+    }
+    if !self.config.summary.is_enabled() {
+      if let Some(tx) = &self.command_tx {
+        tx.send(Action::Error(summary::SummaryError::NotConfigured.to_string()))?;
+      }
+      return Ok(());
+    }
+    self.show_summary = true;
+    self.request_summary()
+  }
+
+  /// Sends the text of the article in the reader to the summarizer, once it has been extracted.
+  fn request_summary(&mut self) -> Result<()> {
+    let item = self.article_list.selected_item().filter(|item| Some(item.id) == self.article_id);
+    let text = self.article_reader.plain_text();
+    if let (Some(item), Some(text), Some(tx)) = (item, text, &self.command_tx) {
+      tx.send(Action::Summarize(item.clone(), text))?;
+      self.summarizing = true;
+    }
+    Ok(())
+  }
+
+  /// Space bar workflow: opens the selected article, then pages through it and moves on to the
+  /// next unread article once its end is reached.
+  fn churn(&mut self) -> Result<()> {
+    if self.focus == Focus::Reader {
+      if self.article_reader.page_down() {
+        return Ok(());
+      }
+      let previous = self.article_list.position();
+      if !self.article_list.select_next_unread() {
+        return Ok(());
+      }
+      self.save_position(previous)?;
+    }
+    self.open_selected()?;
+    if let (Some(item_id), Some(tx)) = (self.article_list.mark_selected_read(), &self.command_tx) {
+      tx.send(Action::MarkRead(vec![item_id]))?;
+    }
+    Ok(())
+  }
+
+  /// Opens the context menu of the selected article, with what the reader can do with it once
+  /// it is open.
+  fn show_menu(&self) -> Result<()> {
+    let (Some(item), Some(tx)) = (self.article_list.selected_item(), &self.command_tx) else {
+      return Ok(());
+    };
+    let mut items = Vec::new();
+    if self.focus == Focus::List {
+      items.push(MenuItem::new("Open", KeyCode::Enter));
+    }
+    items.push(MenuItem::new("Open in browser", KeyCode::Char('o')));
+    if item.comments.is_some() {
+      items.push(MenuItem::new("Open comments", KeyCode::Char('c')));
+    }
+    items.push(MenuItem::new("Open in viewer", KeyCode::Char('V')));
+    match self.focus {
+      Focus::List => {
+        if !item.read {
+          items.push(MenuItem::new("Mark read", KeyCode::Char('r')));
+        }
+        items.push(MenuItem::new("Mark all read", KeyCode::Char('A')));
+      },
+      Focus::Reader => {
+        items.push(MenuItem::new("Read aloud", KeyCode::Char('r')));
+        if self.config.summary.is_enabled() {
+          items.push(MenuItem::new("Summarize", KeyCode::Char('z')));
+        }
+        items.push(MenuItem::new("Show changes", KeyCode::Char('d')));
+        items.push(MenuItem::new("Export", KeyCode::Char('e')));
+        items.push(MenuItem::new("Email", KeyCode::Char('m')));
+      },
+    }
+    items.push(MenuItem::new("Edit note", KeyCode::Char('n')));
+    if self.config.task.command.is_some() {
+      items.push(MenuItem::new("Add as task", KeyCode::Char('t')));
+    }
+    if !self.config.share.is_empty() {
+      items.push(MenuItem::new("Share", KeyCode::Char('P')));
+    }
+    items.push(MenuItem::new("Article info", KeyCode::Char('i')));
+    items.push(MenuItem::new("Feed info", KeyCode::Char('I')));
+    tx.send(Action::ShowContextMenu(item.title.clone(), items))?;
+    Ok(())
+  }
+
+  /// Asks before marking everything in the list read, the articles filtered out too.
+  fn confirm_mark_all_read(&self) -> Result<()> {
+    let unread = self.article_list.unread_count();
+    if let (true, Some(tx)) = (unread > 0, &self.command_tx) {
+      let prompt = format!("Mark all {unread} unread articles here as read?");
+      tx.send(Action::Confirm(prompt, Box::new(Action::MarkAllRead(self.tab))))?;
+    }
+    Ok(())
+  }
+
+  /// The article shown in the reader, once its content has been extracted.
+  fn shown_article(&self) -> Option<ExportedArticle> {
+    let item = self.article_list.selected_item()?;
+    Some(ExportedArticle {
+      title: item.title.clone(),
+      url: item.url.clone(),
+      content: self.article_reader.content()?.to_string(),
+    })
+  }
+
+  /// Opens the export dialog for the article shown in the reader.
+  fn request_export(&self) -> Result<()> {
+    if let (Some(article), Some(tx)) = (self.shown_article(), &self.command_tx) {
+      tx.send(Action::ShowExportDialog(article))?;
+    }
+    Ok(())
+  }
+
+  /// Mails the article shown in the reader to the configured address.
+  fn request_email(&self) -> Result<()> {
+    if let (Some(article), Some(tx)) = (self.shown_article(), &self.command_tx) {
+      tx.send(Action::EmailArticle(article))?;
+    }
+    Ok(())
+  }
+
+  fn open_in_viewer(&self) -> Result<()> {
+    if let (Some(item), Some(tx)) = (self.article_list.selected_item(), &self.command_tx) {
+      tx.send(Action::OpenInViewer(item.clone()))?;
+    }
+    Ok(())
+  }
+
+  fn capture_task(&self, item: &FeedItem) -> Result<()> {
+    if let Some(tx) = &self.command_tx {
+      tx.send(Action::CaptureTask(item.clone()))?;
+    }
+    Ok(())
+  }
+
+  fn share(&self) -> Result<()> {
+    if let (Some(item), Some(tx)) = (self.article_list.selected_item(), &self.command_tx) {
+      if self.config.share.is_empty() {
+        tx.send(Action::Error("No share targets in the config".to_string()))?;
+      } else {
+        tx.send(Action::ShowShareMenu(item.clone()))?;
+      }
+    }
+    Ok(())
+  }
+
+  fn edit_note(&self) -> Result<()> {
+    if let (Some(item), Some(tx)) = (self.article_list.selected_item(), &self.command_tx) {
+      tx.send(Action::ShowNoteEditor(item.clone()))?;
+    }
+    Ok(())
+  }
+
+  /// Saves the rows selected in the reader as a highlight of the article shown.
+  fn save_highlight(&mut self) -> Result<()> {
+    let text = self.article_reader.take_selection();
+    if let (Some(text), Some(item_id), Some(tx)) = (text, self.article_id, &self.command_tx) {
+      tx.send(Action::SaveHighlight(item_id, text))?;
+    }
+    Ok(())
+  }
+
+  fn draw_related(&self, f: &mut Frame<'_>, area: Rect) {
+    let palette = *self.config.palette();
+    let lines: Vec<Line> = self
+      .related
+      .iter()
+      .enumerate()
+      .map(|(i, item)| {
+        let selected = self.selected_related == Some(i);
+        let title_style = if selected {
+          self.config.selected_style()
+        } else {
+          Style::default().fg(palette.foreground)
+        };
+        Line::from(vec![
+          Span::styled(if selected { "┃ " } else { "  " }, title_style),
+          Span::styled(item.title.clone(), title_style),
+          Span::styled(format!(" · {}", item.feed_name), Style::default().fg(palette.muted)),
+        ])
+      })
+      .collect();
+    let block = Block::bordered()
+      .title(" Related · ]/[ select, Enter open ")
+      .border_style(Style::default().fg(palette.muted));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+  }
+
+  fn draw_media(&self, f: &mut Frame<'_>, area: Rect) {
+    let palette = *self.config.palette();
+    let muted_style = Style::default().fg(palette.muted);
+    let offset = (self.selected_enclosure + 1).saturating_sub(MEDIA_ROWS);
+    let lines: Vec<Line> = self
+      .enclosures
+      .iter()
+      .enumerate()
+      .skip(offset)
+      .take(MEDIA_ROWS)
+      .map(|(i, enclosure)| {
+        let selected = self.selected_enclosure == i && self.enclosures.len() > 1;
+        let name_style = if selected {
+          self.config.selected_style()
+        } else {
+          Style::default().fg(palette.foreground)
+        };
+        let name = match enclosure.file_name() {
+          "" => enclosure.url.as_str(),
+          name => name,
+        };
+        let mut details = vec![enclosure.mime_type.as_str()];
+        let size = enclosure.length.map(media::format_size);
+        details.extend(size.as_deref());
+        details.retain(|detail| !detail.is_empty());
+        let mut spans = vec![
+          Span::styled(if selected { "┃ " } else { "  " }, name_style),
+          Span::styled(format!("[{}] ", enclosure.kind()), muted_style),
+          Span::styled(name.to_string(), name_style),
+        ];
+        if !details.is_empty() {
+          spans.push(Span::styled(format!(" · {}", details.join(" · ")), muted_style));
+        }
+        Line::from(spans)
+      })
+      .collect();
+    let title = if self.enclosures.len() > 1 {
+      format!(" Media ({}) · }}/{{ select, O open, D download ", self.enclosures.len())
+    } else {
+      " Media · O open, D download ".to_string()
+    };
+    let block = Block::bordered().title(title).border_style(muted_style);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+  }
+
+  /// Draws the summary, or a placeholder while it is being made, returning the rows it took at
+  /// the top of `area`.
+  fn draw_summary(&self, f: &mut Frame<'_>, area: Rect) -> u16 {
+    let palette = *self.config.palette();
+    let text = match (&self.summary, self.summarizing) {
+      (Some(summary), _) => summary.as_str(),
+      (None, true) => "Summarizing…",
+      (None, false) => return 0,
+    };
+    let paragraph = Paragraph::new(text)
+      .style(Style::default().fg(palette.foreground))
+      .wrap(Wrap { trim: true })
+      .block(
+        Block::bordered()
+          .title(" Summary · z hide ")
+          .border_style(Style::default().fg(palette.accent)),
+      );
+    // Roughly the rows the text wraps to, which can be more where words break early.
+    let width = usize::from(area.width.saturating_sub(2)).max(1);
+    let lines: usize = text.lines().map(|line| line.width().div_ceil(width).max(1)).sum();
+    let rows = (lines as u16).min(SUMMARY_MAX_ROWS).saturating_add(2).min(area.height);
+    f.render_widget(paragraph, Rect { height: rows, ..area });
+    rows
+  }
+
+  fn open_url(&self, url: Option<String>) -> Result<()> {
+    if let (Some(url), Some(tx)) = (url.filter(|url| !url.is_empty()), &self.command_tx) {
+      tx.send(Action::OpenUrl(url))?;
+    }
+    Ok(())
+  }
+
+  fn breadcrumb_line(&self) -> Line<'_> {
+    let muted_style = Style::default().fg(self.config.palette().muted);
+    let separator = Span::styled(" ▸ ", muted_style);
+    let crumbs = self.breadcrumb.iter().chain(self.article_title.as_ref());
+    let mut spans = Vec::new();
+    for (i, crumb) in crumbs.enumerate() {
+      if i > 0 {
+        spans.push(separator.clone());
+      }
+      spans.push(Span::styled(crumb.as_str(), Style::default().add_modifier(Modifier::BOLD)));
+    }
+    if let Some(date) = self.article_date {
+      spans
+        .push(Span::styled(format!(" · {}", self.config.display.format_date(date)), muted_style));
+    }
+    Line::from(spans)
+  }
+
+  pub fn reader_focused(&self) -> bool {
+    self.focus == Focus::Reader
+  }
+
+  /// Moves input to `focus`, naming the article or the list in the terminal's title.
+  fn set_focus(&mut self, focus: Focus) {
+    self.focus = focus;
+    self.article_list.set_active(focus == Focus::List);
+    self.article_reader.set_active(focus == Focus::Reader);
+    if let Some(tx) = &self.command_tx {
+      let context = match focus {
+        Focus::List => self.breadcrumb.last().cloned(),
+        Focus::Reader => self.article_title.clone(),
+      };
+      // The channel only closes as the app quits.
+      let _ = tx.send(Action::SetTerminalTitle(context));
+    }
   }
 }
 
 impl Component for ArticleView<'_> {
   fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
     self.article_list.register_action_handler(tx.clone())?;
-    self.article_reader.register_action_handler(tx)?;
+    self.article_reader.register_action_handler(tx.clone())?;
+    self.command_tx = Some(tx);
     Ok(())
   }
 
   fn register_config_handler(&mut self, config: Config) -> Result<()> {
     self.article_list.register_config_handler(config.clone())?;
-    self.article_reader.register_config_handler(config)?;
+    self.article_reader.register_config_handler(config.clone())?;
+    self.config = config;
     Ok(())
   }
 
@@ -46,49 +553,286 @@ impl Component for ArticleView<'_> {
   }
 
   fn handle_events(&mut self, event: Option<Event>) -> Result<Option<Action>> {
-    if let Some(event) = event.clone() {
-      self.article_list.handle_events(Some(event.clone()))?;
-      self.article_reader.handle_events(Some(event))?;
+    // Focus is owned by this tab, so events are ignored unless the tab is the selected one.
+    if self.selected_tab != self.tab {
+      return Ok(None);
+    }
+    match event {
+      Some(Event::Key(key_event)) => self.handle_key_events(key_event),
+      Some(Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event),
+      _ => Ok(None),
     }
-    Ok(None)
   }
 
   fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
-    self.article_list.handle_key_events(key)?;
+    match (self.focus, key.code) {
+      (Focus::List, KeyCode::Char('l') | KeyCode::Enter) => self.open_selected()?,
+      (_, KeyCode::Char(' ')) => self.churn()?,
+      (Focus::List, KeyCode::Char('o')) => {
+        for item in self.article_list.selection() {
+          self.open_url(Some(item.url.clone()))?;
+        }
+      },
+      (Focus::List, KeyCode::Char('r')) => {
+        let item_ids = self.article_list.mark_selection_read();
+        if let (false, Some(tx)) = (item_ids.is_empty(), &self.command_tx) {
+          tx.send(Action::MarkRead(item_ids))?;
+        }
+      },
+      (Focus::List, KeyCode::Char('A')) => self.confirm_mark_all_read()?,
+      (_, KeyCode::Char('o')) => {
+        let url = self.article_list.selected_item().map(|item| item.url.clone());
+        self.open_url(url)?;
+      },
+      // Ctrl-T cycles the colours.
+      (_, KeyCode::Char('t')) if key.modifiers.contains(KeyModifiers::CONTROL) => {},
+      (Focus::List, KeyCode::Char('t')) => {
+        for item in self.article_list.selection() {
+          self.capture_task(item)?;
+        }
+      },
+      (_, KeyCode::Char('t')) => {
+        if let Some(item) = self.article_list.selected_item() {
+          self.capture_task(item)?;
+        }
+      },
+      (_, KeyCode::Char('.')) => self.show_menu()?,
+      (_, KeyCode::Char('V')) => self.open_in_viewer()?,
+      (_, KeyCode::Char('n')) => self.edit_note()?,
+      (_, KeyCode::Char('P')) => self.share()?,
+      (_, KeyCode::Char('i')) => {
+        if let (Some(item), Some(tx)) = (self.article_list.selected_item(), &self.command_tx) {
+          tx.send(Action::RequestArticleInfo(item.id))?;
+        }
+      },
+      (_, KeyCode::Char('I')) => {
+        if let (Some(item), Some(tx)) = (self.article_list.selected_item(), &self.command_tx) {
+          tx.send(Action::RequestFeedInfo(item.feed_id))?;
+        }
+      },
+      (_, KeyCode::Char('c')) => {
+        let url = self.article_list.selected_item().and_then(|item| item.comments.clone());
+        self.open_url(url)?;
+      },
+      (Focus::Reader, KeyCode::Char('h')) => self.set_focus(Focus::List),
+      (Focus::Reader, KeyCode::Char('y')) => self.save_highlight()?,
+      (Focus::Reader, KeyCode::Char(']')) => self.select_related(true),
+      (Focus::Reader, KeyCode::Char('[')) => self.select_related(false),
+      (Focus::Reader, KeyCode::Enter) if self.selected_related.is_some() => self.open_related()?,
+      (Focus::Reader, KeyCode::Char('}')) => self.select_enclosure(true),
+      (Focus::Reader, KeyCode::Char('{')) => self.select_enclosure(false),
+      (Focus::Reader, KeyCode::Char('O')) => self.use_enclosure(false)?,
+      (Focus::Reader, KeyCode::Char('D')) => self.use_enclosure(true)?,
+      // Ctrl-Z suspends the app.
+      (Focus::Reader, KeyCode::Char('z')) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+        self.toggle_summary()?;
+      },
+      (Focus::Reader, KeyCode::Char('e')) => self.request_export()?,
+      (Focus::Reader, KeyCode::Char('m')) => self.request_email()?,
+      (Focus::Reader, KeyCode::Char('r')) => {
+        if let (Some(text), Some(tx)) = (self.article_reader.plain_text(), &self.command_tx) {
+          tx.send(Action::ReadAloud(text))?;
+        }
+      },
+      (Focus::Reader, KeyCode::Char('d')) => {
+        if self.article_reader.showing_diff() {
+          self.article_reader.hide_diff();
+        } else if let (Some(item), Some(tx)) = (self.article_list.selected_item(), &self.command_tx)
+        {
+          tx.send(Action::RequestRevisionDiff(self.tab, item.id))?;
+        }
+      },
+      (Focus::Reader, KeyCode::Char('s')) => {
+        if let Some(tx) = &self.command_tx {
+          tx.send(Action::StopReadAloud)?;
+        }
+      },
+      (Focus::List, _) => {
+        let previous = self.article_list.position();
+        self.article_list.handle_key_events(key)?;
+        self.save_position(previous)?;
+      },
+      (Focus::Reader, _) => {
+        self.article_reader.handle_key_events(key)?;
+      },
+    }
     Ok(None)
   }
 
   fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
-    self.article_list.handle_mouse_events(mouse)?;
+    if let MouseEventKind::Down(MouseButton::Right) = mouse.kind {
+      let position = Position { x: mouse.column, y: mouse.row };
+      if self.list_area.contains(position) {
+        self.set_focus(Focus::List);
+        let previous = self.article_list.position();
+        if self.article_list.select_at(mouse.column, mouse.row).is_some() {
+          self.save_position(previous)?;
+          self.show_menu()?;
+        }
+      } else if self.reader_area.contains(position) && self.article_title.is_some() {
+        self.set_focus(Focus::Reader);
+        self.show_menu()?;
+      }
+      return Ok(None);
+    }
+    if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+      let position = Position { x: mouse.column, y: mouse.row };
+      if self.list_area.contains(position) {
+        self.set_focus(Focus::List);
+        let previous = self.article_list.position();
+        let double_click = self.article_list.click(mouse.column, mouse.row);
+        self.save_position(previous)?;
+        if double_click {
+          self.open_selected()?;
+        }
+      } else if self.reader_area.contains(position) && self.article_title.is_some() {
+        self.set_focus(Focus::Reader);
+        self.open_url(self.article_reader.link_at(mouse.column, mouse.row))?;
+      }
+      return Ok(None);
+    }
+    if let MouseEventKind::Moved = mouse.kind {
+      self.article_reader.hover(mouse.column, mouse.row);
+      return Ok(None);
+    }
+
+    match self.focus {
+      Focus::List => {
+        let previous = self.article_list.position();
+        self.article_list.handle_mouse_events(mouse)?;
+        self.save_position(previous)?;
+        None
+      },
+      Focus::Reader => self.article_reader.handle_mouse_events(mouse)?,
+    };
     Ok(None)
   }
 
   fn update(&mut self, action: Action) -> Result<Option<Action>> {
-    self.article_list.update(action.clone())?;
-    self.article_reader.update(action.clone())?;
-
     match action {
-      Action::ChangeTab(idx) => {
-        self.selected_idx = idx;
+      Action::ChangeTab(tab) => {
+        self.selected_tab = tab;
+      },
+      Action::UpdateArticleView(_, feed_items) => {
+        // Keeps the selection when the list is reloaded after a refresh.
+        let position = self.article_list.position();
+        self.article_list.set_feed_items(&feed_items);
+        if let Some(position) = position {
+          self.article_list.restore_position(position);
+        }
+      },
+      Action::RestoreFeedPosition(_, position) => {
+        self.article_list.restore_position(position);
       },
-      Action::UpdateArticleView(idx, feed_items) => {
-        if self.idx == idx {
-          self.article_list.set_feed_items(feed_items);
+      // Addressed to the reader alone, so handed over as it is.
+      Action::UpdateReader(..) => {
+        self.article_reader.update(action)?;
+        // Only now is there text to summarize.
+        let summary = &self.config.summary;
+        if summary.auto && summary.is_enabled() && self.summary.is_none() && !self.summarizing {
+          self.request_summary()?;
         }
       },
-      _ => {},
+      Action::ShowRevisionDiff(..) => {
+        self.article_reader.update(action)?;
+      },
+      Action::MarkReadFailed(item_ids) => self.article_list.mark_unread(&item_ids),
+      Action::MarkAllRead(_) => {
+        let item_ids = self.article_list.mark_all_read();
+        if let (false, Some(tx)) = (item_ids.is_empty(), &self.command_tx) {
+          tx.send(Action::MarkRead(item_ids))?;
+        }
+      },
+      Action::UpdateRelated(_, item_id, related) => {
+        if self.article_id == Some(item_id) {
+          self.related = related;
+          self.selected_related = None;
+        }
+      },
+      Action::UpdateEnclosures(_, item_id, enclosures) => {
+        if self.article_id == Some(item_id) {
+          self.enclosures = enclosures;
+          self.selected_enclosure = 0;
+        }
+      },
+      Action::SummaryReady(item_id, summary) => {
+        if self.article_id == Some(item_id) {
+          self.summary = Some(summary);
+          self.summarizing = false;
+        }
+      },
+      Action::SummaryFailed(item_id) => {
+        if self.article_id == Some(item_id) {
+          self.summarizing = false;
+        }
+      },
+      Action::NoteSaved(item_id, note) => {
+        if self.article_id == Some(item_id) {
+          self.article_note = note.clone();
+        }
+        self.article_list.set_note(item_id, note);
+      },
+      action => {
+        self.article_list.update(action.clone())?;
+        self.article_reader.update(action)?;
+      },
     }
     Ok(None)
   }
 
+  fn hyperlinks(&self) -> Vec<(Rect, String)> {
+    self.article_reader.hyperlinks()
+  }
+
   fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+    let [header_area, body_area] =
+      Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+    f.render_widget(Paragraph::new(self.breadcrumb_line()), header_area);
+
     let chunks = Layout::default()
       .direction(Direction::Horizontal)
       .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
-      .split(area);
+      .split(body_area);
 
+    self.list_area = chunks[0];
     self.article_list.draw(f, chunks[0])?;
-    self.article_reader.draw(f, chunks[1])?;
+    let note_height = self
+      .article_note
+      .as_ref()
+      .map_or(0, |note| note.lines().count().min(NOTE_MAX_LINES) as u16 + 2);
+    let media_height =
+      if self.enclosures.is_empty() { 0 } else { self.enclosures.len().min(MEDIA_ROWS) as u16 + 2 };
+    let related_height = if self.related.is_empty() { 0 } else { self.related.len() as u16 + 2 };
+    let [reader_area, note_area, media_area, related_area] = Layout::vertical([
+      Constraint::Fill(1),
+      Constraint::Length(note_height),
+      Constraint::Length(media_height),
+      Constraint::Length(related_height),
+    ])
+    .areas(chunks[1]);
+    if let Some(note) = &self.article_note {
+      let block = Block::bordered()
+        .title(" Note ")
+        .border_style(Style::default().fg(self.config.palette().accent));
+      f.render_widget(
+        Paragraph::new(note.as_str()).wrap(Wrap { trim: false }).block(block),
+        note_area,
+      );
+    }
+    if !self.enclosures.is_empty() {
+      self.draw_media(f, media_area);
+    }
+    if !self.related.is_empty() {
+      self.draw_related(f, related_area);
+    }
+    let mut reader_area = reader_area;
+    if self.show_summary {
+      let rows = self.draw_summary(f, reader_area);
+      reader_area.y += rows;
+      reader_area.height -= rows;
+    }
+    self.reader_area = reader_area;
+    self.article_reader.draw(f, reader_area)?;
     Ok(())
   }
 }
@@ -0,0 +1,219 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{
+  article_view::ArticleView,
+  feed_tree::{FeedTree, TreeNode},
+  Component,
+};
+use crate::{
+  action::{Action, TabId},
+  config::Config,
+  db::SmartView,
+  i18n::Message,
+  tui::{Event, Frame},
+};
+
+/// Two-pane layout used instead of tabs: a tree of groups and feeds on the left, and the
+/// articles of the chosen node with the reader on the right.
+pub struct BrowserView<'a> {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  feed_tree: FeedTree,
+  tree_focused: bool,
+  articles_area: Rect,
+  article_view: ArticleView<'a>,
+  /// The request that loaded the article pane, sent again after a refresh.
+  request: Option<Action>,
+}
+
+impl<'a> BrowserView<'a> {
+  pub fn new() -> Self {
+    Self {
+      command_tx: None,
+      config: Config::default(),
+      feed_tree: FeedTree::new(),
+      tree_focused: true,
+      articles_area: Rect::default(),
+      article_view: ArticleView::new(TabId::next(), Vec::new()),
+      request: None,
+    }
+  }
+
+  fn set_tree_focused(&mut self, focused: bool) {
+    self.tree_focused = focused;
+    self.feed_tree.set_active(focused);
+  }
+
+  /// Replaces the article pane, since each node shows its articles with its own options.
+  fn replace_article_view(&mut self, article_view: ArticleView<'a>) -> Result<()> {
+    self.article_view = article_view;
+    self.article_view.register_config_handler(self.config.clone())?;
+    if let Some(tx) = &self.command_tx {
+      self.article_view.register_action_handler(tx.clone())?;
+    }
+    Ok(())
+  }
+
+  /// Shows the articles of the node selected in the tree.
+  fn open_selected(&mut self) -> Result<()> {
+    match self.feed_tree.selected_node() {
+      Some(node) => self.open_node(node),
+      None => Ok(()),
+    }
+  }
+
+  /// Shows the articles of `node` in a new article pane. The pane gets an id of its own, so
+  /// replies still on their way to the one it replaces are dropped.
+  fn open_node(&mut self, node: TreeNode) -> Result<()> {
+    let tab = TabId::next();
+    let action = match node {
+      TreeNode::Group(group) if group.id == -1 => {
+        self.replace_article_view(
+          ArticleView::new(tab, vec![self.config.text(Message::AllFeeds).to_string()])
+            .with_feed_names(true),
+        )?;
+        Action::RequestUpdateArticleViewAll(tab)
+      },
+      TreeNode::Group(group) if group.id < 0 => {
+        let Some(view) = SmartView::from_group_id(group.id) else {
+          return Ok(());
+        };
+        self.replace_article_view(
+          ArticleView::new(tab, vec![view.name().to_string()]).with_feed_names(true),
+        )?;
+        Action::RequestUpdateArticleViewSmart(tab, view)
+      },
+      TreeNode::Group(group) => {
+        self.replace_article_view(
+          ArticleView::new(tab, vec![group.name.clone()]).with_feed_names(true),
+        )?;
+        Action::RequestUpdateArticleViewGroup(tab, group)
+      },
+      TreeNode::Feed(group, feed) => {
+        let breadcrumb = vec![group.name.clone(), feed.name.clone()];
+        self.replace_article_view(ArticleView::new(tab, breadcrumb).with_feed_id(feed.id))?;
+        self.set_tree_focused(false);
+        Action::RequestUpdateArticleViewFeed(tab, feed)
+      },
+    };
+    self.request = Some(action.clone());
+    if let Some(tx) = &self.command_tx {
+      tx.send(action)?;
+    }
+    Ok(())
+  }
+}
+
+impl Component for BrowserView<'_> {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+    self.feed_tree.register_action_handler(tx.clone())?;
+    self.article_view.register_action_handler(tx.clone())?;
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> Result<()> {
+    self.feed_tree.register_config_handler(config.clone())?;
+    self.article_view.register_config_handler(config.clone())?;
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_events(&mut self, event: Option<Event>) -> Result<Option<Action>> {
+    match event {
+      Some(Event::Key(key)) => {
+        match key.code {
+          KeyCode::Tab => self.set_tree_focused(!self.tree_focused),
+          KeyCode::Char('r') if self.tree_focused => {
+            if let (Some(node), Some(tx)) = (self.feed_tree.selected_node(), &self.command_tx) {
+              tx.send(Action::RequestRefresh(node.refresh_scope()))?;
+            }
+          },
+          _ if self.tree_focused => {
+            if self.feed_tree.handle_key(key)? {
+              self.open_selected()?;
+            }
+          },
+          KeyCode::Char('h') if !self.article_view.reader_focused() => self.set_tree_focused(true),
+          _ => {
+            self.article_view.handle_events(Some(Event::Key(key)))?;
+          },
+        }
+        Ok(None)
+      },
+      Some(Event::Mouse(mouse)) => self.handle_mouse_events(mouse),
+      _ => Ok(None),
+    }
+  }
+
+  fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+    let position = Position { x: mouse.column, y: mouse.row };
+    if self.articles_area.contains(position) {
+      if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+        self.set_tree_focused(false);
+      }
+      self.article_view.handle_events(Some(Event::Mouse(mouse)))?;
+    } else if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+      self.set_tree_focused(true);
+      if self.feed_tree.click(mouse.column, mouse.row) {
+        self.open_selected()?;
+      }
+    } else if let MouseEventKind::Down(MouseButton::Right) = mouse.kind {
+      self.set_tree_focused(true);
+      self.feed_tree.right_click(mouse.column, mouse.row)?;
+    }
+    Ok(None)
+  }
+
+  fn update(&mut self, action: Action) -> Result<Option<Action>> {
+    if let Some(tab) = action.tab() {
+      if tab == self.article_view.tab() {
+        self.article_view.update(action)?;
+      }
+      return Ok(None);
+    }
+    self.feed_tree.update(action.clone())?;
+    self.article_view.update(action.clone())?;
+    match action {
+      Action::Refresh(_) => {
+        if let Some(tx) = &self.command_tx {
+          tx.send(Action::RequestFeedTree)?;
+        }
+      },
+      Action::UpdateFeedTree(tree) => self.feed_tree.set_tree(tree),
+      Action::RefreshFinished => {
+        if let (Some(request), Some(tx)) = (&self.request, &self.command_tx) {
+          tx.send(request.clone())?;
+        }
+      },
+      Action::OpenDestination(destination) => {
+        let node = match destination.feed {
+          Some(feed) => TreeNode::Feed(destination.group, feed),
+          None => TreeNode::Group(destination.group),
+        };
+        self.open_node(node)?;
+      },
+      _ => {},
+    }
+    Ok(None)
+  }
+
+  fn hyperlinks(&self) -> Vec<(Rect, String)> {
+    self.article_view.hyperlinks()
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+    // The last line belongs to the info bar.
+    let [main_area, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+    let [tree_area, articles_area] =
+      Layout::horizontal([Constraint::Percentage(25), Constraint::Percentage(75)]).areas(main_area);
+    self.articles_area = articles_area;
+
+    self.feed_tree.draw(f, tree_area)?;
+    self.article_view.draw(f, articles_area)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,346 @@
+use std::collections::{HashMap, HashSet};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::Rect,
+  style::{Modifier, Style},
+  text::{Line, Span},
+  widgets::{Block, BorderType, List, ListItem, ListState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{
+  mouse::{list_index_at, ClickTracker},
+  scroll, Component,
+};
+use crate::{
+  action::{Action, MenuItem, RefreshScope},
+  config::Config,
+  db::{Feed, Group},
+  i18n::Message,
+  tui::Frame,
+};
+
+const NESTED_INDENT: &str = "  ";
+
+/// A group or feed picked in the tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeNode {
+  Group(Group),
+  Feed(Group, Feed),
+}
+
+impl TreeNode {
+  /// The refresh covering the node.
+  pub fn refresh_scope(&self) -> RefreshScope {
+    match self {
+      TreeNode::Group(group) => RefreshScope::group(group),
+      TreeNode::Feed(_, feed) => RefreshScope::Feed(feed.url.clone()),
+    }
+  }
+}
+
+/// A row of the tree, indexing into the groups and their feeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TreeRow {
+  Group(usize),
+  Feed(usize, usize),
+}
+
+/// Groups with their feeds and subgroups nested below them, each with its unread count. Groups
+/// expand and collapse with `l`/`h` or space, `m` mutes or unmutes the selected feed and `.`
+/// opens the context menu of the selected row.
+pub struct FeedTree {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  tree: Vec<(Group, Vec<Feed>)>,
+  unread: HashMap<i32, usize>,
+  expanded: HashSet<i32>,
+  rows: Vec<TreeRow>,
+  state: ListState,
+  active: bool,
+  area: Rect,
+  clicks: ClickTracker,
+}
+
+impl FeedTree {
+  pub fn new() -> Self {
+    Self {
+      command_tx: None,
+      config: Config::default(),
+      tree: Vec::new(),
+      unread: HashMap::new(),
+      expanded: HashSet::new(),
+      rows: Vec::new(),
+      state: ListState::default().with_selected(Some(0)),
+      active: true,
+      area: Rect::default(),
+      clicks: ClickTracker::default(),
+    }
+  }
+
+  pub fn set_tree(&mut self, tree: Vec<(Group, Vec<Feed>)>) {
+    self.tree = tree;
+    self.rebuild_rows();
+  }
+
+  pub fn set_active(&mut self, active: bool) {
+    self.active = active;
+  }
+
+  pub fn selected_node(&self) -> Option<TreeNode> {
+    let row = self.rows.get(self.state.selected()?)?;
+    Some(match *row {
+      TreeRow::Group(i) => TreeNode::Group(self.tree[i].0.clone()),
+      TreeRow::Feed(i, j) => TreeNode::Feed(self.tree[i].0.clone(), self.tree[i].1[j].clone()),
+    })
+  }
+
+  /// Handles navigation keys, returning whether the selected node should be opened.
+  pub fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
+    if self.rows.is_empty() {
+      return Ok(false);
+    }
+    let page = scroll::page_rows(self.area, 1);
+    let wrap = self.config.display.wrap_lists;
+    if let Some(idx) =
+      scroll::navigate(self.state.selected(), self.rows.len(), key.code, page, wrap)
+    {
+      self.state.select(Some(idx));
+      return Ok(false);
+    }
+    let selected = self.state.selected().unwrap_or(0);
+    match (key.code, self.rows[selected]) {
+      (KeyCode::Char('l') | KeyCode::Right, TreeRow::Group(i)) => self.set_expanded(i, true),
+      (KeyCode::Char('h') | KeyCode::Left, TreeRow::Group(i) | TreeRow::Feed(i, _)) => {
+        self.set_expanded(i, false);
+        self.select_group(i);
+      },
+      (KeyCode::Char(' '), TreeRow::Group(i)) => {
+        let expanded = self.expanded.contains(&self.tree[i].0.id);
+        self.set_expanded(i, !expanded);
+      },
+      (KeyCode::Char('m'), TreeRow::Feed(i, j)) => {
+        let feed = &mut self.tree[i].1[j];
+        feed.muted = !feed.muted;
+        if let Some(tx) = &self.command_tx {
+          tx.send(Action::SetFeedMuted(feed.id, feed.muted))?;
+        }
+      },
+      (KeyCode::Char('i'), TreeRow::Feed(i, j)) => {
+        if let Some(tx) = &self.command_tx {
+          tx.send(Action::RequestFeedInfo(self.tree[i].1[j].id))?;
+        }
+      },
+      (KeyCode::Char('.'), _) => self.show_menu()?,
+      (KeyCode::Enter, _) | (KeyCode::Char('l') | KeyCode::Right, TreeRow::Feed(..)) => {
+        return Ok(true);
+      },
+      _ => {},
+    }
+    Ok(false)
+  }
+
+  /// Selects the row under the mouse, returning whether the click was a double click.
+  pub fn click(&mut self, column: u16, row: u16) -> bool {
+    let offset = self.state.offset();
+    match list_index_at(self.area, offset, 1, self.rows.len(), column, row) {
+      Some(idx) => {
+        self.state.select(Some(idx));
+        self.clicks.click(idx)
+      },
+      None => false,
+    }
+  }
+
+  /// Selects the row under the mouse and opens its context menu.
+  pub fn right_click(&mut self, column: u16, row: u16) -> Result<()> {
+    let offset = self.state.offset();
+    if let Some(idx) = list_index_at(self.area, offset, 1, self.rows.len(), column, row) {
+      self.state.select(Some(idx));
+      self.show_menu()?;
+    }
+    Ok(())
+  }
+
+  /// Opens the context menu of the selected group or feed. Refreshing is left to the view
+  /// holding the tree.
+  fn show_menu(&self) -> Result<()> {
+    let (Some(&row), Some(tx)) =
+      (self.state.selected().and_then(|i| self.rows.get(i)), &self.command_tx)
+    else {
+      return Ok(());
+    };
+    let mut items = vec![MenuItem::new("Open", KeyCode::Enter)];
+    let title = match row {
+      TreeRow::Group(i) => {
+        let (group, feeds) = &self.tree[i];
+        if self.has_children(group, feeds) {
+          let label = if self.expanded.contains(&group.id) { "Collapse" } else { "Expand" };
+          items.push(MenuItem::new(label, KeyCode::Char(' ')));
+        }
+        items.push(MenuItem::new("Refresh", KeyCode::Char('r')));
+        self.config.group_label(&group.name)
+      },
+      TreeRow::Feed(i, j) => {
+        let feed = &self.tree[i].1[j];
+        items.push(MenuItem::new("Refresh", KeyCode::Char('r')));
+        items.push(MenuItem::new(if feed.muted { "Unmute" } else { "Mute" }, KeyCode::Char('m')));
+        items.push(MenuItem::new("Feed info", KeyCode::Char('i')));
+        feed.name.clone()
+      },
+    };
+    tx.send(Action::ShowContextMenu(title, items))?;
+    Ok(())
+  }
+
+  fn set_expanded(&mut self, group: usize, expanded: bool) {
+    let id = self.tree[group].0.id;
+    if expanded {
+      self.expanded.insert(id);
+    } else {
+      self.expanded.remove(&id);
+    }
+    self.rebuild_rows();
+  }
+
+  fn select_group(&mut self, group: usize) {
+    let row = self.rows.iter().position(|row| *row == TreeRow::Group(group));
+    self.state.select(row);
+  }
+
+  fn rebuild_rows(&mut self) {
+    self.rows.clear();
+    for (i, (group, feeds)) in self.tree.iter().enumerate() {
+      if self.ancestors(group).iter().any(|id| !self.expanded.contains(id)) {
+        continue;
+      }
+      self.rows.push(TreeRow::Group(i));
+      if self.expanded.contains(&group.id) {
+        self.rows.extend((0..feeds.len()).map(|j| TreeRow::Feed(i, j)));
+      }
+    }
+    let selected = self.state.selected().unwrap_or(0);
+    self.state.select(Some(selected.min(self.rows.len().saturating_sub(1))));
+  }
+
+  /// The ids of the groups `group` is nested in, innermost first.
+  fn ancestors(&self, group: &Group) -> Vec<i32> {
+    let mut ancestors = Vec::new();
+    let mut parent_id = group.parent_id;
+    while let Some(id) = parent_id.filter(|id| !ancestors.contains(id)) {
+      ancestors.push(id);
+      parent_id =
+        self.tree.iter().find(|(group, _)| group.id == id).and_then(|(group, _)| group.parent_id);
+    }
+    ancestors
+  }
+
+  fn has_children(&self, group: &Group, feeds: &[Feed]) -> bool {
+    !feeds.is_empty() || self.tree.iter().any(|(other, _)| other.parent_id == Some(group.id))
+  }
+
+  /// Unread articles in the group's feeds and those of the groups nested in it.
+  fn group_unread(&self, group: &Group) -> usize {
+    if group.id == -1 {
+      return self.unread.values().sum();
+    }
+    self
+      .tree
+      .iter()
+      .filter(|(other, _)| other.id == group.id || self.ancestors(other).contains(&group.id))
+      .flat_map(|(_, feeds)| feeds)
+      .filter_map(|feed| self.unread.get(&feed.id))
+      .sum()
+  }
+}
+
+impl Component for FeedTree {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn update(&mut self, action: Action) -> Result<Option<Action>> {
+    if let Action::UpdateFeedUnreadCounts(unread) = action {
+      self.unread = unread;
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+    let palette = *self.config.palette();
+    let group_style = Style::default().fg(palette.title).add_modifier(Modifier::BOLD);
+    let feed_style = Style::default().fg(palette.text);
+    let selected_style = self.config.selected_style();
+    let count_style = Style::default().fg(palette.muted);
+    let muted_style = Style::default().fg(palette.muted);
+
+    let items: Vec<ListItem> = self
+      .rows
+      .iter()
+      .enumerate()
+      .map(|(i, row)| {
+        let selected = self.state.selected() == Some(i);
+        let (indent, name, style, unread, muted) = match *row {
+          TreeRow::Group(i) => {
+            let (group, feeds) = &self.tree[i];
+            let marker = match (self.has_children(group, feeds), self.expanded.contains(&group.id))
+            {
+              (false, _) => "  ",
+              (true, true) => "▾ ",
+              (true, false) => "▸ ",
+            };
+            let indent = format!("{}{marker}", NESTED_INDENT.repeat(self.ancestors(group).len()));
+            let style = match self.config.group_color(&group.name) {
+              Some(color) => group_style.fg(color),
+              None => group_style,
+            };
+            let name =
+              if group.id == -1 { self.config.text(Message::AllFeeds) } else { &group.name };
+            let name = self.config.group_label(name);
+            (indent, name, style, self.group_unread(group), false)
+          },
+          TreeRow::Feed(i, j) => {
+            let (group, feeds) = &self.tree[i];
+            let feed = &feeds[j];
+            let style = if feed.muted { muted_style } else { feed_style };
+            (
+              format!("{}    ", NESTED_INDENT.repeat(self.ancestors(group).len())),
+              feed.name.clone(),
+              style,
+              self.unread.get(&feed.id).copied().unwrap_or(0),
+              feed.muted,
+            )
+          },
+        };
+        let style = if selected { selected_style } else { style };
+        let mut spans = vec![Span::raw(indent), Span::styled(name, style)];
+        if muted {
+          spans.push(Span::styled(" (muted)", count_style));
+        } else if unread > 0 {
+          spans.push(Span::styled(format!(" ({unread})"), self.config.unread_style(palette.muted)));
+        }
+        ListItem::new(Line::from(spans))
+      })
+      .collect();
+
+    let border_style =
+      if self.active { Style::default().fg(palette.accent) } else { Style::default() };
+    let mut block = Block::bordered().border_type(BorderType::Rounded).border_style(border_style);
+    if let Some(position) = scroll::position_title(&self.state, self.rows.len()) {
+      block = block.title(position);
+    }
+    let list = List::new(items).block(block).highlight_symbol("┃");
+    f.render_stateful_widget(list, area, &mut self.state);
+    self.area = area;
+    scroll::render_scrollbar(f, area, &self.state, self.rows.len());
+    Ok(())
+  }
+}
@@ -1,18 +1,23 @@
+use chrono::Utc;
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, MouseEvent};
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
   layout::Rect,
-  style::{Color, Modifier, Style},
-  text::{Line, Text},
+  style::{Modifier, Style},
+  text::{Line, Span, Text},
   widgets::{Block, BorderType, List, ListItem, ListState},
 };
 use tokio::sync::mpsc::UnboundedSender;
 
-use super::Component;
+use super::{
+  mouse::{list_index_at, ClickTracker},
+  scroll, Component,
+};
 use crate::{
-  action::Action,
+  action::{Action, MenuItem, RefreshScope, TabId},
   config::Config,
   db::{Feed, Group},
+  i18n::Message,
   mode::Mode,
   tui::Frame,
 };
@@ -22,24 +27,127 @@ pub struct FeedView {
   config: Config,
   mode: Mode,
   group: Group,
-  idx: usize,
-  selected_idx: usize,
+  tab: TabId,
+  selected_tab: TabId,
   feeds: Vec<Feed>,
   state: ListState,
+  area: Rect,
+  clicks: ClickTracker,
 }
 
 impl FeedView {
-  pub fn new(idx: usize, group: Group) -> Self {
+  pub fn new(tab: TabId, group: Group) -> Self {
     Self {
       command_tx: None,
       config: Config::default(),
       mode: Mode::default(),
       group,
-      idx,
-      selected_idx: idx,
+      tab,
+      selected_tab: tab,
       feeds: Vec::new(),
       state: ListState::default().with_selected(Some(0)),
+      area: Rect::default(),
+      clicks: ClickTracker::default(),
+    }
+  }
+
+  fn open_selected(&self) -> Result<()> {
+    let Some(selected_feed) = self.state.selected().and_then(|i| self.feeds.get(i)).cloned() else {
+      return Ok(());
+    };
+    if let Some(tx) = &self.command_tx {
+      if selected_feed.id == -1 {
+        tx.send(Action::NewTabArticleViewGroup(self.group.clone()))?;
+      } else {
+        log::info!("Sending NewTabArticleViewFeed");
+        tx.send(Action::NewTabArticleViewFeed(self.group.clone(), selected_feed))?;
+      }
+    } else {
+      log::error!("No tx!")
+    }
+    Ok(())
+  }
+
+  fn toggle_selected_mute(&mut self) -> Result<()> {
+    let selected_idx = self.state.selected().unwrap_or(0);
+    let Some(feed) = self.feeds.get_mut(selected_idx).filter(|feed| feed.id != -1) else {
+      return Ok(());
+    };
+    feed.muted = !feed.muted;
+    if let Some(tx) = &self.command_tx {
+      tx.send(Action::SetFeedMuted(feed.id, feed.muted))?;
     }
+    Ok(())
+  }
+
+  fn show_selected_info(&self) -> Result<()> {
+    let selected_idx = self.state.selected().unwrap_or(0);
+    if let (Some(feed), Some(tx)) =
+      (self.feeds.get(selected_idx).filter(|feed| feed.id != -1), &self.command_tx)
+    {
+      tx.send(Action::RequestFeedInfo(feed.id))?;
+    }
+    Ok(())
+  }
+
+  /// Opens the context menu of the selected feed, or of the group for All Feeds.
+  fn show_menu(&self) -> Result<()> {
+    let selected_idx = self.state.selected().unwrap_or(0);
+    let (Some(feed), Some(tx)) = (self.feeds.get(selected_idx), &self.command_tx) else {
+      return Ok(());
+    };
+    let mut items =
+      vec![MenuItem::new("Open", KeyCode::Enter), MenuItem::new("Refresh", KeyCode::Char('r'))];
+    if feed.id != -1 {
+      items.push(MenuItem::new(if feed.muted { "Unmute" } else { "Mute" }, KeyCode::Char('m')));
+      items.push(MenuItem::new("Feed info", KeyCode::Char('i')));
+      let link = self.config.feed_link(&feed.url, feed.moved_from.as_deref());
+      if self.config.feed_group(link).is_none() {
+        items.push(MenuItem::new("Delete", KeyCode::Char('D')));
+      }
+    }
+    tx.send(Action::ShowContextMenu(feed.name.clone(), items))?;
+    Ok(())
+  }
+
+  /// Asks before deleting the selected feed. Feeds from the config file would only come back on
+  /// the next refresh, so those are left for the user to remove there.
+  fn delete_selected(&self) -> Result<()> {
+    let selected_idx = self.state.selected().unwrap_or(0);
+    let (Some(feed), Some(tx)) =
+      (self.feeds.get(selected_idx).filter(|feed| feed.id != -1), &self.command_tx)
+    else {
+      return Ok(());
+    };
+    let link = self.config.feed_link(&feed.url, feed.moved_from.as_deref());
+    if self.config.feed_group(link).is_some() {
+      tx.send(Action::Error(format!(
+        "{} is in the config file, remove it there to delete it",
+        feed.name
+      )))?;
+    } else {
+      let prompt = format!("Delete {} and all of its articles?", feed.name);
+      tx.send(Action::Confirm(prompt, Box::new(Action::DeleteFeed(feed.id))))?;
+    }
+    Ok(())
+  }
+
+  /// Refreshes the selected feed, reviving it if it was dead, or the whole group for All Feeds.
+  fn refresh_selected(&mut self) -> Result<()> {
+    let selected_idx = self.state.selected().unwrap_or(0);
+    let Some(feed) = self.feeds.get_mut(selected_idx) else {
+      return Ok(());
+    };
+    let scope = if feed.id == -1 {
+      RefreshScope::group(&self.group)
+    } else {
+      feed.dead = false;
+      RefreshScope::Feed(feed.url.clone())
+    };
+    if let Some(tx) = &self.command_tx {
+      tx.send(Action::RequestRefresh(scope))?;
+    }
+    Ok(())
   }
 }
 
@@ -55,34 +163,24 @@ impl Component for FeedView {
   }
 
   fn handle_key_events(&mut self, key: crossterm::event::KeyEvent) -> Result<Option<Action>> {
-    log::info!("{:?} vs {:?}", self.selected_idx, self.idx);
-    if self.selected_idx == self.idx {
+    log::info!("{:?} vs {:?}", self.selected_tab, self.tab);
+    if self.selected_tab == self.tab && !self.feeds.is_empty() {
       let selected_item_idx = self.state.selected().unwrap_or(0);
+      let page = scroll::page_rows(self.area, 2);
+      let wrap = self.config.display.wrap_lists;
+      if let Some(idx) =
+        scroll::navigate(self.state.selected(), self.feeds.len(), key.code, page, wrap)
+      {
+        self.state.select(Some(idx));
+        return Ok(None);
+      }
       match key.code {
-        KeyCode::Char('j') | KeyCode::Down => {
-          self.state.select(Some((selected_item_idx + 1) % self.feeds.len()));
-        },
-        KeyCode::Char('k') | KeyCode::Up => {
-          if selected_item_idx == 0 {
-            self.state.select(Some(self.feeds.len() - 1));
-          } else {
-            self.state.select(Some(selected_item_idx - 1));
-          }
-        },
-        KeyCode::Char('l') | KeyCode::Enter => {
-          if let Some(tx) = &self.command_tx {
-            let selected_idx = self.state.selected().unwrap();
-            let selected_feed = self.feeds.get(selected_idx).unwrap().clone();
-            if selected_feed.id == -1 {
-              tx.send(Action::NewTabArticleViewGroup(self.group.clone()))?;
-            } else {
-              log::info!("Sending NewTabArticleViewFeed");
-              tx.send(Action::NewTabArticleViewFeed(selected_feed))?;
-            }
-          } else {
-            log::error!("No tx!")
-          }
-        },
+        KeyCode::Char('l') | KeyCode::Enter => self.open_selected()?,
+        KeyCode::Char('m') => self.toggle_selected_mute()?,
+        KeyCode::Char('r') => self.refresh_selected()?,
+        KeyCode::Char('D') => self.delete_selected()?,
+        KeyCode::Char('i') => self.show_selected_info()?,
+        KeyCode::Char('.') => self.show_menu()?,
         _ => {},
       }
     }
@@ -90,23 +188,39 @@ impl Component for FeedView {
   }
 
   fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+    let offset = self.state.offset();
+    let len = self.feeds.len();
+    let Some(idx) = list_index_at(self.area, offset, 2, len, mouse.column, mouse.row) else {
+      return Ok(None);
+    };
+    match mouse.kind {
+      MouseEventKind::Down(MouseButton::Left) => {
+        self.state.select(Some(idx));
+        if self.clicks.click(idx) {
+          self.open_selected()?;
+        }
+      },
+      MouseEventKind::Down(MouseButton::Right) => {
+        self.state.select(Some(idx));
+        self.show_menu()?;
+      },
+      _ => {},
+    }
     Ok(None)
   }
 
   fn update(&mut self, action: Action) -> Result<Option<Action>> {
     match action {
-      Action::ChangeTab(idx) => {
-        self.selected_idx = idx;
+      Action::ChangeTab(tab) => {
+        self.selected_tab = tab;
       },
-      Action::RemoveTab(idx) => {
-        if self.idx > idx {
-          self.idx -= 1;
+      Action::RefreshFinished | Action::DeleteFeed(_) => {
+        if let Some(tx) = &self.command_tx {
+          tx.send(Action::RequestUpdateFeedView(self.tab, self.group.clone()))?;
         }
       },
-      Action::UpdateFeedView(idx, feeds) => {
-        if self.idx == idx {
-          self.feeds = feeds;
-        }
+      Action::UpdateFeedView(_, feeds) => {
+        self.feeds = feeds;
       },
       _ => {},
     }
@@ -114,38 +228,71 @@ impl Component for FeedView {
   }
 
   fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-    let name_style = Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD);
-    let desc_style = Style::default().fg(Color::Gray);
-    let selected_name_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
-    let selected_desc_style = Style::default().fg(Color::Gray);
+    let palette = *self.config.palette();
+    let name_style = Style::default().fg(palette.title).add_modifier(Modifier::BOLD);
+    let desc_style = Style::default().fg(palette.text);
+    let selected_name_style = self.config.selected_style();
+    let selected_desc_style = Style::default().fg(palette.text);
+    let muted_style = Style::default().fg(palette.muted);
+    let dead_style = Style::default().fg(palette.error);
+    let now = Utc::now();
 
     let items: Vec<ListItem> = self
       .feeds
       .iter()
       .enumerate()
       .map(|(i, feed)| {
-        if self.state.selected() == Some(i) {
+        let name = match (feed.id, feed.muted) {
+          (-1, _) => self.config.text(Message::AllFeeds).to_string(),
+          (_, true) => format!("{} (muted)", feed.name),
+          (_, false) => feed.name.clone(),
+        };
+        // How often the feed posts, or how long it has been quiet when far longer than usual.
+        let cadence = feed.cadence.map(|cadence| {
+          if cadence.is_quiet(now) {
+            let since = self.config.display.format_date(cadence.latest);
+            Span::styled(format!("  ◌ quiet since {since}"), dead_style)
+          } else {
+            Span::styled(format!("  {}", cadence.rate()), muted_style)
+          }
+        });
+        if feed.dead {
+          let name_style =
+            if self.state.selected() == Some(i) { selected_name_style } else { muted_style };
           let text = Text::from(vec![
-            Line::styled(&feed.name, selected_name_style),
-            Line::styled(&feed.desc, selected_desc_style),
+            Line::from(vec![Span::styled(name, name_style), Span::styled(" ⚠ dead", dead_style)]),
+            Line::styled("Stopped refreshing after failing repeatedly, r to retry", muted_style),
           ]);
           ListItem::new(text)
-        } else {
+        } else if self.state.selected() == Some(i) {
+          let mut name = Line::from(Span::styled(name, selected_name_style));
+          name.spans.extend(cadence);
+          let text = Text::from(vec![name, Line::styled(&feed.desc, selected_desc_style)]);
+          ListItem::new(text)
+        } else if feed.muted {
           let text = Text::from(vec![
-            Line::styled(&feed.name, name_style),
-            Line::styled(&feed.desc, desc_style),
+            Line::styled(name, muted_style),
+            Line::styled(&feed.desc, muted_style),
           ]);
           ListItem::new(text)
+        } else {
+          let mut name = Line::from(Span::styled(name, name_style));
+          name.spans.extend(cadence);
+          let text = Text::from(vec![name, Line::styled(&feed.desc, desc_style)]);
+          ListItem::new(text)
         }
       })
       .collect();
 
-    let list = List::new(items)
-      .block(Block::bordered().border_type(BorderType::Rounded))
-      .highlight_symbol(" ┃ ")
-      .repeat_highlight_symbol(true);
+    let mut block = Block::bordered().border_type(BorderType::Rounded);
+    if let Some(position) = scroll::position_title(&self.state, self.feeds.len()) {
+      block = block.title(position);
+    }
+    let list = List::new(items).block(block).highlight_symbol(" ┃ ").repeat_highlight_symbol(true);
 
     f.render_stateful_widget(list, area, &mut self.state);
+    self.area = area;
+    scroll::render_scrollbar(f, area, &self.state, self.feeds.len());
 
     Ok(())
   }
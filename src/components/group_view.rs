@@ -1,76 +1,153 @@
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
   layout::Rect,
-  style::{Color, Modifier, Style},
+  style::{Modifier, Style},
   text::{Line, Text},
   widgets::{Block, BorderType, Borders, List, ListItem, ListState},
 };
 use tokio::sync::mpsc::UnboundedSender;
 
-use super::Component;
-use crate::{action::Action, config::Config, db::Group, mode::Mode, tui::Frame};
+use super::{
+  feed_tree::{FeedTree, TreeNode},
+  mouse::{list_index_at, ClickTracker},
+  scroll, Component,
+};
+use crate::{
+  action::{Action, MenuItem, RefreshScope, TabId},
+  config::Config,
+  db::{group_depths, Group, SmartView},
+  i18n::Message,
+  mode::Mode,
+  tui::Frame,
+};
+
+const NESTED_INDENT: &str = "  ";
 
 pub struct GroupView {
   command_tx: Option<UnboundedSender<Action>>,
   config: Config,
   mode: Mode,
-  idx: usize,
-  selected_idx: usize,
+  tab: TabId,
+  selected_tab: TabId,
   groups: Vec<Group>,
   state: ListState,
+  area: Rect,
+  clicks: ClickTracker,
+  /// Replaces the flat group list when `group_tree` is enabled.
+  feed_tree: FeedTree,
 }
 
 impl GroupView {
-  pub fn new() -> Self {
+  pub fn new(tab: TabId) -> Self {
     Self {
       command_tx: None,
       config: Config::default(),
       mode: Mode::default(),
-      idx: 0,
-      selected_idx: 0,
+      tab,
+      selected_tab: tab,
       groups: Vec::new(),
       state: ListState::default().with_selected(Some(0)),
+      area: Rect::default(),
+      clicks: ClickTracker::default(),
+      feed_tree: FeedTree::new(),
+    }
+  }
+
+  fn open_tree_node(&self) -> Result<()> {
+    let action = match self.feed_tree.selected_node() {
+      Some(TreeNode::Group(group)) if group.id == -1 => Action::NewTabArticleViewAll,
+      Some(TreeNode::Group(group)) if group.id < 0 => {
+        match SmartView::from_group_id(group.id) {
+          Some(view) => Action::NewTabArticleViewSmart(view),
+          None => return Ok(()),
+        }
+      },
+      Some(TreeNode::Group(group)) => Action::NewTabArticleViewGroup(group),
+      Some(TreeNode::Feed(group, feed)) => Action::NewTabArticleViewFeed(group, feed),
+      None => return Ok(()),
+    };
+    if let Some(tx) = &self.command_tx {
+      tx.send(action)?;
+    }
+    Ok(())
+  }
+
+  fn refresh_selected(&self) -> Result<()> {
+    let scope = if self.config.group_tree {
+      self.feed_tree.selected_node().map(|node| node.refresh_scope())
+    } else {
+      self.state.selected().and_then(|i| self.groups.get(i)).map(RefreshScope::group)
+    };
+    if let (Some(scope), Some(tx)) = (scope, &self.command_tx) {
+      tx.send(Action::RequestRefresh(scope))?;
     }
+    Ok(())
+  }
+
+  fn show_menu(&self) -> Result<()> {
+    let (Some(group), Some(tx)) =
+      (self.state.selected().and_then(|i| self.groups.get(i)), &self.command_tx)
+    else {
+      return Ok(());
+    };
+    let items =
+      vec![MenuItem::new("Open", KeyCode::Enter), MenuItem::new("Refresh", KeyCode::Char('r'))];
+    tx.send(Action::ShowContextMenu(self.config.group_label(&group.name), items))?;
+    Ok(())
+  }
+
+  fn open_selected(&self) -> Result<()> {
+    let Some(selected_group) = self.state.selected().and_then(|i| self.groups.get(i)).cloned()
+    else {
+      return Ok(());
+    };
+    if let Some(tx) = &self.command_tx {
+      if selected_group.id == -1 {
+        tx.send(Action::NewTabArticleViewAll)?;
+      } else if let Some(view) = SmartView::from_group_id(selected_group.id) {
+        tx.send(Action::NewTabArticleViewSmart(view))?;
+      } else {
+        tx.send(Action::NewTabFeedView(selected_group))?;
+      }
+    }
+    Ok(())
   }
 }
 
 impl Component for GroupView {
   fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+    self.feed_tree.register_action_handler(tx.clone())?;
     self.command_tx = Some(tx);
     Ok(())
   }
 
   fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.feed_tree.register_config_handler(config.clone())?;
     self.config = config;
     Ok(())
   }
 
   fn handle_key_events(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
-    if self.selected_idx == self.idx {
+    if self.selected_tab == self.tab && key.code == KeyCode::Char('r') {
+      self.refresh_selected()?;
+    } else if self.selected_tab == self.tab && self.config.group_tree {
+      if self.feed_tree.handle_key(key)? {
+        self.open_tree_node()?;
+      }
+    } else if self.selected_tab == self.tab && !self.groups.is_empty() {
       let selected_item_idx = self.state.selected().unwrap_or(0);
+      let page = scroll::page_rows(self.area, 3);
+      let wrap = self.config.display.wrap_lists;
+      if let Some(idx) =
+        scroll::navigate(self.state.selected(), self.groups.len(), key.code, page, wrap)
+      {
+        self.state.select(Some(idx));
+        return Ok(None);
+      }
       match key.code {
-        KeyCode::Char('j') | KeyCode::Down => {
-          self.state.select(Some((selected_item_idx + 1) % self.groups.len()));
-        },
-        KeyCode::Char('k') | KeyCode::Up => {
-          if selected_item_idx == 0 {
-            self.state.select(Some(self.groups.len() - 1));
-          } else {
-            self.state.select(Some(selected_item_idx - 1));
-          }
-        },
-        KeyCode::Char('l') | KeyCode::Enter => {
-          if let Some(tx) = &self.command_tx {
-            let selected_idx = self.state.selected().unwrap();
-            let selected_group = self.groups.get(selected_idx).unwrap().clone();
-            if selected_group.id == -1 {
-              tx.send(Action::NewTabArticleViewAll)?;
-            } else {
-              tx.send(Action::NewTabFeedView(selected_group))?;
-            }
-          }
-        },
+        KeyCode::Char('l') | KeyCode::Enter => self.open_selected()?,
+        KeyCode::Char('.') => self.show_menu()?,
         _ => {},
       }
     }
@@ -78,16 +155,48 @@ impl Component for GroupView {
   }
 
   fn handle_mouse_events(&mut self, mouse: MouseEvent) -> color_eyre::Result<Option<Action>> {
+    if self.config.group_tree {
+      match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+          if self.feed_tree.click(mouse.column, mouse.row) {
+            self.open_tree_node()?;
+          }
+        },
+        MouseEventKind::Down(MouseButton::Right) => {
+          self.feed_tree.right_click(mouse.column, mouse.row)?;
+        },
+        _ => {},
+      }
+      return Ok(None);
+    }
+    if self.groups.is_empty() {
+      return Ok(None);
+    }
     let selected_idx = self.state.selected().unwrap_or(0);
+    let (len, wrap) = (self.groups.len(), self.config.display.wrap_lists);
     match mouse.kind {
       MouseEventKind::ScrollUp => {
-        self.state.select(Some((selected_idx + 1) % self.groups.len()));
+        self.state.select(Some(scroll::step(selected_idx, len, true, wrap)))
       },
       MouseEventKind::ScrollDown => {
-        if selected_idx == 0 {
-          self.state.select(Some(self.groups.len() - 1));
-        } else {
-          self.state.select(Some(selected_idx - 1));
+        self.state.select(Some(scroll::step(selected_idx, len, false, wrap)));
+      },
+      MouseEventKind::Down(MouseButton::Left) => {
+        let offset = self.state.offset();
+        let len = self.groups.len();
+        if let Some(idx) = list_index_at(self.area, offset, 3, len, mouse.column, mouse.row) {
+          self.state.select(Some(idx));
+          if self.clicks.click(idx) {
+            self.open_selected()?;
+          }
+        }
+      },
+      MouseEventKind::Down(MouseButton::Right) => {
+        let offset = self.state.offset();
+        let len = self.groups.len();
+        if let Some(idx) = list_index_at(self.area, offset, 3, len, mouse.column, mouse.row) {
+          self.state.select(Some(idx));
+          self.show_menu()?;
         }
       },
       _ => {},
@@ -96,12 +205,17 @@ impl Component for GroupView {
   }
 
   fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    self.feed_tree.update(action.clone())?;
     match action {
       Action::Refresh(groups) => {
         self.groups = groups;
+        if let (true, Some(tx)) = (self.config.group_tree, &self.command_tx) {
+          tx.send(Action::RequestFeedTree)?;
+        }
       },
-      Action::ChangeTab(idx) => {
-        self.selected_idx = idx;
+      Action::UpdateFeedTree(tree) => self.feed_tree.set_tree(tree),
+      Action::ChangeTab(tab) => {
+        self.selected_tab = tab;
       },
       Action::ModeChange(mode) => {
         self.mode = mode;
@@ -112,40 +226,59 @@ impl Component for GroupView {
   }
 
   fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
-    let name_style = Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD);
-    let desc_style = Style::default().fg(Color::Gray);
-    let selected_name_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
-    let selected_desc_style = Style::default().fg(Color::Gray);
+    let palette = *self.config.palette();
+    if self.config.group_tree {
+      return self.feed_tree.draw(f, area);
+    }
 
+    let name_style = Style::default().fg(palette.title).add_modifier(Modifier::BOLD);
+    let desc_style = Style::default().fg(palette.text);
+    let selected_name_style = self.config.selected_style();
+    let selected_desc_style = Style::default().fg(palette.text);
+
+    let depths = group_depths(&self.groups);
     let items: Vec<ListItem> = self
       .groups
       .iter()
       .enumerate()
       .map(|(i, group)| {
+        // Nested groups are indented under the group they are in.
+        let indent = NESTED_INDENT.repeat(depths.get(&group.id).copied().unwrap_or(0));
+        let name = if group.id == -1 { self.config.text(Message::AllFeeds) } else { &group.name };
+        let label = format!("{indent}{}", self.config.group_label(name));
+        let desc = format!("{indent}{}", group.desc);
+        let read = format!("{indent}(0/0) read");
         if self.state.selected() == Some(i) {
           let text = Text::from(vec![
-            Line::styled(&group.name, selected_name_style),
-            Line::styled(&group.desc, selected_desc_style),
-            Line::styled("(0/0) read", selected_desc_style),
+            Line::styled(label, selected_name_style),
+            Line::styled(desc, selected_desc_style),
+            Line::styled(read, selected_desc_style),
           ]);
           ListItem::new(text)
         } else {
+          let group_name_style = match self.config.group_color(&group.name) {
+            Some(color) => name_style.fg(color),
+            None => name_style,
+          };
           let text = Text::from(vec![
-            Line::styled(&group.name, name_style),
-            Line::styled(&group.desc, desc_style),
-            Line::styled("(0/0) read", desc_style),
+            Line::styled(label, group_name_style),
+            Line::styled(desc, desc_style),
+            Line::styled(read, desc_style),
           ]);
           ListItem::new(text)
         }
       })
       .collect();
 
-    let list = List::new(items)
-      .block(Block::bordered().border_type(BorderType::Rounded))
-      .highlight_symbol(" ┃ ")
-      .repeat_highlight_symbol(true);
+    let mut block = Block::bordered().border_type(BorderType::Rounded);
+    if let Some(position) = scroll::position_title(&self.state, self.groups.len()) {
+      block = block.title(position);
+    }
+    let list = List::new(items).block(block).highlight_symbol(" ┃ ").repeat_highlight_symbol(true);
 
     f.render_stateful_widget(list, area, &mut self.state);
+    self.area = area;
+    scroll::render_scrollbar(f, area, &self.state, self.groups.len());
     Ok(())
   }
 }
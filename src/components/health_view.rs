@@ -0,0 +1,213 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span, Text},
+  widgets::{Block, BorderType, List, ListItem, ListState, Paragraph, Wrap},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{scroll, Component};
+use crate::{
+  action::{Action, RefreshScope, TabId},
+  config::Config,
+  db::FeedHealth,
+  extract::Extraction,
+  tui::Frame,
+};
+
+/// Lists every feed with how its recent refreshes went, failing feeds first.
+pub struct HealthView {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  tab: TabId,
+  selected_tab: TabId,
+  health: Vec<FeedHealth>,
+  state: ListState,
+  /// Where the list was last drawn, to page through it.
+  list_area: Rect,
+}
+
+impl HealthView {
+  pub fn new(tab: TabId) -> Self {
+    Self {
+      command_tx: None,
+      config: Config::default(),
+      tab,
+      selected_tab: tab,
+      health: Vec::new(),
+      state: ListState::default().with_selected(Some(0)),
+      list_area: Rect::default(),
+    }
+  }
+
+  /// The configured name of the feed the health was kept for, or its URL.
+  fn feed_name<'a>(&'a self, health: &'a FeedHealth) -> &'a str {
+    let link = self.config.feed_link(&health.url, health.moved_from.as_deref());
+    self.config.feed(link).and_then(|feed| feed.name.as_deref()).unwrap_or(&health.url)
+  }
+
+  fn draw_details(&self, f: &mut Frame<'_>, area: Rect, feed_health: &FeedHealth) {
+    let palette = *self.config.palette();
+    let label_style = Style::default().fg(palette.text);
+    let display = &self.config.display;
+    let last_success =
+      feed_health.last_success.map_or("never".to_string(), |date| display.format_date(date));
+    let mut lines = vec![
+      Line::from(vec![Span::styled("URL           ", label_style), Span::raw(&feed_health.url)]),
+      Line::from(vec![
+        Span::styled("Last attempt  ", label_style),
+        Span::raw(display.format_date(feed_health.last_attempt)),
+      ]),
+      Line::from(vec![Span::styled("Last success  ", label_style), Span::raw(last_success)]),
+      Line::from(vec![
+        Span::styled("Failures      ", label_style),
+        Span::raw(feed_health.failures.to_string()),
+      ]),
+    ];
+    if feed_health.dead {
+      lines.push(Line::from(vec![
+        Span::styled("Status        ", label_style),
+        Span::styled("dead, no longer refreshed", Style::default().fg(palette.error)),
+        Span::styled(" (r to retry)", Style::default().fg(palette.muted)),
+      ]));
+    }
+    if let Some(extraction) = feed_health.extraction {
+      let mut line = Line::from(vec![
+        Span::styled("Extraction    ", label_style),
+        Span::raw(extraction.to_string()),
+      ]);
+      if extraction != Extraction::Readability {
+        line.spans.push(Span::styled(
+          " (readability found too little)",
+          Style::default().fg(palette.muted),
+        ));
+      }
+      lines.push(line);
+    }
+    if let Some(error) = &feed_health.last_error {
+      lines.push(Line::default());
+      lines.push(Line::styled(error.as_str(), Style::default().fg(palette.error)));
+      if let Some(kind) = feed_health.error_kind {
+        lines.push(Line::styled(
+          format!("{kind}: {}", kind.hint()),
+          Style::default().fg(palette.muted),
+        ));
+      }
+    }
+
+    let paragraph = Paragraph::new(Text::from(lines))
+      .wrap(Wrap { trim: false })
+      .block(Block::bordered().border_type(BorderType::Rounded).title("Details"));
+    f.render_widget(paragraph, area);
+  }
+}
+
+impl Component for HealthView {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+    if self.selected_tab == self.tab && !self.health.is_empty() {
+      let selected_item_idx = self.state.selected().unwrap_or(0);
+      let page = scroll::page_rows(self.list_area, 1);
+      let wrap = self.config.display.wrap_lists;
+      if let Some(idx) =
+        scroll::navigate(self.state.selected(), self.health.len(), key.code, page, wrap)
+      {
+        self.state.select(Some(idx));
+        return Ok(None);
+      }
+      match key.code {
+        KeyCode::Char('r') => {
+          if let Some(feed_health) = self.health.get_mut(selected_item_idx) {
+            feed_health.dead = false;
+            if let Some(tx) = &self.command_tx {
+              tx.send(Action::RequestRefresh(RefreshScope::Feed(feed_health.url.clone())))?;
+            }
+          }
+        },
+        _ => {},
+      }
+    }
+    Ok(None)
+  }
+
+  fn update(&mut self, action: Action) -> Result<Option<Action>> {
+    match action {
+      Action::ChangeTab(tab) => {
+        self.selected_tab = tab;
+      },
+      Action::RefreshFinished => {
+        if let Some(tx) = &self.command_tx {
+          tx.send(Action::RequestUpdateHealth(self.tab))?;
+        }
+      },
+      Action::UpdateHealth(_, health) => {
+        self.health = health;
+      },
+      _ => {},
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+    let palette = *self.config.palette();
+    let [list_area, detail_area] =
+      Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(area);
+
+    let healthy_style = Style::default().fg(palette.accent);
+    let failing_style = Style::default().fg(palette.error);
+    let name_style = Style::default().fg(palette.title).add_modifier(Modifier::BOLD);
+    let dead_style = Style::default().fg(palette.muted);
+    let selected_name_style = self.config.selected_style();
+
+    let items: Vec<ListItem> = self
+      .health
+      .iter()
+      .enumerate()
+      .map(|(i, feed_health)| {
+        let style = match (self.state.selected() == Some(i), feed_health.dead) {
+          (true, _) => selected_name_style,
+          (false, true) => dead_style,
+          (false, false) => name_style,
+        };
+        // A half circle marks feeds whose articles readability could not extract.
+        let fell_back = feed_health.extraction.is_some_and(|e| e != Extraction::Readability);
+        let status = match (feed_health.failures, fell_back) {
+          _ if feed_health.dead => Span::styled("⚠ ", failing_style),
+          (0, false) => Span::styled("● ", healthy_style),
+          (0, true) => Span::styled("◐ ", healthy_style),
+          _ => Span::styled("● ", failing_style),
+        };
+        ListItem::new(Line::from(vec![
+          status,
+          Span::styled(self.feed_name(feed_health).to_string(), style),
+        ]))
+      })
+      .collect();
+
+    let mut block = Block::bordered().border_type(BorderType::Rounded).title("Feed health");
+    if let Some(position) = scroll::position_title(&self.state, self.health.len()) {
+      block = block.title(position);
+    }
+    let list = List::new(items).block(block).highlight_symbol(" ┃ ").repeat_highlight_symbol(true);
+    f.render_stateful_widget(list, list_area, &mut self.state);
+    self.list_area = list_area;
+    scroll::render_scrollbar(f, list_area, &self.state, self.health.len());
+
+    if let Some(feed_health) = self.state.selected().and_then(|i| self.health.get(i)) {
+      self.draw_details(f, detail_area, feed_health);
+    }
+
+    Ok(())
+  }
+}
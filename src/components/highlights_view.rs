@@ -0,0 +1,203 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span, Text},
+  widgets::{Block, BorderType, List, ListItem, ListState, Paragraph, Wrap},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{scroll, Component};
+use crate::{
+  action::{Action, TabId},
+  config::Config,
+  db::Highlight,
+  export::ExportedArticle,
+  tui::Frame,
+};
+
+/// Lists the highlights saved from the reader, grouped by article.
+pub struct HighlightsView {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  tab: TabId,
+  selected_tab: TabId,
+  highlights: Vec<Highlight>,
+  state: ListState,
+  /// Where the list was last drawn, to page through it.
+  list_area: Rect,
+}
+
+impl HighlightsView {
+  pub fn new(tab: TabId) -> Self {
+    Self {
+      command_tx: None,
+      config: Config::default(),
+      tab,
+      selected_tab: tab,
+      highlights: Vec::new(),
+      state: ListState::default().with_selected(Some(0)),
+      list_area: Rect::default(),
+    }
+  }
+
+  fn delete_selected(&mut self) -> Result<()> {
+    let Some(idx) = self.state.selected().filter(|&idx| idx < self.highlights.len()) else {
+      return Ok(());
+    };
+    let highlight = self.highlights.remove(idx);
+    if let Some(tx) = &self.command_tx {
+      tx.send(Action::DeleteHighlight(highlight.id))?;
+    }
+    self.state.select(Some(idx.min(self.highlights.len().saturating_sub(1))));
+    Ok(())
+  }
+
+  fn draw_details(&self, f: &mut Frame<'_>, area: Rect, highlight: &Highlight) {
+    let palette = *self.config.palette();
+    let label_style = Style::default().fg(palette.text);
+    let mut lines: Vec<Line> = highlight
+      .text
+      .lines()
+      .map(|line| Line::styled(line, Style::default().fg(palette.foreground)))
+      .collect();
+    lines.push(Line::default());
+    lines
+      .push(Line::from(vec![Span::styled("Article  ", label_style), Span::raw(&highlight.title)]));
+    lines.push(Line::from(vec![
+      Span::styled("Feed     ", label_style),
+      Span::raw(&highlight.feed_name),
+    ]));
+    lines.push(Line::from(vec![
+      Span::styled("Saved    ", label_style),
+      Span::raw(self.config.display.format_date(highlight.created_at)),
+    ]));
+    lines.push(Line::default());
+    lines.push(Line::styled(
+      "[o] open article      [d] delete      [e] export",
+      Style::default().fg(palette.muted),
+    ));
+
+    let paragraph = Paragraph::new(Text::from(lines))
+      .wrap(Wrap { trim: false })
+      .block(Block::bordered().border_type(BorderType::Rounded).title("Highlight"));
+    f.render_widget(paragraph, area);
+  }
+}
+
+impl Component for HighlightsView {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+    if self.selected_tab == self.tab && !self.highlights.is_empty() {
+      let selected_item_idx = self.state.selected().unwrap_or(0);
+      let page = scroll::page_rows(self.list_area, 1);
+      let wrap = self.config.display.wrap_lists;
+      if let Some(idx) =
+        scroll::navigate(self.state.selected(), self.highlights.len(), key.code, page, wrap)
+      {
+        self.state.select(Some(idx));
+        return Ok(None);
+      }
+      match key.code {
+        KeyCode::Char('o') => {
+          if let (Some(highlight), Some(tx)) =
+            (self.highlights.get(selected_item_idx), &self.command_tx)
+          {
+            tx.send(Action::OpenUrl(highlight.url.clone()))?;
+          }
+        },
+        KeyCode::Char('d') => self.delete_selected()?,
+        KeyCode::Char('e') => {
+          if let Some(tx) = &self.command_tx {
+            let article = ExportedArticle::from_highlights(&self.highlights);
+            tx.send(Action::ShowExportDialog(article))?;
+          }
+        },
+        _ => {},
+      }
+    }
+    Ok(None)
+  }
+
+  fn update(&mut self, action: Action) -> Result<Option<Action>> {
+    match action {
+      Action::ChangeTab(tab) => {
+        // Coming back to the tab picks up highlights saved in the meantime.
+        if tab == self.tab && self.selected_tab != tab {
+          if let Some(tx) = &self.command_tx {
+            tx.send(Action::RequestUpdateHighlights(self.tab))?;
+          }
+        }
+        self.selected_tab = tab;
+      },
+      Action::UpdateHighlights(_, highlights) => {
+        self.highlights = highlights;
+        let selected = self.state.selected().unwrap_or(0);
+        self.state.select(Some(selected.min(self.highlights.len().saturating_sub(1))));
+      },
+      _ => {},
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+    let palette = *self.config.palette();
+    let [list_area, detail_area] =
+      Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(area);
+
+    let text_style = Style::default().fg(palette.foreground);
+    let selected_text_style = self.config.selected_style();
+    let title_style = Style::default().fg(palette.title).add_modifier(Modifier::BOLD);
+    let desc_style = Style::default().fg(palette.text);
+
+    let mut items: Vec<ListItem> = Vec::new();
+    for (i, highlight) in self.highlights.iter().enumerate() {
+      let style = if self.state.selected() == Some(i) { selected_text_style } else { text_style };
+      let first_line = highlight.text.lines().next().unwrap_or_default();
+      let mut lines = vec![Line::styled(format!("“{first_line}”"), style)];
+      // Only the first highlight of an article names it.
+      if i == 0 || self.highlights[i - 1].url != highlight.url {
+        lines.insert(0, Line::styled(highlight.title.as_str(), title_style));
+        lines.insert(1, Line::styled(highlight.feed_name.as_str(), desc_style));
+      }
+      items.push(ListItem::new(Text::from(lines)));
+    }
+
+    let block = Block::bordered().border_type(BorderType::Rounded).title("Highlights");
+    if items.is_empty() {
+      let empty = Paragraph::new(Line::styled(
+        "No highlights yet. Select lines in the reader with v and save them with y.",
+        desc_style,
+      ))
+      .wrap(Wrap { trim: false })
+      .block(block);
+      f.render_widget(empty, area);
+      return Ok(());
+    }
+
+    let block = match scroll::position_title(&self.state, self.highlights.len()) {
+      Some(position) => block.title(position),
+      None => block,
+    };
+    let list = List::new(items).block(block).highlight_symbol(" ┃ ");
+    f.render_stateful_widget(list, list_area, &mut self.state);
+    self.list_area = list_area;
+    scroll::render_scrollbar(f, list_area, &self.state, self.highlights.len());
+
+    if let Some(highlight) = self.state.selected().and_then(|i| self.highlights.get(i)) {
+      self.draw_details(f, detail_area, highlight);
+    }
+
+    Ok(())
+  }
+}
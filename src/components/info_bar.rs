@@ -1,29 +1,201 @@
+use std::time::{Duration, Instant};
+
+use chrono::Local;
 use clap::crate_version;
 use ratatui::{
   layout::{Constraint, Direction, Layout, Rect},
+  style::Style,
+  text::{Line, Span},
   widgets::Paragraph,
 };
 
-use crate::{components::Component, config::Config, tui::Frame};
+use crate::{
+  action::Action,
+  components::Component,
+  config::{Config, InfoBarSegment},
+  db::SaveSummary,
+  i18n,
+  mode::Mode,
+  refresh::{FailureKind, RefreshError},
+  tui::Frame,
+};
+
+/// How long a notification stays in the info bar.
+const MESSAGE_DURATION: Duration = Duration::from_secs(10);
+
+struct Message {
+  text: String,
+  is_error: bool,
+  shown_at: Instant,
+}
 
 pub struct InfoBar {
   config: Config,
+  mode: Mode,
+  unread: Option<usize>,
+  refreshing: bool,
+  /// Items added and changed by the refresh in progress.
+  refresh_summary: SaveSummary,
+  /// Why feeds failed in the refresh in progress.
+  refresh_failures: Vec<RefreshError>,
+  speaking: bool,
+  message: Option<Message>,
+  /// Text of each segment as last drawn.
+  drawn: Vec<Option<String>>,
 }
 
 impl InfoBar {
   pub fn new() -> Self {
-    Self { config: Config::default() }
+    Self {
+      config: Config::default(),
+      mode: Mode::default(),
+      unread: None,
+      refreshing: false,
+      refresh_summary: SaveSummary::default(),
+      refresh_failures: Vec::new(),
+      speaking: false,
+      message: None,
+      drawn: Vec::new(),
+    }
+  }
+
+  fn segment_text(&self, segment: InfoBarSegment) -> Option<String> {
+    match segment {
+      InfoBarSegment::Version => Some("Nuuslees ".to_string() + crate_version!()),
+      InfoBarSegment::Mode => {
+        let mode = match self.mode {
+          Mode::Main => i18n::Message::ModeMain,
+          Mode::FeedList => i18n::Message::ModeFeeds,
+          Mode::ViewArticles => i18n::Message::ModeArticles,
+          Mode::Refreshing => i18n::Message::ModeRefreshing,
+        };
+        Some(self.config.text(mode).to_string())
+      },
+      InfoBarSegment::Unread => {
+        self.unread.map(|unread| self.config.format(i18n::Message::Unread, &[&unread]))
+      },
+      InfoBarSegment::Refresh => {
+        self.refreshing.then(|| {
+          let summary = &self.refresh_summary;
+          let mut text =
+            self.config.format(i18n::Message::Refreshing, &[&summary.inserted, &summary.updated]);
+          if !self.refresh_failures.is_empty() {
+            let failed = self.refresh_failures.len();
+            text.push_str(&self.config.format(i18n::Message::RefreshingFailed, &[&failed]));
+          }
+          text
+        })
+      },
+      InfoBarSegment::Speech => {
+        self.speaking.then(|| self.config.text(i18n::Message::ReadingAloud).to_string())
+      },
+      InfoBarSegment::Message => {
+        let message = self.message.as_ref()?;
+        (message.shown_at.elapsed() < MESSAGE_DURATION).then(|| message.text.clone())
+      },
+      InfoBarSegment::Clock => {
+        Some(Local::now().format(&self.config.info_bar.clock_format).to_string())
+      },
+    }
+  }
+}
+
+/// What to tell about the feeds that failed in a refresh: the error itself if only one did,
+/// otherwise how many failed of each kind.
+fn failure_message(config: &Config, failures: &[RefreshError]) -> Option<String> {
+  match failures {
+    [] => None,
+    [error] => Some(config.format(i18n::Message::FeedFailed, &[&error.explained()])),
+    _ => {
+      let mut counts: Vec<(FailureKind, usize)> = Vec::new();
+      for error in failures {
+        match counts.iter_mut().find(|(kind, _)| *kind == error.kind) {
+          Some((_, count)) => *count += 1,
+          None => counts.push((error.kind, 1)),
+        }
+      }
+      let counts: Vec<String> =
+        counts.iter().map(|(kind, count)| format!("{count} {kind}")).collect();
+      Some(config.format(i18n::Message::FeedsFailed, &[&failures.len(), &counts.join(", ")]))
+    },
+  }
+}
+
+impl InfoBar {
+  fn segment_texts(&self) -> Vec<Option<String>> {
+    self.config.info_bar.segments.iter().map(|segment| self.segment_text(*segment)).collect()
   }
 }
 
 impl Component for InfoBar {
+  fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn is_dirty(&self) -> bool {
+    // The clock moves on and messages expire without any action arriving.
+    self.segment_texts() != self.drawn
+  }
+
+  fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    match action {
+      Action::ModeChange(mode) => self.mode = mode,
+      Action::UpdateUnreadCount(unread) => self.unread = Some(unread),
+      Action::RefreshStarted => {
+        self.refreshing = true;
+        self.refresh_summary = SaveSummary::default();
+        self.refresh_failures.clear();
+      },
+      Action::FeedFailed(_, error) => self.refresh_failures.push(error),
+      Action::FeedSaved(summary) => {
+        self.refresh_summary.inserted += summary.inserted;
+        self.refresh_summary.updated += summary.updated;
+      },
+      Action::RefreshFinished => {
+        self.refreshing = false;
+        if let Some(text) = failure_message(&self.config, &self.refresh_failures) {
+          self.message = Some(Message { text, is_error: true, shown_at: Instant::now() });
+        }
+      },
+      Action::ReadAloudStarted => self.speaking = true,
+      Action::ReadAloudFinished => self.speaking = false,
+      Action::Notify(text) => {
+        self.message = Some(Message { text, is_error: false, shown_at: Instant::now() })
+      },
+      Action::Error(text) => {
+        self.message = Some(Message { text, is_error: true, shown_at: Instant::now() })
+      },
+      _ => {},
+    }
+    Ok(None)
+  }
+
   fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
+    let palette = *self.config.palette();
     let info_area = Layout::default()
       .direction(Direction::Vertical)
       .constraints([Constraint::Fill(1), Constraint::Length(1)])
       .split(area)[1];
 
-    let paragraph = Paragraph::new("Nuuslees ".to_string() + crate_version!());
+    let separator = Span::styled(" │ ", Style::default().fg(palette.muted));
+    let mut spans = Vec::new();
+    self.drawn = self.segment_texts();
+    for (segment, text) in self.config.info_bar.segments.iter().zip(&self.drawn) {
+      if let Some(text) = text.clone() {
+        if !spans.is_empty() {
+          spans.push(separator.clone());
+        }
+        let is_error = self.message.as_ref().is_some_and(|message| message.is_error);
+        if *segment == InfoBarSegment::Message && is_error {
+          spans.push(Span::styled(text, Style::default().fg(palette.error)));
+        } else {
+          spans.push(Span::raw(text));
+        }
+      }
+    }
+
+    let paragraph = Paragraph::new(Line::from(spans));
     f.render_widget(paragraph, info_area);
     Ok(())
   }
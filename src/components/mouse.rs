@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+use ratatui::layout::{Position, Rect};
+
+/// Maximum delay between two clicks on the same row for them to count as a double click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Returns the index of the list row under (`column`, `row`) for a bordered list drawn in `area`
+/// whose rows are all `row_height` lines tall and whose first visible row is `offset`.
+pub fn list_index_at(
+  area: Rect,
+  offset: usize,
+  row_height: u16,
+  len: usize,
+  column: u16,
+  row: u16,
+) -> Option<usize> {
+  let inner = area.inner(&ratatui::layout::Margin { vertical: 1, horizontal: 1 });
+  if row_height == 0 || !inner.contains(Position { x: column, y: row }) {
+    return None;
+  }
+  let idx = offset + ((row - inner.y) / row_height) as usize;
+  (idx < len).then_some(idx)
+}
+
+/// Remembers the last clicked row to detect double clicks.
+#[derive(Default)]
+pub struct ClickTracker {
+  last_click: Option<(Instant, usize)>,
+}
+
+impl ClickTracker {
+  /// Records a click on row `idx`, returning whether it completes a double click.
+  pub fn click(&mut self, idx: usize) -> bool {
+    let now = Instant::now();
+    let double = matches!(
+      self.last_click,
+      Some((at, last_idx)) if last_idx == idx && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL
+    );
+    self.last_click = if double { None } else { Some((now, idx)) };
+    double
+  }
+}
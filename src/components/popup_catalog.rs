@@ -0,0 +1,296 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span, Text},
+  widgets::{Block, BorderType, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+  action::Action,
+  catalog::{self, CatalogEntry},
+  components::{scroll, text_input::TextInput, Component},
+  config::Config,
+  fetcher::FetchedFeed,
+  html,
+  tui::Frame,
+};
+
+/// How many of a feed's latest articles its preview lists.
+const PREVIEW_ITEMS: usize = 5;
+
+/// Directory of popular feeds, narrowed down as the user types. Enter fetches the selected feed
+/// and previews it, and Enter again subscribes to it.
+pub struct CatalogPopup {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  visible: bool,
+  entries: Vec<CatalogEntry>,
+  /// Links of the feeds already subscribed to, marked in the list.
+  subscribed: Vec<String>,
+  query: TextInput,
+  matches: Vec<usize>,
+  state: ListState,
+  /// Where the list was last drawn, to page through it.
+  list_area: Rect,
+  /// The feed being previewed, with what was fetched once it arrives.
+  preview: Option<(CatalogEntry, Option<Result<FetchedFeed, String>>)>,
+}
+
+impl CatalogPopup {
+  pub fn new() -> Self {
+    Self {
+      command_tx: None,
+      config: Config::default(),
+      visible: false,
+      entries: catalog::entries(),
+      subscribed: Vec::new(),
+      query: TextInput::new(),
+      matches: Vec::new(),
+      state: ListState::default(),
+      list_area: Rect::default(),
+      preview: None,
+    }
+  }
+
+  fn update_matches(&mut self) {
+    self.matches =
+      (0..self.entries.len()).filter(|&i| self.entries[i].matches(self.query.text())).collect();
+    self.state.select((!self.matches.is_empty()).then_some(0));
+  }
+
+  fn selected_entry(&self) -> Option<&CatalogEntry> {
+    let idx = *self.matches.get(self.state.selected()?)?;
+    self.entries.get(idx)
+  }
+
+  fn preview_selected(&mut self) -> color_eyre::Result<()> {
+    let Some(entry) = self.selected_entry().cloned() else {
+      return Ok(());
+    };
+    if self.subscribed.contains(&entry.link) {
+      return Ok(());
+    }
+    if let Some(tx) = &self.command_tx {
+      tx.send(Action::PreviewFeed(entry.clone()))?;
+    }
+    self.preview = Some((entry, None));
+    Ok(())
+  }
+
+  /// Subscribes to the previewed feed once it has been fetched without trouble.
+  fn subscribe_previewed(&mut self) -> color_eyre::Result<()> {
+    if !matches!(self.preview, Some((_, Some(Ok(_))))) {
+      return Ok(());
+    }
+    let Some((entry, _)) = self.preview.take() else {
+      return Ok(());
+    };
+    if let Some(tx) = &self.command_tx {
+      tx.send(Action::Subscribe(entry.clone()))?;
+    }
+    self.subscribed.push(entry.link);
+    Ok(())
+  }
+
+  fn draw_preview(&self, f: &mut Frame<'_>, area: Rect) {
+    let Some((entry, fetched)) = &self.preview else {
+      return;
+    };
+    let palette = *self.config.palette();
+    let [_, preview_area, _] = Layout::vertical([
+      Constraint::Percentage(15),
+      Constraint::Percentage(70),
+      Constraint::Percentage(15),
+    ])
+    .areas(area);
+    let [_, preview_area, _] = Layout::horizontal([
+      Constraint::Percentage(10),
+      Constraint::Percentage(80),
+      Constraint::Percentage(10),
+    ])
+    .areas(preview_area);
+    let block = Block::bordered().border_type(BorderType::Rounded).title(entry.name.as_str());
+    let inner_area = block.inner(preview_area);
+    let [text_area, help_area] =
+      Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner_area);
+
+    let muted_style = Style::default().fg(palette.muted);
+    let (lines, help) = match fetched {
+      None => (vec![Line::styled(format!("Fetching {}…", entry.link), muted_style)], "[Esc] back"),
+      Some(Err(error)) => {
+        (vec![Line::styled(error.as_str(), Style::default().fg(palette.error))], "[Esc] back")
+      },
+      Some(Ok(feed)) => {
+        let mut lines = vec![
+          Line::styled(
+            feed.name.as_str(),
+            Style::default().fg(palette.title).add_modifier(Modifier::BOLD),
+          ),
+          Line::styled(html::to_plain_text(&feed.desc), Style::default().fg(palette.text)),
+          Line::default(),
+        ];
+        let mut items: Vec<_> = feed.items.iter().collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.pub_date));
+        for item in items.iter().take(PREVIEW_ITEMS) {
+          lines.push(Line::from(vec![
+            Span::styled(
+              format!("{}  ", self.config.display.format_date(item.pub_date)),
+              muted_style,
+            ),
+            Span::styled(item.title.as_str(), Style::default().fg(palette.foreground)),
+          ]));
+        }
+        if items.is_empty() {
+          lines.push(Line::styled("The feed has no articles yet", muted_style));
+        }
+        (lines, "[Enter] subscribe      [Esc] back")
+      },
+    };
+
+    f.render_widget(Clear, preview_area);
+    f.render_widget(block, preview_area);
+    f.render_widget(Paragraph::new(Text::from(lines)).wrap(Wrap { trim: false }), text_area);
+    f.render_widget(Line::styled(help, Style::default().fg(palette.text)), help_area);
+  }
+}
+
+impl Component for CatalogPopup {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+    if !self.visible {
+      return Ok(None);
+    }
+    if self.preview.is_some() {
+      match key.code {
+        KeyCode::Esc => self.preview = None,
+        KeyCode::Enter => self.subscribe_previewed()?,
+        _ => {},
+      }
+      return Ok(None);
+    }
+    let selected = self.state.selected().unwrap_or(0);
+    match key.code {
+      KeyCode::Esc => self.visible = false,
+      KeyCode::Enter => self.preview_selected()?,
+      // The other navigation keys are for the search field.
+      code @ (KeyCode::Down | KeyCode::Up | KeyCode::PageDown | KeyCode::PageUp) => {
+        let page = usize::from(self.list_area.height);
+        let wrap = self.config.display.wrap_lists;
+        if let Some(idx) = scroll::navigate(Some(selected), self.matches.len(), code, page, wrap) {
+          self.state.select(Some(idx));
+        }
+      },
+      _ => {
+        if self.query.handle_key(key) {
+          self.update_matches();
+        }
+      },
+    }
+    Ok(None)
+  }
+
+  fn handle_paste(&mut self, text: String) -> color_eyre::Result<Option<Action>> {
+    if self.visible && self.preview.is_none() {
+      self.query.insert(&text);
+      self.update_matches();
+    }
+    Ok(None)
+  }
+
+  fn captures_input(&self) -> bool {
+    self.visible
+  }
+
+  fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    match action {
+      Action::ShowCatalog(subscribed) => {
+        self.subscribed = subscribed;
+        self.query.clear();
+        self.update_matches();
+        self.preview = None;
+        self.visible = true;
+      },
+      Action::ShowFeedPreview(entry, fetched) => {
+        // A preview closed before its feed arrived is not opened again.
+        if let Some((previewed, pending)) = &mut self.preview {
+          if *previewed == entry {
+            *pending = Some(fetched);
+          }
+        }
+      },
+      _ => {},
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
+    let palette = *self.config.palette();
+    if !self.visible {
+      return Ok(());
+    }
+    let popup_layout = Layout::vertical([
+      Constraint::Percentage(15),
+      Constraint::Fill(1),
+      Constraint::Percentage(15),
+    ])
+    .split(area);
+    let popup_area = Layout::horizontal([
+      Constraint::Percentage(15),
+      Constraint::Percentage(70),
+      Constraint::Percentage(15),
+    ])
+    .split(popup_layout[1])[1];
+    let block = Block::bordered().border_type(BorderType::Rounded).title("Feed catalog");
+    let inner_area = block.inner(popup_area);
+    let [search_area, list_area, help_area] =
+      Layout::vertical([Constraint::Length(2), Constraint::Fill(1), Constraint::Length(1)])
+        .areas(inner_area);
+
+    let label_style = Style::default().fg(palette.text);
+    let value_style = Style::default().fg(palette.foreground).add_modifier(Modifier::BOLD);
+    let category_style = Style::default().fg(palette.title);
+    let desc_style = Style::default().fg(palette.muted);
+    let subscribed_style = Style::default().fg(palette.accent);
+
+    let items: Vec<ListItem> = self
+      .matches
+      .iter()
+      .map(|&i| {
+        let entry = &self.entries[i];
+        let marker = if self.subscribed.contains(&entry.link) { "✓ " } else { "  " };
+        ListItem::new(Line::from(vec![
+          Span::styled(marker, subscribed_style),
+          Span::styled(format!("{:<8} ", entry.category), category_style),
+          Span::styled(entry.name.clone(), value_style),
+          Span::styled(format!("  {}", entry.desc), desc_style),
+        ]))
+      })
+      .collect();
+    let list = List::new(items).highlight_symbol("┃").highlight_style(self.config.selected_style());
+
+    let help = Paragraph::new(Line::styled(
+      "[↑/↓] select      [Enter] preview      [Esc] close",
+      label_style,
+    ));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+    self.query.render_labeled(f, search_area, Span::styled("Search: ", label_style), value_style);
+    f.render_stateful_widget(list, list_area, &mut self.state);
+    self.list_area = list_area;
+    f.render_widget(help, help_area);
+    self.draw_preview(f, popup_area);
+    Ok(())
+  }
+}
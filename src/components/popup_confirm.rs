@@ -0,0 +1,100 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  prelude::{Modifier, Style},
+  text::{Line, Text},
+  widgets::{Block, BorderType, Clear, Paragraph, Wrap},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{action::Action, components::Component, config::Config, i18n::Message, tui::Frame};
+
+/// Asks before an action that cannot be undone, such as quitting or deleting a feed. The action
+/// is sent on `y` and dropped on `n` or Esc.
+pub struct ConfirmPopup {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  /// The question asked and the action sent if the answer is yes.
+  pending: Option<(String, Action)>,
+}
+
+impl ConfirmPopup {
+  pub fn new() -> Self {
+    Self { command_tx: None, config: Config::default(), pending: None }
+  }
+}
+
+impl Component for ConfirmPopup {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+    match key.code {
+      KeyCode::Char('y') => {
+        if let (Some(tx), Some((_, action))) = (&self.command_tx, self.pending.take()) {
+          tx.send(action)?;
+        }
+      },
+      KeyCode::Char('n') | KeyCode::Esc => self.pending = None,
+      _ => {},
+    }
+    Ok(None)
+  }
+
+  fn captures_input(&self) -> bool {
+    self.pending.is_some()
+  }
+
+  fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    if let Action::Confirm(prompt, action) = action {
+      self.pending = Some((prompt, *action));
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
+    let palette = *self.config.palette();
+    let Some((prompt, _)) = &self.pending else {
+      return Ok(());
+    };
+    let percent_x: u16 = 40;
+    let percent_y: u16 = 20;
+
+    let popup_layout = Layout::vertical([
+      Constraint::Percentage((100 - percent_y) / 2),
+      Constraint::Percentage(percent_y),
+      Constraint::Percentage((100 - percent_y) / 2),
+    ])
+    .split(area);
+    let popup_area = Layout::horizontal([
+      Constraint::Percentage((100 - percent_x) / 2),
+      Constraint::Percentage(percent_x),
+      Constraint::Percentage((100 - percent_x) / 2),
+    ])
+    .split(popup_layout[1])[1];
+
+    let text = Text::from(vec![
+      Line::styled(
+        prompt.as_str(),
+        Style::default().fg(palette.foreground).add_modifier(Modifier::BOLD),
+      ),
+      Line::styled(self.config.text(Message::ConfirmKeys), Style::default().fg(palette.text)),
+    ])
+    .centered();
+
+    let paragraph = Paragraph::new(text)
+      .centered()
+      .wrap(Wrap { trim: true })
+      .block(Block::bordered().border_type(BorderType::Rounded));
+    f.render_widget(Clear, popup_area);
+    f.render_widget(paragraph, popup_area);
+    Ok(())
+  }
+}
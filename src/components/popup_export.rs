@@ -0,0 +1,137 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span},
+  widgets::{Block, BorderType, Clear, Paragraph, Wrap},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+  action::Action,
+  components::{text_input::TextInput, Component},
+  config::Config,
+  export::{ExportFormat, ExportedArticle},
+  tui::Frame,
+};
+
+/// Save dialog asking for the format and file name of an exported article.
+pub struct ExportPopup {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  article: Option<ExportedArticle>,
+  format: ExportFormat,
+  filename: TextInput,
+}
+
+impl ExportPopup {
+  pub fn new() -> Self {
+    Self {
+      command_tx: None,
+      config: Config::default(),
+      article: None,
+      format: ExportFormat::default(),
+      filename: TextInput::new(),
+    }
+  }
+
+  fn cycle_format(&mut self) {
+    let old_extension = format!(".{}", self.format.extension());
+    self.format = self.format.next();
+    if let Some(stem) = self.filename.text().strip_suffix(&old_extension) {
+      let filename = format!("{stem}.{}", self.format.extension());
+      self.filename.set_text(&filename);
+    }
+  }
+}
+
+impl Component for ExportPopup {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+    if self.article.is_none() {
+      return Ok(None);
+    }
+    match key.code {
+      KeyCode::Enter => {
+        if let (Some(tx), Some(article)) = (&self.command_tx, self.article.take()) {
+          let filename = self.filename.text().to_string();
+          tx.send(Action::ExportArticle(article, self.format, filename))?;
+        }
+      },
+      KeyCode::Esc => self.article = None,
+      KeyCode::Tab => self.cycle_format(),
+      _ => {
+        self.filename.handle_key(key);
+      },
+    }
+    Ok(None)
+  }
+
+  fn handle_paste(&mut self, text: String) -> color_eyre::Result<Option<Action>> {
+    if self.article.is_some() {
+      self.filename.insert(&text);
+    }
+    Ok(None)
+  }
+
+  fn captures_input(&self) -> bool {
+    self.article.is_some()
+  }
+
+  fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    if let Action::ShowExportDialog(article) = action {
+      self.filename.set_text(&format!("{}.{}", article.file_stem(), self.format.extension()));
+      self.article = Some(article);
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
+    let palette = *self.config.palette();
+    if self.article.is_some() {
+      let popup_layout =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(6), Constraint::Fill(1)])
+          .split(area);
+      let popup_area = Layout::horizontal([
+        Constraint::Percentage(20),
+        Constraint::Percentage(60),
+        Constraint::Percentage(20),
+      ])
+      .split(popup_layout[1])[1];
+
+      let label_style = Style::default().fg(palette.text);
+      let value_style = Style::default().fg(palette.foreground).add_modifier(Modifier::BOLD);
+      let block = Block::bordered().border_type(BorderType::Rounded).title("Export article");
+      let [format_area, file_area, help_area] =
+        Layout::vertical([Constraint::Length(1); 3]).areas(block.inner(popup_area));
+      let format = Paragraph::new(Line::from(vec![
+        Span::styled("Format: ", label_style),
+        Span::styled(self.format.to_string(), value_style),
+        Span::styled("  [Tab] change", label_style),
+      ]));
+      let help = Paragraph::new(Line::styled("[Enter] save      [Esc] cancel", label_style))
+        .wrap(Wrap { trim: false });
+
+      f.render_widget(Clear, popup_area);
+      f.render_widget(block, popup_area);
+      f.render_widget(format, format_area);
+      self.filename.render_labeled(
+        f,
+        file_area,
+        Span::styled("File:   ", label_style),
+        value_style,
+      );
+      f.render_widget(help, help_area);
+    }
+    Ok(())
+  }
+}
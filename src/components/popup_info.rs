@@ -0,0 +1,190 @@
+use chrono::Utc;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span, Text},
+  widgets::{Block, BorderType, Clear, Paragraph, Wrap},
+};
+
+use crate::{
+  action::Action,
+  components::Component,
+  config::Config,
+  db::{FeedInfo, FeedItem},
+  tui::Frame,
+};
+
+/// Width of the label column.
+const LABEL_WIDTH: usize = 14;
+
+/// Shows everything known about a feed or an article as labelled rows, for when the lists cut it
+/// short and for looking into odd items. Any of Esc, `q` or `i` closes it and `j`/`k` scroll.
+pub struct InfoPopup {
+  config: Config,
+  /// The title of the popup and its rows of label and value.
+  info: Option<(String, Vec<(String, String)>)>,
+  scroll: u16,
+}
+
+impl InfoPopup {
+  pub fn new() -> Self {
+    Self { config: Config::default(), info: None, scroll: 0 }
+  }
+
+  fn article_rows(&self, item: &FeedItem) -> Vec<(String, String)> {
+    let mut rows = vec![("Title".to_string(), item.title.clone())];
+    if let Some(author) = &item.author {
+      rows.push(("Author".to_string(), author.clone()));
+    }
+    rows.push(("Published".to_string(), self.config.display.format_date(item.pub_date)));
+    rows.push(("Feed".to_string(), format!("{} ({})", item.feed_name, item.feed_url)));
+    if !item.categories.is_empty() {
+      rows.push(("Categories".to_string(), item.categories.join(", ")));
+    }
+    rows.push(("URL".to_string(), item.url.clone()));
+    if let Some(comments) = &item.comments {
+      rows.push(("Comments".to_string(), comments.clone()));
+    }
+    if let Some(guid) = &item.guid {
+      rows.push(("GUID".to_string(), guid.clone()));
+    }
+    rows.push(("ID".to_string(), item.id.to_string()));
+    rows.push(("Read".to_string(), if item.read { "yes" } else { "no" }.to_string()));
+    if let Some(alert) = &item.alert {
+      rows.push(("Alert".to_string(), alert.clone()));
+    }
+    rows
+  }
+
+  fn feed_rows(&self, info: &FeedInfo) -> Vec<(String, String)> {
+    let display = &self.config.display;
+    let mut rows = vec![
+      ("Name".to_string(), info.feed.name.clone()),
+      ("Feed".to_string(), info.feed.url.clone()),
+    ];
+    if let Some(site_url) = &info.site_url {
+      rows.push(("Site".to_string(), site_url.clone()));
+    }
+    if !info.feed.desc.is_empty() {
+      rows.push(("Description".to_string(), info.feed.desc.clone()));
+    }
+    rows.push(("Articles".to_string(), format!("{} stored, {} unread", info.items, info.unread)));
+    if let Some(cadence) = info.feed.cadence {
+      rows.push(("Latest".to_string(), display.format_date(cadence.latest)));
+      let mut posts = cadence.rate();
+      if cadence.is_quiet(Utc::now()) {
+        posts.push_str(", but has gone quiet");
+      }
+      rows.push(("Posts".to_string(), posts));
+    }
+    match &info.health {
+      Some(health) => {
+        rows.push(("Last fetch".to_string(), display.format_date(health.last_attempt)));
+        let status = match (&health.last_error, health.dead) {
+          (_, true) => format!("dead after {} failures, refresh it to revive", health.failures),
+          (Some(error), false) => format!("failing ({} in a row): {error}", health.failures),
+          (None, false) => "ok".to_string(),
+        };
+        rows.push(("Status".to_string(), status));
+        if let Some(kind) = health.error_kind.filter(|_| health.last_error.is_some()) {
+          rows.push(("Fix".to_string(), kind.hint().to_string()));
+        }
+        if let Some(success) = health.last_success {
+          rows.push(("Last success".to_string(), display.format_date(success)));
+        }
+      },
+      None => rows.push(("Last fetch".to_string(), "never".to_string())),
+    }
+    if info.feed.muted {
+      rows.push(("Muted".to_string(), "yes".to_string()));
+    }
+    rows
+  }
+}
+
+impl Component for InfoPopup {
+  fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+    if self.info.is_none() {
+      return Ok(None);
+    }
+    match key.code {
+      KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('i') => self.info = None,
+      KeyCode::Char('j') | KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+      KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+      _ => {},
+    }
+    Ok(None)
+  }
+
+  fn captures_input(&self) -> bool {
+    self.info.is_some()
+  }
+
+  fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    match action {
+      Action::ShowFeedInfo(info) => {
+        self.info = Some((info.feed.name.clone(), self.feed_rows(&info)));
+        self.scroll = 0;
+      },
+      Action::ShowArticleInfo(item) => {
+        self.info = Some(("Article".to_string(), self.article_rows(&item)));
+        self.scroll = 0;
+      },
+      _ => {},
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
+    let palette = *self.config.palette();
+    let Some((title, rows)) = &self.info else {
+      return Ok(());
+    };
+    let [_, popup_area, _] = Layout::vertical([
+      Constraint::Percentage(20),
+      Constraint::Percentage(60),
+      Constraint::Percentage(20),
+    ])
+    .areas(area);
+    let [_, popup_area, _] = Layout::horizontal([
+      Constraint::Percentage(20),
+      Constraint::Percentage(60),
+      Constraint::Percentage(20),
+    ])
+    .areas(popup_area);
+    let block = Block::bordered().border_type(BorderType::Rounded).title(title.as_str());
+    let inner_area = block.inner(popup_area);
+    let [rows_area, help_area] =
+      Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner_area);
+
+    let label_style = Style::default().fg(palette.text).add_modifier(Modifier::BOLD);
+    let value_style = Style::default().fg(palette.foreground);
+    let lines: Vec<Line> = rows
+      .iter()
+      .map(|(label, value)| {
+        Line::from(vec![
+          Span::styled(format!("{label:<LABEL_WIDTH$}"), label_style),
+          Span::styled(value.as_str(), value_style),
+        ])
+      })
+      .collect();
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+    f.render_widget(
+      Paragraph::new(Text::from(lines)).wrap(Wrap { trim: false }).scroll((self.scroll, 0)),
+      rows_area,
+    );
+    f.render_widget(
+      Line::styled("[j/k] scroll      [esc] close", Style::default().fg(palette.muted)),
+      help_area,
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,127 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span},
+  widgets::{Block, BorderType, Clear, List, ListItem, ListState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+  action::{Action, MenuItem},
+  components::{scroll, Component},
+  config::Config,
+  tui::Frame,
+};
+
+/// What can be done with a group, feed or article row, opened with `.` or a right click. Enter
+/// does the selected entry, as does pressing its key, and Esc or `.` closes the menu.
+pub struct MenuPopup {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  /// The title of the menu and its entries.
+  menu: Option<(String, Vec<MenuItem>)>,
+  state: ListState,
+}
+
+impl MenuPopup {
+  pub fn new() -> Self {
+    Self { command_tx: None, config: Config::default(), menu: None, state: ListState::default() }
+  }
+
+  /// Closes the menu and presses the key of the entry.
+  fn choose(&mut self, idx: usize) -> color_eyre::Result<()> {
+    let Some((_, items)) = self.menu.take() else {
+      return Ok(());
+    };
+    if let (Some(item), Some(tx)) = (items.get(idx), &self.command_tx) {
+      tx.send(Action::PressKey(item.key))?;
+    }
+    Ok(())
+  }
+}
+
+impl Component for MenuPopup {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+    let Some((_, items)) = &self.menu else {
+      return Ok(None);
+    };
+    let len = items.len();
+    if let Some(idx) =
+      scroll::navigate(self.state.selected(), len, key.code, len, self.config.display.wrap_lists)
+    {
+      self.state.select(Some(idx));
+      return Ok(None);
+    }
+    let selected = self.state.selected().unwrap_or(0);
+    match key.code {
+      KeyCode::Esc | KeyCode::Char('.') => self.menu = None,
+      KeyCode::Enter => self.choose(selected)?,
+      code => {
+        if let Some(idx) = items.iter().position(|item| item.key.code == code) {
+          self.choose(idx)?;
+        }
+      },
+    }
+    Ok(None)
+  }
+
+  fn captures_input(&self) -> bool {
+    self.menu.is_some()
+  }
+
+  fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    if let Action::ShowContextMenu(title, items) = action {
+      self.menu = Some((title, items));
+      self.state.select(Some(0));
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
+    let palette = *self.config.palette();
+    let Some((title, items)) = &self.menu else {
+      return Ok(());
+    };
+    let label_width = items.iter().map(|item| item.label.chars().count()).max().unwrap_or(0);
+    let key_width = items.iter().map(|item| item.key_label().chars().count()).max().unwrap_or(0);
+    let width = (label_width + key_width + 8).max(title.chars().count() + 4) as u16;
+    let height = items.len() as u16 + 2;
+    let [_, popup_area, _] =
+      Layout::vertical([Constraint::Fill(1), Constraint::Length(height), Constraint::Fill(1)])
+        .areas(area);
+    let [_, popup_area, _] =
+      Layout::horizontal([Constraint::Fill(1), Constraint::Length(width), Constraint::Fill(1)])
+        .areas(popup_area);
+
+    let label_style = Style::default().fg(palette.foreground);
+    let key_style = Style::default().fg(palette.muted);
+    let rows: Vec<ListItem> = items
+      .iter()
+      .map(|item| {
+        ListItem::new(Line::from(vec![
+          Span::styled(format!("{:<label_width$}  ", item.label), label_style),
+          Span::styled(format!("{:>key_width$}", item.key_label()), key_style),
+        ]))
+      })
+      .collect();
+    let list = List::new(rows)
+      .block(Block::bordered().border_type(BorderType::Rounded).title(title.as_str()))
+      .highlight_symbol("┃ ")
+      .highlight_style(self.config.selected_style());
+
+    f.render_widget(Clear, popup_area);
+    f.render_stateful_widget(list, popup_area, &mut self.state);
+    Ok(())
+  }
+}
@@ -0,0 +1,120 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Modifier, Style},
+  text::Line,
+  widgets::{Block, BorderType, Clear, Paragraph},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+  action::Action,
+  components::{text_input::TextInput, Component},
+  config::Config,
+  db::FeedItem,
+  tui::Frame,
+};
+
+/// Edits the note attached to an article. Enter starts a new line, so saving takes Ctrl-S, and
+/// saving an empty note removes it.
+pub struct NotePopup {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  item: Option<FeedItem>,
+  note: TextInput,
+}
+
+impl NotePopup {
+  pub fn new() -> Self {
+    Self { command_tx: None, config: Config::default(), item: None, note: TextInput::multiline() }
+  }
+
+  fn save(&mut self) -> color_eyre::Result<()> {
+    if let (Some(tx), Some(item)) = (&self.command_tx, self.item.take()) {
+      tx.send(Action::SaveNote(item.id, self.note.text().trim_end().to_string()))?;
+    }
+    Ok(())
+  }
+}
+
+impl Component for NotePopup {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+    if self.item.is_none() {
+      return Ok(None);
+    }
+    match key.code {
+      KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => self.save()?,
+      KeyCode::Esc => self.item = None,
+      _ => {
+        self.note.handle_key(key);
+      },
+    }
+    Ok(None)
+  }
+
+  fn handle_paste(&mut self, text: String) -> color_eyre::Result<Option<Action>> {
+    if self.item.is_some() {
+      self.note.insert(&text);
+    }
+    Ok(None)
+  }
+
+  fn captures_input(&self) -> bool {
+    self.item.is_some()
+  }
+
+  fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    if let Action::ShowNoteEditor(item) = action {
+      self.note.set_text(item.note.as_deref().unwrap_or_default());
+      self.item = Some(item);
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
+    let palette = *self.config.palette();
+    let Some(item) = &self.item else {
+      return Ok(());
+    };
+    let popup_layout = Layout::vertical([
+      Constraint::Percentage(25),
+      Constraint::Fill(1),
+      Constraint::Percentage(25),
+    ])
+    .split(area);
+    let popup_area = Layout::horizontal([
+      Constraint::Percentage(20),
+      Constraint::Percentage(60),
+      Constraint::Percentage(20),
+    ])
+    .split(popup_layout[1])[1];
+    let block =
+      Block::bordered().border_type(BorderType::Rounded).title(format!("Note on {}", item.title));
+    let inner_area = block.inner(popup_area);
+    let [note_area, help_area] =
+      Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner_area);
+
+    let label_style = Style::default().fg(palette.text);
+    let value_style = Style::default().fg(palette.foreground).add_modifier(Modifier::BOLD);
+    let help = Paragraph::new(Line::styled(
+      "[Enter] new line      [Ctrl-S] save      [Esc] cancel",
+      label_style,
+    ));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+    self.note.render(f, note_area, value_style);
+    f.render_widget(help, help_area);
+    Ok(())
+  }
+}
@@ -0,0 +1,143 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span},
+  widgets::{Block, BorderType, Clear, List, ListItem, ListState, Paragraph},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+  action::Action,
+  components::{scroll, Component},
+  config::Config,
+  db::FeedItem,
+  tui::Frame,
+};
+
+/// Lists the share targets from the config for an article. Enter or a target's number shares it.
+pub struct SharePopup {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  item: Option<FeedItem>,
+  state: ListState,
+}
+
+impl SharePopup {
+  pub fn new() -> Self {
+    Self { command_tx: None, config: Config::default(), item: None, state: ListState::default() }
+  }
+
+  fn share(&mut self, idx: usize) -> color_eyre::Result<()> {
+    if idx >= self.config.share.len() {
+      return Ok(());
+    }
+    if let (Some(tx), Some(item)) = (&self.command_tx, self.item.take()) {
+      tx.send(Action::Share(idx, item))?;
+    }
+    Ok(())
+  }
+}
+
+impl Component for SharePopup {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+    if self.item.is_none() {
+      return Ok(None);
+    }
+    let len = self.config.share.len();
+    // Every target fits, so a page is the whole list.
+    if let Some(idx) =
+      scroll::navigate(self.state.selected(), len, key.code, len, self.config.display.wrap_lists)
+    {
+      self.state.select(Some(idx));
+      return Ok(None);
+    }
+    let selected = self.state.selected().unwrap_or(0);
+    match key.code {
+      KeyCode::Esc | KeyCode::Char('q') => self.item = None,
+      KeyCode::Enter => self.share(selected)?,
+      KeyCode::Char(c @ '1'..='9') => self.share(c as usize - '1' as usize)?,
+      _ => {},
+    }
+    Ok(None)
+  }
+
+  fn captures_input(&self) -> bool {
+    self.item.is_some()
+  }
+
+  fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    if let Action::ShowShareMenu(item) = action {
+      self.item = Some(item);
+      self.state.select(Some(0));
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
+    let palette = *self.config.palette();
+    let Some(item) = &self.item else {
+      return Ok(());
+    };
+    let height = self.config.share.len() as u16 + 5;
+    let [_, popup_area, _] =
+      Layout::vertical([Constraint::Fill(1), Constraint::Length(height), Constraint::Fill(1)])
+        .areas(area);
+    let [_, popup_area, _] = Layout::horizontal([
+      Constraint::Percentage(30),
+      Constraint::Percentage(40),
+      Constraint::Percentage(30),
+    ])
+    .areas(popup_area);
+    let block = Block::bordered().border_type(BorderType::Rounded).title("Share");
+    let inner_area = block.inner(popup_area);
+    let [title_area, list_area, help_area] =
+      Layout::vertical([Constraint::Length(2), Constraint::Fill(1), Constraint::Length(1)])
+        .areas(inner_area);
+
+    let text_style = Style::default().fg(palette.foreground);
+    let selected_style = self.config.selected_style();
+    let number_style = Style::default().fg(palette.muted);
+
+    let items: Vec<ListItem> = self
+      .config
+      .share
+      .iter()
+      .enumerate()
+      .map(|(i, target)| {
+        let style = if self.state.selected() == Some(i) { selected_style } else { text_style };
+        let number = if i < 9 { format!("{} ", i + 1) } else { "  ".to_string() };
+        ListItem::new(Line::from(vec![
+          Span::styled(number, number_style),
+          Span::styled(target.name.as_str(), style),
+        ]))
+      })
+      .collect();
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+    f.render_widget(
+      Paragraph::new(Line::styled(
+        item.title.as_str(),
+        Style::default().fg(palette.title).add_modifier(Modifier::BOLD),
+      )),
+      title_area,
+    );
+    f.render_stateful_widget(List::new(items).highlight_symbol(" ┃ "), list_area, &mut self.state);
+    f.render_widget(
+      Line::styled("[enter] share      [esc] cancel", Style::default().fg(palette.muted)),
+      help_area,
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,194 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Modifier, Style},
+  text::{Line, Span},
+  widgets::{Block, BorderType, Clear, List, ListItem, ListState, Paragraph},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+  action::Action,
+  components::{scroll, text_input::TextInput, Component},
+  config::Config,
+  switcher::Destination,
+  tui::Frame,
+};
+
+/// Jumps to any group or feed, narrowed down by fuzzy matching as the user types. Enter opens
+/// the destination, or focuses its tab when it is already open.
+pub struct SwitcherPopup {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  visible: bool,
+  destinations: Vec<Destination>,
+  query: TextInput,
+  /// Indices into `destinations`, best match first.
+  matches: Vec<usize>,
+  state: ListState,
+  /// Where the list was last drawn, to page through it.
+  list_area: Rect,
+}
+
+impl SwitcherPopup {
+  pub fn new() -> Self {
+    Self {
+      command_tx: None,
+      config: Config::default(),
+      visible: false,
+      destinations: Vec::new(),
+      query: TextInput::new(),
+      matches: Vec::new(),
+      state: ListState::default(),
+      list_area: Rect::default(),
+    }
+  }
+
+  fn update_matches(&mut self) {
+    let mut scored: Vec<(i64, usize)> = (0..self.destinations.len())
+      .filter_map(|i| self.destinations[i].score(self.query.text()).map(|score| (score, i)))
+      .collect();
+    // Stable, so equally good matches keep the order of the group list.
+    scored.sort_by_key(|&(score, _)| -score);
+    self.matches = scored.into_iter().map(|(_, i)| i).collect();
+    self.state.select((!self.matches.is_empty()).then_some(0));
+  }
+
+  fn open_selected(&mut self) -> color_eyre::Result<()> {
+    let Some(&idx) = self.state.selected().and_then(|i| self.matches.get(i)) else {
+      return Ok(());
+    };
+    if let Some(tx) = &self.command_tx {
+      tx.send(Action::OpenDestination(self.destinations[idx].clone()))?;
+    }
+    self.visible = false;
+    Ok(())
+  }
+}
+
+impl Component for SwitcherPopup {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+    if !self.visible {
+      return Ok(None);
+    }
+    let selected = self.state.selected().unwrap_or(0);
+    match key.code {
+      KeyCode::Esc => self.visible = false,
+      KeyCode::Enter => self.open_selected()?,
+      // The other navigation keys are for the search field.
+      code @ (KeyCode::Down | KeyCode::Up | KeyCode::PageDown | KeyCode::PageUp) => {
+        let page = usize::from(self.list_area.height);
+        let wrap = self.config.display.wrap_lists;
+        if let Some(idx) = scroll::navigate(Some(selected), self.matches.len(), code, page, wrap) {
+          self.state.select(Some(idx));
+        }
+      },
+      _ => {
+        if self.query.handle_key(key) {
+          self.update_matches();
+        }
+      },
+    }
+    Ok(None)
+  }
+
+  fn handle_paste(&mut self, text: String) -> color_eyre::Result<Option<Action>> {
+    if self.visible {
+      self.query.insert(&text);
+      self.update_matches();
+    }
+    Ok(None)
+  }
+
+  fn captures_input(&self) -> bool {
+    self.visible
+  }
+
+  fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+    if let Action::ShowSwitcher(destinations) = action {
+      self.destinations = destinations;
+      self.query.clear();
+      self.update_matches();
+      self.visible = true;
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
+    let palette = *self.config.palette();
+    if !self.visible {
+      return Ok(());
+    }
+    let popup_layout = Layout::vertical([
+      Constraint::Percentage(20),
+      Constraint::Fill(1),
+      Constraint::Percentage(20),
+    ])
+    .split(area);
+    let popup_area = Layout::horizontal([
+      Constraint::Percentage(25),
+      Constraint::Percentage(50),
+      Constraint::Percentage(25),
+    ])
+    .split(popup_layout[1])[1];
+    let block = Block::bordered().border_type(BorderType::Rounded).title("Go to");
+    let inner_area = block.inner(popup_area);
+    let [search_area, list_area, help_area] =
+      Layout::vertical([Constraint::Length(2), Constraint::Fill(1), Constraint::Length(1)])
+        .areas(inner_area);
+
+    let label_style = Style::default().fg(palette.text);
+    let value_style = Style::default().fg(palette.foreground).add_modifier(Modifier::BOLD);
+    let group_style = Style::default().fg(palette.title).add_modifier(Modifier::BOLD);
+    let count_style = Style::default().fg(palette.muted);
+
+    let items: Vec<ListItem> = self
+      .matches
+      .iter()
+      .map(|&i| {
+        let destination = &self.destinations[i];
+        let mut spans = match &destination.feed {
+          Some(feed) => {
+            vec![
+              Span::styled(feed.name.clone(), value_style),
+              Span::styled(format!("  {}", destination.group.name), count_style),
+            ]
+          },
+          None => {
+            let style = match self.config.group_color(&destination.group.name) {
+              Some(color) => group_style.fg(color),
+              None => group_style,
+            };
+            vec![Span::styled(self.config.group_label(&destination.group.name), style)]
+          },
+        };
+        if let Some(unread) = destination.unread.filter(|&unread| unread > 0) {
+          spans.push(Span::styled(format!(" ({unread})"), count_style));
+        }
+        ListItem::new(Line::from(spans))
+      })
+      .collect();
+    let list = List::new(items).highlight_symbol("┃").highlight_style(self.config.selected_style());
+
+    let help =
+      Paragraph::new(Line::styled("[↑/↓] select      [Enter] open      [Esc] close", label_style));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+    self.query.render_labeled(f, search_area, Span::styled("Go to: ", label_style), value_style);
+    f.render_stateful_widget(list, list_area, &mut self.state);
+    self.list_area = list_area;
+    f.render_widget(help, help_area);
+    Ok(())
+  }
+}
@@ -0,0 +1,75 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+  layout::{Alignment, Margin, Rect},
+  widgets::{
+    block::{Position, Title},
+    ListState, Scrollbar, ScrollbarOrientation, ScrollbarState,
+  },
+};
+
+use crate::tui::Frame;
+
+/// The row a navigation key selects in a list of `len` rows, `page` of which fit on screen, or
+/// `None` for other keys. `j`/`k` and the arrows move by a row, PageUp/PageDown by a page and
+/// Home/End go to the first and last row.
+pub fn navigate(
+  selected: Option<usize>,
+  len: usize,
+  code: KeyCode,
+  page: usize,
+  wrap: bool,
+) -> Option<usize> {
+  if len == 0 {
+    return None;
+  }
+  let selected = selected.unwrap_or(0).min(len - 1);
+  Some(match code {
+    KeyCode::Char('j') | KeyCode::Down => step(selected, len, true, wrap),
+    KeyCode::Char('k') | KeyCode::Up => step(selected, len, false, wrap),
+    KeyCode::PageDown => (selected + page.max(1)).min(len - 1),
+    KeyCode::PageUp => selected.saturating_sub(page.max(1)),
+    KeyCode::Home => 0,
+    KeyCode::End => len - 1,
+    _ => return None,
+  })
+}
+
+/// The row after or before `selected` in a list of `len` rows. Past either end the selection
+/// wraps around to the other if `wrap` is set, and stays put otherwise.
+pub fn step(selected: usize, len: usize, down: bool, wrap: bool) -> usize {
+  match (down, wrap) {
+    (true, true) => (selected + 1) % len,
+    (true, false) => (selected + 1).min(len - 1),
+    (false, true) => selected.checked_sub(1).unwrap_or(len - 1),
+    (false, false) => selected.saturating_sub(1),
+  }
+}
+
+/// How many rows `row_height` lines tall fit in a bordered list drawn in `area`, at least one.
+pub fn page_rows(area: Rect, row_height: u16) -> usize {
+  usize::from(area.height.saturating_sub(2) / row_height.max(1)).max(1)
+}
+
+/// Where the selected row is in a list of `len`, e.g. ` 12/345 `, for the bottom-right corner
+/// of the list's border. Empty lists have none.
+pub fn position_title(state: &ListState, len: usize) -> Option<Title<'static>> {
+  let selected = state.selected().filter(|_| len > 0)?.min(len - 1);
+  let title = Title::from(format!(" {}/{len} ", selected + 1));
+  Some(title.position(Position::Bottom).alignment(Alignment::Right))
+}
+
+/// Draws a scrollbar on the right border of a list of `len` rows drawn in `area`, its thumb at
+/// the selected row.
+pub fn render_scrollbar(f: &mut Frame<'_>, area: Rect, state: &ListState, len: usize) {
+  let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+    .begin_symbol(None)
+    .end_symbol(None)
+    .track_symbol(None)
+    .thumb_symbol("▌");
+  let mut scrollbar_state = ScrollbarState::new(len).position(state.selected().unwrap_or(0));
+  f.render_stateful_widget(
+    scrollbar,
+    area.inner(&Margin { vertical: 1, horizontal: 0 }),
+    &mut scrollbar_state,
+  );
+}
@@ -0,0 +1,185 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::{Color, Modifier, Style},
+  text::{Line, Text},
+  widgets::{Bar, BarChart, BarGroup, Block, BorderType, Gauge, List, ListItem, ListState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{scroll, Component};
+use crate::{
+  action::{Action, TabId},
+  config::Config,
+  db::FeedStats,
+  tui::Frame,
+};
+
+/// Number of weeks of history shown in the activity chart.
+pub const STATS_WEEKS: usize = 12;
+
+pub struct StatsView {
+  command_tx: Option<UnboundedSender<Action>>,
+  config: Config,
+  tab: TabId,
+  selected_tab: TabId,
+  stats: Vec<FeedStats>,
+  state: ListState,
+  /// Where the list was last drawn, to page through it.
+  list_area: Rect,
+}
+
+impl StatsView {
+  pub fn new(tab: TabId) -> Self {
+    Self {
+      command_tx: None,
+      config: Config::default(),
+      tab,
+      selected_tab: tab,
+      stats: Vec::new(),
+      state: ListState::default().with_selected(Some(0)),
+      list_area: Rect::default(),
+    }
+  }
+
+  fn draw_activity(&self, f: &mut Frame<'_>, area: Rect, feed_stats: &FeedStats) {
+    let palette = *self.config.palette();
+    let bars: Vec<Bar> = feed_stats
+      .weekly
+      .iter()
+      .enumerate()
+      .map(|(i, count)| {
+        let weeks_ago = feed_stats.weekly.len() - 1 - i;
+        let label = if weeks_ago == 0 { "now".to_string() } else { format!("-{weeks_ago}w") };
+        Bar::default().value(*count).label(Line::from(label))
+      })
+      .collect();
+
+    let chart = BarChart::default()
+      .block(Block::bordered().border_type(BorderType::Rounded).title("Articles per week"))
+      .data(BarGroup::default().bars(&bars))
+      .bar_width(4)
+      .bar_gap(1)
+      .bar_style(Style::default().fg(palette.selected))
+      .value_style(Style::default().fg(self.config.color(Color::Black)).bg(palette.selected));
+    f.render_widget(chart, area);
+  }
+
+  fn draw_read_ratio(&self, f: &mut Frame<'_>, area: Rect, feed_stats: &FeedStats) {
+    let palette = *self.config.palette();
+    let ratio =
+      if feed_stats.total == 0 { 0.0 } else { feed_stats.read as f64 / feed_stats.total as f64 };
+    let gauge = Gauge::default()
+      .block(Block::bordered().border_type(BorderType::Rounded).title("Read"))
+      .gauge_style(Style::default().fg(palette.title))
+      .ratio(ratio)
+      .label(format!("{}/{} read ({:.0}%)", feed_stats.read, feed_stats.total, ratio * 100.0));
+    f.render_widget(gauge, area);
+  }
+}
+
+impl Component for StatsView {
+  fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+    self.command_tx = Some(tx);
+    Ok(())
+  }
+
+  fn register_config_handler(&mut self, config: Config) -> Result<()> {
+    self.config = config;
+    Ok(())
+  }
+
+  fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+    if self.selected_tab == self.tab && !self.stats.is_empty() {
+      let selected_item_idx = self.state.selected().unwrap_or(0);
+      let page = scroll::page_rows(self.list_area, 1);
+      let wrap = self.config.display.wrap_lists;
+      if let Some(idx) =
+        scroll::navigate(self.state.selected(), self.stats.len(), key.code, page, wrap)
+      {
+        self.state.select(Some(idx));
+        return Ok(None);
+      }
+      match key.code {
+        KeyCode::Char('p') => {
+          if let Some(tx) = &self.command_tx {
+            let prompt = format!(
+              "Delete read articles older than {} days? Those with notes or highlights are kept.",
+              self.config.fetch.prune_read_after_days
+            );
+            tx.send(Action::Confirm(prompt, Box::new(Action::PruneArticles)))?;
+          }
+        },
+        _ => {},
+      }
+    }
+    Ok(None)
+  }
+
+  fn update(&mut self, action: Action) -> Result<Option<Action>> {
+    match action {
+      Action::ChangeTab(tab) => {
+        self.selected_tab = tab;
+      },
+      Action::UpdateStats(_, stats) => {
+        self.stats = stats;
+      },
+      Action::PruneArticles => {
+        if let Some(tx) = &self.command_tx {
+          tx.send(Action::RequestUpdateStats(self.tab))?;
+        }
+      },
+      _ => {},
+    }
+    Ok(None)
+  }
+
+  fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+    let palette = *self.config.palette();
+    let [list_area, detail_area] =
+      Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(area);
+
+    let name_style = Style::default().fg(palette.title).add_modifier(Modifier::BOLD);
+    let selected_name_style = self.config.selected_style();
+    let desc_style = Style::default().fg(palette.text);
+
+    let items: Vec<ListItem> = self
+      .stats
+      .iter()
+      .enumerate()
+      .map(|(i, feed_stats)| {
+        let style = if self.state.selected() == Some(i) { selected_name_style } else { name_style };
+        let recent: u64 = feed_stats.weekly.iter().sum();
+        ListItem::new(Text::from(vec![
+          Line::styled(&feed_stats.name, style),
+          Line::styled(
+            format!(
+              "{}/{} read, {} in the last {} weeks",
+              feed_stats.read, feed_stats.total, recent, STATS_WEEKS
+            ),
+            desc_style,
+          ),
+        ]))
+      })
+      .collect();
+
+    let mut block = Block::bordered().border_type(BorderType::Rounded).title("Most read feeds");
+    if let Some(position) = scroll::position_title(&self.state, self.stats.len()) {
+      block = block.title(position);
+    }
+    let list = List::new(items).block(block).highlight_symbol(" ┃ ").repeat_highlight_symbol(true);
+    f.render_stateful_widget(list, list_area, &mut self.state);
+    self.list_area = list_area;
+    scroll::render_scrollbar(f, list_area, &self.state, self.stats.len());
+
+    if let Some(feed_stats) = self.state.selected().and_then(|i| self.stats.get(i)) {
+      let [chart_area, ratio_area] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(detail_area);
+      self.draw_activity(f, chart_area, feed_stats);
+      self.draw_read_ratio(f, ratio_area, feed_stats);
+    }
+
+    Ok(())
+  }
+}
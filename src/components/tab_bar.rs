@@ -1,25 +1,75 @@
+use std::{collections::HashMap, ops::Range};
+
 use color_eyre::eyre::Result;
 use ratatui::{
-  layout::{Constraint, Direction, Layout, Rect},
-  widgets::Tabs,
+  layout::{Constraint, Direction, Layout, Position, Rect},
+  style::{Color, Modifier, Style},
+  symbols,
+  text::{Line, Span},
   Frame,
 };
 
 use super::Component;
+use crate::{
+  action::Action,
+  config::{Colors, Config, TabBarConfig},
+};
+
+/// Which unread articles a tab counts in its title.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TabUnread {
+  #[default]
+  None,
+  All,
+  Group(i32),
+  Feed(i32),
+}
 
+struct Tab {
+  name: String,
+  unread: TabUnread,
+  color: Option<Color>,
+}
+
+/// Narrowest a title is cut to before the tabs scroll instead.
+const MIN_TITLE_WIDTH: usize = 8;
+/// Stands in for cut text and for tabs scrolled out of view.
+const MORE: &str = "…";
+/// Cells taken by the scroll marker on either side.
+const MARKER_WIDTH: usize = 2;
+
+/// The titles of the open tabs, each with its unread count, and the total unread count on the
+/// right. Counts arrive as actions whenever articles are read or fetched.
+///
+/// Titles are cut short to fit, and when even that is not enough the bar scrolls to keep the
+/// selected tab in view, with `…` on the side where more tabs are.
 #[derive(Default)]
 pub struct TabBar {
-  tabs: Vec<String>,
+  config: TabBarConfig,
+  colors: Colors,
+  tabs: Vec<Tab>,
   selected_tab: usize,
+  /// First tab in view when the tabs scroll.
+  first_visible: usize,
+  /// The columns each tab, or scroll marker, was last drawn over, and the tab it selects.
+  hits: Vec<(Range<u16>, usize)>,
+  area: Rect,
+  total_unread: Option<usize>,
+  feed_unread: HashMap<i32, usize>,
+  group_unread: HashMap<i32, usize>,
 }
 
 impl TabBar {
   pub fn new() -> Self {
-    Self { tabs: Vec::new(), selected_tab: 0 }
+    Self::default()
+  }
+
+  pub fn add_tab(&mut self, name: String, unread: TabUnread, color: Option<Color>) {
+    self.tabs.push(Tab { name, unread, color });
   }
 
-  pub fn add_tab(&mut self, tab: String) {
-    self.tabs.push(tab);
+  pub fn name(&self, tab_idx: usize) -> Option<&str> {
+    self.tabs.get(tab_idx).map(|tab| tab.name.as_str())
   }
 
   pub fn remove_tab(&mut self, tab_idx: usize) {
@@ -29,12 +79,167 @@ impl TabBar {
   pub fn select(&mut self, tab_idx: usize) {
     self.selected_tab = tab_idx;
   }
+
+  /// The title of the tab at `idx`, with its name cut so the whole title is at most `max_width`
+  /// wide. The number and unread count are never cut.
+  fn title(&self, idx: usize, tab: &Tab, max_width: usize) -> String {
+    let unread = match tab.unread {
+      TabUnread::None => None,
+      TabUnread::All => self.total_unread,
+      TabUnread::Group(id) => Some(self.group_unread.get(&id).copied().unwrap_or(0)),
+      TabUnread::Feed(id) => Some(self.feed_unread.get(&id).copied().unwrap_or(0)),
+    };
+    let number = if self.config.numbered { format!("{} ", idx + 1) } else { String::new() };
+    let count = match unread {
+      Some(unread) if unread > 0 => format!(" ({unread})"),
+      _ => String::new(),
+    };
+    let name_width = max_width.saturating_sub(width(&number) + width(&count));
+    format!("{number}{}{count}", truncate(&tab.name, name_width))
+  }
+
+  fn titles(&self, max_width: usize) -> Vec<String> {
+    self.tabs.iter().enumerate().map(|(idx, tab)| self.title(idx, tab, max_width)).collect()
+  }
+
+  /// Returns the tab whose label, or the scroll marker leading to it, was drawn under (`column`,
+  /// `row`).
+  pub fn tab_at(&self, column: u16, row: u16) -> Option<usize> {
+    if !self.area.contains(Position { x: column, y: row }) {
+      return None;
+    }
+    self.hits.iter().find(|(columns, _)| columns.contains(&column)).map(|&(_, idx)| idx)
+  }
+
+  /// The last tab that fits in `room` cells when the tabs start at `first_visible`.
+  fn last_visible(&self, titles: &[String], room: usize) -> usize {
+    let mut used = 0;
+    let mut last = self.first_visible;
+    for (idx, title) in titles.iter().enumerate().skip(self.first_visible) {
+      // One cell of padding either side of each title, and a divider before all but the first.
+      used += width(title) + 2 + usize::from(idx > self.first_visible);
+      if used > room {
+        break;
+      }
+      last = idx;
+    }
+    last
+  }
 }
 
 impl Component for TabBar {
+  fn register_config_handler(&mut self, config: Config) -> Result<()> {
+    self.config = config.tab_bar;
+    self.colors = config.theme.colors;
+    Ok(())
+  }
+
+  fn update(&mut self, action: Action) -> Result<Option<Action>> {
+    match action {
+      Action::UpdateUnreadCount(unread) => self.total_unread = Some(unread),
+      Action::UpdateFeedUnreadCounts(unread) => self.feed_unread = unread,
+      Action::UpdateGroupUnreadCounts(unread) => self.group_unread = unread,
+      _ => {},
+    }
+    Ok(None)
+  }
+
   fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-    let tabs = Tabs::new(self.tabs.clone()).select(self.selected_tab);
-    f.render_widget(tabs, area);
+    let badge = self.total_unread.map(|unread| format!(" {unread} unread ")).unwrap_or_default();
+    let layout = Layout::default()
+      .direction(Direction::Horizontal)
+      .constraints([Constraint::Fill(1), Constraint::Length(badge.chars().count() as u16)])
+      .split(area);
+    self.area = layout[0];
+    let room = usize::from(self.area.width);
+    let tabs_width = |titles: &[String]| -> usize {
+      titles.iter().map(|title| width(title) + 3).sum::<usize>().saturating_sub(1)
+    };
+
+    // Titles shrink towards MIN_TITLE_WIDTH before anything scrolls out of view.
+    let mut max_width = self.config.max_title_width.max(MIN_TITLE_WIDTH);
+    let mut titles = self.titles(max_width);
+    while tabs_width(&titles) > room && max_width > MIN_TITLE_WIDTH {
+      max_width -= 1;
+      titles = self.titles(max_width);
+    }
+
+    let (first, last) = if tabs_width(&titles) > room {
+      let room = room.saturating_sub(2 * MARKER_WIDTH);
+      self.first_visible = self.first_visible.min(self.selected_tab);
+      while self.first_visible < self.selected_tab
+        && self.last_visible(&titles, room) < self.selected_tab
+      {
+        self.first_visible += 1;
+      }
+      (self.first_visible, self.last_visible(&titles, room))
+    } else {
+      self.first_visible = 0;
+      (0, titles.len().saturating_sub(1))
+    };
+    let scrolls = first > 0 || last + 1 < titles.len();
+
+    let marker_style = Style::default().add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut x = self.area.x;
+    self.hits.clear();
+    if scrolls {
+      let marker = if first > 0 { format!("{MORE} ") } else { " ".repeat(MARKER_WIDTH) };
+      if first > 0 {
+        self.hits.push((x..x + MARKER_WIDTH as u16, first - 1));
+      }
+      spans.push(Span::styled(marker, marker_style));
+      x += MARKER_WIDTH as u16;
+    }
+    for (idx, title) in titles.iter().enumerate().take(last + 1).skip(first) {
+      if idx > first {
+        spans.push(Span::raw(symbols::line::VERTICAL));
+        x += 1;
+      }
+      let tab = &self.tabs[idx];
+      let color = tab.color.filter(|_| self.colors != Colors::Off);
+      let mut style = color.map_or(Style::default(), |color| Style::default().fg(color));
+      if idx == self.selected_tab {
+        style = style.add_modifier(Modifier::REVERSED);
+      }
+      let label = format!(" {title} ");
+      let label_width = width(&label) as u16;
+      self.hits.push((x..x.saturating_add(label_width), idx));
+      x = x.saturating_add(label_width);
+      spans.push(Span::styled(label, style));
+    }
+    if last + 1 < titles.len() {
+      spans.push(Span::styled(format!(" {MORE}"), marker_style));
+      self.hits.push((x..x + MARKER_WIDTH as u16, last + 1));
+    }
+
+    f.render_widget(Line::from(spans), layout[0]);
+    let badge_style = match self.colors {
+      Colors::Off => Style::default().add_modifier(Modifier::REVERSED),
+      Colors::Normal | Colors::HighContrast => Style::default().fg(Color::Black).bg(Color::Cyan),
+    };
+    f.render_widget(Span::styled(badge, badge_style), layout[1]);
     Ok(())
   }
 }
+
+/// Width of `text` in terminal cells.
+fn width(text: &str) -> usize {
+  Span::raw(text).width()
+}
+
+/// Cuts `text` to at most `max_width` cells, ending it with `…` when anything was cut.
+fn truncate(text: &str, max_width: usize) -> String {
+  if width(text) <= max_width {
+    return text.to_string();
+  }
+  let mut cut = String::new();
+  let mut buf = [0; 4];
+  for c in text.chars() {
+    if width(&cut) + width(c.encode_utf8(&mut buf)) + width(MORE) > max_width {
+      break;
+    }
+    cut.push(c);
+  }
+  format!("{}{MORE}", cut.trim_end())
+}
@@ -1,34 +1,71 @@
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{
+  layout::{Constraint, Direction, Layout, Rect},
+  style::Color,
+};
 use tokio::sync::mpsc::UnboundedSender;
 
-use super::{article_view, feed_view::FeedView, tab_bar::TabBar};
+use super::{
+  article_view,
+  feed_view::FeedView,
+  tab_bar::{TabBar, TabUnread},
+};
 use crate::{
-  action::Action,
+  action::{Action, TabId},
   components::{
-    article_list::ArticleList, article_reader::ArticleReader, article_view::ArticleView,
-    group_view::GroupView, Component,
+    article_list::ArticleList, article_reader::ArticleReader, article_view::ArticleView, tab_kind,
+    Component, TabKind,
   },
   config::Config,
+  i18n::Message,
   mode::Mode,
   tui::{Event, Frame},
 };
 
+/// What an article tab lists, so the quick switcher can focus it instead of opening it again,
+/// and the request loading it, sent again after a refresh.
+struct ArticleTab {
+  /// The group and feed ids of the destination.
+  key: (i32, Option<i32>),
+  request: Action,
+}
+
+struct Tab {
+  id: TabId,
+  component: Box<dyn Component>,
+  /// The articles the tab lists, if it lists any.
+  articles: Option<ArticleTab>,
+}
+
 pub struct TabViewer {
   command_tx: Option<UnboundedSender<Action>>,
   config: Config,
   mode: Mode,
   tab_bar: TabBar,
-  tabs: Vec<Box<dyn Component>>,
+  tabs: Vec<Tab>,
   selected_tab: usize,
+  /// The requests loading the tabs opened at launch, sent once the app is listening.
+  launch_requests: Vec<Action>,
 }
 
 impl TabViewer {
-  pub fn new() -> Self {
+  /// Opens a tab for each of the views named `tabs` in `components::TABS`, leaving out unknown
+  /// names, or just Groups if that leaves none.
+  pub fn new(tabs: &[String]) -> Self {
+    let mut kinds: Vec<&TabKind> = tabs.iter().filter_map(|name| tab_kind(name)).collect();
+    if kinds.is_empty() {
+      kinds.extend(tab_kind("groups"));
+    }
     let mut tab_bar = TabBar::new();
-    tab_bar.add_tab("Groups".to_string());
-    let group_view = GroupView::new();
+    let mut opened = Vec::new();
+    let mut launch_requests = Vec::new();
+    for kind in kinds {
+      let id = TabId::next();
+      tab_bar.add_tab(kind.title.to_string(), TabUnread::None, None);
+      opened.push(Tab { id, component: (kind.build)(id), articles: None });
+      launch_requests.extend((kind.request)(id));
+    }
 
     Self {
       command_tx: None,
@@ -36,29 +73,43 @@ impl TabViewer {
       mode: Mode::Main,
       tab_bar,
       selected_tab: 0,
-      tabs: vec![Box::new(group_view)],
+      tabs: opened,
+      launch_requests,
     }
   }
 
-  pub fn add_new_tab(&mut self, tab_name: String, component: Box<dyn Component>) -> Result<()> {
-    self.tabs.push(component);
-    self.selected_tab = self.tabs.len() - 1;
-    self.tab_bar.add_tab(tab_name);
-    self.tab_bar.select(self.tabs.len() - 1);
-
+  /// Opens a new tab with the view registered as `name` in `components::TABS`, returning the
+  /// request that loads it.
+  fn open_tab(&mut self, name: &str) -> Result<Option<Action>> {
+    let Some(kind) = tab_kind(name) else {
+      return Ok(None);
+    };
+    let id = TabId::next();
+    let mut component = (kind.build)(id);
     if let Some(tx) = &self.command_tx {
-      tx.send(Action::ChangeTab(self.selected_tab))?;
+      component.register_action_handler(tx.clone())?;
     }
+    self.add_new_tab(id, kind.title.to_string(), TabUnread::None, None, component)?;
+    Ok((kind.request)(id))
+  }
 
-    Ok(())
+  pub fn add_new_tab(
+    &mut self,
+    id: TabId,
+    tab_name: String,
+    unread: TabUnread,
+    color: Option<Color>,
+    mut component: Box<dyn Component>,
+  ) -> Result<()> {
+    component.register_config_handler(self.config.clone())?;
+    self.tabs.push(Tab { id, component, articles: None });
+    self.tab_bar.add_tab(tab_name, unread, color);
+    self.select_tab(self.tabs.len() - 1)
   }
 
   pub fn remove_tab(&mut self, tab_idx: usize) {
     self.tabs.remove(tab_idx);
     self.tab_bar.remove_tab(tab_idx);
-
-    // TODO: Update the indices of the rest of the tabs or send action to do it.
-    for i in tab_idx..self.tabs.len() {}
   }
 
   pub fn select_tab(&mut self, idx: usize) -> Result<()> {
@@ -66,41 +117,57 @@ impl TabViewer {
     self.tab_bar.select(idx);
 
     if let Some(tx) = &self.command_tx {
-      tx.send(Action::ChangeTab(idx))?;
+      tx.send(Action::ChangeTab(self.tabs[idx].id))?;
+      tx.send(Action::SetTerminalTitle(self.tab_bar.name(idx).map(str::to_string)))?;
     }
 
     Ok(())
   }
+
+  /// Records what the newest tab lists, returning the request that loads it.
+  fn track_last_tab(&mut self, key: (i32, Option<i32>), request: Action) -> Option<Action> {
+    if let Some(tab) = self.tabs.last_mut() {
+      tab.articles = Some(ArticleTab { key, request: request.clone() });
+    }
+    Some(request)
+  }
 }
 
 impl Component for TabViewer {
   fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
-    for component in &mut self.tabs {
-      component.register_action_handler(tx.clone())?;
+    for tab in &mut self.tabs {
+      tab.component.register_action_handler(tx.clone())?;
     }
     self.command_tx = Some(tx);
     Ok(())
   }
 
   fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
-    for component in &mut self.tabs {
-      component.register_config_handler(config.clone())?;
+    for tab in &mut self.tabs {
+      tab.component.register_config_handler(config.clone())?;
     }
+    self.tab_bar.register_config_handler(config.clone())?;
     self.config = config;
     Ok(())
   }
 
   fn init(&mut self, area: Rect) -> color_eyre::Result<()> {
-    for component in &mut self.tabs {
-      component.init(area)?;
+    for tab in &mut self.tabs {
+      tab.component.init(area)?;
+    }
+    // The tabs open at launch load what they show like any opened later.
+    if let Some(tx) = &self.command_tx {
+      for request in self.launch_requests.drain(..) {
+        tx.send(request)?;
+      }
     }
     Ok(())
   }
 
   fn handle_events(&mut self, event: Option<Event>) -> color_eyre::Result<Option<Action>> {
-    if let Some(event) = event.clone() {
-      for component in &mut self.tabs {
-        component.handle_events(Some(event.clone()))?;
+    if let Some(Event::Key(_)) = event {
+      for tab in &mut self.tabs {
+        tab.component.handle_events(event.clone())?;
       }
     }
 
@@ -129,15 +196,37 @@ impl Component for TabViewer {
         KeyCode::Char('L') => {
           self.select_tab((self.selected_tab + 1) % self.tabs.len())?;
         },
+        KeyCode::Char('S') => {
+          if let Some(tx) = &self.command_tx {
+            tx.send(Action::NewTabStats)?;
+          }
+        },
+        KeyCode::Char('F') => {
+          if let Some(tx) = &self.command_tx {
+            tx.send(Action::NewTabHealth)?;
+          }
+        },
+        KeyCode::Char('Y') => {
+          if let Some(tx) = &self.command_tx {
+            tx.send(Action::NewTabHighlights)?;
+          }
+        },
         _ => {},
       };
     } else {
       match key.code {
         KeyCode::Char('x') => {
           if self.selected_tab != 0 {
+            let removed = self.tabs[self.selected_tab].id;
             self.remove_tab(self.selected_tab);
             self.select_tab(self.selected_tab - 1)?;
-            return Ok(Some(Action::RemoveTab(self.selected_tab + 1)));
+            return Ok(Some(Action::RemoveTab(removed)));
+          }
+        },
+        KeyCode::Char(digit @ '1'..='9') => {
+          let idx = digit as usize - '1' as usize;
+          if idx < self.tabs.len() {
+            self.select_tab(idx)?;
           }
         },
         _ => {},
@@ -148,59 +237,158 @@ impl Component for TabViewer {
   }
 
   fn handle_mouse_events(&mut self, mouse: MouseEvent) -> color_eyre::Result<Option<Action>> {
-    for component in &mut self.tabs {
-      component.handle_mouse_events(mouse.clone())?;
+    if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+      if let Some(idx) = self.tab_bar.tab_at(mouse.column, mouse.row) {
+        self.select_tab(idx)?;
+        return Ok(None);
+      }
+    }
+    // Only the visible tab is under the mouse.
+    if let Some(tab) = self.tabs.get_mut(self.selected_tab) {
+      tab.component.handle_events(Some(Event::Mouse(mouse)))?;
     }
     Ok(None)
   }
 
   fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
-    for component in &mut self.tabs {
-      component.update(action.clone())?;
+    if let Some(id) = action.tab() {
+      // A tab closed since its request was sent no longer takes the reply.
+      if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == id) {
+        tab.component.update(action)?;
+      }
+      return Ok(None);
     }
+    for tab in &mut self.tabs {
+      tab.component.update(action.clone())?;
+    }
+    self.tab_bar.update(action.clone())?;
 
     match action {
       Action::ModeChange(mode) => {
         self.mode = mode;
       },
       Action::NewTabFeedView(group) => {
-        let mut feed_view = FeedView::new(self.tabs.len(), group.clone());
+        let id = TabId::next();
+        let mut feed_view = FeedView::new(id, group.clone());
         if let Some(tx) = &self.command_tx {
           feed_view.register_action_handler(tx.clone())?;
         }
-        self.add_new_tab(group.name.clone(), Box::new(feed_view))?;
-        return Ok(Some(Action::RequestUpdateFeedView(self.tabs.len() - 1, group)));
+        self.add_new_tab(
+          id,
+          self.config.group_label(&group.name),
+          TabUnread::Group(group.id),
+          self.config.group_color(&group.name),
+          Box::new(feed_view),
+        )?;
+        return Ok(Some(Action::RequestUpdateFeedView(id, group)));
       },
       Action::NewTabArticleViewAll => {
-        let mut article_view = ArticleView::new(self.tabs.len());
+        let id = TabId::next();
+        let mut article_view =
+          ArticleView::new(id, vec![self.config.text(Message::AllFeeds).to_string()])
+            .with_feed_names(true);
+        if let Some(tx) = &self.command_tx {
+          article_view.register_action_handler(tx.clone())?;
+        }
+        self.add_new_tab(
+          id,
+          self.config.text(Message::AllArticles).to_string(),
+          TabUnread::All,
+          None,
+          Box::new(article_view),
+        )?;
+        let request = Action::RequestUpdateArticleViewAll(id);
+        return Ok(self.track_last_tab((-1, None), request));
+      },
+      Action::NewTabArticleViewSmart(view) => {
+        let id = TabId::next();
+        let mut article_view =
+          ArticleView::new(id, vec![view.name().to_string()]).with_feed_names(true);
         if let Some(tx) = &self.command_tx {
           article_view.register_action_handler(tx.clone())?;
         }
-        self.add_new_tab("All Articles".to_string(), Box::new(article_view))?;
-        return Ok(Some(Action::RequestUpdateArticleViewAll(self.tabs.len() - 1)));
+        self.add_new_tab(
+          id,
+          view.name().to_string(),
+          TabUnread::None,
+          None,
+          Box::new(article_view),
+        )?;
+        let request = Action::RequestUpdateArticleViewSmart(id, view);
+        return Ok(self.track_last_tab((view.group_id(), None), request));
       },
-      Action::NewTabArticleViewFeed(feed) => {
-        let mut article_view = ArticleView::new(self.tabs.len());
+      Action::NewTabArticleViewFeed(group, feed) => {
+        let id = TabId::next();
+        let feed_config =
+          self.config.feed(self.config.feed_link(&feed.url, feed.moved_from.as_deref()));
+        let mut article_view = ArticleView::new(id, vec![group.name.clone(), feed.name.clone()])
+          .with_feed_id(feed.id)
+          .with_list_view(
+            feed_config.and_then(|feed| feed.sort),
+            feed_config.is_some_and(|feed| feed.unread_only),
+          );
         if let Some(tx) = &self.command_tx {
           article_view.register_action_handler(tx.clone())?;
         }
-        self.add_new_tab(feed.name.clone(), Box::new(article_view))?;
+        self.add_new_tab(
+          id,
+          feed.name.clone(),
+          TabUnread::Feed(feed.id),
+          None,
+          Box::new(article_view),
+        )?;
         log::info!("Sending RequestUpdateArticleViewFeed");
-        return Ok(Some(Action::RequestUpdateArticleViewFeed(self.tabs.len() - 1, feed)));
+        let key = (group.id, Some(feed.id));
+        let request = Action::RequestUpdateArticleViewFeed(id, feed);
+        return Ok(self.track_last_tab(key, request));
       },
+      Action::NewTabStats => return self.open_tab("stats"),
+      Action::NewTabHealth => return self.open_tab("health"),
+      Action::NewTabHighlights => return self.open_tab("highlights"),
       Action::NewTabArticleViewGroup(group) => {
-        let mut article_view = ArticleView::new(self.tabs.len());
+        let id = TabId::next();
+        let mut article_view = ArticleView::new(id, vec![group.name.clone()]).with_feed_names(true);
         if let Some(tx) = &self.command_tx {
           article_view.register_action_handler(tx.clone())?;
         }
-        self.add_new_tab(group.name.clone(), Box::new(article_view))?;
-        return Ok(Some(Action::RequestUpdateArticleViewGroup(self.tabs.len(), group)));
+        self.add_new_tab(
+          id,
+          self.config.group_label(&group.name),
+          TabUnread::Group(group.id),
+          self.config.group_color(&group.name),
+          Box::new(article_view),
+        )?;
+        let key = (group.id, None);
+        let request = Action::RequestUpdateArticleViewGroup(id, group);
+        return Ok(self.track_last_tab(key, request));
+      },
+      Action::OpenDestination(destination) => {
+        let key = destination.key();
+        let open = self
+          .tabs
+          .iter()
+          .position(|tab| tab.articles.as_ref().is_some_and(|articles| articles.key == key));
+        match open {
+          Some(idx) => self.select_tab(idx)?,
+          None => return Ok(Some(destination.new_tab_action())),
+        }
+      },
+      Action::RefreshFinished => {
+        if let Some(tx) = &self.command_tx {
+          for articles in self.tabs.iter().filter_map(|tab| tab.articles.as_ref()) {
+            tx.send(articles.request.clone())?;
+          }
+        }
       },
       _ => {},
     }
     Ok(None)
   }
 
+  fn hyperlinks(&self) -> Vec<(Rect, String)> {
+    self.tabs.get(self.selected_tab).map_or_else(Vec::new, |tab| tab.component.hyperlinks())
+  }
+
   fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> color_eyre::Result<()> {
     let layout = Layout::default()
       .direction(Direction::Vertical)
@@ -211,8 +399,8 @@ impl Component for TabViewer {
     self.tab_bar.draw(f, tab_area)?;
 
     let main_area = layout[1];
-    if let Some(component) = self.tabs.get_mut(self.selected_tab) {
-      component.draw(f, main_area)?;
+    if let Some(tab) = self.tabs.get_mut(self.selected_tab) {
+      tab.component.draw(f, main_area)?;
     }
 
     Ok(())
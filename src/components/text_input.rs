@@ -0,0 +1,297 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+  layout::{Constraint, Layout, Rect},
+  style::Style,
+  text::{Line, Span},
+  widgets::Paragraph,
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::tui::Frame;
+
+/// A text field for popups to embed. The cursor moves by character and line, `Ctrl-W` and `Ctrl-U`
+/// delete the word and the line before it, and pasted text goes in at once.
+///
+/// A multi-line field takes Enter as a new line and wraps long lines at the edge of its area. A
+/// single-line one leaves Enter to the popup and scrolls sideways to keep the cursor in view.
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+  text: String,
+  /// Byte offset of the cursor in `text`, always on a character boundary.
+  cursor: usize,
+  multiline: bool,
+}
+
+impl TextInput {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn multiline() -> Self {
+    Self { multiline: true, ..Self::default() }
+  }
+
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.text.is_empty()
+  }
+
+  /// Replaces the text, with the cursor at its end.
+  pub fn set_text(&mut self, text: &str) {
+    self.text.clear();
+    self.cursor = 0;
+    self.insert(text);
+  }
+
+  pub fn clear(&mut self) {
+    self.set_text("");
+  }
+
+  /// Inserts text at the cursor, e.g. when pasted. A single-line field gets its line breaks as
+  /// spaces.
+  pub fn insert(&mut self, text: &str) {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let text = if self.multiline { text } else { text.replace('\n', " ") };
+    self.text.insert_str(self.cursor, &text);
+    self.cursor += text.len();
+  }
+
+  /// Edits the text or moves the cursor for `key`, returning whether it did. Keys it leaves
+  /// alone, like Esc, Tab and Enter on a single line, are for the popup to handle.
+  pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+      KeyCode::Char('a') if ctrl => self.cursor = self.line_start(),
+      KeyCode::Char('e') if ctrl => self.cursor = self.line_end(),
+      KeyCode::Char('w') if ctrl => self.delete_back_to(self.word_start()),
+      KeyCode::Char('u') if ctrl => self.delete_back_to(self.line_start()),
+      KeyCode::Char(_) if ctrl || key.modifiers.contains(KeyModifiers::ALT) => return false,
+      KeyCode::Char(c) => self.insert(c.encode_utf8(&mut [0; 4])),
+      KeyCode::Enter if self.multiline => self.insert("\n"),
+      KeyCode::Backspace => self.delete_back_to(self.prev_boundary()),
+      KeyCode::Delete => {
+        let next = self.next_boundary();
+        self.text.replace_range(self.cursor..next, "");
+      },
+      KeyCode::Left => self.cursor = self.prev_boundary(),
+      KeyCode::Right => self.cursor = self.next_boundary(),
+      KeyCode::Home => self.cursor = self.line_start(),
+      KeyCode::End => self.cursor = self.line_end(),
+      KeyCode::Up if self.multiline => self.move_line(false),
+      KeyCode::Down if self.multiline => self.move_line(true),
+      _ => return false,
+    }
+    true
+  }
+
+  fn prev_boundary(&self) -> usize {
+    self.text[..self.cursor].char_indices().next_back().map_or(0, |(i, _)| i)
+  }
+
+  fn next_boundary(&self) -> usize {
+    self.text[self.cursor..].chars().next().map_or(self.cursor, |c| self.cursor + c.len_utf8())
+  }
+
+  fn line_start(&self) -> usize {
+    self.text[..self.cursor].rfind('\n').map_or(0, |i| i + 1)
+  }
+
+  fn line_end(&self) -> usize {
+    self.text[self.cursor..].find('\n').map_or(self.text.len(), |i| self.cursor + i)
+  }
+
+  /// Start of the word before the cursor, past any whitespace in between.
+  fn word_start(&self) -> usize {
+    let before = self.text[..self.cursor].trim_end();
+    // Whitespace such as a no-break or ideographic space takes more than one byte.
+    before
+      .char_indices()
+      .rev()
+      .find(|(_, c)| c.is_whitespace())
+      .map_or(0, |(i, c)| i + c.len_utf8())
+  }
+
+  fn delete_back_to(&mut self, start: usize) {
+    self.text.replace_range(start..self.cursor, "");
+    self.cursor = start;
+  }
+
+  /// Moves the cursor to the line below or above, as near the same column as that line allows.
+  fn move_line(&mut self, down: bool) {
+    let start = self.line_start();
+    let column = self.text[start..self.cursor].width();
+    let target = if down {
+      let end = self.line_end();
+      if end == self.text.len() {
+        return;
+      }
+      end + 1
+    } else {
+      if start == 0 {
+        return;
+      }
+      self.text[..start - 1].rfind('\n').map_or(0, |i| i + 1)
+    };
+    let mut width = 0;
+    self.cursor = target;
+    for c in self.text[target..].chars().take_while(|&c| c != '\n') {
+      width += c.width().unwrap_or(0);
+      if width > column {
+        break;
+      }
+      self.cursor += c.len_utf8();
+    }
+  }
+
+  /// Draws the text in `area` and puts the terminal's cursor where typing goes.
+  pub fn render(&self, f: &mut Frame<'_>, area: Rect, style: Style) {
+    if area.width == 0 || area.height == 0 {
+      return;
+    }
+    let (rows, (row, column)) = if self.multiline {
+      self.wrap(usize::from(area.width))
+    } else {
+      (vec![self.text.clone()], (0, self.text[..self.cursor].width()))
+    };
+    let scroll_y = row.saturating_sub(usize::from(area.height) - 1);
+    let scroll_x =
+      if self.multiline { 0 } else { column.saturating_sub(usize::from(area.width) - 1) };
+    let lines: Vec<Line> = rows.into_iter().map(|row| Line::styled(row, style)).collect();
+    let paragraph = Paragraph::new(lines).scroll((scroll_y as u16, scroll_x as u16));
+    f.render_widget(paragraph, area);
+    f.set_cursor(area.x + (column - scroll_x) as u16, area.y + (row - scroll_y) as u16);
+  }
+
+  /// Draws `label` followed by the field on the first row of `area`.
+  pub fn render_labeled(&self, f: &mut Frame<'_>, area: Rect, label: Span, style: Style) {
+    let [label_area, input_area] =
+      Layout::horizontal([Constraint::Length(label.width() as u16), Constraint::Fill(1)])
+        .areas(Rect { height: area.height.min(1), ..area });
+    f.render_widget(label, label_area);
+    self.render(f, input_area, style);
+  }
+
+  /// Breaks the text into rows at most `width` cells wide, returning them with the row and column
+  /// of the cursor.
+  fn wrap(&self, width: usize) -> (Vec<String>, (usize, usize)) {
+    let mut rows = vec![String::new()];
+    let mut column = 0;
+    let mut cursor = None;
+    for (i, c) in self.text.char_indices() {
+      let char_width = c.width().unwrap_or(0);
+      if c != '\n' && column > 0 && column + char_width > width {
+        rows.push(String::new());
+        column = 0;
+      }
+      if i == self.cursor {
+        cursor = Some((rows.len() - 1, column));
+      }
+      if c == '\n' {
+        rows.push(String::new());
+        column = 0;
+      } else {
+        rows.last_mut().unwrap().push(c);
+        column += char_width;
+      }
+    }
+    let mut cursor = cursor.unwrap_or((rows.len() - 1, column));
+    // A cursor after a full row goes to the start of the next.
+    if cursor.1 >= width {
+      cursor = (cursor.0 + 1, 0);
+      if cursor.0 == rows.len() {
+        rows.push(String::new());
+      }
+    }
+    (rows, cursor)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  fn press(input: &mut TextInput, code: KeyCode) {
+    input.handle_key(KeyEvent::from(code));
+  }
+
+  fn ctrl(input: &mut TextInput, c: char) {
+    input.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+  }
+
+  #[test]
+  fn ctrl_w_deletes_the_word_before_the_cursor() {
+    let mut input = TextInput::new();
+    input.set_text("rust  release notes  ");
+    ctrl(&mut input, 'w');
+    assert_eq!(input.text(), "rust  release ");
+    ctrl(&mut input, 'w');
+    assert_eq!(input.text(), "rust  ");
+  }
+
+  #[test]
+  fn ctrl_w_stops_at_multi_byte_whitespace() {
+    for space in ['\u{a0}', '\u{3000}'] {
+      let mut input = TextInput::new();
+      input.set_text(&format!("東京{space}ニュース"));
+      ctrl(&mut input, 'w');
+      assert_eq!(input.text(), format!("東京{space}"));
+      input.insert("x");
+      assert_eq!(input.text(), format!("東京{space}x"));
+    }
+  }
+
+  #[test]
+  fn moves_and_deletes_by_character() {
+    let mut input = TextInput::new();
+    input.set_text("añb");
+    press(&mut input, KeyCode::Left);
+    press(&mut input, KeyCode::Backspace);
+    assert_eq!(input.text(), "ab");
+    press(&mut input, KeyCode::Home);
+    press(&mut input, KeyCode::Delete);
+    press(&mut input, KeyCode::Char('é'));
+    assert_eq!(input.text(), "éb");
+  }
+
+  #[test]
+  fn a_single_line_takes_pasted_lines_as_spaces() {
+    let mut input = TextInput::new();
+    input.insert("one\r\ntwo\nthree");
+    assert_eq!(input.text(), "one two three");
+    assert!(!input.handle_key(KeyEvent::from(KeyCode::Enter)));
+  }
+
+  #[test]
+  fn ctrl_u_deletes_back_to_the_line_start() {
+    let mut input = TextInput::multiline();
+    input.set_text("first\nsecond line");
+    ctrl(&mut input, 'u');
+    assert_eq!(input.text(), "first\n");
+  }
+
+  #[test]
+  fn up_and_down_keep_as_near_the_column_as_the_line_allows() {
+    let mut input = TextInput::multiline();
+    input.set_text("abcdef\nxy\nuvwxyz");
+    press(&mut input, KeyCode::Up);
+    press(&mut input, KeyCode::Up);
+    input.insert("|");
+    assert_eq!(input.text(), "ab|cdef\nxy\nuvwxyz");
+    press(&mut input, KeyCode::Down);
+    press(&mut input, KeyCode::Down);
+    input.insert("|");
+    assert_eq!(input.text(), "ab|cdef\nxy\nuv|wxyz");
+  }
+
+  #[test]
+  fn wraps_long_lines_with_the_cursor() {
+    let mut input = TextInput::multiline();
+    input.set_text("abcdefg\nhi");
+    assert_eq!(input.wrap(3), (vec!["abc".into(), "def".into(), "g".into(), "hi".into()], (3, 2)));
+  }
+}
@@ -1,17 +1,29 @@
 use std::{collections::HashMap, fmt, path::PathBuf};
 
+use chrono::{
+  format::{Item, StrftimeItems},
+  DateTime, Local, Locale, NaiveTime, Utc,
+};
 use color_eyre::eyre::Result;
 use config::Value;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
 use ratatui::style::{Color, Modifier, Style};
+use regex::Regex;
 use serde::{
   de::{self, Deserializer, MapAccess, Visitor},
   Deserialize, Serialize,
 };
 use serde_json::Value as JsonValue;
 
-use crate::{action::Action, mode::Mode};
+use crate::{
+  action::Action,
+  components,
+  export::ExportFormat,
+  html,
+  i18n::{Language, Message},
+  mode::Mode,
+};
 
 #[derive(Clone, Debug, Deserialize, Default)]
 pub struct AppConfig {
@@ -28,6 +40,57 @@ pub struct Config {
   #[serde(default = "default_as_true")]
   pub confirm_quit: bool,
   #[serde(default)]
+  pub layout: LayoutMode,
+  /// Bars and popups drawn over the main view, from the bottom up, by their names in
+  /// `components::REGISTRY`. The main view itself comes from `layout` and is not listed.
+  #[serde(default = "default_components")]
+  pub components: Vec<String>,
+  /// Views open in tabs at launch in the tabs layout, by their names in `components::TABS`:
+  /// `groups`, `stats`, `health` or `highlights`.
+  #[serde(default = "default_tabs")]
+  pub tabs: Vec<String>,
+  /// Shows feeds nested under their groups on the Groups tab instead of a flat group list.
+  #[serde(default)]
+  pub group_tree: bool,
+  /// Name of a group whose articles are opened on launch, e.g. `News` or `Today`.
+  #[serde(default)]
+  pub start_group: Option<String>,
+  /// URL of a feed whose articles are opened on launch, taking precedence over `start_group`.
+  #[serde(default)]
+  pub start_feed: Option<String>,
+  #[serde(default)]
+  pub fetch: FetchConfig,
+  #[serde(default)]
+  pub info_bar: InfoBarConfig,
+  #[serde(default)]
+  pub tab_bar: TabBarConfig,
+  #[serde(default)]
+  pub email: EmailConfig,
+  #[serde(default)]
+  pub tts: TtsConfig,
+  #[serde(default)]
+  pub viewer: ViewerConfig,
+  #[serde(default)]
+  pub task: TaskConfig,
+  #[serde(default)]
+  pub summary: SummaryConfig,
+  #[serde(default)]
+  pub alerts: AlertConfig,
+  #[serde(default)]
+  pub theme: ThemeConfig,
+  #[serde(default)]
+  pub display: DisplayConfig,
+  #[serde(default)]
+  pub reader: ReaderConfig,
+  #[serde(default)]
+  pub media: MediaConfig,
+  /// Entries of the share menu, in order.
+  #[serde(default = "default_share")]
+  pub share: Vec<ShareTarget>,
+  /// What happens to stored feeds once they are taken out of the config.
+  #[serde(default)]
+  pub removed_feeds: RemovedFeeds,
+  #[serde(default)]
   pub groups: Vec<GroupConfig>,
 }
 
@@ -42,7 +105,8 @@ impl Config {
     let config_files = [("config.toml", config::FileFormat::Toml)];
     let mut found_config = false;
     for (file, format) in &config_files {
-      builder = builder.add_source(config::File::from(config_dir.join(file)).format(*format).required(false));
+      builder = builder
+        .add_source(config::File::from(config_dir.join(file)).format(*format).required(false));
       if config_dir.join(file).exists() {
         found_config = true
       }
@@ -51,17 +115,89 @@ impl Config {
       log::error!("No configuration file found. Application may not behave as expected");
     }
 
-    let cfg: Self = builder.build()?.try_deserialize()?;
+    let mut cfg: Self = builder.build()?.try_deserialize()?;
+    // Any value of NO_COLOR but an empty one turns colours off, whatever the config says.
+    if std::env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+      cfg.theme.colors = Colors::Off;
+    }
+    if cfg.display.hyperlinks.is_none() {
+      cfg.display.hyperlinks = Some(terminal_hyperlinks(|name| std::env::var(name).ok()));
+    }
+    cfg.validate()?;
 
     Ok(cfg)
   }
+
+  /// Rejects values that would otherwise only fail once they are used, such as on every draw.
+  fn validate(&self) -> Result<(), config::ConfigError> {
+    for (key, format) in [
+      ("display.date_format", &self.display.date_format),
+      ("info_bar.clock_format", &self.info_bar.clock_format),
+    ] {
+      if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(config::ConfigError::Message(format!(
+          "{key} `{format}` is not a valid strftime format"
+        )));
+      }
+    }
+    let main_views = [LayoutMode::Tabs, LayoutMode::Panes].map(LayoutMode::main_view);
+    if let Some(name) = self.components.iter().find(|name| main_views.contains(&name.as_str())) {
+      return Err(config::ConfigError::Message(format!(
+        "components: `{name}` is a main view, choose it with `layout` instead"
+      )));
+    }
+    if let Some(name) = self.tabs.iter().find(|name| components::tab_kind(name).is_none()) {
+      return Err(config::ConfigError::Message(format!("tabs: there is no view named `{name}`")));
+    }
+    let max_content_size = self.fetch.max_content_size;
+    if max_content_size != 0 && max_content_size < html::MIN_CONTENT_SIZE {
+      return Err(config::ConfigError::Message(format!(
+        "fetch.max_content_size must be 0 or at least {} bytes, not {max_content_size}",
+        html::MIN_CONTENT_SIZE
+      )));
+    }
+    Ok(())
+  }
+}
+
+/// How groups, feeds and articles are arranged on screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutMode {
+  /// Every opened group and feed gets its own tab.
+  #[default]
+  Tabs,
+  /// A group and feed tree beside the articles, without tabs.
+  Panes,
+}
+
+impl LayoutMode {
+  /// The name of the layout's main view in `components::REGISTRY`.
+  pub fn main_view(self) -> &'static str {
+    match self {
+      LayoutMode::Tabs => "tabs",
+      LayoutMode::Panes => "panes",
+    }
+  }
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct GroupConfig {
   pub name: String,
   pub desc: String,
+  #[serde(default)]
   pub feeds: Vec<FeedConfig>,
+  /// Groups nested in this one. Group names must be unique at every level.
+  #[serde(default)]
+  pub groups: Vec<GroupConfig>,
+  /// Color of the group's name in the group list, tab titles and article rows.
+  pub color: Option<Color>,
+  /// A short marker such as an emoji, shown in front of the group's name.
+  pub icon: Option<String>,
+  /// Unread articles published more than this many days ago are archived after a refresh, out
+  /// of the way of every view but Archive. Applies to the group's own feeds, nested groups set
+  /// their own.
+  pub archive_unread_after_days: Option<u32>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -69,8 +205,1046 @@ pub struct FeedConfig {
   pub name: Option<String>,
   pub desc: Option<String>,
   pub link: String,
+  /// Overrides the global request timeout for this feed.
+  pub timeout_secs: Option<u64>,
+  /// Color of the badge marking this feed's articles, derived from the link when unset.
+  pub color: Option<Color>,
+  /// Keeps refreshing the feed but leaves it out of unread counts and notices.
+  #[serde(default)]
+  pub mute: bool,
+  #[serde(default)]
+  pub kind: FeedKind,
+  /// Overrides the global `fetch.mark_read_after_days` for this feed.
+  pub mark_read_after_days: Option<u32>,
+  /// CSS selector of the article text on this feed's pages, used when readability finds too little.
+  pub content_selector: Option<String>,
+  /// Order the feed's article list opens in, the order of the feed itself when unset. Oldest
+  /// first suits serialized fiction and comics.
+  pub sort: Option<SortOrder>,
+  /// Opens the feed's article list with read articles hidden.
+  #[serde(default)]
+  pub unread_only: bool,
+  /// Overrides the global `fetch.refresh_interval_mins` for this feed.
+  pub refresh_interval_mins: Option<u64>,
+  /// Find-and-replace rules applied in order to the feed's articles as they are fetched, e.g.
+  /// to strip a `[Sponsor]` prefix from titles.
+  #[serde(default)]
+  pub rewrite: Vec<RewriteRule>,
+}
+
+/// A regular expression replaced in the title or description of a feed's articles.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RewriteRule {
+  #[serde(deserialize_with = "deserialize_regex")]
+  pub find: Regex,
+  /// What matches are replaced with, where `$1` stands for the first group. Empty removes them.
+  #[serde(default)]
+  pub replace: String,
+  #[serde(default)]
+  pub target: RewriteTarget,
+}
+
+/// The parts of an article a rewrite rule changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewriteTarget {
+  #[default]
+  Title,
+  Desc,
+  Both,
+}
+
+impl RewriteRule {
+  /// Applies the rule to an article's title and description, trimming what is left.
+  pub fn apply(&self, title: &mut String, desc: &mut String) {
+    let fields = match self.target {
+      RewriteTarget::Title => [Some(title), None],
+      RewriteTarget::Desc => [None, Some(desc)],
+      RewriteTarget::Both => [Some(title), Some(desc)],
+    };
+    for field in fields.into_iter().flatten() {
+      let rewritten = self.find.replace_all(field, self.replace.as_str());
+      *field = rewritten.trim().to_string();
+    }
+  }
+}
+
+/// Compiles a regular expression as the config is read, so a bad one is reported then.
+fn deserialize_regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+  let pattern = String::deserialize(deserializer)?;
+  Regex::new(&pattern).map_err(de::Error::custom)
+}
+
+/// Order of an article list by publication date.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+  #[default]
+  NewestFirst,
+  OldestFirst,
+}
+
+impl SortOrder {
+  pub fn toggled(self) -> Self {
+    match self {
+      SortOrder::NewestFirst => SortOrder::OldestFirst,
+      SortOrder::OldestFirst => SortOrder::NewestFirst,
+    }
+  }
+}
+
+/// Where a feed's articles come from, and so what its `link` means.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedKind {
+  /// An RSS document at `link`.
+  #[default]
+  Rss,
+  /// The arXiv API, with `link` as a search query such as `cat:cs.AI`.
+  Arxiv,
+  /// The Hacker News API, with `link` naming a story list such as `topstories`.
+  Hn,
+  /// The posts of a Mastodon account or hashtag, with `link` as `@user@instance`,
+  /// `#tag@instance` or the profile or tag page. Read from the public API, or from the instance's
+  /// RSS when the API is closed.
+  Mastodon,
+}
+
+/// What happens to a stored feed that is neither in the config nor subscribed to from the app.
+/// Groups left without feeds are deleted either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RemovedFeeds {
+  /// Leaves it where it was.
+  #[default]
+  Keep,
+  /// Moves it to the Archived group with its articles, notes and highlights as they were.
+  Archive,
+  /// Deletes it along with its articles and everything kept about them.
+  Delete,
+}
+
+/// Which feeds are refreshed when the app starts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StartupRefresh {
+  /// Starts instantly with what is stored, refreshing only when asked to.
+  Never,
+  #[default]
+  Always,
+  /// Only the feeds that have not been refreshed within their refresh interval.
+  #[serde(alias = "stale_only")]
+  StaleOnly,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct FetchConfig {
+  /// Which feeds are refreshed at launch.
+  pub on_startup: StartupRefresh,
+  /// Minutes after a successful refresh before a feed counts as stale.
+  pub refresh_interval_mins: u64,
+  /// Seconds before a single request is abandoned.
+  pub timeout_secs: u64,
+  /// How many times a transient failure is retried.
+  pub retries: u32,
+  /// Delay before the first retry, doubled on each further attempt.
+  pub backoff_ms: u64,
+  pub max_redirects: usize,
+  /// Seconds after which a whole refresh is abandoned, however many feeds are left.
+  pub refresh_deadline_secs: u64,
+  /// User agents tried in turn when a feed refuses the default one with 403 Forbidden.
+  pub user_agents: Vec<String>,
+  /// Upper bound on how long a `Retry-After` header can delay a retry.
+  pub max_retry_after_secs: u64,
+  /// Articles published more than this many days ago are marked read on refresh.
+  pub mark_read_after_days: Option<u32>,
+  /// Read articles that arrived more than this many days ago are deleted when pruning from the
+  /// stats tab. Articles with a note or highlights are kept.
+  pub prune_read_after_days: u32,
+  /// Extracted articles shorter than this many characters are taken to have failed, and the
+  /// next way of finding the text is tried.
+  pub min_article_chars: usize,
+  /// Megabytes of extracted articles kept in the data directory, 0 to not cache them.
+  pub article_cache_mb: u64,
+  /// Requests a minute sent to any one host, 0 for no limit. Keeps refreshes of many feeds on
+  /// the same site, such as dozens of subreddits, from being turned away with 429 Too Many
+  /// Requests.
+  pub host_requests_per_minute: u32,
+  /// Limits for particular hosts, replacing `host_requests_per_minute`. A host also covers its
+  /// subdomains, so `reddit.com` applies to `www.reddit.com`.
+  pub host_rate_limits: HashMap<String, u32>,
+  /// Failed refreshes in a row after which a feed is taken to be dead and no longer refreshed,
+  /// 0 to keep trying forever.
+  pub dead_after_failures: u32,
+  /// Bytes of content kept for an article, whether from its feed or extracted from its page,
+  /// 0 for no limit, otherwise at least 1024. Anything longer is cut short with a note saying so.
+  pub max_content_size: usize,
+  /// Hours between rounds of database upkeep, run after a refresh, 0 to leave it to
+  /// `nuuslees db vacuum`.
+  pub maintenance_interval_hours: u64,
+}
+
+impl FetchConfig {
+  /// How long `feed` stays fresh after a successful refresh.
+  pub fn refresh_interval(&self, feed: &FeedConfig) -> chrono::Duration {
+    let minutes = feed.refresh_interval_mins.unwrap_or(self.refresh_interval_mins);
+    // An interval too long to represent means the feed never goes stale.
+    i64::try_from(minutes)
+      .ok()
+      .and_then(chrono::Duration::try_minutes)
+      .unwrap_or(chrono::Duration::max_value())
+  }
+
+  /// The requests a minute allowed to `host`, 0 for no limit.
+  pub fn host_requests_per_minute(&self, host: &str) -> u32 {
+    self
+      .host_rate_limits
+      .iter()
+      .filter(|(limited, _)| {
+        host == limited.as_str() || host.ends_with(&format!(".{limited}"))
+      })
+      // The most specific host wins.
+      .max_by_key(|(limited, _)| limited.len())
+      .map_or(self.host_requests_per_minute, |(_, limit)| *limit)
+  }
+}
+
+impl Default for FetchConfig {
+  fn default() -> Self {
+    Self {
+      on_startup: StartupRefresh::default(),
+      refresh_interval_mins: 60,
+      timeout_secs: 30,
+      retries: 3,
+      backoff_ms: 500,
+      max_redirects: 5,
+      refresh_deadline_secs: 300,
+      user_agents: vec![
+        "Mozilla/5.0 (X11; Linux x86_64; rv:126.0) Gecko/20100101 Firefox/126.0".to_string(),
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_5) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Safari/605.1.15".to_string(),
+      ],
+      max_retry_after_secs: 120,
+      mark_read_after_days: None,
+      prune_read_after_days: 90,
+      min_article_chars: 500,
+      article_cache_mb: 50,
+      host_requests_per_minute: 0,
+      host_rate_limits: HashMap::new(),
+      dead_after_failures: 20,
+      max_content_size: 512 * 1024,
+      maintenance_interval_hours: 24,
+    }
+  }
+}
+
+/// A piece of information shown in the info bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InfoBarSegment {
+  Version,
+  Mode,
+  Unread,
+  Refresh,
+  Speech,
+  /// The latest notification or error, for a few seconds.
+  Message,
+  Clock,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct InfoBarConfig {
+  /// Segments to show, from left to right.
+  pub segments: Vec<InfoBarSegment>,
+  /// strftime format used by the clock segment.
+  pub clock_format: String,
+}
+
+impl Default for InfoBarConfig {
+  fn default() -> Self {
+    Self {
+      segments: vec![
+        InfoBarSegment::Version,
+        InfoBarSegment::Mode,
+        InfoBarSegment::Unread,
+        InfoBarSegment::Refresh,
+        InfoBarSegment::Speech,
+        InfoBarSegment::Message,
+        InfoBarSegment::Clock,
+      ],
+      clock_format: "%H:%M".to_string(),
+    }
+  }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TabBarConfig {
+  /// Widest a tab title is drawn, in cells, before it is cut short with `…`. Titles shrink further
+  /// when the open tabs would not fit otherwise, and past that the tabs scroll.
+  pub max_title_width: usize,
+  /// Puts each tab's number in front of its title. The number keys select tabs either way.
+  pub numbered: bool,
+}
+
+impl Default for TabBarConfig {
+  fn default() -> Self {
+    Self { max_title_width: 24, numbered: false }
+  }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+  /// Address articles are sent to.
+  pub to: Option<String>,
+  /// Sender address, the recipient when unset.
+  pub from: Option<String>,
+  /// Shell command given the whole message on stdin, e.g. `msmtp -t`. Preferred over `smtp`.
+  pub command: Option<String>,
+  pub smtp: Option<SmtpConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SmtpConfig {
+  pub host: String,
+  /// Defaults to 587 with STARTTLS and 465 otherwise.
+  pub port: Option<u16>,
+  pub username: Option<String>,
+  pub password: Option<String>,
+  /// Name of a secret set with `nuuslees auth set`, used instead of a plaintext `password`.
+  pub password_secret: Option<String>,
+  /// Upgrade a plain connection with STARTTLS instead of connecting over TLS.
+  #[serde(default = "default_as_true")]
+  pub starttls: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TtsConfig {
+  /// Shell command reading the article text aloud from stdin, e.g. `say` or
+  /// `piper --model voice.onnx --output-raw | aplay -r 22050 -f S16_LE`.
+  pub command: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ViewerConfig {
+  /// Shell command opening an article outside the app, e.g. `w3m %url` or `glow -p %file`.
+  /// `%url` stands for the article's link and `%file` for the article exported to a file in the
+  /// data directory, removed once the command exits. Both are handed to the shell as arguments.
+  pub command: Option<String>,
+  /// Format of the file given as `%file`.
+  pub format: ExportFormat,
+  /// Opens articles in the viewer instead of the built-in reader.
+  pub replace_reader: bool,
+}
+
+/// An entry of the share menu, doing one of three things with the selected article: opening
+/// `url`, running `command` or, with `email` set, emailing it with the `email` settings.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ShareTarget {
+  pub name: String,
+  /// Link opened in the browser, e.g. `https://mastodon.social/share?text=%title%20%url`.
+  /// `%title`, `%url` and `%feed` are replaced with the article's title, link and feed name,
+  /// URL-encoded.
+  pub url: Option<String>,
+  /// Shell command run in the background, with the same placeholders handed to the shell as
+  /// arguments, as in `task.command`.
+  pub command: Option<String>,
+  pub email: bool,
+}
+
+fn default_components() -> Vec<String> {
+  ["info_bar", "export", "catalog", "switcher", "note", "share", "info", "menu", "confirm"]
+    .map(String::from)
+    .to_vec()
+}
+
+fn default_tabs() -> Vec<String> {
+  vec!["groups".to_string()]
+}
+
+fn default_share() -> Vec<ShareTarget> {
+  vec![
+    ShareTarget {
+      name: "Hacker News".to_string(),
+      url: Some("https://news.ycombinator.com/submitlink?u=%url&t=%title".to_string()),
+      ..Default::default()
+    },
+    ShareTarget { name: "Email".to_string(), email: true, ..Default::default() },
+  ]
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TaskConfig {
+  /// Shell command adding the selected article to a task manager, e.g.
+  /// `task add Read %title +reading`. `%title`, `%url` and `%feed` stand for the article's
+  /// title, link and feed name, handed to the shell as arguments so they are never run as code.
+  pub command: Option<String>,
+}
+
+/// Watch keywords, whose new articles are flagged, listed under Alerts and announced.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+  /// Words or phrases such as `CVE` or `rustc release`, matched against the titles and
+  /// descriptions of new articles, ignoring case.
+  pub keywords: Vec<String>,
+  /// Also announces each alert on the desktop, with `notify-send` unless `notify_command` is set.
+  pub desktop: bool,
+  /// Shell command showing a desktop notification instead of `notify-send`, with `%title`, `%url`
+  /// and `%feed` filled in as in `task.command`.
+  pub notify_command: Option<String>,
+}
+
+/// An external summarizer for articles, off unless `command` or `endpoint` is set.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SummaryConfig {
+  /// Shell command given the article's text on stdin, printing its summary. `%title`, `%url` and
+  /// `%feed` are filled in as in `task.command`.
+  pub command: Option<String>,
+  /// URL the article is posted to as JSON with `title`, `url` and `text`, e.g. a local LLM
+  /// server. It answers with the summary as plain text or in the `summary` field of an object.
+  pub endpoint: Option<String>,
+  /// Summarizes every article as it is opened, rather than only when asked to with `z`.
+  pub auto: bool,
+  pub timeout_secs: u64,
+}
+
+impl Default for SummaryConfig {
+  fn default() -> Self {
+    Self { command: None, endpoint: None, auto: false, timeout_secs: 120 }
+  }
+}
+
+impl SummaryConfig {
+  pub fn is_enabled(&self) -> bool {
+    self.command.is_some() || self.endpoint.is_some()
+  }
+}
+
+impl Default for TtsConfig {
+  fn default() -> Self {
+    Self { command: "espeak".to_string() }
+  }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+  /// strftime format of dates in article lists, the reader header and the health tab.
+  pub date_format: String,
+  /// Shows dates from the last `relative_days` days as e.g. "3 h ago".
+  pub relative_dates: bool,
+  pub relative_days: i64,
+  /// Locale of the interface and of month and weekday names, e.g. `af_ZA`. Taken from `LC_TIME`
+  /// or `LANG` when unset. The interface is in English for locales it is not translated to.
+  pub locale: Option<String>,
+  /// Lines of description under each title in article lists, from 0 for titles only up to 3.
+  pub preview_lines: u8,
+  /// Shows descriptions as plain text instead of with the HTML tags and entities feeds put in.
+  pub strip_html: bool,
+  /// Sets the title of the terminal window to the tab or article on screen.
+  pub terminal_title: bool,
+  /// Moving past the end of a list goes back to its start, and the other way round.
+  pub wrap_lists: bool,
+  /// Marks links in the reader as terminal hyperlinks (OSC 8), which many terminals open on
+  /// Ctrl+click. When unset, they are marked only in terminals known to support them, as others
+  /// may print the codes instead.
+  pub hyperlinks: Option<bool>,
+}
+
+impl Default for DisplayConfig {
+  fn default() -> Self {
+    Self {
+      date_format: "%Y-%m-%d %H:%M".to_string(),
+      relative_dates: true,
+      relative_days: 7,
+      locale: None,
+      preview_lines: 1,
+      strip_html: true,
+      terminal_title: true,
+      wrap_lists: true,
+      hyperlinks: None,
+    }
+  }
+}
+
+/// Files attached to articles, listed beneath the reader.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MediaConfig {
+  /// Where attachments are downloaded to, the user's download directory when unset.
+  pub download_dir: Option<PathBuf>,
+}
+
+/// How articles are laid out in the reader. Each option can also be toggled while reading.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct ReaderConfig {
+  /// Widest the text runs, in columns, centred in the reader. 0 uses the whole width.
+  pub max_width: u16,
+  /// Blank lines between paragraphs.
+  pub paragraph_spacing: u8,
+  /// Splits words that do not fit at the end of a line with a hyphen instead of moving them to
+  /// the next one.
+  pub hyphenate: bool,
+  /// Stretches the spaces between words so lines fill the column evenly.
+  pub justify: bool,
+}
+
+impl Default for ReaderConfig {
+  fn default() -> Self {
+    Self { max_width: 80, paragraph_spacing: 1, hyphenate: false, justify: false }
+  }
+}
+
+impl DisplayConfig {
+  /// The configured number of description lines, capped at 3.
+  pub fn preview_lines(&self) -> usize {
+    usize::from(self.preview_lines.min(3))
+  }
+
+  /// Formats `date` in local time, as configured.
+  pub fn format_date(&self, date: DateTime<Utc>) -> String {
+    let age = Utc::now().signed_duration_since(date);
+    if self.relative_dates && age.num_seconds() >= 0 && age.num_days() < self.relative_days {
+      let language = self.language();
+      return match (age.num_days(), age.num_hours(), age.num_minutes()) {
+        (0, 0, 0) => language.text(Message::JustNow).to_string(),
+        (0, 0, minutes) => language.format(Message::MinutesAgo, &[&minutes]),
+        (0, hours, _) => language.format(Message::HoursAgo, &[&hours]),
+        (1, ..) => language.text(Message::Yesterday).to_string(),
+        (days, ..) => language.format(Message::DaysAgo, &[&days]),
+      };
+    }
+    date.with_timezone(&Local).format_localized(&self.date_format, self.locale()).to_string()
+  }
+
+  /// The language of the interface.
+  pub fn language(&self) -> Language {
+    Language::from_locale(&self.locale_name())
+  }
+
+  fn locale(&self) -> Locale {
+    // Drops the encoding of names like `en_US.UTF-8`.
+    let name = self.locale_name();
+    let name = name.split('.').next().unwrap_or_default();
+    Locale::try_from(name).unwrap_or(Locale::POSIX)
+  }
+
+  fn locale_name(&self) -> String {
+    self
+      .locale
+      .clone()
+      .or_else(|| std::env::var("LC_ALL").ok())
+      .or_else(|| std::env::var("LC_TIME").ok())
+      .or_else(|| std::env::var("LANG").ok())
+      .unwrap_or_default()
+  }
+}
+
+/// Colors the interface is drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct Palette {
+  /// Names of groups, feeds and articles.
+  pub title: Color,
+  /// The selected row.
+  pub selected: Color,
+  /// Body text.
+  pub foreground: Color,
+  /// Descriptions and labels.
+  pub text: Color,
+  /// Counts, separators and muted feeds.
+  pub muted: Color,
+  /// Borders of the focused pane.
+  pub accent: Color,
+  pub error: Color,
+}
+
+impl Palette {
+  pub const DARK: Self = Self {
+    title: Color::Magenta,
+    selected: Color::Cyan,
+    foreground: Color::White,
+    text: Color::Gray,
+    muted: Color::DarkGray,
+    accent: Color::Green,
+    error: Color::Red,
+  };
+  pub const HIGH_CONTRAST_DARK: Self = Self {
+    title: Color::White,
+    selected: Color::Yellow,
+    foreground: Color::White,
+    text: Color::White,
+    muted: Color::Gray,
+    accent: Color::Yellow,
+    error: Color::LightRed,
+  };
+  pub const HIGH_CONTRAST_LIGHT: Self = Self {
+    title: Color::Black,
+    selected: Color::Blue,
+    foreground: Color::Black,
+    text: Color::Black,
+    muted: Color::DarkGray,
+    accent: Color::Blue,
+    error: Color::Red,
+  };
+  pub const LIGHT: Self = Self {
+    title: Color::Magenta,
+    selected: Color::Blue,
+    foreground: Color::Black,
+    text: Color::DarkGray,
+    muted: Color::Gray,
+    accent: Color::Green,
+    error: Color::Red,
+  };
+  /// The terminal's own colours throughout.
+  pub const NO_COLOR: Self = Self {
+    title: Color::Reset,
+    selected: Color::Reset,
+    foreground: Color::Reset,
+    text: Color::Reset,
+    muted: Color::Reset,
+    accent: Color::Reset,
+    error: Color::Reset,
+  };
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ThemeVariant {
+  #[default]
+  Dark,
+  Light,
+}
+
+impl ThemeVariant {
+  pub fn toggled(self) -> Self {
+    match self {
+      ThemeVariant::Dark => ThemeVariant::Light,
+      ThemeVariant::Light => ThemeVariant::Dark,
+    }
+  }
+}
+
+/// How much the interface leans on colour, cycled with Ctrl-T.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Colors {
+  /// The configured dark and light palettes.
+  #[default]
+  Normal,
+  /// Stark palettes, with selected rows reversed and unread markers underlined so that nothing
+  /// is told apart by colour alone.
+  HighContrast,
+  /// No colours at all, as when `NO_COLOR` is set, with the same markers as `high_contrast`.
+  Off,
+}
+
+impl Colors {
+  pub fn next(self) -> Self {
+    match self {
+      Colors::Normal => Colors::HighContrast,
+      Colors::HighContrast => Colors::Off,
+      Colors::Off => Colors::Normal,
+    }
+  }
+}
+
+/// How the dark or light palette is chosen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+  #[default]
+  Dark,
+  Light,
+  /// Light between `day_starts` and `night_starts` local time, dark otherwise.
+  Time,
+  /// Follows the terminal's `COLORFGBG` or the desktop's `GTK_THEME`, dark when neither says.
+  System,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+  pub mode: ThemeMode,
+  /// `HH:MM` local time from which the `time` mode uses the light palette.
+  pub day_starts: String,
+  /// `HH:MM` local time from which the `time` mode uses the dark palette.
+  pub night_starts: String,
+  pub dark: Palette,
+  pub light: Palette,
+  pub colors: Colors,
+  /// The palette in use, picked by the app from `mode` or toggled by hand.
+  #[serde(skip)]
+  pub variant: ThemeVariant,
+}
+
+impl Default for ThemeConfig {
+  fn default() -> Self {
+    Self {
+      mode: ThemeMode::default(),
+      day_starts: "07:00".to_string(),
+      night_starts: "19:00".to_string(),
+      dark: Palette::DARK,
+      light: Palette::LIGHT,
+      colors: Colors::default(),
+      variant: ThemeVariant::default(),
+    }
+  }
+}
+
+impl ThemeConfig {
+  /// Returns the variant `mode` asks for at the local time `now`.
+  pub fn preferred_variant(&self, now: NaiveTime) -> ThemeVariant {
+    match self.mode {
+      ThemeMode::Dark => ThemeVariant::Dark,
+      ThemeMode::Light => ThemeVariant::Light,
+      ThemeMode::Time => {
+        let parse =
+          |time: &str, default| NaiveTime::parse_from_str(time, "%H:%M").unwrap_or(default);
+        let day = parse(&self.day_starts, NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        let night = parse(&self.night_starts, NaiveTime::from_hms_opt(19, 0, 0).unwrap());
+        // The day may wrap past midnight when it starts after the night does.
+        let is_day =
+          if day <= night { day <= now && now < night } else { now >= day || now < night };
+        if is_day {
+          ThemeVariant::Light
+        } else {
+          ThemeVariant::Dark
+        }
+      },
+      ThemeMode::System => system_variant().unwrap_or_default(),
+    }
+  }
+}
+
+/// Whether the terminal is known to support OSC 8 hyperlinks, going by the variables `var` reads
+/// from its environment. Multiplexers such as tmux and screen may not pass them on.
+fn terminal_hyperlinks(var: impl Fn(&str) -> Option<String>) -> bool {
+  let term = var("TERM").unwrap_or_default();
+  let multiplexed = var("TMUX").is_some() || term.starts_with("screen");
+  if multiplexed || term.is_empty() || term == "dumb" || term == "linux" {
+    return false;
+  }
+  let program = var("TERM_PROGRAM").unwrap_or_default();
+  let vte = var("VTE_VERSION").and_then(|version| version.parse::<u32>().ok());
+  matches!(program.as_str(), "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper")
+    || ["kitty", "foot", "alacritty", "ghostty", "wezterm"].iter().any(|name| term.contains(name))
+    || ["KITTY_WINDOW_ID", "WT_SESSION", "KONSOLE_VERSION"].iter().any(|name| var(name).is_some())
+    // VTE terminals such as GNOME Terminal have them from 0.50 on.
+    || vte.is_some_and(|version| version >= 5000)
+}
+
+/// Reads the background hint terminals such as rxvt and Konsole export as `COLORFGBG=fg;bg`,
+/// then GTK's theme name.
+fn system_variant() -> Option<ThemeVariant> {
+  if let Ok(colors) = std::env::var("COLORFGBG") {
+    if let Some(Ok(background)) = colors.rsplit(';').next().map(str::parse::<u8>) {
+      let light = background == 7 || background >= 9;
+      return Some(if light { ThemeVariant::Light } else { ThemeVariant::Dark });
+    }
+  }
+  let gtk_theme = std::env::var("GTK_THEME").ok()?;
+  let dark = gtk_theme.to_lowercase().contains("dark");
+  Some(if dark { ThemeVariant::Dark } else { ThemeVariant::Light })
+}
+
+impl Config {
+  /// The palette of the theme variant in use.
+  pub fn palette(&self) -> &Palette {
+    match (self.theme.colors, self.theme.variant) {
+      (Colors::Normal, ThemeVariant::Dark) => &self.theme.dark,
+      (Colors::Normal, ThemeVariant::Light) => &self.theme.light,
+      (Colors::HighContrast, ThemeVariant::Dark) => &Palette::HIGH_CONTRAST_DARK,
+      (Colors::HighContrast, ThemeVariant::Light) => &Palette::HIGH_CONTRAST_LIGHT,
+      (Colors::Off, _) => &Palette::NO_COLOR,
+    }
+  }
+
+  /// The name of the selected row, reversed when colour alone should not set it apart.
+  pub fn selected_style(&self) -> Style {
+    let style = Style::default().fg(self.palette().selected).add_modifier(Modifier::BOLD);
+    match self.theme.colors {
+      Colors::Normal => style,
+      Colors::HighContrast | Colors::Off => style.add_modifier(Modifier::REVERSED),
+    }
+  }
+
+  /// A marker of new or unread articles in `color`, underlined and bold when colour alone should
+  /// not set it apart.
+  pub fn unread_style(&self, color: Color) -> Style {
+    let style = Style::default().fg(color);
+    match self.theme.colors {
+      Colors::Normal => style,
+      Colors::HighContrast | Colors::Off => {
+        style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+      },
+    }
+  }
+
+  /// `color`, or the terminal's own colour when colours are off.
+  pub fn color(&self, color: Color) -> Color {
+    match self.theme.colors {
+      Colors::Normal | Colors::HighContrast => color,
+      Colors::Off => Color::Reset,
+    }
+  }
+
+  /// Links and footnote numbers in articles, underlined when colour alone should not set them
+  /// apart.
+  pub fn link_style(&self) -> Style {
+    let style = Style::default().fg(self.color(Color::Blue));
+    match self.theme.colors {
+      Colors::Normal => style,
+      Colors::HighContrast | Colors::Off => style.add_modifier(Modifier::UNDERLINED),
+    }
+  }
+
+  /// Every configured group, each followed by the groups nested in it.
+  pub fn all_groups(&self) -> Vec<&GroupConfig> {
+    fn push<'c>(groups: &'c [GroupConfig], all: &mut Vec<&'c GroupConfig>) {
+      for group in groups {
+        all.push(group);
+        push(&group.groups, all);
+      }
+    }
+    let mut all = Vec::new();
+    push(&self.groups, &mut all);
+    all
+  }
+
+  /// The configured feed at `url`.
+  pub fn feed(&self, url: &str) -> Option<&FeedConfig> {
+    self.all_groups().into_iter().flat_map(|group| &group.feeds).find(|feed| feed.link == url)
+  }
+
+  /// The link a stored feed is configured under: its `url`, or the URL it moved from after a
+  /// permanent redirect while the config still has that one. Config lookups for stored feeds go
+  /// through this, as a moved feed is stored under its new URL.
+  pub fn feed_link<'a>(&self, url: &'a str, moved_from: Option<&'a str>) -> &'a str {
+    match moved_from {
+      Some(moved_from) if self.feed(url).is_none() && self.feed(moved_from).is_some() => moved_from,
+      _ => url,
+    }
+  }
+
+  /// The configured group named `name`, at any level.
+  pub fn group(&self, name: &str) -> Option<&GroupConfig> {
+    self.all_groups().into_iter().find(|group| group.name == name)
+  }
+
+  /// The configured group the feed at `url` is in.
+  pub fn feed_group(&self, url: &str) -> Option<&GroupConfig> {
+    self.all_groups().into_iter().find(|group| group.feeds.iter().any(|feed| feed.link == url))
+  }
+
+  /// `message` in the language of the interface.
+  pub fn text(&self, message: Message) -> &'static str {
+    self.display.language().text(message)
+  }
+
+  /// `message` in the language of the interface, with its `{}` replaced by `args` in order.
+  pub fn format(&self, message: Message, args: &[&dyn fmt::Display]) -> String {
+    self.display.language().format(message, args)
+  }
+
+  /// The name of the group with its icon in front, if it has one.
+  pub fn group_label(&self, name: &str) -> String {
+    match self.group(name).and_then(|group| group.icon.as_deref()) {
+      Some(icon) => format!("{icon} {name}"),
+      None => name.to_string(),
+    }
+  }
+
+  /// The configured color of the group named `name`, unless colours are off.
+  pub fn group_color(&self, name: &str) -> Option<Color> {
+    self.group(name).and_then(|group| group.color).map(|color| self.color(color))
+  }
+
+  /// Returns the badge color of the feed at `url`, either configured or picked from the URL.
+  pub fn feed_color(&self, url: &str) -> Color {
+    const PALETTE: [Color; 12] = [
+      Color::Red,
+      Color::Green,
+      Color::Yellow,
+      Color::Blue,
+      Color::Magenta,
+      Color::Cyan,
+      Color::LightRed,
+      Color::LightGreen,
+      Color::LightYellow,
+      Color::LightBlue,
+      Color::LightMagenta,
+      Color::LightCyan,
+    ];
+
+    let color = match self.feed(url).and_then(|feed| feed.color) {
+      Some(color) => color,
+      None => PALETTE[(crate::utils::stable_hash(url) % PALETTE.len() as u64) as usize],
+    };
+    self.color(color)
+  }
 }
 
 const fn default_as_true() -> bool {
   true
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rejects_an_invalid_date_format() {
+    let mut config = Config::default();
+    assert!(config.validate().is_ok());
+    config.display.date_format = "%Y-%Q".to_string();
+    assert!(config.validate().is_err());
+  }
+
+  fn rule(find: &str, replace: &str, target: RewriteTarget) -> RewriteRule {
+    RewriteRule { find: Regex::new(find).unwrap(), replace: replace.to_string(), target }
+  }
+
+  /// The title and description of an article after `rule` is applied to them.
+  fn rewrite(rule: &RewriteRule, title: &str, desc: &str) -> (String, String) {
+    let (mut title, mut desc) = (title.to_string(), desc.to_string());
+    rule.apply(&mut title, &mut desc);
+    (title, desc)
+  }
+
+  #[test]
+  fn a_match_in_the_title_only_rewrites_the_title() {
+    let both = rule(r"\[Sponsored\]", "", RewriteTarget::Both);
+    assert_eq!(
+      rewrite(&both, "[Sponsored] Rust 1.80", "A new release."),
+      ("Rust 1.80".to_string(), "A new release.".to_string())
+    );
+    let title = rule(r"\[Sponsored\]", "", RewriteTarget::Title);
+    assert_eq!(
+      rewrite(&title, "[Sponsored] Rust 1.80", "[Sponsored] post"),
+      ("Rust 1.80".to_string(), "[Sponsored] post".to_string())
+    );
+  }
+
+  #[test]
+  fn a_match_in_the_description_only_rewrites_the_description() {
+    let both = rule(r"\s*Read more…$", "", RewriteTarget::Both);
+    assert_eq!(
+      rewrite(&both, "Rust 1.80", "A new release. Read more…"),
+      ("Rust 1.80".to_string(), "A new release.".to_string())
+    );
+    let desc = rule(r"\s*Read more…$", "", RewriteTarget::Desc);
+    assert_eq!(
+      rewrite(&desc, "Read more…", "A new release. Read more…"),
+      ("Read more…".to_string(), "A new release.".to_string())
+    );
+  }
+
+  #[test]
+  fn rewrite_rules_replace_capture_groups() {
+    let rule = rule(r"^(\w+) v(\d+)", "$1 version $2", RewriteTarget::Both);
+    assert_eq!(
+      rewrite(&rule, "Ratatui v26 is out", "Ratatui v26"),
+      ("Ratatui version 26 is out".to_string(), "Ratatui version 26".to_string())
+    );
+  }
+
+  #[test]
+  fn rewrite_rules_without_a_match_leave_the_article_alone() {
+    let rule = rule(r"\[Sponsored\]", "", RewriteTarget::Both);
+    assert_eq!(
+      rewrite(&rule, "Rust 1.80", "A new release."),
+      ("Rust 1.80".to_string(), "A new release.".to_string())
+    );
+  }
+
+  #[test]
+  fn rejects_main_views_listed_as_components() {
+    let mut config = Config { components: default_components(), ..Default::default() };
+    assert!(config.validate().is_ok());
+    for main_view in ["tabs", "panes"] {
+      config.components = vec!["info_bar".to_string(), main_view.to_string()];
+      assert!(config.validate().is_err());
+    }
+  }
+
+  #[test]
+  fn rejects_a_content_size_too_small_for_the_marker() {
+    let mut config = Config::default();
+    config.fetch.max_content_size = 0;
+    assert!(config.validate().is_ok());
+    config.fetch.max_content_size = 20;
+    assert!(config.validate().is_err());
+  }
+
+  #[test]
+  fn feeds_keep_their_badge_colors_only_while_colors_are_on() {
+    let mut config = Config::default();
+    assert_ne!(config.feed_color("https://example.com/feed"), Color::Reset);
+    config.theme.colors = Colors::Off;
+    assert_eq!(config.feed_color("https://example.com/feed"), Color::Reset);
+    assert_eq!(
+      config.link_style(),
+      Style::default().fg(Color::Reset).add_modifier(Modifier::UNDERLINED)
+    );
+  }
+
+  #[test]
+  fn moved_feeds_are_found_under_the_link_still_in_the_config() {
+    let mut config = Config::default();
+    let feed =
+      FeedConfig { link: "https://old.example.com/feed".to_string(), ..Default::default() };
+    config.groups.push(GroupConfig {
+      name: "News".to_string(),
+      feeds: vec![feed],
+      ..Default::default()
+    });
+    let old = Some("https://old.example.com/feed");
+    assert_eq!(
+      config.feed_link("https://new.example.com/feed", old),
+      "https://old.example.com/feed"
+    );
+    assert!(config.feed_group(config.feed_link("https://new.example.com/feed", old)).is_some());
+    assert_eq!(
+      config.feed_link("https://new.example.com/feed", None),
+      "https://new.example.com/feed"
+    );
+    // Once the config has the new link, that is the one used.
+    config.groups[0].feeds[0].link = "https://new.example.com/feed".to_string();
+    assert_eq!(
+      config.feed_link("https://new.example.com/feed", old),
+      "https://new.example.com/feed"
+    );
+  }
+
+  #[test]
+  fn hyperlinks_are_marked_only_in_terminals_known_to_support_them() {
+    let env = |vars: &'static [(&str, &str)]| {
+      move |name: &str| {
+        vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+      }
+    };
+    assert!(terminal_hyperlinks(env(&[("TERM", "xterm-kitty")])));
+    assert!(terminal_hyperlinks(env(&[("TERM", "xterm-256color"), ("VTE_VERSION", "7600")])));
+    assert!(terminal_hyperlinks(env(&[("TERM", "xterm-256color"), ("WT_SESSION", "1")])));
+    assert!(!terminal_hyperlinks(env(&[("TERM", "xterm-256color")])));
+    assert!(!terminal_hyperlinks(env(&[("TERM", "linux")])));
+    assert!(!terminal_hyperlinks(env(&[("TERM", "tmux-256color"), ("TMUX", "/tmp/tmux")])));
+  }
+
+  #[test]
+  fn a_huge_refresh_interval_saturates() {
+    let fetch = FetchConfig { refresh_interval_mins: 30, ..Default::default() };
+    let feed = |mins| FeedConfig { refresh_interval_mins: Some(mins), ..Default::default() };
+    assert_eq!(fetch.refresh_interval(&FeedConfig::default()), chrono::Duration::minutes(30));
+    assert_eq!(fetch.refresh_interval(&feed(u64::MAX)), chrono::Duration::max_value());
+    assert_eq!(fetch.refresh_interval(&feed(i64::MAX as u64)), chrono::Duration::max_value());
+  }
+}
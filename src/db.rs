@@ -1,26 +1,31 @@
-use std::path::Path;
+use std::{
+  collections::{HashMap, HashSet},
+  path::{Path, PathBuf},
+  time::Duration,
+};
 
-use chrono::Utc;
-use reqwest::Client;
+use chrono::{DateTime, Datelike, Days, Local, LocalResult, NaiveDate, TimeZone, Utc};
 use rusqlite::{Connection, ErrorCode, Result};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::sync::mpsc::{self, UnboundedSender};
 
-use crate::config::Config;
+use crate::{
+  alerts,
+  config::{Config, FeedConfig, RemovedFeeds},
+  extract::Extraction,
+  fetcher::FetchedFeed,
+  html, keywords,
+  media::Enclosure,
+  refresh::{FailureKind, RefreshError},
+  state::ItemState,
+  utils,
+};
 
 #[derive(Error, Debug)]
 pub enum DbError {
-  #[error("Database error")]
+  #[error("Database error: {0}")]
   RusqliteError(#[from] rusqlite::Error),
-
-  #[error("Network error")]
-  ReqwestError(#[from] reqwest::Error),
-
-  #[error("RSS error")]
-  RssError(#[from] rss::Error),
-
-  #[error("Custom error: {0}")]
-  Custom(String),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -28,6 +33,110 @@ pub struct Group {
   pub id: i32,
   pub name: String,
   pub desc: String,
+  /// The group this one is nested in, if any.
+  pub parent_id: Option<i32>,
+}
+
+/// A built-in view listed with the groups: the articles published in a window of time, the ones
+/// opened most recently, the ones matching a watch keyword, or the ones archived unread.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SmartView {
+  Today,
+  Last24Hours,
+  Yesterday,
+  ThisWeek,
+  History,
+  Alerts,
+  Archive,
+}
+
+impl SmartView {
+  pub const ALL: [SmartView; 7] = [
+    SmartView::Today,
+    SmartView::Last24Hours,
+    SmartView::Yesterday,
+    SmartView::ThisWeek,
+    SmartView::History,
+    SmartView::Alerts,
+    SmartView::Archive,
+  ];
+
+  /// The id of the view's entry in the group list. Like "All Feeds", these are negative.
+  pub fn group_id(self) -> i32 {
+    match self {
+      SmartView::Today => -2,
+      SmartView::Last24Hours => -3,
+      SmartView::Yesterday => -4,
+      SmartView::ThisWeek => -5,
+      SmartView::History => -6,
+      SmartView::Alerts => -7,
+      SmartView::Archive => -8,
+    }
+  }
+
+  pub fn from_group_id(id: i32) -> Option<Self> {
+    Self::ALL.into_iter().find(|view| view.group_id() == id)
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      SmartView::Today => "Today",
+      SmartView::Last24Hours => "Last 24 hours",
+      SmartView::Yesterday => "Yesterday",
+      SmartView::ThisWeek => "This Week",
+      SmartView::History => "History",
+      SmartView::Alerts => "Alerts",
+      SmartView::Archive => "Archive",
+    }
+  }
+
+  fn desc(self) -> &'static str {
+    match self {
+      SmartView::Today => "Articles published since midnight",
+      SmartView::Last24Hours => "Articles published in the last 24 hours",
+      SmartView::Yesterday => "Articles published yesterday",
+      SmartView::ThisWeek => "Articles published since Monday",
+      SmartView::History => "Articles you opened, the most recent first",
+      SmartView::Alerts => "New articles matching your watch keywords",
+      SmartView::Archive => "Articles archived unread, left out of every other view",
+    }
+  }
+
+  pub fn group(self) -> Group {
+    Group {
+      id: self.group_id(),
+      name: self.name().to_string(),
+      desc: self.desc().to_string(),
+      parent_id: None,
+    }
+  }
+
+  /// The start and end of the window at `now`, with days starting at local midnight. History,
+  /// Alerts and Archive are not windows of time and have none.
+  pub fn bounds(self, now: DateTime<Local>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let today = now.date_naive();
+    let tomorrow = local_midnight(today + Days::new(1));
+    let bounds = match self {
+      SmartView::Today => (local_midnight(today), tomorrow),
+      SmartView::Last24Hours => ((now - chrono::Duration::hours(24)).to_utc(), tomorrow),
+      SmartView::Yesterday => (local_midnight(today - Days::new(1)), local_midnight(today)),
+      SmartView::ThisWeek => {
+        let monday = today - Days::new(u64::from(now.weekday().num_days_from_monday()));
+        (local_midnight(monday), tomorrow)
+      },
+      SmartView::History | SmartView::Alerts | SmartView::Archive => return None,
+    };
+    Some(bounds)
+  }
+}
+
+/// The first moment of `date` in local time, which is not always 00:00 on days clocks change.
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+  let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+  match Local.from_local_datetime(&midnight) {
+    LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => time.to_utc(),
+    LocalResult::None => Local.from_utc_datetime(&midnight).to_utc(),
+  }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -37,19 +146,427 @@ pub struct Feed {
   pub name: String,
   pub desc: String,
   pub url: String,
+  /// The URL the feed was stored under before it moved permanently, which the config may still
+  /// have.
+  pub moved_from: Option<String>,
   pub updated_at: chrono::DateTime<Utc>,
+  /// Muted feeds are still refreshed but left out of unread counts and notices.
+  pub muted: bool,
+  /// Feeds that failed too many refreshes in a row are no longer refreshed until revived.
+  pub dead: bool,
+  /// How often the feed posts, unknown until it has at least two articles. Only set by
+  /// [`Database::get_feeds_from_group`] and [`Database::get_feed_info`].
+  pub cadence: Option<Cadence>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct FeedItem {
   pub id: i32,
   pub feed_id: i32,
+  pub feed_name: String,
+  pub feed_url: String,
+  /// The URL the owning feed was stored under before it moved, see [`Feed::moved_from`].
+  pub feed_moved_from: Option<String>,
   pub title: String,
   pub url: String,
   pub desc: String,
   pub content: String,
   pub read: bool,
+  /// Arrived since its feed was last viewed.
+  pub new: bool,
   pub pub_date: chrono::DateTime<Utc>,
+  /// The discussion page of aggregator feeds such as Hacker News or Lobsters.
+  pub comments: Option<String>,
+  pub comments_count: Option<u32>,
+  /// The reader's own note on the article.
+  pub note: Option<String>,
+  /// The watch keyword the article matched when it arrived.
+  pub alert: Option<String>,
+  /// Categories or tags the feed gave the article. Only set on fetched articles, the stored ones
+  /// keep them in the keyword index instead.
+  pub categories: Vec<String>,
+  /// Who wrote the article, as the feed names them. Like `guid`, only read back from the database
+  /// by [`Database::get_article_info`].
+  pub author: Option<String>,
+  /// The feed's own identifier for the article.
+  pub guid: Option<String>,
+  /// Files attached to the article. Only set on fetched articles, the stored ones are read with
+  /// [`Database::get_enclosures`].
+  pub enclosures: Vec<Enclosure>,
+}
+
+/// Selects the columns read by [`feed_item_from_row`], joined with the owning feed.
+const FEED_ITEM_SELECT: &str =
+  "SELECT feed_items.id, feed_items.feed_id, feeds.name, feeds.url, feed_items.title,
+  feed_items.url, feed_items.desc, feed_items.read, feed_items.pub_date, feed_items.comments,
+  feed_items.comments_count, COALESCE(feed_items.added_at > feeds.last_viewed_at, 0),
+  item_notes.note, item_alerts.keyword, feeds.moved_from
+  FROM feed_items
+  JOIN feeds ON feed_items.feed_id = feeds.id
+  LEFT JOIN item_notes ON item_notes.item_id = feed_items.id
+  LEFT JOIN item_alerts ON item_alerts.item_id = feed_items.id";
+
+fn feed_item_from_row(row: &rusqlite::Row) -> Result<FeedItem> {
+  Ok(FeedItem {
+    id: row.get(0)?,
+    feed_id: row.get(1)?,
+    feed_name: row.get(2)?,
+    feed_url: row.get(3)?,
+    title: row.get(4)?,
+    url: row.get(5)?,
+    desc: row.get(6)?,
+    content: "".to_string(),
+    read: row.get::<_, i32>(7)? != 0,
+    pub_date: get_date(row, 8)?,
+    comments: row.get(9)?,
+    comments_count: row.get(10)?,
+    new: row.get::<_, i32>(11)? != 0,
+    note: row.get(12)?,
+    alert: row.get(13)?,
+    feed_moved_from: row.get(14)?,
+    categories: Vec::new(),
+    author: None,
+    guid: None,
+    enclosures: Vec::new(),
+  })
+}
+
+/// Names `subgroups` the id in `?1` and the ids of all groups nested in it.
+const SUBGROUPS: &str = "WITH RECURSIVE subgroups(id) AS (
+    SELECT ?1 UNION SELECT groups.id FROM groups JOIN subgroups ON groups.parent_id = subgroups.id
+  )";
+
+/// Selects the columns read by [`feed_from_row`], with whether the feed is dead from its health.
+const FEED_SELECT: &str = "SELECT feeds.id, feeds.group_id, feeds.name, feeds.desc, feeds.url,
+    feeds.updated_at, feeds.muted, COALESCE(feed_health.dead, 0), feeds.moved_from
+  FROM feeds
+  LEFT JOIN feed_health ON feed_health.url = feeds.url";
+
+fn feed_from_row(row: &rusqlite::Row) -> Result<Feed> {
+  Ok(Feed {
+    id: row.get(0)?,
+    group_id: row.get(1)?,
+    name: row.get(2)?,
+    desc: row.get(3)?,
+    url: row.get(4)?,
+    updated_at: get_date(row, 5)?,
+    muted: row.get::<_, i32>(6)? != 0,
+    dead: row.get::<_, i32>(7)? != 0,
+    moved_from: row.get(8)?,
+    cadence: None,
+  })
+}
+
+/// Reads a timestamp column, reporting a malformed one as an error rather than panicking.
+fn get_date(row: &rusqlite::Row, idx: usize) -> Result<DateTime<Utc>> {
+  let date: String = row.get(idx)?;
+  date.parse().map_err(|error| {
+    rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(error))
+  })
+}
+
+/// Appends the groups nested in `parent_id` to `sorted`, each followed by its own subgroups.
+/// Groups whose parent is gone, or that are nested in themselves, end up at the top level.
+fn push_nested(groups: &[Group], parent_id: Option<i32>, sorted: &mut Vec<Group>) {
+  let is_root = |group: &Group| {
+    group.parent_id.map_or(true, |parent_id| groups.iter().all(|other| other.id != parent_id))
+  };
+  for group in groups {
+    let child = match parent_id {
+      Some(parent_id) => group.parent_id == Some(parent_id),
+      None => is_root(group),
+    };
+    if child && sorted.iter().all(|sorted| sorted.id != group.id) {
+      sorted.push(group.clone());
+      push_nested(groups, Some(group.id), sorted);
+    }
+  }
+  if parent_id.is_none() {
+    for group in groups {
+      if sorted.iter().all(|sorted| sorted.id != group.id) {
+        sorted.push(group.clone());
+        push_nested(groups, Some(group.id), sorted);
+      }
+    }
+  }
+}
+
+/// How deeply each of `groups` is nested, by id, with top-level groups at 0.
+pub fn group_depths<'g>(groups: impl IntoIterator<Item = &'g Group>) -> HashMap<i32, usize> {
+  let parents: HashMap<i32, Option<i32>> =
+    groups.into_iter().map(|group| (group.id, group.parent_id)).collect();
+  parents
+    .keys()
+    .map(|&id| {
+      let mut depth = 0;
+      let mut parent_id = parents[&id];
+      // Bounded by the number of groups in case the nesting loops.
+      while let Some(id) = parent_id.filter(|_| depth < parents.len()) {
+        depth += 1;
+        parent_id = parents.get(&id).copied().flatten();
+      }
+      (id, depth)
+    })
+    .collect()
+}
+
+/// The title and text of a feed item at some point in time.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ItemRevision {
+  pub item_id: i32,
+  pub title: String,
+  pub desc: String,
+  pub content: String,
+  pub saved_at: chrono::DateTime<Utc>,
+}
+
+impl ItemRevision {
+  /// The text compared between revisions, the full content when the feed provides it.
+  pub fn body(&self) -> &str {
+    if self.content.is_empty() {
+      &self.desc
+    } else {
+      &self.content
+    }
+  }
+}
+
+/// What saving a fetched item did to the stored one.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ItemChange {
+  Inserted,
+  Updated,
+  Unchanged,
+}
+
+/// How many items of a fetched feed were new and how many changed since the last refresh.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SaveSummary {
+  pub inserted: usize,
+  pub updated: usize,
+  /// The new items matching a watch keyword.
+  pub alerts: Vec<FeedItem>,
+}
+
+/// Where the article list of a feed was left, so it can be restored when the feed is reopened.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FeedPosition {
+  pub item_id: i32,
+  pub list_offset: usize,
+}
+
+/// What the database takes up on disk and where it goes.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StorageStats {
+  /// Size of the database file, including pages freed but not yet given back.
+  pub file_size: u64,
+  /// Bytes in pages freed by deletions, which a vacuum gives back.
+  pub free_size: u64,
+  pub items: u64,
+  pub revisions: u64,
+  /// Every feed, those storing the most first.
+  pub feeds: Vec<FeedStorage>,
+  /// The items storing the most, largest first.
+  pub largest: Vec<ItemStorage>,
+}
+
+/// How much a feed's items store.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FeedStorage {
+  pub name: String,
+  pub items: u64,
+  /// Bytes of titles, descriptions and content.
+  pub bytes: u64,
+}
+
+/// How much a single item stores.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ItemStorage {
+  pub title: String,
+  pub feed_name: String,
+  pub url: String,
+  pub bytes: u64,
+}
+
+/// Everything known about a feed, for its info popup.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FeedInfo {
+  pub feed: Feed,
+  /// The website the feed belongs to, if it names one.
+  pub site_url: Option<String>,
+  pub items: u64,
+  pub unread: u64,
+  pub health: Option<FeedHealth>,
+}
+
+/// How many of a feed's newest articles its posting interval is averaged over.
+const CADENCE_SAMPLE: i64 = 20;
+
+/// Fewest articles a feed needs before it can be called quiet, so one that has only posted twice
+/// is not judged by the gap between them.
+const QUIET_MIN_SAMPLE: u32 = 5;
+
+/// How many of its usual intervals a feed has to go without posting to count as quiet.
+const QUIET_FACTOR: i64 = 4;
+
+/// How often a feed posts, going by the publication dates of its newest stored articles.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Cadence {
+  /// Average seconds between the articles.
+  pub interval_secs: i64,
+  /// When the newest article was published.
+  pub latest: DateTime<Utc>,
+  /// How many articles the average was taken over.
+  pub samples: u32,
+}
+
+impl Cadence {
+  /// The rate in words, e.g. `~3/week`.
+  pub fn rate(&self) -> String {
+    const DAY: f64 = 86_400.0;
+    if self.interval_secs <= 0 {
+      return "all at once".to_string();
+    }
+    let per_day = DAY / self.interval_secs as f64;
+    if per_day >= 1.0 {
+      format!("~{}/day", per_day.round())
+    } else if per_day * 7.0 >= 1.0 {
+      format!("~{}/week", (per_day * 7.0).round())
+    } else if per_day * 30.0 >= 1.0 {
+      format!("~{}/month", (per_day * 30.0).round())
+    } else {
+      "less than monthly".to_string()
+    }
+  }
+
+  /// Whether the feed has gone silent far beyond its usual cadence: several of its intervals, and
+  /// at least two days, without a new article.
+  pub fn is_quiet(&self, now: DateTime<Utc>) -> bool {
+    let silence = (now - self.latest).num_seconds();
+    self.samples >= QUIET_MIN_SAMPLE
+      && silence > self.interval_secs.saturating_mul(QUIET_FACTOR).max(2 * 86_400)
+  }
+}
+
+/// Reading statistics of a single feed.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FeedStats {
+  pub feed_id: i32,
+  pub name: String,
+  pub total: u64,
+  pub read: u64,
+  /// Articles published per week, oldest week first and the current week last.
+  pub weekly: Vec<u64>,
+}
+
+/// Group that feeds taken out of the config are moved to when they are archived.
+pub const ARCHIVE_GROUP: &str = "Archived";
+
+/// Tables keeping something about an article by its `item_id`.
+const ITEM_TABLES: [&str; 8] = [
+  "item_revisions",
+  "history",
+  "item_notes",
+  "highlights",
+  "item_keywords",
+  "item_enclosures",
+  "item_summaries",
+  "item_alerts",
+];
+
+/// What a round of upkeep did.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Maintenance {
+  /// Articles deleted because their feed was gone.
+  pub orphans: usize,
+  /// Bytes handed back to the file system.
+  pub freed: u64,
+}
+
+/// How long a statement waits for another connection's write lock before giving up.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How refreshing a feed has been going, kept by URL so feeds that never loaded show up too.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FeedHealth {
+  pub url: String,
+  /// Set when a feed stored under `url` moved there from another URL.
+  pub moved_from: Option<String>,
+  pub last_attempt: chrono::DateTime<Utc>,
+  pub last_success: Option<chrono::DateTime<Utc>>,
+  pub last_error: Option<String>,
+  /// What kind of failure `last_error` was.
+  pub error_kind: Option<FailureKind>,
+  /// Failed refreshes since the last successful one.
+  pub failures: u32,
+  /// How the text of the last article opened from the feed was found.
+  pub extraction: Option<Extraction>,
+  /// Whether the feed failed so often it is no longer refreshed.
+  pub dead: bool,
+}
+
+/// Lines of an article saved from the reader.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Highlight {
+  pub id: i32,
+  pub text: String,
+  pub created_at: chrono::DateTime<Utc>,
+  /// Title, link and feed of the article the lines were taken from.
+  pub title: String,
+  pub url: String,
+  pub feed_name: String,
+}
+
+/// A write queued by the UI and applied by [`Database::spawn_writer`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DbWrite {
+  MarkRead(Vec<i32>),
+}
+
+/// What keeps the database from opening.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbProblem {
+  /// The file is damaged or is not a database at all.
+  Corrupt(String),
+  /// Another process kept the database locked for longer than the busy timeout.
+  Locked,
+}
+
+impl DbProblem {
+  /// The problem behind `error`, if it is one that trying again would not fix by itself.
+  pub fn of(error: &rusqlite::Error) -> Option<Self> {
+    match error.sqlite_error_code()? {
+      ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => {
+        Some(DbProblem::Corrupt(error.to_string()))
+      },
+      ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => Some(DbProblem::Locked),
+      _ => None,
+    }
+  }
+}
+
+/// The database file in `data_dir`.
+pub fn db_path(data_dir: &Path) -> PathBuf {
+  data_dir.join("nuuslees.db")
+}
+
+/// Moves the database in `data_dir` aside, along with its write-ahead log, so the next start
+/// creates a new one. Returns where it went, or `None` if there was none.
+pub fn backup(data_dir: &Path) -> std::io::Result<Option<PathBuf>> {
+  let path = db_path(data_dir);
+  if !path.exists() {
+    return Ok(None);
+  }
+  let suffix = Local::now().format("%Y%m%d-%H%M%S");
+  let backup = data_dir.join(format!("nuuslees.db.{suffix}.bak"));
+  for extension in ["-wal", "-shm"] {
+    let file = data_dir.join(format!("nuuslees.db{extension}"));
+    if file.exists() {
+      std::fs::rename(file, data_dir.join(format!("nuuslees.db.{suffix}.bak{extension}")))?;
+    }
+  }
+  std::fs::rename(path, &backup)?;
+  Ok(Some(backup))
 }
 
 pub struct Database {
@@ -59,16 +576,61 @@ pub struct Database {
 
 impl Database {
   pub async fn new(data_dir: &str) -> Result<Self> {
-    let db_path = format!("{data_dir}/nuuslees.db");
-    let conn = Connection::open(db_path)?;
+    let conn = Connection::open(db_path(Path::new(data_dir)))?;
+    // WAL lets readers carry on while a refresh is writing, and the busy timeout makes a second
+    // writer wait its turn instead of failing with `SQLITE_BUSY`.
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
     Ok(Self { conn, config: None })
   }
 
+  /// A migrated database that lives only as long as the connection, for tests.
+  #[cfg(test)]
+  pub async fn in_memory() -> Result<Self> {
+    let db = Self { conn: Connection::open_in_memory()?, config: None };
+    db.init().await?;
+    Ok(db)
+  }
+
+  /// Opens and migrates the database, which reads every table, returning what keeps it from
+  /// being used if anything does. Other errors are returned as they are.
+  pub async fn check(data_dir: &str) -> Result<Option<DbProblem>> {
+    let opened = match Database::new(data_dir).await {
+      Ok(db) => db.init().await,
+      Err(error) => Err(error),
+    };
+    match opened {
+      Ok(()) => Ok(None),
+      Err(error) => DbProblem::of(&error).map(Some).ok_or(error),
+    }
+  }
+
   pub fn set_config(&mut self, config: Config) {
     self.config = Some(config);
   }
 
+  /// Moves this connection to a blocking thread that applies queued writes in order, so the
+  /// caller never waits on SQLite. `on_done` is called with the outcome of every write.
+  pub fn spawn_writer<F>(self, on_done: F) -> UnboundedSender<DbWrite>
+  where
+    F: Fn(DbWrite, Result<(), DbError>) + Send + 'static,
+  {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::task::spawn_blocking(move || {
+      while let Some(write) = rx.blocking_recv() {
+        let result = match &write {
+          DbWrite::MarkRead(item_ids) => self.mark_read(item_ids),
+        };
+        on_done(write, result);
+      }
+    });
+    tx
+  }
+
   pub async fn init(&self) -> Result<()> {
+    // Lets upkeep hand freed pages back bit by bit. It only takes effect on a new database, or
+    // an old one after a full vacuum.
+    self.conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL")?;
     self.conn.execute(
       "CREATE TABLE IF NOT EXISTS groups (
         id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -86,6 +648,7 @@ impl Database {
         desc TEXT,
         url TEXT NOT NULL,
         updated_at TEXT NOT NULL,
+        muted INTEGER NOT NULL DEFAULT 0,
         FOREIGN KEY(group_id) REFERENCES groups(id),
         UNIQUE(url)
       )",
@@ -101,157 +664,558 @@ impl Database {
         content TEXT,
         read INTEGER NOT NULL,
         pub_date TEXT NOT NULL,
+        comments TEXT,
+        comments_count INTEGER,
         FOREIGN KEY(feed_id) REFERENCES feeds(id),
         UNIQUE(url)
       )",
       [],
     )?;
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS feed_positions (
+        feed_id INTEGER PRIMARY KEY,
+        item_id INTEGER NOT NULL,
+        list_offset INTEGER NOT NULL,
+        FOREIGN KEY(feed_id) REFERENCES feeds(id)
+      )",
+      [],
+    )?;
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS feed_health (
+        url TEXT PRIMARY KEY,
+        last_attempt TEXT NOT NULL,
+        last_success TEXT,
+        last_error TEXT,
+        failures INTEGER NOT NULL DEFAULT 0
+      )",
+      [],
+    )?;
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS item_revisions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        item_id INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        desc TEXT,
+        content TEXT,
+        saved_at TEXT NOT NULL,
+        FOREIGN KEY(item_id) REFERENCES feed_items(id)
+      )",
+      [],
+    )?;
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS subscriptions (
+        link TEXT PRIMARY KEY,
+        group_name TEXT NOT NULL,
+        name TEXT
+      )",
+      [],
+    )?;
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS history (
+        item_id INTEGER PRIMARY KEY,
+        opened_at TEXT NOT NULL,
+        FOREIGN KEY(item_id) REFERENCES feed_items(id)
+      )",
+      [],
+    )?;
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS item_notes (
+        item_id INTEGER PRIMARY KEY,
+        note TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        FOREIGN KEY(item_id) REFERENCES feed_items(id)
+      )",
+      [],
+    )?;
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS highlights (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        item_id INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        FOREIGN KEY(item_id) REFERENCES feed_items(id)
+      )",
+      [],
+    )?;
+    // Categories and title words of each article, for finding related ones.
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS item_keywords (
+        item_id INTEGER NOT NULL,
+        keyword TEXT NOT NULL,
+        PRIMARY KEY (item_id, keyword),
+        FOREIGN KEY(item_id) REFERENCES feed_items(id)
+      )",
+      [],
+    )?;
+    self
+      .conn
+      .execute("CREATE INDEX IF NOT EXISTS item_keywords_keyword ON item_keywords (keyword)", [])?;
+    // Articles that matched a watch keyword when they arrived.
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS item_alerts (
+        item_id INTEGER PRIMARY KEY,
+        keyword TEXT NOT NULL,
+        alerted_at TEXT NOT NULL,
+        FOREIGN KEY(item_id) REFERENCES feed_items(id)
+      )",
+      [],
+    )?;
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS item_summaries (
+        item_id INTEGER PRIMARY KEY,
+        summary TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        FOREIGN KEY(item_id) REFERENCES feed_items(id)
+      )",
+      [],
+    )?;
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS item_enclosures (
+        item_id INTEGER NOT NULL,
+        url TEXT NOT NULL,
+        mime_type TEXT NOT NULL,
+        length INTEGER,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (item_id, url),
+        FOREIGN KEY(item_id) REFERENCES feed_items(id)
+      )",
+      [],
+    )?;
+    // Values the app keeps for itself, such as when upkeep last ran.
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+      )",
+      [],
+    )?;
+    // Articles imported as read before any feed had fetched them.
+    self.conn.execute(
+      "CREATE TABLE IF NOT EXISTS imported_read (
+        url TEXT PRIMARY KEY
+      )",
+      [],
+    )?;
+    self.add_column_if_missing("feeds", "muted", "INTEGER NOT NULL DEFAULT 0")?;
+    // The `mute` flag of the config as last seen, to tell a change to it from a runtime toggle.
+    self.add_column_if_missing("feeds", "config_muted", "INTEGER NOT NULL DEFAULT 0")?;
+    self.add_column_if_missing("feed_items", "comments", "TEXT")?;
+    self.add_column_if_missing("feed_items", "comments_count", "INTEGER")?;
+    self.add_column_if_missing("feed_health", "extraction", "TEXT")?;
+    self.add_column_if_missing("groups", "parent_id", "INTEGER REFERENCES groups(id)")?;
+    self.add_column_if_missing("feed_health", "dead", "INTEGER NOT NULL DEFAULT 0")?;
+    self.add_column_if_missing("feed_items", "added_at", "TEXT")?;
+    self.add_column_if_missing("feeds", "last_viewed_at", "TEXT")?;
+    self.add_column_if_missing("feed_health", "error_kind", "TEXT")?;
+    self.add_column_if_missing("feeds", "moved_from", "TEXT")?;
+    self.add_column_if_missing("feed_items", "archived", "INTEGER NOT NULL DEFAULT 0")?;
+    self.add_column_if_missing("feeds", "site_url", "TEXT")?;
+    self.add_column_if_missing("feed_items", "author", "TEXT")?;
+    self.add_column_if_missing("feed_items", "guid", "TEXT")?;
+    self.index_unindexed_titles()?;
+
+    Ok(())
+  }
+
+  /// Indexes the titles of articles stored before the keyword index existed. Their categories
+  /// were not kept, so those are picked up as the articles are fetched again.
+  fn index_unindexed_titles(&self) -> Result<()> {
+    let mut stmt = self.conn.prepare(
+      "SELECT id, title FROM feed_items
+      WHERE NOT EXISTS (SELECT 1 FROM item_keywords WHERE item_id = feed_items.id)",
+    )?;
+    let items = stmt
+      .query_map([], |row| Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?)))?
+      .collect::<Result<Vec<_>>>()?;
+    if items.is_empty() {
+      return Ok(());
+    }
+    let transaction = self.conn.unchecked_transaction()?;
+    for (id, title) in items {
+      self.index_keywords(id, &title, &[])?;
+    }
+    transaction.commit()
+  }
 
+  /// Replaces the keywords an article is indexed under.
+  fn index_keywords(&self, item_id: i32, title: &str, categories: &[String]) -> Result<()> {
+    self.conn.prepare_cached("DELETE FROM item_keywords WHERE item_id = ?1")?.execute([item_id])?;
+    let mut stmt = self
+      .conn
+      .prepare_cached("INSERT OR IGNORE INTO item_keywords (item_id, keyword) VALUES (?1, ?2)")?;
+    for keyword in keywords::keywords(title, categories) {
+      stmt.execute(rusqlite::params![item_id, keyword])?;
+    }
     Ok(())
   }
 
-  pub async fn refresh_feeds(&self) -> Result<(), DbError> {
-    let client = Client::new();
+  /// Replaces the files attached to an article.
+  fn save_enclosures(&self, item_id: i32, enclosures: &[Enclosure]) -> Result<()> {
+    self
+      .conn
+      .prepare_cached("DELETE FROM item_enclosures WHERE item_id = ?1")?
+      .execute([item_id])?;
+    let mut stmt = self.conn.prepare_cached(
+      "INSERT OR IGNORE INTO item_enclosures (item_id, url, mime_type, length, position)
+      VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for (position, enclosure) in enclosures.iter().enumerate() {
+      stmt.execute(rusqlite::params![
+        item_id,
+        enclosure.url,
+        enclosure.mime_type,
+        enclosure.length.map(|length| length as i64),
+        position as i64
+      ])?;
+    }
+    Ok(())
+  }
 
-    if let Some(config) = &self.config {
-      for group in &config.groups {
-        let new_group = Group { id: 0, name: group.name.clone(), desc: group.desc.clone() };
-        let group_id = match self.upsert_group(new_group) {
-          Ok(id) => id,
-          Err(error) => {
-            log::error!("Failed to upsert group: {:?}", error);
-            continue;
-          },
-        };
+  /// Adds a column introduced after the table was first created by an older version.
+  fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+      if row.get::<_, String>(1)? == column {
+        return Ok(());
+      }
+    }
+    self.conn.execute(&format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"), [])?;
+    Ok(())
+  }
+
+  /// Stores a fetched feed and its items under the given group, in a single transaction.
+  pub fn save_fetched_feed(
+    &self,
+    group_id: i32,
+    fetched: FetchedFeed,
+  ) -> Result<SaveSummary, DbError> {
+    let transaction = self.conn.unchecked_transaction()?;
+    let url = match fetched.moved_to {
+      Some(moved_to) => {
+        self.move_feed(&fetched.url, &moved_to)?;
+        moved_to
+      },
+      None => fetched.url,
+    };
+    let new_feed = Feed {
+      id: 0, // Placeholder
+      group_id,
+      name: fetched.name,
+      desc: fetched.desc,
+      url,
+      moved_from: None,
+      updated_at: Utc::now(),
+      muted: fetched.muted,
+      dead: false,
+      cadence: None,
+    };
+    let feed_name = new_feed.name.clone();
+    let (feed_id, muted) = self.upsert_feed(new_feed)?;
+    self.conn.execute("UPDATE feeds SET site_url = ?2 WHERE id = ?1", rusqlite::params![
+      feed_id,
+      fetched.site_url
+    ])?;
 
-        for feed in &group.feeds {
-          let content = client.get(&feed.link).send().await?.text().await?;
-          let channel = rss::Channel::read_from(content.as_bytes())?;
-
-          let new_feed = Feed {
-            id: 0, // Placeholder
-            group_id,
-            name: feed.name.clone().unwrap_or(channel.title().to_string()),
-            desc: feed.desc.clone().unwrap_or(channel.description().to_string()),
-            url: feed.link.clone(),
-            updated_at: Utc::now(),
-          };
-
-          let feed_id = match self.upsert_feed(new_feed) {
-            Ok(id) => id,
-            Err(error) => {
-              log::error!("Failed to upsert feed: {:?}", error);
-              continue;
-            },
-          };
-
-          for item in channel.items() {
-            let content = "".to_string();
-
-            let feed_item = FeedItem {
-              id: 0,
-              feed_id,
-              title: item.title().unwrap_or_default().to_string(),
-              url: item.link().unwrap_or_default().to_string(),
-              desc: item.description().unwrap_or_default().to_string(),
-              content,
-              read: false,
-              pub_date: item
-                .pub_date()
-                .unwrap_or_default()
-                .parse::<chrono::DateTime<Utc>>()
-                .unwrap_or(Utc::now()),
-            };
-
-            match self.upsert_feed_item(feed_item) {
-              Ok(_) => (),
-              Err(error) => log::error!("Failed to upsert feed item: {:?}", error),
-            }
+    let keywords = self.config.as_ref().map_or(&[][..], |config| &config.alerts.keywords[..]);
+    let mut summary = SaveSummary::default();
+    for mut feed_item in fetched.items {
+      feed_item.feed_id = feed_id;
+      let alert = alerts::matching(keywords, &feed_item).map(|keyword| {
+        let mut alert = feed_item.clone();
+        alert.feed_name = feed_name.clone();
+        alert.alert = Some(keyword.to_string());
+        alert
+      });
+      match self.upsert_feed_item(feed_item) {
+        Ok(ItemChange::Inserted) => {
+          summary.inserted += 1;
+          if let Some(alert) = alert {
+            self.record_alert(&alert)?;
+            summary.alerts.push(alert);
           }
-        }
+        },
+        Ok(ItemChange::Updated) => summary.updated += 1,
+        Ok(ItemChange::Unchanged) => (),
+        Err(error) => log::error!("Failed to upsert feed item: {:?}", error),
       }
-    } else {
-      log::error!("Failed to get config");
     }
+    if let Some(cutoff) = fetched.mark_read_after_days.and_then(utils::days_ago) {
+      self.mark_read_before(feed_id, cutoff)?;
+    }
+    transaction.commit()?;
+    // Nothing a muted feed brings in is worth a notice, but for what is watched for.
+    if muted {
+      return Ok(SaveSummary { alerts: summary.alerts, ..SaveSummary::default() });
+    }
+    Ok(summary)
+  }
+
+  /// Points a stored feed at its new URL, keeping its items. Nothing changes if a feed with the
+  /// new URL is already stored.
+  pub fn move_feed(&self, old_url: &str, new_url: &str) -> Result<(), DbError> {
+    // The config still has the old URL, which keeps the feed from counting as removed from it.
+    self.conn.execute("UPDATE OR IGNORE feeds SET url = ?2, moved_from = ?1 WHERE url = ?1", [
+      old_url, new_url,
+    ])?;
     Ok(())
   }
 
   pub fn upsert_group(&self, group: Group) -> Result<i32, DbError> {
-    self.conn.execute(
-      "INSERT INTO groups (name, desc) VALUES (?1, ?2)
-            ON CONFLICT(name) DO UPDATE SET desc=excluded.desc",
-      rusqlite::params![group.name, group.desc],
+    // `last_insert_rowid` is stale when the conflict branch runs, so the id is returned instead.
+    let id = self.conn.query_row(
+      "INSERT INTO groups (name, desc, parent_id) VALUES (?1, ?2, ?3)
+            ON CONFLICT(name) DO UPDATE SET desc=excluded.desc, parent_id=excluded.parent_id
+            RETURNING id",
+      rusqlite::params![group.name, group.desc, group.parent_id],
+      |row| row.get(0),
     )?;
-    Ok(self.conn.last_insert_rowid() as i32)
+    Ok(id)
   }
 
-  pub fn upsert_feed(&self, feed: Feed) -> Result<i32, DbError> {
-    self.conn.execute(
-      "INSERT INTO feeds (group_id, name, desc, url, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)
-            ON CONFLICT(url) DO UPDATE SET name=excluded.name, desc=excluded.desc, updated_at=excluded.updated_at",
-      rusqlite::params![feed.group_id, feed.name, feed.desc, feed.url, feed.updated_at.to_rfc3339()],
+  /// Returns the feed's id and whether it is muted. A feed muted or unmuted at runtime keeps its
+  /// stored flag until `mute` is changed in the config, which then wins.
+  pub fn upsert_feed(&self, feed: Feed) -> Result<(i32, bool), DbError> {
+    let (id, muted) = self.conn.query_row(
+      "INSERT INTO feeds (group_id, name, desc, url, updated_at, muted, config_muted) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+            ON CONFLICT(url) DO UPDATE SET group_id=excluded.group_id, name=excluded.name, desc=excluded.desc, updated_at=excluded.updated_at,
+              muted=CASE WHEN config_muted = excluded.config_muted THEN muted ELSE excluded.muted END, config_muted=excluded.config_muted
+            RETURNING id, muted",
+      rusqlite::params![
+        feed.group_id,
+        feed.name,
+        feed.desc,
+        feed.url,
+        feed.updated_at.to_rfc3339(),
+        feed.muted as i32
+      ],
+      |row| Ok((row.get(0)?, row.get::<_, i32>(1)? != 0)),
     )?;
-    Ok(self.conn.last_insert_rowid() as i32)
+    Ok((id, muted))
   }
 
-  pub fn upsert_feed_item(&self, feed_item: FeedItem) -> Result<i32, DbError> {
-    self.conn.execute(
-      "INSERT INTO feed_items (feed_id, title, url, desc, content, read, pub_date) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-            ON CONFLICT(url) DO UPDATE SET title=excluded.title, desc=excluded.desc, content=excluded.content, read=excluded.read, pub_date=excluded.pub_date",
-      rusqlite::params![
-                feed_item.feed_id,
-                feed_item.title,
-                feed_item.url,
-                feed_item.desc,
-                feed_item.content,
-                feed_item.read as i32,
-                feed_item.pub_date.to_rfc3339()
-            ],
-    )?;
-    Ok(self.conn.last_insert_rowid() as i32)
+  pub fn set_feed_muted(&self, feed_id: i32, muted: bool) -> Result<(), DbError> {
+    self.conn.execute("UPDATE feeds SET muted = ?2 WHERE id = ?1", rusqlite::params![
+      feed_id,
+      muted as i32
+    ])?;
+    Ok(())
   }
 
-  pub fn get_groups(&self) -> Result<Vec<Group>, DbError> {
-    let mut stmt = self.conn.prepare("SELECT * FROM groups")?;
-    let group_iter = stmt
-      .query_map([], |row| Ok(Group { id: row.get(0)?, name: row.get(1)?, desc: row.get(2)? }))?;
+  /// Deletes a feed with its articles and everything kept about them, all of it or none.
+  pub fn delete_feed(&self, feed_id: i32) -> Result<(), DbError> {
+    let tx = self.conn.unchecked_transaction()?;
+    let url: String =
+      tx.query_row("SELECT url FROM feeds WHERE id = ?1", [feed_id], |row| row.get(0))?;
+    let item_ids = tx
+      .prepare("SELECT id FROM feed_items WHERE feed_id = ?1")?
+      .query_map([feed_id], |row| row.get(0))?
+      .collect::<Result<Vec<i32>>>()?;
+    self.delete_items(&item_ids)?;
+    tx.execute("DELETE FROM feed_positions WHERE feed_id = ?1", [feed_id])?;
+    tx.execute("DELETE FROM feed_health WHERE url = ?1", [&url])?;
+    tx.execute("DELETE FROM subscriptions WHERE link = ?1", [&url])?;
+    tx.execute("DELETE FROM feeds WHERE id = ?1", [feed_id])?;
+    tx.commit()?;
+    Ok(())
+  }
 
-    let all_group = Group {
-      id: -1,
-      name: "All Feeds".to_string(),
-      desc: "See all feeds in all groups".to_string(),
+  /// Moves a feed to the Archived group, leaving its articles as they were.
+  fn archive_feed(&self, feed_id: i32) -> Result<(), DbError> {
+    let group = Group {
+      id: 0,
+      name: ARCHIVE_GROUP.to_string(),
+      desc: "Feeds no longer in the config".to_string(),
+      parent_id: None,
     };
-    let mut groups = vec![all_group];
-    for group in group_iter {
-      groups.push(group?);
-    }
-    Ok(groups)
+    let tx = self.conn.unchecked_transaction()?;
+    let group_id = self.upsert_group(group)?;
+    tx.execute("UPDATE feeds SET group_id = ?2 WHERE id = ?1", [feed_id, group_id])?;
+    tx.commit()?;
+    Ok(())
   }
 
-  pub fn get_group_id(&self, group_name: &str) -> Result<i32, DbError> {
-    let mut stmt = self.conn.prepare("SELECT id FROM groups WHERE name = ?1")?;
-    let mut rows = stmt.query([group_name])?;
-    if let Some(row) = rows.next()? {
-      Ok(row.get(0)?)
-    } else {
-      Ok(-1)
+  /// Archives or deletes the stored feeds whose URL is not in `feed_urls`, as `removed` says,
+  /// then deletes the groups left empty whose name is not in `group_names`. Returns the names of
+  /// the feeds archived or deleted.
+  pub fn remove_unconfigured(
+    &self,
+    feed_urls: &HashSet<String>,
+    group_names: &HashSet<String>,
+    removed: RemovedFeeds,
+  ) -> Result<Vec<String>, DbError> {
+    let mut names = Vec::new();
+    if removed != RemovedFeeds::Keep {
+      let archive_id = self.get_group_id(ARCHIVE_GROUP)?;
+      let mut stmt = self.conn.prepare("SELECT id, name, url, moved_from, group_id FROM feeds")?;
+      let feeds = stmt
+        .query_map([], |row| {
+          Ok((
+            row.get::<_, i32>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, i32>(4)?,
+          ))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+      for (feed_id, name, url, moved_from, group_id) in feeds {
+        let configured = feed_urls.contains(&url)
+          || moved_from.is_some_and(|moved_from| feed_urls.contains(&moved_from));
+        if configured || group_id == archive_id {
+          continue;
+        }
+        match removed {
+          RemovedFeeds::Delete => self.delete_feed(feed_id)?,
+          _ => self.archive_feed(feed_id)?,
+        }
+        log::info!("Removed {name} ({url}), it is no longer in the config");
+        names.push(name);
+      }
+    }
+    // Emptying a nested group can leave its parent empty in turn.
+    loop {
+      let mut stmt = self.conn.prepare(
+        "SELECT id, name FROM groups
+        WHERE id NOT IN (SELECT group_id FROM feeds)
+          AND id NOT IN (SELECT parent_id FROM groups WHERE parent_id IS NOT NULL)",
+      )?;
+      let empty = stmt
+        .query_map([], |row| Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>>>()?;
+      let unconfigured: Vec<i32> = empty
+        .into_iter()
+        .filter(|(_, name)| !group_names.contains(name))
+        .map(|(id, _)| id)
+        .collect();
+      if unconfigured.is_empty() {
+        break;
+      }
+      for group_id in unconfigured {
+        self.conn.execute("DELETE FROM groups WHERE id = ?1", [group_id])?;
+      }
     }
+    Ok(names)
   }
 
-  pub fn get_feeds(&self) -> Result<Vec<Feed>, DbError> {
-    let mut stmt =
-      self.conn.prepare("SELECT id, group_id, name, desc, url, updated_at FROM feeds")?;
-    let feed_iter = stmt.query_map([], |row| {
-      Ok(Feed {
-        id: row.get(0)?,
-        group_id: row.get(1)?,
-        name: row.get(2)?,
-        desc: row.get(3)?,
-        url: row.get(4)?,
-        updated_at: row.get::<_, String>(5)?.parse::<chrono::DateTime<Utc>>().unwrap(),
-      })
+  /// Deletes articles along with their revisions, history, notes, highlights, keywords,
+  /// attachments, summaries and alerts.
+  fn delete_items(&self, item_ids: &[i32]) -> Result<(), DbError> {
+    for table in ITEM_TABLES {
+      let mut stmt = self.conn.prepare(&format!("DELETE FROM {table} WHERE item_id = ?1"))?;
+      for item_id in item_ids {
+        stmt.execute([item_id])?;
+      }
+    }
+    let mut stmt = self.conn.prepare("DELETE FROM feed_items WHERE id = ?1")?;
+    for item_id in item_ids {
+      stmt.execute([item_id])?;
+    }
+    Ok(())
+  }
+
+  /// Stores an article, or updates the stored one with the same URL. An updated article keeps
+  /// the earlier of its two dates, as feeds without dates give each fetch the current time.
+  pub fn upsert_feed_item(&self, mut feed_item: FeedItem) -> Result<ItemChange, DbError> {
+    let max_content_size = self.config.as_ref().map_or(0, |config| config.fetch.max_content_size);
+    if html::truncate(&mut feed_item.content, max_content_size) {
+      log::info!("Truncated the content of {}", feed_item.url);
+    }
+    let change = self.save_revision_if_changed(&feed_item)?;
+    let mut stmt = self.conn.prepare_cached(
+      "INSERT INTO feed_items (feed_id, title, url, desc, content, read, pub_date, comments, comments_count, added_at, author, guid)
+            VALUES (?1, ?2, ?3, ?4, ?5, MAX(?6, EXISTS(SELECT 1 FROM imported_read WHERE url = ?3)), ?7, ?8, ?9, ?10, ?11, ?12)
+            ON CONFLICT(url) DO UPDATE SET title=excluded.title, desc=excluded.desc, content=excluded.content, pub_date=MIN(pub_date, excluded.pub_date), comments=excluded.comments, comments_count=excluded.comments_count, author=excluded.author, guid=excluded.guid
+            RETURNING id",
+    )?;
+    let id = stmt.query_row(
+      rusqlite::params![
+        feed_item.feed_id,
+        feed_item.title,
+        feed_item.url,
+        feed_item.desc,
+        feed_item.content,
+        feed_item.read as i32,
+        feed_item.pub_date.to_rfc3339(),
+        feed_item.comments,
+        feed_item.comments_count,
+        Utc::now().to_rfc3339(),
+        feed_item.author,
+        feed_item.guid
+      ],
+      |row| row.get(0),
+    )?;
+    if change != ItemChange::Unchanged || !feed_item.categories.is_empty() {
+      self.index_keywords(id, &feed_item.title, &feed_item.categories)?;
+    }
+    if change != ItemChange::Unchanged || !feed_item.enclosures.is_empty() {
+      self.save_enclosures(id, &feed_item.enclosures)?;
+    }
+    Ok(change)
+  }
+
+  /// Keeps the stored version of an item that is about to be overwritten with edited text.
+  fn save_revision_if_changed(&self, feed_item: &FeedItem) -> Result<ItemChange, DbError> {
+    let mut stmt = self.conn.prepare_cached(
+      "SELECT id, title, COALESCE(desc, ''), COALESCE(content, '') FROM feed_items WHERE url = ?1",
+    )?;
+    let mut rows = stmt.query([&feed_item.url])?;
+    let Some(row) = rows.next()? else {
+      return Ok(ItemChange::Inserted);
+    };
+    let (id, title, desc, content): (i32, String, String, String) =
+      (row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?);
+    if title == feed_item.title && desc == feed_item.desc && content == feed_item.content {
+      return Ok(ItemChange::Unchanged);
+    }
+    self
+      .conn
+      .prepare_cached(
+        "INSERT INTO item_revisions (item_id, title, desc, content, saved_at)
+              VALUES (?1, ?2, ?3, ?4, ?5)",
+      )?
+      .execute(rusqlite::params![id, title, desc, content, Utc::now().to_rfc3339()])?;
+    Ok(ItemChange::Updated)
+  }
+
+  /// Returns All Feeds, the smart views, then the stored groups with each followed by the
+  /// groups nested in it.
+  pub fn get_groups(&self) -> Result<Vec<Group>, DbError> {
+    let mut stmt = self.conn.prepare("SELECT id, name, desc, parent_id FROM groups")?;
+    let group_iter = stmt.query_map([], |row| {
+      Ok(Group { id: row.get(0)?, name: row.get(1)?, desc: row.get(2)?, parent_id: row.get(3)? })
     })?;
 
+    let all_group = Group {
+      id: -1,
+      name: "All Feeds".to_string(),
+      desc: "See all feeds in all groups".to_string(),
+      parent_id: None,
+    };
+    let mut groups = vec![all_group];
+    groups.extend(SmartView::ALL.map(SmartView::group));
+    let mut stored = Vec::new();
+    for group in group_iter {
+      stored.push(group?);
+    }
+    push_nested(&stored, None, &mut groups);
+    Ok(groups)
+  }
+
+  pub fn get_group_id(&self, group_name: &str) -> Result<i32, DbError> {
+    let mut stmt = self.conn.prepare("SELECT id FROM groups WHERE name = ?1")?;
+    let mut rows = stmt.query([group_name])?;
+    if let Some(row) = rows.next()? {
+      Ok(row.get(0)?)
+    } else {
+      Ok(-1)
+    }
+  }
+
+  pub fn get_feeds(&self) -> Result<Vec<Feed>, DbError> {
+    let mut stmt = self.conn.prepare(FEED_SELECT)?;
+    let feed_iter = stmt.query_map([], feed_from_row)?;
+
     let mut feeds = Vec::new();
     for feed in feed_iter {
       feeds.push(feed?);
@@ -260,22 +1224,30 @@ impl Database {
   }
 
   pub fn get_feed_items(&self) -> Result<Vec<FeedItem>, DbError> {
-    let mut stmt = self
-      .conn
-      .prepare("SELECT id, feed_id, title, url, desc, content, read, pub_date FROM feed_items")?;
+    let mut stmt =
+      self.conn.prepare(&format!("{FEED_ITEM_SELECT} WHERE feed_items.archived = 0"))?;
+    let feed_item_iter = stmt.query_map([], feed_item_from_row)?;
 
-    let feed_item_iter = stmt.query_map([], |row| {
-      Ok(FeedItem {
-        id: row.get(0)?,
-        feed_id: row.get(1)?,
-        title: row.get(2)?,
-        url: row.get(3)?,
-        desc: row.get(4)?,
-        content: "".to_string(),
-        read: row.get::<_, i32>(6)? != 0,
-        pub_date: row.get::<_, String>(7)?.parse::<chrono::DateTime<Utc>>().unwrap(),
-      })
-    })?;
+    let mut feed_items = Vec::new();
+    for feed_item in feed_item_iter {
+      feed_items.push(feed_item?);
+    }
+    Ok(feed_items)
+  }
+
+  /// Articles published from `start` up to but not including `end`, newest first.
+  pub fn get_feed_items_between(
+    &self,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+  ) -> Result<Vec<FeedItem>, DbError> {
+    let mut stmt = self.conn.prepare(&format!(
+      "{FEED_ITEM_SELECT} WHERE feed_items.pub_date >= ?1 AND feed_items.pub_date < ?2
+        AND feed_items.archived = 0
+      ORDER BY feed_items.pub_date DESC"
+    ))?;
+    let feed_item_iter = stmt
+      .query_map(rusqlite::params![start.to_rfc3339(), end.to_rfc3339()], feed_item_from_row)?;
 
     let mut feed_items = Vec::new();
     for feed_item in feed_item_iter {
@@ -284,23 +1256,63 @@ impl Database {
     Ok(feed_items)
   }
 
-  pub fn get_feed_items_from_feed(&self, feed_id: i32) -> Result<Vec<FeedItem>, DbError> {
-    let mut stmt = self
+  /// Articles that arrived at `since` or later, the most recently published first.
+  pub fn get_feed_items_added_since(&self, since: DateTime<Utc>) -> Result<Vec<FeedItem>, DbError> {
+    let mut stmt = self.conn.prepare(&format!(
+      "{FEED_ITEM_SELECT} WHERE feed_items.added_at >= ?1 ORDER BY feed_items.pub_date DESC"
+    ))?;
+    let feed_item_iter = stmt.query_map([since.to_rfc3339()], feed_item_from_row)?;
+
+    let mut feed_items = Vec::new();
+    for feed_item in feed_item_iter {
+      feed_items.push(feed_item?);
+    }
+    Ok(feed_items)
+  }
+
+  /// Remembers that an article was opened just now.
+  pub fn record_opened(&self, item_id: i32) -> Result<(), DbError> {
+    self.conn.execute(
+      "INSERT INTO history (item_id, opened_at) VALUES (?1, ?2)
+            ON CONFLICT(item_id) DO UPDATE SET opened_at=excluded.opened_at",
+      rusqlite::params![item_id, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+  }
+
+  /// Attaches `note` to an article, replacing any earlier one. A blank note removes it.
+  pub fn save_note(&self, item_id: i32, note: &str) -> Result<(), DbError> {
+    if note.trim().is_empty() {
+      self.conn.execute("DELETE FROM item_notes WHERE item_id = ?1", [item_id])?;
+    } else {
+      self.conn.execute(
+        "INSERT INTO item_notes (item_id, note, updated_at) VALUES (?1, ?2, ?3)
+              ON CONFLICT(item_id) DO UPDATE SET note=excluded.note, updated_at=excluded.updated_at",
+        rusqlite::params![item_id, note, Utc::now().to_rfc3339()],
+      )?;
+    }
+    Ok(())
+  }
+
+  /// Flags a newly stored article with the watch keyword it matched.
+  fn record_alert(&self, item: &FeedItem) -> Result<(), DbError> {
+    self
       .conn
-      .prepare("SELECT id, feed_id, title, url, desc, content, read, pub_date FROM feed_items WHERE feed_id = ?1")?;
+      .prepare_cached(
+        "INSERT OR IGNORE INTO item_alerts (item_id, keyword, alerted_at)
+            SELECT id, ?2, ?3 FROM feed_items WHERE url = ?1",
+      )?
+      .execute(rusqlite::params![item.url, item.alert, Utc::now().to_rfc3339()])?;
+    Ok(())
+  }
 
-    let feed_item_iter = stmt.query_map([feed_id], |row| {
-      Ok(FeedItem {
-        id: row.get(0)?,
-        feed_id: row.get(1)?,
-        title: row.get(2)?,
-        url: row.get(3)?,
-        desc: row.get(4)?,
-        content: "".to_string(),
-        read: row.get::<_, i32>(6)? != 0,
-        pub_date: row.get::<_, String>(7)?.parse::<chrono::DateTime<Utc>>().unwrap(),
-      })
-    })?;
+  /// Articles that matched a watch keyword, the most recent alert first.
+  pub fn get_alerts(&self) -> Result<Vec<FeedItem>, DbError> {
+    let mut stmt = self.conn.prepare(&format!(
+      "{FEED_ITEM_SELECT} WHERE item_alerts.item_id IS NOT NULL AND feed_items.archived = 0
+      ORDER BY item_alerts.alerted_at DESC"
+    ))?;
+    let feed_item_iter = stmt.query_map([], feed_item_from_row)?;
 
     let mut feed_items = Vec::new();
     for feed_item in feed_item_iter {
@@ -309,27 +1321,147 @@ impl Database {
     Ok(feed_items)
   }
 
-  pub fn get_feed_items_from_group(&self, group_id: i32) -> Result<Vec<FeedItem>, DbError> {
-    let mut stmt = self.conn.prepare(
-      "SELECT feed_items.*
-           FROM feed_items
-           JOIN feeds ON feed_items.feed_id = feeds.id
-           WHERE feeds.group_id = ?",
+  /// Articles archived unread, the most recently published first.
+  pub fn get_archived(&self) -> Result<Vec<FeedItem>, DbError> {
+    let mut stmt = self.conn.prepare(&format!(
+      "{FEED_ITEM_SELECT} WHERE feed_items.archived = 1 ORDER BY feed_items.pub_date DESC"
+    ))?;
+    let feed_item_iter = stmt.query_map([], feed_item_from_row)?;
+
+    let mut feed_items = Vec::new();
+    for feed_item in feed_item_iter {
+      feed_items.push(feed_item?);
+    }
+    Ok(feed_items)
+  }
+
+  pub fn save_summary(&self, item_id: i32, summary: &str) -> Result<(), DbError> {
+    self.conn.execute(
+      "INSERT INTO item_summaries (item_id, summary, created_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT(item_id) DO UPDATE SET summary=excluded.summary, created_at=excluded.created_at",
+      rusqlite::params![item_id, summary, Utc::now().to_rfc3339()],
     )?;
+    Ok(())
+  }
+
+  pub fn get_summary(&self, item_id: i32) -> Result<Option<String>, DbError> {
+    let mut stmt = self.conn.prepare("SELECT summary FROM item_summaries WHERE item_id = ?1")?;
+    let mut rows = stmt.query([item_id])?;
+    match rows.next()? {
+      Some(row) => Ok(Some(row.get(0)?)),
+      None => Ok(None),
+    }
+  }
 
-    let feed_item_iter = stmt.query_map([group_id], |row| {
-      Ok(FeedItem {
+  pub fn save_highlight(&self, item_id: i32, text: &str) -> Result<(), DbError> {
+    self.conn.execute(
+      "INSERT INTO highlights (item_id, text, created_at) VALUES (?1, ?2, ?3)",
+      rusqlite::params![item_id, text, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+  }
+
+  /// Every highlight with the article it was taken from, the most recent article first and the
+  /// highlights of an article in the order they were saved.
+  pub fn get_highlights(&self) -> Result<Vec<Highlight>, DbError> {
+    let mut stmt = self.conn.prepare(
+      "SELECT highlights.id, highlights.text, highlights.created_at, feed_items.title,
+              feed_items.url, feeds.name
+      FROM highlights
+      JOIN feed_items ON feed_items.id = highlights.item_id
+      JOIN feeds ON feeds.id = feed_items.feed_id
+      ORDER BY
+        (SELECT MAX(created_at) FROM highlights AS latest WHERE latest.item_id = highlights.item_id)
+          DESC,
+        highlights.item_id,
+        highlights.id",
+    )?;
+    let highlight_iter = stmt.query_map([], |row| {
+      Ok(Highlight {
         id: row.get(0)?,
-        feed_id: row.get(1)?,
-        title: row.get(2)?,
-        url: row.get(3)?,
-        desc: row.get(4)?,
-        content: "".to_string(),
-        read: row.get::<_, i32>(6)? != 0,
-        pub_date: row.get::<_, String>(7)?.parse::<chrono::DateTime<Utc>>().unwrap(),
+        text: row.get(1)?,
+        created_at: get_date(row, 2)?,
+        title: row.get(3)?,
+        url: row.get(4)?,
+        feed_name: row.get(5)?,
       })
     })?;
 
+    let mut highlights = Vec::new();
+    for highlight in highlight_iter {
+      highlights.push(highlight?);
+    }
+    Ok(highlights)
+  }
+
+  pub fn delete_highlight(&self, id: i32) -> Result<(), DbError> {
+    self.conn.execute("DELETE FROM highlights WHERE id = ?1", [id])?;
+    Ok(())
+  }
+
+  /// Articles sharing keywords with the given one, those sharing the most first. A shared
+  /// category counts twice as much as a shared title word.
+  pub fn get_related_items(&self, item_id: i32, limit: usize) -> Result<Vec<FeedItem>, DbError> {
+    let mut stmt = self.conn.prepare(&format!(
+      "{FEED_ITEM_SELECT} JOIN (
+        SELECT other.item_id,
+          SUM(CASE WHEN other.keyword LIKE '#%' THEN 2 ELSE 1 END) AS score
+        FROM item_keywords AS mine
+        JOIN item_keywords AS other ON other.keyword = mine.keyword AND other.item_id != mine.item_id
+        WHERE mine.item_id = ?1
+        GROUP BY other.item_id
+        -- A single title word in common is too often a coincidence.
+        HAVING score >= 2
+      ) AS related ON related.item_id = feed_items.id
+      ORDER BY related.score DESC, feed_items.pub_date DESC
+      LIMIT ?2"
+    ))?;
+    let feed_item_iter =
+      stmt.query_map(rusqlite::params![item_id, limit as i64], feed_item_from_row)?;
+
+    let mut feed_items = Vec::new();
+    for feed_item in feed_item_iter {
+      feed_items.push(feed_item?);
+    }
+    Ok(feed_items)
+  }
+
+  /// Articles that have been opened, the most recently opened first.
+  pub fn get_history(&self) -> Result<Vec<FeedItem>, DbError> {
+    let mut stmt = self.conn.prepare(&format!(
+      "{FEED_ITEM_SELECT} JOIN history ON history.item_id = feed_items.id
+      WHERE feed_items.archived = 0
+      ORDER BY history.opened_at DESC"
+    ))?;
+    let feed_item_iter = stmt.query_map([], feed_item_from_row)?;
+
+    let mut feed_items = Vec::new();
+    for feed_item in feed_item_iter {
+      feed_items.push(feed_item?);
+    }
+    Ok(feed_items)
+  }
+
+  pub fn get_feed_items_from_feed(&self, feed_id: i32) -> Result<Vec<FeedItem>, DbError> {
+    let mut stmt = self.conn.prepare(&format!(
+      "{FEED_ITEM_SELECT} WHERE feed_items.feed_id = ?1 AND feed_items.archived = 0"
+    ))?;
+    let feed_item_iter = stmt.query_map([feed_id], feed_item_from_row)?;
+
+    let mut feed_items = Vec::new();
+    for feed_item in feed_item_iter {
+      feed_items.push(feed_item?);
+    }
+    Ok(feed_items)
+  }
+
+  pub fn get_feed_items_from_group(&self, group_id: i32) -> Result<Vec<FeedItem>, DbError> {
+    let mut stmt = self.conn.prepare(&format!(
+      "{SUBGROUPS} {FEED_ITEM_SELECT} WHERE feeds.group_id IN (SELECT id FROM subgroups)
+        AND feed_items.archived = 0"
+    ))?;
+    let feed_item_iter = stmt.query_map([group_id], feed_item_from_row)?;
+
     let mut feed_items = Vec::new();
     for feed_item in feed_item_iter {
       feed_items.push(feed_item?);
@@ -337,20 +1469,36 @@ impl Database {
     Ok(feed_items)
   }
 
+  /// Remembers that the feeds were just viewed, so only articles arriving later count as new.
+  pub fn mark_feeds_viewed(&self, feed_ids: &HashSet<i32>) -> Result<(), DbError> {
+    let now = Utc::now().to_rfc3339();
+    let tx = self.conn.unchecked_transaction()?;
+    {
+      let mut stmt = tx.prepare("UPDATE feeds SET last_viewed_at = ?2 WHERE id = ?1")?;
+      for feed_id in feed_ids {
+        stmt.execute(rusqlite::params![feed_id, now])?;
+      }
+    }
+    tx.commit()?;
+    Ok(())
+  }
+
+  /// The id of the group and of every group nested in it, however deeply.
+  pub fn get_subgroup_ids(&self, group_id: i32) -> Result<HashSet<i32>, DbError> {
+    let mut stmt = self.conn.prepare(&format!("{SUBGROUPS} SELECT id FROM subgroups"))?;
+    let id_iter = stmt.query_map([group_id], |row| row.get(0))?;
+
+    let mut ids = HashSet::new();
+    for id in id_iter {
+      ids.insert(id?);
+    }
+    Ok(ids)
+  }
+
   pub fn get_feeds_from_group(&self, group_id: i32) -> Result<Vec<Feed>, DbError> {
-    let mut stmt = self
-      .conn
-      .prepare("SELECT id, group_id, name, desc, url, updated_at FROM feeds WHERE group_id = ?1")?;
-    let feed_iter = stmt.query_map(rusqlite::params![group_id], |row| {
-      Ok(Feed {
-        id: row.get(0)?,
-        group_id: row.get(1)?,
-        name: row.get(2)?,
-        desc: row.get(3)?,
-        url: row.get(4)?,
-        updated_at: row.get::<_, String>(5)?.parse::<chrono::DateTime<Utc>>().unwrap(),
-      })
-    })?;
+    let mut stmt = self.conn.prepare(&format!("{FEED_SELECT} WHERE feeds.group_id = ?1"))?;
+    let feed_iter = stmt.query_map(rusqlite::params![group_id], feed_from_row)?;
+    let mut cadences = self.get_cadences()?;
 
     let all_feed = Feed {
       id: -1,
@@ -358,12 +1506,799 @@ impl Database {
       name: "All Feeds".to_string(),
       desc: "See all feeds in this group".to_string(),
       url: String::new(),
+      moved_from: None,
       updated_at: chrono::Utc::now(),
+      muted: false,
+      dead: false,
+      cadence: None,
     };
     let mut feeds = vec![all_feed];
     for feed in feed_iter {
-      feeds.push(feed?);
+      let mut feed = feed?;
+      feed.cadence = cadences.remove(&feed.id);
+      feeds.push(feed);
     }
     Ok(feeds)
   }
+
+  pub fn save_feed_position(&self, feed_id: i32, position: FeedPosition) -> Result<(), DbError> {
+    self.conn.execute(
+      "INSERT INTO feed_positions (feed_id, item_id, list_offset) VALUES (?1, ?2, ?3)
+            ON CONFLICT(feed_id) DO UPDATE SET item_id=excluded.item_id, list_offset=excluded.list_offset",
+      rusqlite::params![feed_id, position.item_id, position.list_offset as i64],
+    )?;
+    Ok(())
+  }
+
+  pub fn get_feed_position(&self, feed_id: i32) -> Result<Option<FeedPosition>, DbError> {
+    let mut stmt =
+      self.conn.prepare("SELECT item_id, list_offset FROM feed_positions WHERE feed_id = ?1")?;
+    let mut rows = stmt.query([feed_id])?;
+    if let Some(row) = rows.next()? {
+      Ok(Some(FeedPosition { item_id: row.get(0)?, list_offset: row.get::<_, i64>(1)? as usize }))
+    } else {
+      Ok(None)
+    }
+  }
+
+  /// Returns the current version of an item followed by its earlier revisions, newest first.
+  pub fn get_item_revisions(&self, item_id: i32) -> Result<Vec<ItemRevision>, DbError> {
+    let mut stmt = self.conn.prepare(
+      "SELECT id, title, COALESCE(desc, ''), COALESCE(content, ''), ?2 FROM feed_items WHERE id = ?1
+      UNION ALL
+      SELECT * FROM (
+        SELECT item_id, title, COALESCE(desc, ''), COALESCE(content, ''), saved_at
+        FROM item_revisions WHERE item_id = ?1 ORDER BY id DESC
+      )",
+    )?;
+    let revision_iter =
+      stmt.query_map(rusqlite::params![item_id, Utc::now().to_rfc3339()], |row| {
+        Ok(ItemRevision {
+          item_id: row.get(0)?,
+          title: row.get(1)?,
+          desc: row.get(2)?,
+          content: row.get(3)?,
+          saved_at: get_date(row, 4)?,
+        })
+      })?;
+
+    let mut revisions = Vec::new();
+    for revision in revision_iter {
+      revisions.push(revision?);
+    }
+    Ok(revisions)
+  }
+
+  /// Marks articles as read, all of them or none.
+  pub fn mark_read(&self, item_ids: &[i32]) -> Result<(), DbError> {
+    let tx = self.conn.unchecked_transaction()?;
+    {
+      let mut stmt = tx.prepare("UPDATE feed_items SET read = 1 WHERE id = ?1")?;
+      for item_id in item_ids {
+        stmt.execute([item_id])?;
+      }
+    }
+    tx.commit()?;
+    Ok(())
+  }
+
+  /// The read state of every article, including those imported but not fetched yet.
+  pub fn get_item_states(&self) -> Result<Vec<ItemState>, DbError> {
+    let mut stmt = self.conn.prepare(
+      "SELECT url, read FROM feed_items
+      UNION ALL
+      SELECT url, 1 FROM imported_read WHERE url NOT IN (SELECT url FROM feed_items)
+      ORDER BY url",
+    )?;
+    let state_iter =
+      stmt.query_map([], |row| Ok(ItemState { url: row.get(0)?, read: row.get(1)? }))?;
+
+    let mut states = Vec::new();
+    for state in state_iter {
+      states.push(state?);
+    }
+    Ok(states)
+  }
+
+  /// Restores the read state of articles by URL, all of it or none. Read articles that have not
+  /// been fetched are remembered and marked read when they arrive. Returns the number of
+  /// articles updated or remembered, leaving out states that match no article and change nothing.
+  pub fn import_item_states(&self, states: &[ItemState]) -> Result<usize, DbError> {
+    let tx = self.conn.unchecked_transaction()?;
+    let mut applied = 0;
+    {
+      let mut update = tx.prepare("UPDATE feed_items SET read = ?2 WHERE url = ?1")?;
+      let mut remember = tx.prepare("INSERT OR IGNORE INTO imported_read (url) VALUES (?1)")?;
+      for state in states {
+        let updated = update.execute(rusqlite::params![state.url, state.read])?;
+        applied += updated;
+        if updated == 0 && state.read {
+          applied += remember.execute([&state.url])?;
+        }
+      }
+    }
+    tx.commit()?;
+    Ok(applied)
+  }
+
+  /// Marks the articles of a feed published before `cutoff` as read.
+  pub fn mark_read_before(&self, feed_id: i32, cutoff: DateTime<Utc>) -> Result<(), DbError> {
+    self.conn.execute(
+      "UPDATE feed_items SET read = 1 WHERE feed_id = ?1 AND read = 0 AND pub_date < ?2",
+      rusqlite::params![feed_id, cutoff.to_rfc3339()],
+    )?;
+    Ok(())
+  }
+
+  /// Archives the unread articles of the group's own feeds published before `cutoff`, which
+  /// leaves them out of every view but Archive and out of unread counts. Returns the number
+  /// archived.
+  pub fn archive_unread_before(
+    &self,
+    group_id: i32,
+    cutoff: DateTime<Utc>,
+  ) -> Result<usize, DbError> {
+    let archived = self.conn.execute(
+      "UPDATE feed_items SET archived = 1
+      WHERE feed_id IN (SELECT id FROM feeds WHERE group_id = ?1)
+        AND read = 0 AND archived = 0 AND pub_date < ?2",
+      rusqlite::params![group_id, cutoff.to_rfc3339()],
+    )?;
+    Ok(archived)
+  }
+
+  /// Deletes read articles stored before `cutoff`, keeping those with a note or highlights. Going
+  /// by when they arrived rather than their publication date, which feeds get wrong, keeps an
+  /// article that arrived with an old date from being deleted at once. Their URLs are remembered
+  /// as read, so any a feed still lists come back read. Returns the number deleted.
+  pub fn prune_read_items(&self, cutoff: DateTime<Utc>) -> Result<usize, DbError> {
+    let tx = self.conn.unchecked_transaction()?;
+    let items = tx
+      .prepare(
+        "SELECT id, url FROM feed_items
+        WHERE read = 1 AND COALESCE(added_at, pub_date) < ?1
+          AND id NOT IN (SELECT item_id FROM item_notes)
+          AND id NOT IN (SELECT item_id FROM highlights)",
+      )?
+      .query_map([cutoff.to_rfc3339()], |row| Ok((row.get(0)?, row.get(1)?)))?
+      .collect::<Result<Vec<(i32, Option<String>)>>>()?;
+    {
+      let mut remember = tx.prepare("INSERT OR IGNORE INTO imported_read (url) VALUES (?1)")?;
+      for url in items.iter().filter_map(|(_, url)| url.as_ref()) {
+        remember.execute([url])?;
+      }
+    }
+    let item_ids: Vec<i32> = items.iter().map(|(id, _)| *id).collect();
+    self.delete_items(&item_ids)?;
+    tx.commit()?;
+    Ok(item_ids.len())
+  }
+
+  /// Deletes articles whose feed is gone, then whatever is kept about articles that are gone.
+  /// Such leftovers come from databases written before foreign keys were enforced. Feeds taken
+  /// out of the config are not orphans: [`Database::remove_unconfigured`] deals with them as
+  /// `removed_feeds` says. Returns how many articles were deleted.
+  fn prune_orphans(&self) -> Result<usize, DbError> {
+    let tx = self.conn.unchecked_transaction()?;
+    let item_ids = tx
+      .prepare("SELECT id FROM feed_items WHERE feed_id NOT IN (SELECT id FROM feeds)")?
+      .query_map([], |row| row.get(0))?
+      .collect::<Result<Vec<i32>>>()?;
+    self.delete_items(&item_ids)?;
+    for table in ITEM_TABLES {
+      tx.execute(
+        &format!("DELETE FROM {table} WHERE item_id NOT IN (SELECT id FROM feed_items)"),
+        [],
+      )?;
+    }
+    tx.execute("DELETE FROM feed_positions WHERE feed_id NOT IN (SELECT id FROM feeds)", [])?;
+    tx.commit()?;
+    Ok(item_ids.len())
+  }
+
+  fn free_bytes(&self) -> Result<u64, DbError> {
+    let page_size: i64 = self.conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    let free_pages: i64 = self.conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+    Ok((page_size * free_pages) as u64)
+  }
+
+  /// Whether more than `interval` has passed since upkeep last ran.
+  pub fn maintenance_due(&self, interval: chrono::Duration) -> Result<bool, DbError> {
+    let mut stmt = self.conn.prepare("SELECT value FROM meta WHERE key = 'maintained_at'")?;
+    let mut rows = stmt.query([])?;
+    let Some(row) = rows.next()? else {
+      return Ok(true);
+    };
+    let maintained_at: DateTime<Utc> = row.get(0)?;
+    Ok(Utc::now() - maintained_at > interval)
+  }
+
+  /// Keeps queries quick and the file small over months of use: deletes orphaned articles,
+  /// refreshes the statistics the query planner goes by and hands back freed pages.
+  pub fn run_maintenance(&self) -> Result<Maintenance, DbError> {
+    let orphans = self.prune_orphans()?;
+    self.conn.execute_batch("ANALYZE")?;
+    let free_before = self.free_bytes()?;
+    self.conn.execute_batch("PRAGMA incremental_vacuum")?;
+    let freed = free_before.saturating_sub(self.free_bytes()?);
+    self.conn.execute(
+      "INSERT INTO meta (key, value) VALUES ('maintained_at', ?1)
+           ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+      [Utc::now().to_rfc3339()],
+    )?;
+    Ok(Maintenance { orphans, freed })
+  }
+
+  /// Runs upkeep, then rewrites the whole file to compact it. This takes a while on a large
+  /// database and keeps anything else from writing meanwhile.
+  pub fn vacuum(&self) -> Result<Maintenance, DbError> {
+    let mut maintenance = self.run_maintenance()?;
+    let size_before = self.file_size()?;
+    self.conn.execute_batch("VACUUM")?;
+    maintenance.freed += size_before.saturating_sub(self.file_size()?);
+    Ok(maintenance)
+  }
+
+  fn file_size(&self) -> Result<u64, DbError> {
+    let page_size: i64 = self.conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    let pages: i64 = self.conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    Ok((page_size * pages) as u64)
+  }
+
+  /// Counts unread articles, leaving out muted feeds.
+  pub fn count_unread(&self) -> Result<usize, DbError> {
+    let count: i64 = self.conn.query_row(
+      "SELECT COUNT(*) FROM feed_items
+      JOIN feeds ON feed_items.feed_id = feeds.id
+      WHERE feed_items.read = 0 AND feed_items.archived = 0 AND feeds.muted = 0",
+      [],
+      |row| row.get(0),
+    )?;
+    Ok(count as usize)
+  }
+
+  /// Returns the number of unread articles of every unmuted feed that has any, by feed id.
+  pub fn count_unread_by_feed(&self) -> Result<HashMap<i32, usize>, DbError> {
+    let mut stmt = self.conn.prepare(
+      "SELECT feed_items.feed_id, COUNT(*) FROM feed_items
+      JOIN feeds ON feed_items.feed_id = feeds.id
+      WHERE feed_items.read = 0 AND feed_items.archived = 0 AND feeds.muted = 0
+      GROUP BY feed_items.feed_id",
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut counts = HashMap::new();
+    while let Some(row) = rows.next()? {
+      counts.insert(row.get(0)?, row.get::<_, i64>(1)? as usize);
+    }
+    Ok(counts)
+  }
+
+  /// Returns the number of unread articles of every group that has any, by group id, leaving
+  /// out muted feeds. A group's count includes the groups nested in it.
+  pub fn count_unread_by_group(&self) -> Result<HashMap<i32, usize>, DbError> {
+    let mut stmt = self.conn.prepare(
+      "WITH RECURSIVE ancestors(group_id, ancestor_id) AS (
+        SELECT id, id FROM groups
+        UNION
+        SELECT ancestors.group_id, groups.parent_id FROM ancestors
+        JOIN groups ON groups.id = ancestors.ancestor_id
+        WHERE groups.parent_id IS NOT NULL
+      )
+      SELECT ancestors.ancestor_id, COUNT(*) FROM feed_items
+      JOIN feeds ON feed_items.feed_id = feeds.id
+      JOIN ancestors ON ancestors.group_id = feeds.group_id
+      WHERE feed_items.read = 0 AND feed_items.archived = 0 AND feeds.muted = 0
+      GROUP BY ancestors.ancestor_id",
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut counts = HashMap::new();
+    while let Some(row) = rows.next()? {
+      counts.insert(row.get(0)?, row.get::<_, i64>(1)? as usize);
+    }
+    Ok(counts)
+  }
+
+  /// Adds a feed subscribed to from within the app, refreshed along with the configured ones.
+  pub fn add_subscription(&self, group_name: &str, feed: &FeedConfig) -> Result<(), DbError> {
+    self.conn.execute(
+      "INSERT OR IGNORE INTO subscriptions (link, group_name, name) VALUES (?1, ?2, ?3)",
+      rusqlite::params![feed.link, group_name, feed.name],
+    )?;
+    Ok(())
+  }
+
+  /// Returns the feeds subscribed to from within the app, with the name of their group.
+  pub fn get_subscriptions(&self) -> Result<Vec<(String, FeedConfig)>, DbError> {
+    let mut stmt = self.conn.prepare("SELECT group_name, link, name FROM subscriptions")?;
+    let subscription_iter = stmt.query_map([], |row| {
+      let feed = FeedConfig { link: row.get(1)?, name: row.get(2)?, ..Default::default() };
+      Ok((row.get(0)?, feed))
+    })?;
+
+    let mut subscriptions = Vec::new();
+    for subscription in subscription_iter {
+      subscriptions.push(subscription?);
+    }
+    Ok(subscriptions)
+  }
+
+  pub fn record_fetch_success(&self, url: &str) -> Result<(), DbError> {
+    let now = Utc::now().to_rfc3339();
+    self.conn.execute(
+      "INSERT INTO feed_health (url, last_attempt, last_success, last_error, failures)
+            VALUES (?1, ?2, ?2, NULL, 0)
+            ON CONFLICT(url) DO UPDATE SET last_attempt=excluded.last_attempt, last_success=excluded.last_success, last_error=NULL, error_kind=NULL, failures=0, dead=0",
+      [url, &now],
+    )?;
+    Ok(())
+  }
+
+  /// Counts a failed refresh, marking the feed dead once `dead_after` refreshes in a row have
+  /// failed. A `dead_after` of 0 never marks it dead.
+  pub fn record_fetch_failure(
+    &self,
+    url: &str,
+    error: &RefreshError,
+    dead_after: u32,
+  ) -> Result<(), DbError> {
+    self.conn.execute(
+      "INSERT INTO feed_health (url, last_attempt, last_error, error_kind, failures, dead)
+            VALUES (?1, ?2, ?3, ?5, 1, ?4 = 1)
+            ON CONFLICT(url) DO UPDATE SET last_attempt=excluded.last_attempt, last_error=excluded.last_error, error_kind=excluded.error_kind, failures=failures + 1,
+              dead = dead OR (?4 > 0 AND failures + 1 >= ?4)",
+      rusqlite::params![
+        url,
+        Utc::now().to_rfc3339(),
+        error.message,
+        dead_after,
+        error.kind.to_string()
+      ],
+    )?;
+    Ok(())
+  }
+
+  /// The URLs of the feeds no longer refreshed because they kept failing.
+  pub fn get_dead_feeds(&self) -> Result<HashSet<String>, DbError> {
+    let mut stmt = self.conn.prepare("SELECT url FROM feed_health WHERE dead = 1")?;
+    let url_iter = stmt.query_map([], |row| row.get(0))?;
+
+    let mut urls = HashSet::new();
+    for url in url_iter {
+      urls.insert(url?);
+    }
+    Ok(urls)
+  }
+
+  /// Refreshes the dead feed at `url` again. Its failures are kept, so one more failure marks
+  /// it dead again.
+  pub fn revive_feed(&self, url: &str) -> Result<(), DbError> {
+    self.conn.execute("UPDATE feed_health SET dead = 0 WHERE url = ?1", [url])?;
+    Ok(())
+  }
+
+  /// Remembers how the text of an article from the feed at `url` was last found.
+  pub fn record_extraction(&self, url: &str, extraction: Extraction) -> Result<(), DbError> {
+    self.conn.execute("UPDATE feed_health SET extraction = ?2 WHERE url = ?1", [
+      url,
+      &extraction.to_string(),
+    ])?;
+    Ok(())
+  }
+
+  /// The files attached to an article, in the order the feed listed them.
+  pub fn get_enclosures(&self, item_id: i32) -> Result<Vec<Enclosure>, DbError> {
+    let mut stmt = self.conn.prepare(
+      "SELECT url, mime_type, length FROM item_enclosures WHERE item_id = ?1 ORDER BY position",
+    )?;
+    let enclosures = stmt
+      .query_map([item_id], |row| {
+        Ok(Enclosure {
+          url: row.get(0)?,
+          mime_type: row.get(1)?,
+          length: row.get::<_, Option<i64>>(2)?.map(|length| length as u64),
+        })
+      })?
+      .collect::<Result<Vec<_>>>()?;
+    Ok(enclosures)
+  }
+
+  /// What the database takes up, with the `largest` items storing the most.
+  pub fn get_storage_stats(&self, largest: usize) -> Result<StorageStats, DbError> {
+    let count = |table: &str| -> Result<u64, DbError> {
+      let count: i64 =
+        self.conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| row.get(0))?;
+      Ok(count as u64)
+    };
+
+    let mut stmt = self.conn.prepare(
+      "SELECT feeds.name, COUNT(feed_items.id),
+              COALESCE(SUM(LENGTH(CAST(feed_items.title AS BLOB)) + COALESCE(LENGTH(CAST(feed_items.desc AS BLOB)), 0)
+                + COALESCE(LENGTH(CAST(feed_items.content AS BLOB)), 0)), 0) AS bytes
+           FROM feeds
+           LEFT JOIN feed_items ON feed_items.feed_id = feeds.id
+           GROUP BY feeds.id
+           ORDER BY bytes DESC, feeds.name",
+    )?;
+    let feeds_iter = stmt.query_map([], |row| {
+      Ok(FeedStorage {
+        name: row.get(0)?,
+        items: row.get::<_, i64>(1)? as u64,
+        bytes: row.get::<_, i64>(2)? as u64,
+      })
+    })?;
+    let mut feeds = Vec::new();
+    for feed in feeds_iter {
+      feeds.push(feed?);
+    }
+
+    let mut stmt = self.conn.prepare(
+      "SELECT feed_items.title, feeds.name, feed_items.url,
+              LENGTH(CAST(feed_items.title AS BLOB)) + COALESCE(LENGTH(CAST(feed_items.desc AS BLOB)), 0)
+                + COALESCE(LENGTH(CAST(feed_items.content AS BLOB)), 0) AS bytes
+           FROM feed_items
+           JOIN feeds ON feeds.id = feed_items.feed_id
+           ORDER BY bytes DESC
+           LIMIT ?1",
+    )?;
+    let items_iter = stmt.query_map([largest as i64], |row| {
+      Ok(ItemStorage {
+        title: row.get(0)?,
+        feed_name: row.get(1)?,
+        url: row.get(2)?,
+        bytes: row.get::<_, i64>(3)? as u64,
+      })
+    })?;
+    let mut items = Vec::new();
+    for item in items_iter {
+      items.push(item?);
+    }
+
+    Ok(StorageStats {
+      file_size: self.file_size()?,
+      free_size: self.free_bytes()?,
+      items: count("feed_items")?,
+      revisions: count("item_revisions")?,
+      feeds,
+      largest: items,
+    })
+  }
+
+  /// The article with the given id with everything stored about it: its author, its feed's
+  /// identifier for it and its categories, as indexed, in lower case.
+  pub fn get_article_info(&self, item_id: i32) -> Result<Option<FeedItem>, DbError> {
+    let mut stmt = self.conn.prepare(&format!("{FEED_ITEM_SELECT} WHERE feed_items.id = ?1"))?;
+    let mut rows = stmt.query([item_id])?;
+    let Some(row) = rows.next()? else {
+      return Ok(None);
+    };
+    let mut item = feed_item_from_row(row)?;
+    (item.author, item.guid) = self.conn.query_row(
+      "SELECT author, guid FROM feed_items WHERE id = ?1",
+      [item_id],
+      |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let mut stmt = self.conn.prepare(
+      "SELECT substr(keyword, 2) FROM item_keywords WHERE item_id = ?1 AND keyword LIKE '#%'
+           ORDER BY keyword",
+    )?;
+    item.categories = stmt.query_map([item_id], |row| row.get(0))?.collect::<Result<_>>()?;
+    Ok(Some(item))
+  }
+
+  /// What is stored about `feed` and how its refreshes have gone.
+  pub fn get_feed_info(&self, feed: Feed) -> Result<FeedInfo, DbError> {
+    let (site_url, items, unread) = self.conn.query_row(
+      "SELECT feeds.site_url, COUNT(feed_items.id), COALESCE(SUM(feed_items.read = 0), 0)
+           FROM feeds
+           LEFT JOIN feed_items ON feed_items.feed_id = feeds.id
+           WHERE feeds.id = ?1",
+      [feed.id],
+      |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64, row.get::<_, i64>(2)? as u64)),
+    )?;
+    let mut feed = feed;
+    feed.cadence = self.get_cadences()?.remove(&feed.id);
+    let health = self.get_feed_health()?.into_iter().find(|health| health.url == feed.url);
+    Ok(FeedInfo { feed, site_url, items, unread, health })
+  }
+
+  /// The cadence of every feed with at least two articles, by feed id.
+  pub fn get_cadences(&self) -> Result<HashMap<i32, Cadence>, DbError> {
+    let mut stmt = self.conn.prepare(
+      "SELECT feed_id, MAX(pub_date), MIN(pub_date), COUNT(*) FROM (
+        SELECT feed_id, pub_date,
+          ROW_NUMBER() OVER (PARTITION BY feed_id ORDER BY pub_date DESC) AS position
+        FROM feed_items
+      )
+      WHERE position <= ?1
+      GROUP BY feed_id
+      HAVING COUNT(*) >= 2",
+    )?;
+    let mut rows = stmt.query([CADENCE_SAMPLE])?;
+    let mut cadences = HashMap::new();
+    while let Some(row) = rows.next()? {
+      let latest = get_date(row, 1)?;
+      let samples: u32 = row.get(3)?;
+      let span = (latest - get_date(row, 2)?).num_seconds();
+      let cadence = Cadence { interval_secs: span / i64::from(samples - 1), latest, samples };
+      cadences.insert(row.get(0)?, cadence);
+    }
+    Ok(cadences)
+  }
+
+  /// The full text the feed provided for an item, empty when it only had a description.
+  pub fn get_item_content(&self, item_id: i32) -> Result<String, DbError> {
+    let content = self.conn.query_row(
+      "SELECT COALESCE(content, '') FROM feed_items WHERE id = ?1",
+      [item_id],
+      |row| row.get(0),
+    )?;
+    Ok(content)
+  }
+
+  /// Returns the refresh health of every feed, failing feeds first.
+  pub fn get_feed_health(&self) -> Result<Vec<FeedHealth>, DbError> {
+    let mut stmt = self.conn.prepare(
+      "SELECT feed_health.url, feed_health.last_attempt, feed_health.last_success,
+             feed_health.last_error, feed_health.failures, feed_health.extraction,
+             feed_health.dead, feed_health.error_kind, feeds.moved_from
+           FROM feed_health
+           LEFT JOIN feeds ON feeds.url = feed_health.url
+           ORDER BY failures DESC, feed_health.url",
+    )?;
+    let health_iter = stmt.query_map([], |row| {
+      Ok(FeedHealth {
+        url: row.get(0)?,
+        moved_from: row.get(8)?,
+        last_attempt: get_date(row, 1)?,
+        last_success: row
+          .get::<_, Option<String>>(2)?
+          .and_then(|date| date.parse::<chrono::DateTime<Utc>>().ok()),
+        last_error: row.get(3)?,
+        error_kind: row.get::<_, Option<String>>(7)?.and_then(|kind| kind.parse().ok()),
+        failures: row.get(4)?,
+        extraction: row.get::<_, Option<String>>(5)?.and_then(|extraction| extraction.parse().ok()),
+        dead: row.get::<_, i32>(6)? != 0,
+      })
+    })?;
+
+    let mut health = Vec::new();
+    for feed_health in health_iter {
+      health.push(feed_health?);
+    }
+    Ok(health)
+  }
+
+  /// Collects per-feed statistics, with weekly article counts over the last `weeks` weeks.
+  pub fn get_feed_stats(&self, weeks: usize) -> Result<Vec<FeedStats>, DbError> {
+    let mut stmt = self.conn.prepare(
+      "SELECT feeds.id, feeds.name, COUNT(feed_items.id), COALESCE(SUM(feed_items.read), 0)
+           FROM feeds
+           LEFT JOIN feed_items ON feed_items.feed_id = feeds.id
+           GROUP BY feeds.id
+           ORDER BY 4 DESC, 3 DESC",
+    )?;
+    let stats_iter = stmt.query_map([], |row| {
+      Ok(FeedStats {
+        feed_id: row.get(0)?,
+        name: row.get(1)?,
+        total: row.get::<_, i64>(2)? as u64,
+        read: row.get::<_, i64>(3)? as u64,
+        weekly: vec![0; weeks],
+      })
+    })?;
+    let mut stats = Vec::new();
+    for feed_stats in stats_iter {
+      stats.push(feed_stats?);
+    }
+
+    let mut stmt = self.conn.prepare(
+      "SELECT feed_id, CAST((julianday('now') - julianday(pub_date)) / 7 AS INTEGER) AS week, COUNT(*)
+           FROM feed_items
+           WHERE julianday('now') - julianday(pub_date) BETWEEN 0 AND ?1 * 7
+           GROUP BY feed_id, week",
+    )?;
+    let mut rows = stmt.query([weeks as i64])?;
+    while let Some(row) = rows.next()? {
+      let feed_id: i32 = row.get(0)?;
+      let weeks_ago = row.get::<_, i64>(1)? as usize;
+      let count = row.get::<_, i64>(2)? as u64;
+      if let Some(feed_stats) = stats.iter_mut().find(|feed_stats| feed_stats.feed_id == feed_id) {
+        if weeks_ago < weeks {
+          feed_stats.weekly[weeks - 1 - weeks_ago] = count;
+        }
+      }
+    }
+    Ok(stats)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  /// A database holding one group, whose id is returned with it.
+  async fn database() -> (Database, i32) {
+    let db = Database::in_memory().await.unwrap();
+    let group = Group { id: 0, name: "News".to_string(), desc: String::new(), parent_id: None };
+    let group_id = db.upsert_group(group).unwrap();
+    (db, group_id)
+  }
+
+  fn feed(group_id: i32, muted: bool) -> Feed {
+    Feed {
+      id: 0,
+      group_id,
+      name: "Feed".to_string(),
+      desc: String::new(),
+      url: "https://example.com/feed".to_string(),
+      moved_from: None,
+      updated_at: Utc::now(),
+      muted,
+      dead: false,
+      cadence: None,
+    }
+  }
+
+  fn item(feed_id: i32, pub_date: DateTime<Utc>) -> FeedItem {
+    FeedItem {
+      feed_id,
+      title: "Article".to_string(),
+      url: "https://example.com/article".to_string(),
+      pub_date,
+      ..Default::default()
+    }
+  }
+
+  fn cadence(interval_secs: i64, samples: u32) -> Cadence {
+    let latest = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+    Cadence { interval_secs, latest, samples }
+  }
+
+  #[tokio::test]
+  async fn a_moved_feed_keeps_the_url_it_moved_from() {
+    let (db, group_id) = database().await;
+    let (feed_id, _) = db.upsert_feed(feed(group_id, false)).unwrap();
+    db.upsert_feed_item(item(feed_id, Utc::now())).unwrap();
+    db.move_feed("https://example.com/feed", "https://example.org/feed").unwrap();
+    db.record_fetch_success("https://example.org/feed").unwrap();
+
+    let moved_from = Some("https://example.com/feed".to_string());
+    let stored = db.get_feeds_from_group(group_id).unwrap().pop().unwrap();
+    assert_eq!(
+      (stored.url.as_str(), &stored.moved_from),
+      ("https://example.org/feed", &moved_from)
+    );
+    assert_eq!(db.get_feed_items_from_feed(feed_id).unwrap()[0].feed_moved_from, moved_from);
+    assert_eq!(db.get_feed_health().unwrap()[0].moved_from, moved_from);
+  }
+
+  #[test]
+  fn cadence_rates_read_in_the_largest_fitting_unit() {
+    const DAY: i64 = 86_400;
+    let rates: Vec<String> = [0, DAY / 3, DAY, DAY * 2, DAY * 7, DAY * 10, DAY * 60]
+      .map(|secs| cadence(secs, 10).rate())
+      .into();
+    assert_eq!(rates, vec![
+      "all at once",
+      "~3/day",
+      "~1/day",
+      "~4/week",
+      "~1/week",
+      "~3/month",
+      "less than monthly"
+    ]);
+  }
+
+  #[test]
+  fn a_feed_is_quiet_after_several_of_its_intervals() {
+    let hourly = cadence(3_600, 10);
+    let weekly = cadence(7 * 86_400, 10);
+    let after = |days| hourly.latest + chrono::Duration::days(days);
+    // An hourly feed is given the two days' grace, a weekly one four of its weeks.
+    assert!(!hourly.is_quiet(after(2)));
+    assert!(hourly.is_quiet(after(3)));
+    assert!(!weekly.is_quiet(after(28)));
+    assert!(weekly.is_quiet(after(29)));
+    // Too few articles to go by.
+    assert!(!cadence(3_600, QUIET_MIN_SAMPLE - 1).is_quiet(after(30)));
+  }
+
+  #[tokio::test]
+  async fn an_updated_article_keeps_its_date() {
+    let (db, group_id) = database().await;
+    let (feed_id, _) = db.upsert_feed(feed(group_id, false)).unwrap();
+    let published = Utc::now() - chrono::Duration::days(30);
+    db.upsert_feed_item(item(feed_id, published)).unwrap();
+    db.upsert_feed_item(FeedItem { title: "Edited".to_string(), ..item(feed_id, Utc::now()) })
+      .unwrap();
+    let items = db.get_feed_items_from_feed(feed_id).unwrap();
+    assert_eq!(items[0].title, "Edited");
+    assert_eq!(items[0].pub_date.timestamp(), published.timestamp());
+  }
+
+  #[tokio::test]
+  async fn a_refetched_article_stays_read() {
+    let (db, group_id) = database().await;
+    let (feed_id, _) = db.upsert_feed(feed(group_id, false)).unwrap();
+    db.upsert_feed_item(item(feed_id, Utc::now())).unwrap();
+    db.mark_read(&[db.get_feed_items_from_feed(feed_id).unwrap()[0].id]).unwrap();
+    db.upsert_feed_item(item(feed_id, Utc::now())).unwrap();
+    assert!(db.get_feed_items_from_feed(feed_id).unwrap()[0].read);
+  }
+
+  #[tokio::test]
+  async fn unmuting_in_the_config_unmutes_the_feed() {
+    let (db, group_id) = database().await;
+    assert_eq!(db.upsert_feed(feed(group_id, true)).unwrap().1, true);
+    assert_eq!(db.upsert_feed(feed(group_id, false)).unwrap().1, false);
+  }
+
+  #[tokio::test]
+  async fn a_runtime_mute_lasts_until_the_config_changes() {
+    let (db, group_id) = database().await;
+    let (id, _) = db.upsert_feed(feed(group_id, false)).unwrap();
+    db.set_feed_muted(id, true).unwrap();
+    assert_eq!(db.upsert_feed(feed(group_id, false)).unwrap().1, true);
+    db.upsert_feed(feed(group_id, true)).unwrap();
+    db.set_feed_muted(id, false).unwrap();
+    assert_eq!(db.upsert_feed(feed(group_id, true)).unwrap().1, false);
+  }
+
+  #[tokio::test]
+  async fn importing_states_counts_only_what_it_applied() {
+    let (db, group_id) = database().await;
+    let (feed_id, _) = db.upsert_feed(feed(group_id, false)).unwrap();
+    db.upsert_feed_item(item(feed_id, Utc::now())).unwrap();
+    let state = |url: &str, read: bool| ItemState { url: url.to_string(), read };
+    let states = [
+      state("https://example.com/article", true),
+      state("https://example.com/later", true),
+      state("https://example.com/unread", false),
+    ];
+    assert_eq!(db.import_item_states(&states).unwrap(), 2);
+    assert!(db.get_feed_items_from_feed(feed_id).unwrap()[0].read);
+  }
+
+  #[tokio::test]
+  async fn a_feed_deleted_from_the_config_leaves_no_orphans() {
+    let (db, group_id) = database().await;
+    let (feed_id, _) = db.upsert_feed(feed(group_id, false)).unwrap();
+    db.upsert_feed_item(item(feed_id, Utc::now())).unwrap();
+    let names = HashSet::from(["News".to_string()]);
+    let removed = db.remove_unconfigured(&HashSet::new(), &names, RemovedFeeds::Delete).unwrap();
+    assert_eq!(removed, vec!["Feed".to_string()]);
+    assert_eq!(db.get_feed_items_from_feed(feed_id).unwrap(), Vec::new());
+    assert_eq!(db.run_maintenance().unwrap().orphans, 0);
+  }
+
+  #[tokio::test]
+  async fn archived_articles_leave_alerts_and_history() {
+    let (db, group_id) = database().await;
+    let (feed_id, _) = db.upsert_feed(feed(group_id, false)).unwrap();
+    let alert = FeedItem {
+      alert: Some("CVE".to_string()),
+      ..item(feed_id, Utc::now() - chrono::Duration::days(30))
+    };
+    db.upsert_feed_item(alert.clone()).unwrap();
+    db.record_alert(&alert).unwrap();
+    db.record_opened(db.get_feed_items_from_feed(feed_id).unwrap()[0].id).unwrap();
+    assert_eq!((db.get_alerts().unwrap().len(), db.get_history().unwrap().len()), (1, 1));
+    assert_eq!(db.archive_unread_before(group_id, Utc::now()).unwrap(), 1);
+    assert_eq!((db.get_alerts().unwrap().len(), db.get_history().unwrap().len()), (0, 0));
+  }
+
+  #[tokio::test]
+  async fn pruning_goes_by_when_an_article_arrived() {
+    let (db, group_id) = database().await;
+    let (feed_id, _) = db.upsert_feed(feed(group_id, false)).unwrap();
+    let published = Utc::now() - chrono::Duration::days(365);
+    db.upsert_feed_item(FeedItem { read: true, ..item(feed_id, published) }).unwrap();
+
+    let cutoff = Utc::now() - chrono::Duration::days(90);
+    assert_eq!(db.prune_read_items(cutoff).unwrap(), 0);
+    assert_eq!(db.prune_read_items(Utc::now() + chrono::Duration::days(1)).unwrap(), 1);
+    assert!(db.get_feed_items_from_feed(feed_id).unwrap().is_empty());
+  }
 }
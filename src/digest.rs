@@ -0,0 +1,198 @@
+use std::{collections::HashMap, fmt::Write, path::Path};
+
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::{eyre, Result};
+
+use crate::{
+  db::{Database, Feed, FeedItem},
+  export::escape,
+  html,
+};
+
+/// Longest description shown under an article, in characters.
+const DESC_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DigestFormat {
+  #[default]
+  Markdown,
+  Html,
+}
+
+impl DigestFormat {
+  /// The format a file name asks for by its extension, if any.
+  pub fn from_path(path: &Path) -> Option<Self> {
+    match path.extension()?.to_str()? {
+      "md" | "markdown" => Some(DigestFormat::Markdown),
+      "html" | "htm" => Some(DigestFormat::Html),
+      _ => None,
+    }
+  }
+}
+
+/// The articles of one feed in the digest.
+struct FeedSection<'a> {
+  feed: &'a Feed,
+  items: Vec<&'a FeedItem>,
+}
+
+/// Parses an age such as `30m`, `24h`, `7d` or `2w`.
+pub fn parse_age(age: &str) -> Result<Duration> {
+  let age = age.trim();
+  let split = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+  let (count, unit) = age.split_at(split);
+  let count: i64 = count.parse().map_err(|_| eyre!("`{age}` does not start with a number"))?;
+  let duration = match unit.trim() {
+    "m" | "min" => Duration::try_minutes(count),
+    "h" | "" => Duration::try_hours(count),
+    "d" => Duration::try_days(count),
+    "w" => Duration::try_weeks(count),
+    unit => return Err(eyre!("Unknown unit `{unit}`, expected m, h, d or w")),
+  };
+  duration.ok_or_else(|| eyre!("`{age}` is too long"))
+}
+
+/// Lists the articles that arrived since `since`, grouped by group and feed in the order of the
+/// group list. Muted feeds are left out. Returns the digest and the number of articles in it.
+pub fn digest(
+  db: &Database,
+  since: DateTime<Utc>,
+  format: DigestFormat,
+) -> Result<(String, usize)> {
+  let items = db.get_feed_items_added_since(since)?;
+  let feeds: Vec<Feed> = db.get_feeds()?.into_iter().filter(|feed| !feed.muted).collect();
+  let mut by_feed: HashMap<i32, Vec<&FeedItem>> = HashMap::new();
+  for item in &items {
+    by_feed.entry(item.feed_id).or_default().push(item);
+  }
+
+  let mut groups = Vec::new();
+  for group in db.get_groups()?.into_iter().filter(|group| group.id >= 0) {
+    let sections: Vec<FeedSection> = feeds
+      .iter()
+      .filter(|feed| feed.group_id == group.id)
+      .filter_map(|feed| by_feed.remove(&feed.id).map(|items| FeedSection { feed, items }))
+      .collect();
+    if !sections.is_empty() {
+      groups.push((group.name, sections));
+    }
+  }
+
+  let count = groups.iter().flat_map(|(_, sections)| sections).map(|s| s.items.len()).sum();
+  let title = format!("{} digest since {}", env!("CARGO_PKG_NAME"), since.format("%Y-%m-%d %H:%M"));
+  let digest = match format {
+    DigestFormat::Markdown => markdown(&title, &groups),
+    DigestFormat::Html => html(&title, &groups),
+  };
+  Ok((digest, count))
+}
+
+fn markdown(title: &str, groups: &[(String, Vec<FeedSection>)]) -> String {
+  let mut out = format!("# {title}\n");
+  if groups.is_empty() {
+    out.push_str("\nNo new articles.\n");
+  }
+  for (group, sections) in groups {
+    let _ = write!(out, "\n## {group}\n");
+    for section in sections {
+      let _ = write!(out, "\n### {}\n\n", section.feed.name);
+      for item in &section.items {
+        let _ = writeln!(out, "- [{}]({})", markdown_text(&item.title), markdown_url(&item.url));
+        let desc = short_desc(item);
+        if !desc.is_empty() {
+          let _ = writeln!(out, "  {desc}");
+        }
+      }
+    }
+  }
+  out
+}
+
+/// `text` with the characters that would end or nest a Markdown link's text, or turn a line into
+/// a heading, escaped.
+pub(crate) fn markdown_text(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    if matches!(c, '\\' | '[' | ']' | '#') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+/// `url` with the parentheses and spaces that would end a Markdown link's target percent-encoded.
+pub(crate) fn markdown_url(url: &str) -> String {
+  url.replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+}
+
+fn html(title: &str, groups: &[(String, Vec<FeedSection>)]) -> String {
+  let mut out = format!(
+    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\"/>\n<title>{0}</title>\n</head>\n\
+     <body>\n<h1>{0}</h1>\n",
+    escape(title)
+  );
+  if groups.is_empty() {
+    out.push_str("<p>No new articles.</p>\n");
+  }
+  for (group, sections) in groups {
+    let _ = writeln!(out, "<h2>{}</h2>", escape(group));
+    for section in sections {
+      let _ = writeln!(out, "<h3>{}</h3>\n<ul>", escape(&section.feed.name));
+      for item in &section.items {
+        let _ = write!(out, "<li><a href=\"{}\">{}</a>", escape(&item.url), escape(&item.title));
+        let desc = short_desc(item);
+        if !desc.is_empty() {
+          let _ = write!(out, "<br/>{}", escape(&desc));
+        }
+        out.push_str("</li>\n");
+      }
+      out.push_str("</ul>\n");
+    }
+  }
+  out.push_str("</body>\n</html>\n");
+  out
+}
+
+/// The start of the article's description as plain text, cut at a word.
+fn short_desc(item: &FeedItem) -> String {
+  let desc = html::to_plain_text(&item.desc);
+  if desc.chars().count() <= DESC_CHARS {
+    return desc;
+  }
+  let cut: String = desc.chars().take(DESC_CHARS).collect();
+  let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(start, _)| start);
+  format!("{}…", cut.trim_end_matches(|c: char| c.is_ascii_punctuation()))
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn parses_ages_in_every_unit() {
+    assert_eq!(parse_age("30m").unwrap(), Duration::minutes(30));
+    assert_eq!(parse_age("24").unwrap(), Duration::hours(24));
+    assert_eq!(parse_age(" 7d ").unwrap(), Duration::days(7));
+    assert_eq!(parse_age("2w").unwrap(), Duration::weeks(2));
+    assert!(parse_age("3y").is_err());
+    assert!(parse_age("d").is_err());
+  }
+
+  #[test]
+  fn rejects_ages_out_of_range() {
+    assert!(parse_age("9223372036854775807w").is_err());
+    assert!(parse_age("99999999999999d").is_err());
+  }
+
+  #[test]
+  fn escapes_markdown_links() {
+    assert_eq!(markdown_text(r"[Rust] 1.80 \ more"), r"\[Rust\] 1.80 \\ more");
+    assert_eq!(
+      markdown_url("https://en.wikipedia.org/wiki/Rust_(language) x"),
+      "https://en.wikipedia.org/wiki/Rust_%28language%29%20x"
+    );
+  }
+}
@@ -0,0 +1,104 @@
+use std::process::{ExitStatus, Stdio};
+
+use lettre::{
+  message::{header::ContentType, Mailbox},
+  transport::smtp::authentication::Credentials,
+  AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use thiserror::Error;
+use tokio::{io::AsyncWriteExt, process::Command};
+
+use crate::{
+  config::EmailConfig,
+  export::ExportedArticle,
+  secrets::{self, SecretError},
+};
+
+#[derive(Error, Debug)]
+pub enum EmailError {
+  #[error("No recipient configured, set `email.to`")]
+  NoRecipient,
+
+  #[error("Neither `email.command` nor `email.smtp` is configured")]
+  NoTransport,
+
+  #[error("Invalid address: {0}")]
+  AddressError(#[from] lettre::address::AddressError),
+
+  #[error("Failed to build message: {0}")]
+  MessageError(#[from] lettre::error::Error),
+
+  #[error("SMTP error: {0}")]
+  SmtpError(#[from] lettre::transport::smtp::Error),
+
+  #[error("{0}")]
+  SecretError(#[from] SecretError),
+
+  #[error("IO error: {0}")]
+  IoError(#[from] std::io::Error),
+
+  #[error("Email command failed: {0}")]
+  CommandFailed(ExitStatus),
+}
+
+/// Mails the article's link and text to the configured recipient.
+pub async fn send_article(
+  config: &EmailConfig,
+  article: &ExportedArticle,
+) -> Result<(), EmailError> {
+  let to: Mailbox = config.to.as_deref().ok_or(EmailError::NoRecipient)?.parse()?;
+  let from = match &config.from {
+    Some(from) => from.parse()?,
+    None => to.clone(),
+  };
+  let message = Message::builder()
+    .from(from)
+    .to(to)
+    .subject(&article.title)
+    .header(ContentType::TEXT_PLAIN)
+    .body(article.markdown())?;
+
+  if let Some(command) = &config.command {
+    return send_with_command(command, &message.formatted()).await;
+  }
+  let Some(smtp) = &config.smtp else {
+    return Err(EmailError::NoTransport);
+  };
+  let mut transport = if smtp.starttls {
+    AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)?
+  } else {
+    AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)?
+  };
+  if let Some(port) = smtp.port {
+    transport = transport.port(port);
+  }
+  let password = match (&smtp.password, &smtp.password_secret) {
+    (Some(password), _) => Some(password.clone()),
+    (None, Some(name)) => Some(secrets::get(name)?),
+    (None, None) => None,
+  };
+  if let (Some(username), Some(password)) = (&smtp.username, password) {
+    transport = transport.credentials(Credentials::new(username.clone(), password));
+  }
+  transport.build().send(message).await?;
+  Ok(())
+}
+
+async fn send_with_command(command: &str, message: &[u8]) -> Result<(), EmailError> {
+  let mut child = Command::new("sh")
+    .arg("-c")
+    .arg(command)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn()?;
+  if let Some(mut stdin) = child.stdin.take() {
+    stdin.write_all(message).await?;
+  }
+  let status = child.wait().await?;
+  if status.success() {
+    Ok(())
+  } else {
+    Err(EmailError::CommandFailed(status))
+  }
+}
@@ -0,0 +1,563 @@
+use std::{
+  fs::File,
+  io::{self, Write},
+  path::Path,
+};
+
+use chrono::Utc;
+use html5ever::{parse_document, tendril::TendrilSink, tree_builder::TreeBuilderOpts, ParseOpts};
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use thiserror::Error;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+  db::Highlight,
+  digest::{markdown_text, markdown_url},
+};
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+  #[error("IO error: {0}")]
+  IoError(#[from] io::Error),
+
+  #[error("EPUB error: {0}")]
+  ZipError(#[from] zip::result::ZipError),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum ExportFormat {
+  #[default]
+  Markdown,
+  Html,
+  Epub,
+}
+
+impl ExportFormat {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      ExportFormat::Markdown => "md",
+      ExportFormat::Html => "html",
+      ExportFormat::Epub => "epub",
+    }
+  }
+
+  pub fn next(&self) -> Self {
+    match self {
+      ExportFormat::Markdown => ExportFormat::Html,
+      ExportFormat::Html => ExportFormat::Epub,
+      ExportFormat::Epub => ExportFormat::Markdown,
+    }
+  }
+}
+
+/// An article as shown in the reader, ready to be written out.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedArticle {
+  pub title: String,
+  pub url: String,
+  /// The extracted article HTML.
+  pub content: String,
+}
+
+impl ExportedArticle {
+  /// A file name derived from the article title, without extension.
+  pub fn file_stem(&self) -> String {
+    let stem: String = self
+      .title
+      .chars()
+      .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+      .collect();
+    let stem = stem.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    if stem.is_empty() {
+      "article".to_string()
+    } else {
+      stem
+    }
+  }
+
+  /// The saved highlights as one document, quoted under the article each was taken from.
+  pub fn from_highlights(highlights: &[Highlight]) -> Self {
+    let mut content = String::new();
+    let mut article = None;
+    for highlight in highlights {
+      if article != Some((&highlight.title, &highlight.url)) {
+        article = Some((&highlight.title, &highlight.url));
+        content.push_str(&format!(
+          "<h2><a href=\"{}\">{}</a></h2>\n<p><em>{}</em></p>\n",
+          escape(&highlight.url),
+          escape(&highlight.title),
+          escape(&highlight.feed_name)
+        ));
+      }
+      let lines: Vec<String> = highlight.text.lines().map(escape).collect();
+      content.push_str(&format!("<blockquote><p>{}</p></blockquote>\n", lines.join("<br/>")));
+    }
+    Self { title: "Highlights".to_string(), url: String::new(), content }
+  }
+
+  /// The article as Markdown, headed by its title and link.
+  pub fn markdown(&self) -> String {
+    article_markdown(self, &parse_html(&self.content))
+  }
+}
+
+pub fn export_article(
+  article: &ExportedArticle,
+  format: ExportFormat,
+  path: &Path,
+) -> Result<(), ExportError> {
+  let document = parse_html(&article.content);
+  match format {
+    ExportFormat::Markdown => std::fs::write(path, article_markdown(article, &document))?,
+    ExportFormat::Html => std::fs::write(path, to_standalone_html(article, &document))?,
+    ExportFormat::Epub => write_epub(article, &document, File::create(path)?)?,
+  }
+  Ok(())
+}
+
+/// Headed by the title and, unless the article has none, its link.
+fn article_markdown(article: &ExportedArticle, document: &Handle) -> String {
+  let title = markdown_text(&article.title);
+  if article.url.is_empty() {
+    return format!("# {title}\n\n{}", to_markdown(document));
+  }
+  format!("# {title}\n\n<{}>\n\n{}", markdown_url(&article.url), to_markdown(document))
+}
+
+fn parse_html(html: &str) -> Handle {
+  let opts = ParseOpts {
+    tree_builder: TreeBuilderOpts { drop_doctype: true, ..Default::default() },
+    ..Default::default()
+  };
+  parse_document(RcDom::default(), opts).one(html).document
+}
+
+fn attribute(handle: &Handle, attribute: &str) -> Option<String> {
+  match &handle.data {
+    NodeData::Element { attrs, .. } => {
+      attrs
+        .borrow()
+        .iter()
+        .find(|attr| attr.name.local.as_ref() == attribute)
+        .map(|attr| attr.value.to_string())
+    },
+    _ => None,
+  }
+}
+
+/// Converts a parsed document to Markdown by walking the DOM.
+fn to_markdown(document: &Handle) -> String {
+  let mut markdown = String::new();
+  walk_markdown(document, &mut markdown, &mut Vec::new());
+  let mut collapsed = String::new();
+  for line in markdown.lines() {
+    if line.trim().is_empty() && (collapsed.is_empty() || collapsed.ends_with("\n\n")) {
+      continue;
+    }
+    collapsed.push_str(line.trim_end());
+    collapsed.push('\n');
+  }
+  collapsed
+}
+
+/// Ordinals of the lists enclosing the current node, `None` for unordered lists.
+type ListStack = Vec<Option<usize>>;
+
+fn walk_children(handle: &Handle, markdown: &mut String, lists: &mut ListStack) {
+  for child in handle.children.borrow().iter() {
+    walk_markdown(child, markdown, lists);
+  }
+}
+
+fn walk_markdown(handle: &Handle, markdown: &mut String, lists: &mut ListStack) {
+  match &handle.data {
+    NodeData::Document => walk_children(handle, markdown, lists),
+    NodeData::Text { contents } => {
+      let mut text = String::new();
+      let mut last_was_space = markdown.ends_with([' ', '\n']);
+      for c in contents.borrow().chars() {
+        if c.is_whitespace() {
+          if !last_was_space {
+            text.push(' ');
+          }
+          last_was_space = true;
+        } else {
+          text.push(c);
+          last_was_space = false;
+        }
+      }
+      markdown.push_str(&markdown_text(&text));
+    },
+    NodeData::Element { name, .. } => {
+      match name.local.as_ref() {
+        "script" | "style" | "head" => {},
+        "p" | "div" | "section" | "article" => {
+          markdown.push_str("\n\n");
+          walk_children(handle, markdown, lists);
+          markdown.push_str("\n\n");
+        },
+        heading @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+          let level = heading[1..].parse().unwrap_or(1);
+          markdown.push_str("\n\n");
+          markdown.push_str(&"#".repeat(level));
+          markdown.push(' ');
+          walk_children(handle, markdown, lists);
+          markdown.push_str("\n\n");
+        },
+        "br" => markdown.push_str("  \n"),
+        "hr" => markdown.push_str("\n\n---\n\n"),
+        "strong" | "b" => {
+          markdown.push_str("**");
+          walk_children(handle, markdown, lists);
+          markdown.push_str("**");
+        },
+        "em" | "i" => {
+          markdown.push('*');
+          walk_children(handle, markdown, lists);
+          markdown.push('*');
+        },
+        "code" => {
+          markdown.push('`');
+          markdown.push_str(&text_content(handle));
+          markdown.push('`');
+        },
+        "pre" => {
+          markdown.push_str("\n\n```\n");
+          markdown.push_str(&text_content(handle));
+          markdown.push_str("\n```\n\n");
+        },
+        "blockquote" => {
+          let mut quote = String::new();
+          walk_children(handle, &mut quote, lists);
+          markdown.push_str("\n\n");
+          for line in to_markdown_lines(&quote) {
+            markdown.push_str("> ");
+            markdown.push_str(line);
+            markdown.push('\n');
+          }
+          markdown.push('\n');
+        },
+        "a" => {
+          markdown.push('[');
+          walk_children(handle, markdown, lists);
+          markdown.push(']');
+          let href = attribute(handle, "href").unwrap_or_default();
+          markdown.push_str(&format!("({})", markdown_url(&href)));
+        },
+        "img" => {
+          let alt = markdown_text(&attribute(handle, "alt").unwrap_or_default());
+          let src = markdown_url(&attribute(handle, "src").unwrap_or_default());
+          markdown.push_str(&format!("![{alt}]({src})"));
+        },
+        "ul" | "ol" => {
+          lists.push((name.local.as_ref() == "ol").then_some(1));
+          markdown.push('\n');
+          walk_children(handle, markdown, lists);
+          lists.pop();
+          markdown.push('\n');
+        },
+        "li" => {
+          let depth = lists.len().saturating_sub(1);
+          markdown.push('\n');
+          markdown.push_str(&"  ".repeat(depth));
+          match lists.last_mut() {
+            Some(Some(ordinal)) => {
+              markdown.push_str(&format!("{ordinal}. "));
+              *ordinal += 1;
+            },
+            _ => markdown.push_str("- "),
+          }
+          walk_children(handle, markdown, lists);
+        },
+        _ => walk_children(handle, markdown, lists),
+      }
+    },
+    _ => {},
+  }
+}
+
+fn to_markdown_lines(markdown: &str) -> impl Iterator<Item = &str> {
+  markdown.trim().lines().map(str::trim_end)
+}
+
+fn text_content(handle: &Handle) -> String {
+  let mut text = String::new();
+  for child in handle.children.borrow().iter() {
+    match &child.data {
+      NodeData::Text { contents } => text.push_str(&contents.borrow()),
+      _ => text.push_str(&text_content(child)),
+    }
+  }
+  text
+}
+
+const VOID_ELEMENTS: [&str; 13] = [
+  "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+  "wbr",
+];
+
+/// Elements left out of the HTML and EPUB exports, along with everything inside them.
+const DROPPED_ELEMENTS: [&str; 5] = ["script", "style", "iframe", "object", "embed"];
+
+/// Attributes that hold a link, which are only kept when it is a web or mail link.
+const URL_ATTRIBUTES: [&str; 5] = ["href", "src", "action", "formaction", "poster"];
+
+/// Whether an attribute from feed HTML is safe to copy into an export: event handlers are dropped,
+/// and links are only kept when they point at the web or a mail address.
+fn is_safe_attribute(name: &str, value: &str) -> bool {
+  if name.to_ascii_lowercase().starts_with("on") {
+    return false;
+  }
+  if !URL_ATTRIBUTES.contains(&name.to_ascii_lowercase().as_str()) {
+    return true;
+  }
+  Url::parse(value.trim()).is_ok_and(|url| matches!(url.scheme(), "http" | "https" | "mailto"))
+}
+
+pub fn escape(text: &str) -> String {
+  text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Serializes the children of the document's `<body>` as well-formed XHTML, which is valid for
+/// both the HTML and EPUB exports.
+fn body_xhtml(document: &Handle) -> String {
+  let mut xhtml = String::new();
+  match find_element(document, "body") {
+    Some(body) => {
+      for child in body.children.borrow().iter() {
+        write_xhtml(child, &mut xhtml);
+      }
+    },
+    None => write_xhtml(document, &mut xhtml),
+  }
+  xhtml
+}
+
+fn find_element(handle: &Handle, tag_name: &str) -> Option<Handle> {
+  if let NodeData::Element { name, .. } = &handle.data {
+    if name.local.as_ref() == tag_name {
+      return Some(handle.clone());
+    }
+  }
+  handle.children.borrow().iter().find_map(|child| find_element(child, tag_name))
+}
+
+fn write_xhtml(handle: &Handle, xhtml: &mut String) {
+  match &handle.data {
+    NodeData::Document => {
+      for child in handle.children.borrow().iter() {
+        write_xhtml(child, xhtml);
+      }
+    },
+    NodeData::Text { contents } => xhtml.push_str(&escape(&contents.borrow())),
+    NodeData::Element { name, attrs, .. } => {
+      let tag_name = name.local.as_ref();
+      if DROPPED_ELEMENTS.contains(&tag_name) {
+        return;
+      }
+      xhtml.push('<');
+      xhtml.push_str(tag_name);
+      for attr in attrs.borrow().iter() {
+        if !is_safe_attribute(attr.name.local.as_ref(), &attr.value) {
+          continue;
+        }
+        xhtml.push_str(&format!(" {}=\"{}\"", attr.name.local.as_ref(), escape(&attr.value)));
+      }
+      if VOID_ELEMENTS.contains(&tag_name) {
+        xhtml.push_str("/>");
+        return;
+      }
+      xhtml.push('>');
+      for child in handle.children.borrow().iter() {
+        write_xhtml(child, xhtml);
+      }
+      xhtml.push_str(&format!("</{tag_name}>"));
+    },
+    _ => {},
+  }
+}
+
+fn to_standalone_html(article: &ExportedArticle, document: &Handle) -> String {
+  format!(
+    "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\"/>
+<title>{title}</title>
+<style>body {{ max-width: 40em; margin: 2em auto; font-family: serif; line-height: 1.5; }}</style>
+</head>
+<body>
+<h1>{title}</h1>
+{link}{body}
+</body>
+</html>
+",
+    title = escape(&article.title),
+    link = link_paragraph(&article.url),
+    body = body_xhtml(document),
+  )
+}
+
+/// The article's link on a line of its own, or nothing when it has none.
+fn link_paragraph(url: &str) -> String {
+  if url.is_empty() {
+    return String::new();
+  }
+  format!("<p><a href=\"{0}\">{0}</a></p>\n", escape(url))
+}
+
+/// EPUB readers need an identifier, which is the article's link when it has one.
+fn epub_identifier(article: &ExportedArticle) -> String {
+  if article.url.is_empty() {
+    return format!("urn:{}:{}", env!("CARGO_PKG_NAME"), article.file_stem());
+  }
+  article.url.clone()
+}
+
+fn write_epub<W: Write + io::Seek>(
+  article: &ExportedArticle,
+  document: &Handle,
+  writer: W,
+) -> Result<(), ExportError> {
+  let title = escape(&article.title);
+  let mut zip = ZipWriter::new(writer);
+
+  // The mimetype must be the first entry and must not be compressed.
+  zip
+    .start_file("mimetype", FileOptions::default().compression_method(CompressionMethod::Stored))?;
+  zip.write_all(b"application/epub+zip")?;
+
+  let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+  zip.start_file("META-INF/container.xml", options)?;
+  zip.write_all(
+    br#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#,
+  )?;
+
+  zip.start_file("OEBPS/content.opf", options)?;
+  zip.write_all(
+    format!(
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="id">{url}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:language>en</dc:language>
+    <meta property="dcterms:modified">{modified}</meta>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="article" href="article.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="article"/>
+  </spine>
+</package>
+"#,
+      url = escape(&epub_identifier(article)),
+      modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+    )
+    .as_bytes(),
+  )?;
+
+  zip.start_file("OEBPS/nav.xhtml", options)?;
+  zip.write_all(
+    format!(
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head><title>{title}</title></head>
+<body>
+  <nav epub:type="toc"><ol><li><a href="article.xhtml">{title}</a></li></ol></nav>
+</body>
+</html>
+"#
+    )
+    .as_bytes(),
+  )?;
+
+  zip.start_file("OEBPS/article.xhtml", options)?;
+  zip.write_all(
+    format!(
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head><title>{title}</title></head>
+<body>
+<h1>{title}</h1>
+{body}
+</body>
+</html>
+"#,
+      body = body_xhtml(document),
+    )
+    .as_bytes(),
+  )?;
+
+  zip.finish()?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  fn article(title: &str, url: &str, content: &str) -> ExportedArticle {
+    ExportedArticle { title: title.to_string(), url: url.to_string(), content: content.to_string() }
+  }
+
+  #[test]
+  fn converts_links_and_images_to_markdown() {
+    let article = article(
+      "Rust",
+      "https://example.com/rust",
+      "<p>See <a href=\"https://example.com/a b\">the <em>docs</em></a>.</p>\
+       <p><img alt=\"Ferris\" src=\"https://example.com/ferris.png\"/></p>",
+    );
+    assert_eq!(
+      article.markdown(),
+      "# Rust\n\n<https://example.com/rust>\n\nSee [the *docs*](https://example.com/a%20b).\n\n\
+       ![Ferris](https://example.com/ferris.png)\n\n"
+    );
+  }
+
+  #[test]
+  fn escapes_titles_text_and_links_in_markdown() {
+    let article = article(
+      "#1 [Rust] tips",
+      "https://en.wikipedia.org/wiki/Rust_(language) x",
+      "<p><a href=\"https://example.com/(a)\">[x]</a> <code>a[0]</code></p>\
+       <img alt=\"a]b\" src=\"https://example.com/a b.png\"/>",
+    );
+    assert_eq!(
+      article.markdown(),
+      "# \\#1 \\[Rust\\] tips\n\n<https://en.wikipedia.org/wiki/Rust_%28language%29%20x>\n\n\
+       [\\[x\\]](https://example.com/%28a%29) `a[0]`\n\n![a\\]b](https://example.com/a%20b.png)\n"
+    );
+  }
+
+  #[test]
+  fn drops_scripts_handlers_and_unsafe_links_from_xhtml() {
+    let document = parse_html(
+      "<p onclick=\"steal()\" class=\"lead\">Hi <a href=\"javascript:steal()\">there</a> \
+       <a href=\"https://example.com\" onmouseover=\"steal()\">link</a> \
+       <a href=\"mailto:me@example.com\">mail</a></p>\
+       <img src=\"data:image/png;base64,AAAA\"/><img src=\"https://example.com/a.png\"/>\
+       <script>steal()</script><style>p {}</style><iframe src=\"https://example.com\"></iframe>\
+       <object data=\"x\"></object><embed src=\"https://example.com/x\"/>",
+    );
+    assert_eq!(
+      body_xhtml(&document),
+      "<p class=\"lead\">Hi <a>there</a> <a href=\"https://example.com\">link</a> \
+       <a href=\"mailto:me@example.com\">mail</a></p><img/><img src=\"https://example.com/a.png\"/>"
+    );
+  }
+}
@@ -0,0 +1,99 @@
+use readability::extractor;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::html;
+
+/// How the text shown in the reader was found, from the most to the least reliable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Extraction {
+  /// Readability found the article in the page.
+  Readability,
+  /// The full text the feed itself carries, e.g. in `content:encoded`.
+  FeedContent,
+  /// The elements matching the feed's `content_selector`.
+  Selector,
+  /// Everything in the page's `<body>`.
+  Body,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Extracted {
+  /// HTML for the reader.
+  pub content: String,
+  pub extraction: Extraction,
+}
+
+/// Finds the article in `page`, the HTML at `url`. Readability is tried first, and whenever a
+/// strategy comes up with less than `min_chars` of text the next one is tried: the content the
+/// feed provided, the feed's CSS `selector`, then the whole body. If none is long enough, the
+/// longest is used.
+pub fn extract(
+  page: &str,
+  url: &str,
+  feed_content: &str,
+  selector: Option<&str>,
+  min_chars: usize,
+) -> Option<Extracted> {
+  let document = Html::parse_document(page);
+  let candidates = [
+    (Extraction::Readability, readability(page, url)),
+    (Extraction::FeedContent, Some(feed_content.to_string())),
+    (Extraction::Selector, selector.and_then(|selector| select(&document, selector))),
+    (Extraction::Body, select(&document, "body")),
+  ];
+
+  let mut longest: Option<(usize, Extracted)> = None;
+  for (extraction, content) in candidates {
+    let Some(content) = content else {
+      continue;
+    };
+    let chars = html::to_plain_text(&content).chars().count();
+    if chars >= min_chars {
+      return Some(Extracted { content, extraction });
+    }
+    let longer = longest.as_ref().map_or(chars > 0, |(longest, _)| chars > *longest);
+    if longer {
+      longest = Some((chars, Extracted { content, extraction }));
+    }
+  }
+  longest.map(|(_, extracted)| extracted)
+}
+
+fn readability(page: &str, url: &str) -> Option<String> {
+  let url = Url::parse(url).ok()?;
+  match extractor::extract(&mut page.as_bytes(), &url) {
+    Ok(product) => Some(product.content),
+    Err(error) => {
+      log::warn!("Readability failed on {url}: {error}");
+      None
+    },
+  }
+}
+
+/// The HTML of every element matching `selector`, one after the other.
+fn select(document: &Html, selector: &str) -> Option<String> {
+  let selector = match Selector::parse(selector) {
+    Ok(selector) => selector,
+    Err(error) => {
+      log::warn!("Invalid content selector {selector:?}: {error}");
+      return None;
+    },
+  };
+  let content: String = document.select(&selector).map(without_scripts).collect();
+  Some(content).filter(|content| !content.is_empty())
+}
+
+/// The inner HTML of `element`, leaving out the scripts and styles a page body is full of.
+fn without_scripts(element: ElementRef) -> String {
+  let mut content = element.inner_html();
+  for child in element.descendants().filter_map(ElementRef::wrap) {
+    if matches!(child.value().name(), "script" | "style" | "noscript") {
+      content = content.replace(&child.html(), "");
+    }
+  }
+  content
+}
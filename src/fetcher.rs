@@ -0,0 +1,1181 @@
+use std::{collections::HashMap, future::Future, sync::Mutex, time::Duration};
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+  header::{CONTENT_TYPE, LOCATION, RETRY_AFTER, USER_AGENT},
+  redirect::Policy,
+  Client, Response, StatusCode, Url,
+};
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::time::Instant;
+
+use crate::{
+  config::{FeedConfig, FeedKind, FetchConfig},
+  db::FeedItem,
+  export::escape,
+  html,
+  media::Enclosure,
+  secrets::{self, SecretError},
+};
+
+#[derive(Error, Debug)]
+pub enum FetchError {
+  #[error("Network error: {0}")]
+  ReqwestError(#[from] reqwest::Error),
+
+  #[error("Request timed out")]
+  Timeout,
+
+  #[error("HTTP status {status}")]
+  Status { status: StatusCode, retry_after: Option<Duration> },
+
+  #[error("RSS error: {0}")]
+  RssError(#[from] rss::Error),
+
+  #[error("Atom error: {0}")]
+  AtomError(#[from] atom_syndication::Error),
+
+  #[error("JSON error: {0}")]
+  JsonError(#[from] serde_json::Error),
+
+  #[error("Not a Mastodon account or hashtag: {0}")]
+  InvalidMastodonLink(String),
+
+  #[error("Too many redirects")]
+  TooManyRedirects,
+
+  #[error("Redirect without a valid location")]
+  InvalidRedirect,
+
+  #[error("{0}")]
+  SecretError(#[from] SecretError),
+
+  #[error("No RSS feed found on the page")]
+  NoFeedFound,
+}
+
+impl FetchError {
+  /// Whether the request could succeed if it is tried again.
+  pub fn is_retryable(&self) -> bool {
+    match self {
+      FetchError::ReqwestError(error) => error.is_timeout() || error.is_connect(),
+      FetchError::Timeout => true,
+      FetchError::Status { status, .. } => {
+        status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+      },
+      FetchError::RssError(_)
+      | FetchError::AtomError(_)
+      | FetchError::JsonError(_)
+      | FetchError::InvalidMastodonLink(_)
+      | FetchError::TooManyRedirects
+      | FetchError::InvalidRedirect
+      | FetchError::SecretError(_)
+      | FetchError::NoFeedFound => false,
+    }
+  }
+}
+
+/// Paths sites commonly serve their feed at, tried when a page links to none.
+const FEED_PATHS: [&str; 4] = ["/feed", "/rss.xml", "/feed.xml", "/index.xml"];
+const ARXIV_API: &str = "https://export.arxiv.org/api/query";
+/// How many of the newest papers matching an arXiv query are fetched.
+const ARXIV_RESULTS: usize = 50;
+const HN_API: &str = "https://hacker-news.firebaseio.com/v0";
+/// How many stories from the top of a Hacker News list are fetched.
+const HN_STORIES: usize = 30;
+/// How many of the newest posts of a Mastodon account or hashtag are fetched, the API's maximum.
+const MASTODON_POSTS: usize = 40;
+/// Longest title made from the start of a post, in characters.
+const MASTODON_TITLE_LEN: usize = 80;
+
+/// A feed as fetched from the network, not yet linked to anything in the database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchedFeed {
+  pub name: String,
+  pub desc: String,
+  pub url: String,
+  /// The website the feed belongs to, if it names one.
+  pub site_url: Option<String>,
+  /// Where the feed now lives, if it was permanently redirected from its configured link.
+  pub moved_to: Option<String>,
+  /// Whether the config mutes this feed.
+  pub muted: bool,
+  /// Age in days after which the feed's articles are marked read.
+  pub mark_read_after_days: Option<u32>,
+  pub items: Vec<FeedItem>,
+}
+
+/// A downloaded document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+  pub body: String,
+  /// The final URL, set only when every redirect on the way there was permanent.
+  pub moved_to: Option<String>,
+}
+
+/// The transport used to download feed documents, so that fetching can run without a network.
+pub trait Transport {
+  /// Downloads `url`, identifying as `user_agent` instead of the default when given.
+  fn get(
+    &self,
+    url: &str,
+    user_agent: Option<&str>,
+  ) -> impl Future<Output = Result<Document, FetchError>> + Send;
+}
+
+pub struct HttpTransport {
+  client: Client,
+  max_redirects: usize,
+}
+
+impl HttpTransport {
+  pub fn new(config: &FetchConfig) -> Result<Self, FetchError> {
+    let client = Client::builder()
+      .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+      // Redirects are followed by hand, to tell permanent moves from temporary ones.
+      .redirect(Policy::none())
+      .build()?;
+    Ok(Self { client, max_redirects: config.max_redirects })
+  }
+
+  /// Sends a request for `url`, following redirects by hand, and returns the successful
+  /// response, along with its final URL when every redirect on the way there was permanent.
+  async fn send(
+    &self,
+    url: &str,
+    user_agent: Option<&str>,
+  ) -> Result<(Response, Option<String>), FetchError> {
+    let mut current = url.to_string();
+    let mut permanent = true;
+    for _ in 0..=self.max_redirects {
+      let mut request = self.client.get(&current);
+      if let Some(user_agent) = user_agent {
+        request = request.header(USER_AGENT, user_agent);
+      }
+      // Errors would otherwise carry the URL, along with any secrets filled into it.
+      let response = request.send().await.map_err(reqwest::Error::without_url)?;
+      let status = response.status();
+      // Not a redirect, though in the 3xx range. Requests are never conditional, so there is no
+      // cached copy it could refer to.
+      if status == StatusCode::NOT_MODIFIED {
+        return Err(FetchError::Status { status, retry_after: None });
+      }
+      if status.is_redirection() {
+        let location = response
+          .headers()
+          .get(LOCATION)
+          .and_then(|location| location.to_str().ok())
+          .and_then(|location| response.url().join(location).ok())
+          .ok_or(FetchError::InvalidRedirect)?;
+        permanent &=
+          status == StatusCode::MOVED_PERMANENTLY || status == StatusCode::PERMANENT_REDIRECT;
+        current = location.to_string();
+        continue;
+      }
+      if !status.is_success() {
+        return Err(FetchError::Status { status, retry_after: retry_after(&response) });
+      }
+      let moved_to = (permanent && current != url).then_some(current);
+      return Ok((response, moved_to));
+    }
+    Err(FetchError::TooManyRedirects)
+  }
+}
+
+impl Transport for HttpTransport {
+  async fn get(&self, url: &str, user_agent: Option<&str>) -> Result<Document, FetchError> {
+    let (response, moved_to) = self.send(url, user_agent).await?;
+    let body = response.text().await.map_err(reqwest::Error::without_url)?;
+    Ok(Document { body, moved_to })
+  }
+}
+
+/// Spaces out the requests sent to each host, as configured by `host_requests_per_minute`.
+#[derive(Default)]
+struct HostLimiter {
+  /// When each host may next be sent a request.
+  next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostLimiter {
+  /// Waits for the host of `url` to be free, reserving the slot after it for the next request.
+  async fn wait(&self, config: &FetchConfig, url: &str) {
+    let Some(host) = Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string)) else {
+      return;
+    };
+    let per_minute = config.host_requests_per_minute(&host);
+    if per_minute == 0 {
+      return;
+    }
+    let interval = Duration::from_secs(60) / per_minute;
+    let slot = {
+      let mut next_slot = self.next_slot.lock().unwrap();
+      let now = Instant::now();
+      let slot = next_slot.get(&host).map_or(now, |next| (*next).max(now));
+      next_slot.insert(host.clone(), slot + interval);
+      slot
+    };
+    if slot > Instant::now() {
+      log::debug!("Waiting {}ms to fetch from {host}", (slot - Instant::now()).as_millis());
+      tokio::time::sleep_until(slot).await;
+    }
+  }
+}
+
+pub struct Fetcher<T: Transport = HttpTransport> {
+  transport: T,
+  config: FetchConfig,
+  limiter: HostLimiter,
+}
+
+impl Fetcher<HttpTransport> {
+  pub fn new(config: FetchConfig) -> Result<Self, FetchError> {
+    let transport = HttpTransport::new(&config)?;
+    Ok(Self { transport, config, limiter: HostLimiter::default() })
+  }
+
+  /// Starts downloading `url`, returning the response to read the body of as it arrives, for
+  /// files such as podcast episodes that are too large to hold in memory.
+  pub async fn download(&self, url: &str) -> Result<Response, FetchError> {
+    self.limiter.wait(&self.config, url).await;
+    Ok(self.transport.send(url, None).await?.0)
+  }
+
+  /// Posts `body` as JSON to `url`, such as a summarizer's endpoint, and returns the answer. It is
+  /// sent once, as a POST need not be safe to repeat.
+  pub async fn post_json(&self, url: &str, body: &serde_json::Value) -> Result<String, FetchError> {
+    let response = self
+      .transport
+      .client
+      .post(url)
+      .header(CONTENT_TYPE, "application/json")
+      .body(body.to_string())
+      .send()
+      .await
+      .map_err(reqwest::Error::without_url)?;
+    let status = response.status();
+    if !status.is_success() {
+      return Err(FetchError::Status { status, retry_after: retry_after(&response) });
+    }
+    Ok(response.text().await.map_err(reqwest::Error::without_url)?)
+  }
+}
+
+impl<T: Transport> Fetcher<T> {
+  pub fn with_transport(transport: T, config: FetchConfig) -> Self {
+    Self { transport, config, limiter: HostLimiter::default() }
+  }
+
+  /// Downloads and parses a feed, retrying transient failures with exponential backoff.
+  ///
+  /// Each attempt is bounded by the feed's own timeout, falling back to the global one.
+  pub async fn fetch_feed(&self, feed: &FeedConfig) -> Result<FetchedFeed, FetchError> {
+    let timeout = Duration::from_secs(feed.timeout_secs.unwrap_or(self.config.timeout_secs));
+    let mut fetched = match feed.kind {
+      FeedKind::Rss => {
+        let url = secrets::expand(&feed.link)?;
+        // Logged by its configured link, which has the placeholders rather than the secrets.
+        let document = self.get_with_retry_as(&url, redact(&feed.link), timeout).await?;
+        let channel = rss::Channel::read_from(document.body.as_bytes())?;
+        let mut fetched = parse_channel(feed, &channel);
+        // The new location would have the secrets filled in, so the link is left as configured.
+        if !secrets::has_placeholders(&feed.link) {
+          fetched.moved_to = document.moved_to;
+        }
+        fetched
+      },
+      FeedKind::Arxiv => self.fetch_arxiv(feed, timeout).await?,
+      FeedKind::Hn => self.fetch_hn(feed, timeout).await?,
+      FeedKind::Mastodon => self.fetch_mastodon(feed, timeout).await?,
+    };
+    fetched.mark_read_after_days = feed.mark_read_after_days.or(self.config.mark_read_after_days);
+    for item in &mut fetched.items {
+      for rule in &feed.rewrite {
+        rule.apply(&mut item.title, &mut item.desc);
+      }
+    }
+    Ok(fetched)
+  }
+
+  /// Fetches the newest papers matching the feed's arXiv search query.
+  async fn fetch_arxiv(
+    &self,
+    feed: &FeedConfig,
+    timeout: Duration,
+  ) -> Result<FetchedFeed, FetchError> {
+    let document = self.get_with_retry(&arxiv_url(&feed.link), timeout).await?;
+    let atom = atom_syndication::Feed::read_from(document.body.as_bytes())?;
+    Ok(parse_arxiv(feed, &atom))
+  }
+
+  /// Fetches the stories at the top of the Hacker News list named by the feed's link, one
+  /// request per story as the API requires.
+  async fn fetch_hn(
+    &self,
+    feed: &FeedConfig,
+    timeout: Duration,
+  ) -> Result<FetchedFeed, FetchError> {
+    let list = self.get_with_retry(&format!("{HN_API}/{}.json", feed.link), timeout).await?;
+    let ids: Vec<u64> = serde_json::from_str(&list.body)?;
+    let stories = futures::future::join_all(ids.iter().take(HN_STORIES).map(|id| {
+      async move {
+        let document = self.get_with_retry(&format!("{HN_API}/item/{id}.json"), timeout).await?;
+        // Deleted stories come back as `null`.
+        Ok::<_, FetchError>(serde_json::from_str::<Option<HnStory>>(&document.body)?)
+      }
+    }))
+    .await;
+
+    let mut items = Vec::new();
+    for story in stories {
+      match story {
+        Ok(Some(story)) => items.push(story.into_item(feed)),
+        Ok(None) => {},
+        Err(error) => log::warn!("Failed to fetch a story of {}: {error}", feed.link),
+      }
+    }
+    Ok(FetchedFeed {
+      name: feed.name.clone().unwrap_or("Hacker News".to_string()),
+      desc: feed.desc.clone().unwrap_or(format!("Hacker News {}", feed.link)),
+      url: feed.link.clone(),
+      site_url: Some("https://news.ycombinator.com".to_string()),
+      moved_to: None,
+      muted: feed.mute,
+      mark_read_after_days: None,
+      items,
+    })
+  }
+
+  /// Fetches the newest posts of the Mastodon account or hashtag named by the feed's link. Some
+  /// instances only open their API to signed-in users, so a refusal falls back to their RSS.
+  async fn fetch_mastodon(
+    &self,
+    feed: &FeedConfig,
+    timeout: Duration,
+  ) -> Result<FetchedFeed, FetchError> {
+    let source = MastodonSource::parse(&feed.link)
+      .ok_or_else(|| FetchError::InvalidMastodonLink(feed.link.clone()))?;
+    let (desc, items) = match self.fetch_mastodon_api(feed, &source, timeout).await {
+      Ok(fetched) => fetched,
+      Err(error @ (FetchError::Status { .. } | FetchError::JsonError(_))) => {
+        log::warn!("Mastodon API of {} failed ({error}), reading its RSS", source.instance());
+        let document = self.get_with_retry(&source.rss_url(), timeout).await?;
+        let channel = rss::Channel::read_from(document.body.as_bytes())?;
+        let mut fetched = parse_channel(feed, &channel);
+        for (item, rss_item) in fetched.items.iter_mut().zip(channel.items()) {
+          // Posts have no title of their own.
+          if item.title.is_empty() {
+            item.title = mastodon_title("", &item.desc, &rss_media_kinds(rss_item));
+          }
+        }
+        (fetched.desc, fetched.items)
+      },
+      Err(error) => return Err(error),
+    };
+    Ok(FetchedFeed {
+      name: feed.name.clone().unwrap_or(source.to_string()),
+      desc: feed.desc.clone().unwrap_or(desc),
+      url: feed.link.clone(),
+      site_url: Some(source.web_url()),
+      moved_to: None,
+      muted: feed.mute,
+      mark_read_after_days: None,
+      items,
+    })
+  }
+
+  /// Reads the posts from the API, returning them with the account's bio or the hashtag.
+  async fn fetch_mastodon_api(
+    &self,
+    feed: &FeedConfig,
+    source: &MastodonSource,
+    timeout: Duration,
+  ) -> Result<(String, Vec<FeedItem>), FetchError> {
+    let (desc, url) = match source {
+      MastodonSource::Account { instance, acct } => {
+        let lookup = self
+          .get_with_retry(
+            &format!("https://{instance}/api/v1/accounts/lookup?acct={acct}"),
+            timeout,
+          )
+          .await?;
+        let account: MastodonAccount = serde_json::from_str(&lookup.body)?;
+        let url = format!(
+          "https://{instance}/api/v1/accounts/{}/statuses?limit={MASTODON_POSTS}&exclude_replies=true",
+          account.id
+        );
+        (html::to_plain_text(&account.note), url)
+      },
+      MastodonSource::Tag { instance, tag } => {
+        let url = format!("https://{instance}/api/v1/timelines/tag/{tag}?limit={MASTODON_POSTS}");
+        (format!("Posts tagged #{tag} on {instance}"), url)
+      },
+    };
+    let document = self.get_with_retry(&url, timeout).await?;
+    let statuses: Vec<MastodonStatus> = serde_json::from_str(&document.body)?;
+    Ok((desc, statuses.into_iter().map(|status| status.into_item(feed)).collect()))
+  }
+
+  /// Downloads the article page at `url`, retrying like a feed under the global timeout.
+  pub async fn fetch_page(&self, url: &str) -> Result<String, FetchError> {
+    let timeout = Duration::from_secs(self.config.timeout_secs);
+    Ok(self.get_with_retry(url, timeout).await?.body)
+  }
+
+  /// Finds the feed behind `url`, which can be the feed itself or a page of its site. A page is
+  /// searched for `<link rel="alternate">` tags naming RSS feeds, and failing those the usual
+  /// feed paths of the site are tried. The feed comes back with `url` set to where it was found.
+  pub async fn discover(&self, url: &str) -> Result<FetchedFeed, FetchError> {
+    let url = if url.contains("://") { url.to_string() } else { format!("https://{url}") };
+    let timeout = Duration::from_secs(self.config.timeout_secs);
+    let document = self.get_with_retry(&url, timeout).await?;
+    if let Ok(channel) = rss::Channel::read_from(document.body.as_bytes()) {
+      let mut fetched = parse_channel(&FeedConfig { link: url, ..Default::default() }, &channel);
+      if let Some(moved_to) = document.moved_to {
+        fetched.url = moved_to;
+      }
+      return Ok(fetched);
+    }
+    let base = Url::parse(document.moved_to.as_deref().unwrap_or(&url))
+      .map_err(|_| FetchError::NoFeedFound)?;
+    for candidate in feed_candidates(&document.body, &base) {
+      let feed = FeedConfig { link: candidate, ..Default::default() };
+      match self.fetch_feed(&feed).await {
+        Ok(mut fetched) => {
+          if let Some(moved_to) = fetched.moved_to.take() {
+            fetched.url = moved_to;
+          }
+          return Ok(fetched);
+        },
+        Err(error) => log::info!("No feed at {}: {error}", redact(&feed.link)),
+      }
+    }
+    Err(FetchError::NoFeedFound)
+  }
+
+  /// Retries transient failures with exponential backoff, or after the delay the server asked
+  /// for. A feed refusing the request with 403 is tried again with each alternate user agent.
+  /// Every attempt waits its turn under the host's rate limit, outside of the timeout.
+  async fn get_with_retry(&self, url: &str, timeout: Duration) -> Result<Document, FetchError> {
+    self.get_with_retry_as(url, redact(url), timeout).await
+  }
+
+  /// [`Self::get_with_retry`], naming the URL as `shown` in the log.
+  async fn get_with_retry_as(
+    &self,
+    url: &str,
+    shown: &str,
+    timeout: Duration,
+  ) -> Result<Document, FetchError> {
+    let mut attempt = 0;
+    let mut user_agents = self.config.user_agents.iter();
+    let mut user_agent = None;
+    loop {
+      self.limiter.wait(&self.config, url).await;
+      let result = tokio::time::timeout(timeout, self.transport.get(url, user_agent))
+        .await
+        .unwrap_or(Err(FetchError::Timeout));
+      match result {
+        Ok(document) => return Ok(document),
+        Err(FetchError::Status { status: StatusCode::FORBIDDEN, .. })
+          if !user_agents.as_slice().is_empty() =>
+        {
+          user_agent = user_agents.next().map(String::as_str);
+          log::warn!("Fetching {shown} was refused, retrying with another user agent");
+        },
+        Err(error) if error.is_retryable() && attempt < self.config.retries => {
+          let backoff =
+            Duration::from_millis(self.config.backoff_ms.saturating_mul(1 << attempt.min(16)));
+          let delay = match &error {
+            FetchError::Status { retry_after: Some(retry_after), .. } => {
+              (*retry_after).min(Duration::from_secs(self.config.max_retry_after_secs))
+            },
+            _ => backoff,
+          };
+          log::warn!("Fetching {shown} failed ({error}), retrying in {}ms", delay.as_millis());
+          tokio::time::sleep(delay).await;
+          attempt += 1;
+        },
+        Err(error) => return Err(error),
+      }
+    }
+  }
+}
+
+/// The RSS feeds `page` links to, or the usual feed paths when it links to none, resolved
+/// against `base`.
+fn feed_candidates(page: &str, base: &Url) -> Vec<String> {
+  let document = Html::parse_document(page);
+  let selector =
+    Selector::parse(r#"link[rel~="alternate"][type="application/rss+xml"][href]"#).unwrap();
+  let mut candidates: Vec<String> = document
+    .select(&selector)
+    .filter_map(|link| base.join(link.value().attr("href")?).ok())
+    .map(|url| url.to_string())
+    .collect();
+  if candidates.is_empty() {
+    candidates = FEED_PATHS
+      .iter()
+      .filter_map(|path| base.join(path).ok())
+      .map(|url| url.to_string())
+      .collect();
+  }
+  candidates.dedup();
+  candidates
+}
+
+/// `url` without its query string, which can carry access tokens, for logging.
+fn redact(url: &str) -> &str {
+  url.split_once('?').map_or(url, |(base, _)| base)
+}
+
+/// Reads a `Retry-After` header given in seconds. The HTTP date form is rare for feeds and ignored.
+fn retry_after(response: &Response) -> Option<Duration> {
+  let retry_after = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+  retry_after.trim().parse().ok().map(Duration::from_secs)
+}
+
+fn parse_channel(feed: &FeedConfig, channel: &rss::Channel) -> FetchedFeed {
+  let items = channel
+    .items()
+    .iter()
+    .map(|item| {
+      FeedItem {
+        id: 0,
+        feed_id: 0,
+        feed_name: String::new(),
+        feed_url: feed.link.clone(),
+        feed_moved_from: None,
+        title: item.title().unwrap_or_default().to_string(),
+        url: item.link().unwrap_or_default().to_string(),
+        desc: item.description().unwrap_or_default().to_string(),
+        content: item.content().unwrap_or_default().to_string(),
+        read: false,
+        new: false,
+        note: None,
+        alert: None,
+        pub_date: item
+          .pub_date()
+          .or_else(|| item.dublin_core_ext().and_then(|dc| dc.dates().first()).map(String::as_str))
+          .and_then(parse_date)
+          .unwrap_or(Utc::now()),
+        comments: item.comments().map(str::to_string),
+        comments_count: slash_comments(item),
+        categories: item.categories().iter().map(|category| category.name().to_string()).collect(),
+        author: item
+          .author()
+          .or_else(|| {
+            item.dublin_core_ext().and_then(|dc| dc.creators().first()).map(String::as_str)
+          })
+          .map(str::to_string),
+        guid: item.guid().map(|guid| guid.value().to_string()),
+        enclosures: rss_enclosures(item),
+      }
+    })
+    .collect();
+
+  FetchedFeed {
+    name: feed.name.clone().unwrap_or(channel.title().to_string()),
+    desc: feed.desc.clone().unwrap_or(html::to_plain_text(channel.description())),
+    url: feed.link.clone(),
+    site_url: Some(channel.link().to_string()).filter(|link| !link.is_empty()),
+    moved_to: None,
+    muted: feed.mute,
+    mark_read_after_days: None,
+    items,
+  }
+}
+
+/// Reads an item's date, which RSS gives in RFC 2822 and Dublin Core and some feeds in RFC 3339.
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+  let date = date.trim();
+  DateTime::parse_from_rfc2822(date)
+    .or_else(|_| DateTime::parse_from_rfc3339(date))
+    .ok()
+    .map(|date| date.with_timezone(&Utc))
+}
+
+/// The item's `<enclosure>` followed by any `media:content` elements, as podcast and photo feeds
+/// attach their files.
+fn rss_enclosures(item: &rss::Item) -> Vec<Enclosure> {
+  let mut enclosures: Vec<Enclosure> = item
+    .enclosure()
+    .map(|enclosure| {
+      Enclosure {
+        url: enclosure.url().to_string(),
+        mime_type: enclosure.mime_type().to_string(),
+        length: enclosure.length().trim().parse().ok().filter(|&length| length > 0),
+      }
+    })
+    .into_iter()
+    .collect();
+  let contents = item.extensions().get("media").and_then(|media| media.get("content"));
+  for content in contents.into_iter().flatten() {
+    let attrs = content.attrs();
+    let Some(url) = attrs.get("url").filter(|url| !url.is_empty()) else {
+      continue;
+    };
+    if enclosures.iter().any(|enclosure| &enclosure.url == url) {
+      continue;
+    }
+    enclosures.push(Enclosure {
+      url: url.clone(),
+      mime_type: attrs.get("type").or(attrs.get("medium")).cloned().unwrap_or_default(),
+      length: attrs.get("fileSize").and_then(|size| size.trim().parse().ok()),
+    });
+  }
+  enclosures
+}
+
+/// Reads the comment count some feeds give in the `slash:comments` extension element.
+fn slash_comments(item: &rss::Item) -> Option<u32> {
+  let extension = item.extensions().get("slash")?.get("comments")?.first()?;
+  extension.value()?.trim().parse().ok()
+}
+
+/// The API request for the newest papers matching the search `query`.
+fn arxiv_url(query: &str) -> String {
+  let mut url = Url::parse(ARXIV_API).unwrap();
+  url
+    .query_pairs_mut()
+    .append_pair("search_query", &collapse_whitespace(query))
+    .append_pair("sortBy", "submittedDate")
+    .append_pair("sortOrder", "descending")
+    .append_pair("max_results", &ARXIV_RESULTS.to_string());
+  url.to_string()
+}
+
+fn parse_arxiv(feed: &FeedConfig, atom: &atom_syndication::Feed) -> FetchedFeed {
+  let items = atom
+    .entries()
+    .iter()
+    .map(|entry| {
+      let url = entry
+        .links()
+        .iter()
+        .find(|link| link.rel() == "alternate")
+        .map_or(entry.id(), |link| link.href());
+      let authors: Vec<&str> = entry.authors().iter().map(|author| author.name()).collect();
+      let summary = entry.summary().map(|summary| summary.as_str()).unwrap_or_default();
+      FeedItem {
+        id: 0,
+        feed_id: 0,
+        feed_name: String::new(),
+        feed_url: feed.link.clone(),
+        feed_moved_from: None,
+        // Titles and abstracts are hard-wrapped in the API response.
+        title: collapse_whitespace(entry.title().as_str()),
+        url: url.to_string(),
+        desc: format!("{} — {}", authors.join(", "), collapse_whitespace(summary)),
+        content: String::new(),
+        read: false,
+        new: false,
+        note: None,
+        alert: None,
+        pub_date: entry.published().unwrap_or(entry.updated()).with_timezone(&Utc),
+        comments: None,
+        comments_count: None,
+        categories: entry.categories().iter().map(|category| category.term().to_string()).collect(),
+        author: Some(authors.join(", ")).filter(|authors| !authors.is_empty()),
+        guid: Some(entry.id().to_string()),
+        enclosures: Vec::new(),
+      }
+    })
+    .collect();
+
+  FetchedFeed {
+    name: feed.name.clone().unwrap_or(format!("arXiv {}", feed.link)),
+    desc: feed.desc.clone().unwrap_or(collapse_whitespace(atom.title().as_str())),
+    url: feed.link.clone(),
+    site_url: Some("https://arxiv.org".to_string()),
+    moved_to: None,
+    muted: feed.mute,
+    mark_read_after_days: None,
+    items,
+  }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+  text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A story as returned by the Hacker News item API.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct HnStory {
+  id: u64,
+  title: String,
+  /// Missing for Ask HN and other text posts.
+  url: Option<String>,
+  by: String,
+  score: u64,
+  descendants: u64,
+  time: i64,
+}
+
+impl HnStory {
+  fn into_item(self, feed: &FeedConfig) -> FeedItem {
+    let discussion = format!("https://news.ycombinator.com/item?id={}", self.id);
+    FeedItem {
+      id: 0,
+      feed_id: 0,
+      feed_name: String::new(),
+      feed_url: feed.link.clone(),
+      feed_moved_from: None,
+      title: self.title,
+      url: self.url.unwrap_or(discussion.clone()),
+      desc: format!("{} points by {} | {} comments", self.score, self.by, self.descendants),
+      content: String::new(),
+      read: false,
+      new: false,
+      note: None,
+      alert: None,
+      pub_date: DateTime::from_timestamp(self.time, 0).unwrap_or(Utc::now()),
+      comments: Some(discussion),
+      comments_count: Some(self.descendants as u32),
+      categories: Vec::new(),
+      author: Some(self.by),
+      guid: Some(self.id.to_string()),
+      enclosures: Vec::new(),
+    }
+  }
+}
+
+/// A Mastodon account or hashtag, as named by the link of a `mastodon` feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MastodonSource {
+  Account { instance: String, acct: String },
+  Tag { instance: String, tag: String },
+}
+
+impl MastodonSource {
+  /// Reads `@user@instance`, `#tag@instance`, `https://instance/@user` or
+  /// `https://instance/tags/tag`.
+  fn parse(link: &str) -> Option<Self> {
+    let link = link.trim();
+    let (instance, name) =
+      if let Some(rest) = link.strip_prefix("https://").or_else(|| link.strip_prefix("http://")) {
+        let (instance, path) = rest.split_once('/')?;
+        let path = path.trim_end_matches('/');
+        (instance, path.strip_prefix("tags/").map_or(path.to_string(), |tag| format!("#{tag}")))
+      } else {
+        let (name, instance) = link.trim_start_matches('@').split_once('@')?;
+        let name = if link.starts_with('#') { name.to_string() } else { format!("@{name}") };
+        (instance, name)
+      };
+    let valid = |part: &str| !part.is_empty() && !part.contains(['/', '?', '#', '@', ' ']);
+    if !valid(instance) {
+      return None;
+    }
+    let instance = instance.to_lowercase();
+    if let Some(tag) = name.strip_prefix('#').filter(|tag| valid(tag)) {
+      Some(MastodonSource::Tag { instance, tag: tag.to_string() })
+    } else {
+      let acct = name.strip_prefix('@').filter(|acct| valid(acct))?;
+      Some(MastodonSource::Account { instance, acct: acct.to_string() })
+    }
+  }
+
+  fn instance(&self) -> &str {
+    match self {
+      MastodonSource::Account { instance, .. } | MastodonSource::Tag { instance, .. } => instance,
+    }
+  }
+
+  /// The page of the account or hashtag on its instance.
+  fn web_url(&self) -> String {
+    match self {
+      MastodonSource::Account { instance, acct } => format!("https://{instance}/@{acct}"),
+      MastodonSource::Tag { instance, tag } => format!("https://{instance}/tags/{tag}"),
+    }
+  }
+
+  fn rss_url(&self) -> String {
+    format!("{}.rss", self.web_url())
+  }
+}
+
+impl std::fmt::Display for MastodonSource {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      MastodonSource::Account { instance, acct } => write!(f, "@{acct}@{instance}"),
+      MastodonSource::Tag { instance, tag } => write!(f, "#{tag}@{instance}"),
+    }
+  }
+}
+
+/// An account as returned by the Mastodon API.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MastodonAccount {
+  id: String,
+  acct: String,
+  /// The bio, in HTML.
+  note: String,
+}
+
+/// A post as returned by the Mastodon API.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MastodonStatus {
+  /// The post's page, missing for some remote posts.
+  url: Option<String>,
+  uri: String,
+  created_at: DateTime<Utc>,
+  content: String,
+  /// The content warning, if any.
+  spoiler_text: String,
+  account: MastodonAccount,
+  /// The post boosted, when this is a boost.
+  reblog: Option<Box<MastodonStatus>>,
+  media_attachments: Vec<MastodonMedia>,
+  replies_count: u32,
+  tags: Vec<MastodonTag>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MastodonMedia {
+  /// `image`, `gifv`, `video` or `audio`.
+  #[serde(rename = "type")]
+  kind: String,
+  url: String,
+  description: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MastodonTag {
+  name: String,
+}
+
+impl MastodonStatus {
+  fn into_item(self, feed: &FeedConfig) -> FeedItem {
+    let created_at = self.created_at;
+    let (status, boosted) = match self.reblog {
+      Some(reblog) => (*reblog, true),
+      None => (self, false),
+    };
+    let media_kinds: Vec<&str> = status.media_attachments.iter().map(|m| m.kind.as_str()).collect();
+    let mut title = mastodon_title(&status.spoiler_text, &status.content, &media_kinds);
+    if boosted {
+      title = format!("Boosted @{}: {title}", status.account.acct);
+    }
+    let mut content = status.content.clone();
+    for media in &status.media_attachments {
+      let label = media.description.as_deref().filter(|d| !d.is_empty()).unwrap_or(&media.kind);
+      // The description is whatever the poster wrote, so none of it may be read as markup.
+      content.push_str(&format!(
+        "<p><a href=\"{}\">[{}] {}</a></p>",
+        escape(&media.url),
+        escape(&media.kind),
+        escape(label)
+      ));
+    }
+    let enclosures = status
+      .media_attachments
+      .into_iter()
+      .map(|media| {
+        Enclosure {
+          url: media.url,
+          mime_type: if media.kind == "gifv" { "video".to_string() } else { media.kind },
+          length: None,
+        }
+      })
+      .collect();
+    let guid = status.uri.clone();
+    let url = status.url.unwrap_or(status.uri);
+    FeedItem {
+      id: 0,
+      feed_id: 0,
+      feed_name: String::new(),
+      feed_url: feed.link.clone(),
+      feed_moved_from: None,
+      title,
+      url: url.clone(),
+      desc: html::to_plain_text(&status.content),
+      content,
+      read: false,
+      new: false,
+      note: None,
+      alert: None,
+      pub_date: created_at,
+      comments: Some(url),
+      comments_count: Some(status.replies_count),
+      categories: status.tags.into_iter().map(|tag| tag.name).collect(),
+      author: Some(format!("@{}", status.account.acct)),
+      guid: Some(guid),
+      enclosures,
+    }
+  }
+}
+
+/// A title for a post, which has none: its content warning or the start of its text, followed
+/// by what media it carries, such as `[2 images]`.
+fn mastodon_title(spoiler: &str, content: &str, media_kinds: &[&str]) -> String {
+  let text = if spoiler.is_empty() {
+    collapse_whitespace(&html::to_plain_text(content))
+  } else {
+    format!("CW: {}", collapse_whitespace(spoiler))
+  };
+  let mut title: String = text.chars().take(MASTODON_TITLE_LEN).collect();
+  if text.chars().count() > MASTODON_TITLE_LEN {
+    title.push('…');
+  }
+  let mut counts: Vec<(&str, usize)> = Vec::new();
+  for kind in media_kinds {
+    let kind = match *kind {
+      "gifv" => "video",
+      "" | "unknown" => "attachment",
+      kind => kind,
+    };
+    match counts.iter_mut().find(|(k, _)| *k == kind) {
+      Some((_, count)) => *count += 1,
+      None => counts.push((kind, 1)),
+    }
+  }
+  if !counts.is_empty() {
+    let media: Vec<String> = counts
+      .iter()
+      .map(|(kind, count)| format!("{count} {kind}{}", if *count > 1 { "s" } else { "" }))
+      .collect();
+    title = format!("{title} [{}]", media.join(", ")).trim_start().to_string();
+  }
+  title
+}
+
+/// The kinds of the media an RSS item carries in `media:content` elements, as Mastodon's RSS
+/// gives them.
+fn rss_media_kinds(item: &rss::Item) -> Vec<&str> {
+  let Some(contents) = item.extensions().get("media").and_then(|media| media.get("content")) else {
+    return Vec::new();
+  };
+  contents.iter().map(|content| content.attrs().get("medium").map_or("", String::as_str)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::VecDeque;
+
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::testing::{MockServer, Reply};
+
+  const FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Fixture</title><link>https://example.com</link>
+<description>A feed</description>
+<item><title>First</title><link>https://example.com/1</link></item>
+</channel></rss>"#;
+
+  /// Answers with its responses in turn, recording the user agent of each request.
+  #[derive(Default)]
+  struct MockTransport {
+    responses: Mutex<VecDeque<Result<Document, FetchError>>>,
+    user_agents: Mutex<Vec<Option<String>>>,
+  }
+
+  impl MockTransport {
+    fn new(responses: Vec<Result<Document, FetchError>>) -> Self {
+      Self { responses: Mutex::new(responses.into()), ..Default::default() }
+    }
+  }
+
+  impl Transport for MockTransport {
+    async fn get(&self, _url: &str, user_agent: Option<&str>) -> Result<Document, FetchError> {
+      self.user_agents.lock().unwrap().push(user_agent.map(str::to_string));
+      self.responses.lock().unwrap().pop_front().expect("no response left")
+    }
+  }
+
+  fn document(moved_to: Option<&str>) -> Result<Document, FetchError> {
+    Ok(Document { body: FEED.to_string(), moved_to: moved_to.map(str::to_string) })
+  }
+
+  fn status(code: u16) -> Result<Document, FetchError> {
+    Err(FetchError::Status { status: StatusCode::from_u16(code).unwrap(), retry_after: None })
+  }
+
+  fn config() -> FetchConfig {
+    FetchConfig {
+      retries: 2,
+      backoff_ms: 1,
+      user_agents: vec!["Other".to_string()],
+      ..Default::default()
+    }
+  }
+
+  fn feed(link: &str) -> FeedConfig {
+    FeedConfig { link: link.to_string(), ..Default::default() }
+  }
+
+  #[tokio::test]
+  async fn retries_transient_failures() {
+    let transport = MockTransport::new(vec![status(503), Err(FetchError::Timeout), document(None)]);
+    let fetcher = Fetcher::with_transport(transport, config());
+    let fetched = fetcher.fetch_feed(&feed("https://example.com/feed")).await.unwrap();
+    assert_eq!(fetched.items.len(), 1);
+    assert_eq!(fetcher.transport.user_agents.lock().unwrap().len(), 3);
+  }
+
+  #[tokio::test]
+  async fn gives_up_after_the_configured_retries() {
+    let transport = MockTransport::new(vec![status(503), status(503), status(503)]);
+    let fetcher = Fetcher::with_transport(transport, config());
+    let error = fetcher.fetch_feed(&feed("https://example.com/feed")).await.unwrap_err();
+    assert!(matches!(error, FetchError::Status { status: StatusCode::SERVICE_UNAVAILABLE, .. }));
+    assert!(fetcher.transport.responses.lock().unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn does_not_retry_client_errors() {
+    let transport = MockTransport::new(vec![status(404), document(None)]);
+    let fetcher = Fetcher::with_transport(transport, config());
+    assert!(fetcher.fetch_feed(&feed("https://example.com/feed")).await.is_err());
+    assert_eq!(fetcher.transport.user_agents.lock().unwrap().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn caps_the_delay_asked_for_by_retry_after() {
+    let retry_after = Err(FetchError::Status {
+      status: StatusCode::TOO_MANY_REQUESTS,
+      retry_after: Some(Duration::from_secs(3600)),
+    });
+    let transport = MockTransport::new(vec![retry_after, document(None)]);
+    let fetcher =
+      Fetcher::with_transport(transport, FetchConfig { max_retry_after_secs: 0, ..config() });
+    let fetched = tokio::time::timeout(
+      Duration::from_secs(5),
+      fetcher.fetch_feed(&feed("https://example.com/feed")),
+    )
+    .await;
+    assert!(fetched.unwrap().is_ok());
+  }
+
+  #[tokio::test]
+  async fn tries_another_user_agent_when_forbidden() {
+    let transport = MockTransport::new(vec![status(403), document(None)]);
+    let fetcher = Fetcher::with_transport(transport, config());
+    fetcher.fetch_feed(&feed("https://example.com/feed")).await.unwrap();
+    assert_eq!(*fetcher.transport.user_agents.lock().unwrap(), [None, Some("Other".to_string())]);
+  }
+
+  #[tokio::test]
+  async fn reports_a_permanent_move() {
+    let transport = MockTransport::new(vec![document(Some("https://example.com/new"))]);
+    let fetcher = Fetcher::with_transport(transport, config());
+    let fetched = fetcher.fetch_feed(&feed("https://example.com/feed")).await.unwrap();
+    assert_eq!(fetched.moved_to.as_deref(), Some("https://example.com/new"));
+  }
+
+  #[test]
+  fn reads_rss_and_dublin_core_dates() {
+    let expected = DateTime::parse_from_rfc3339("2024-06-03T14:30:00Z").unwrap();
+    assert_eq!(parse_date("Mon, 03 Jun 2024 16:30:00 +0200"), Some(expected.into()));
+    assert_eq!(parse_date(" Mon, 03 Jun 2024 14:30:00 GMT "), Some(expected.into()));
+    assert_eq!(parse_date("2024-06-03T14:30:00Z"), Some(expected.into()));
+    assert_eq!(parse_date("yesterday"), None);
+  }
+
+  #[test]
+  fn encodes_the_arxiv_query() {
+    assert_eq!(
+      arxiv_url("cat:cs.CL AND  ti:\"large language\" & more"),
+      "https://export.arxiv.org/api/query?search_query=cat%3Acs.CL+AND+ti%3A%22large+language%22+%26+more&sortBy=submittedDate&sortOrder=descending&max_results=50"
+    );
+  }
+
+  #[tokio::test]
+  async fn follows_permanent_redirects_to_the_new_location() {
+    let server = MockServer::start(vec![
+      ("/old", vec![Reply::redirect(301, "/older")]),
+      ("/older", vec![Reply::redirect(308, "/feed")]),
+      ("/feed", vec![Reply::ok(FEED)]),
+    ])
+    .await;
+    let transport = HttpTransport::new(&FetchConfig::default()).unwrap();
+    let document = transport.get(&server.url("/old"), None).await.unwrap();
+    assert_eq!(document.body, FEED);
+    assert_eq!(document.moved_to, Some(server.url("/feed")));
+  }
+
+  #[tokio::test]
+  async fn a_temporary_redirect_is_not_a_move() {
+    let server = MockServer::start(vec![
+      ("/old", vec![Reply::redirect(301, "/tmp")]),
+      ("/tmp", vec![Reply::redirect(302, "/feed")]),
+      ("/feed", vec![Reply::ok(FEED)]),
+    ])
+    .await;
+    let transport = HttpTransport::new(&FetchConfig::default()).unwrap();
+    let document = transport.get(&server.url("/old"), None).await.unwrap();
+    assert_eq!(document.moved_to, None);
+  }
+
+  #[tokio::test]
+  async fn stops_after_the_redirect_limit() {
+    let server = MockServer::start(vec![("/loop", vec![Reply::redirect(302, "/loop")])]).await;
+    let transport =
+      HttpTransport::new(&FetchConfig { max_redirects: 2, ..Default::default() }).unwrap();
+    let error = transport.get(&server.url("/loop"), None).await.unwrap_err();
+    assert!(matches!(error, FetchError::TooManyRedirects));
+    assert_eq!(server.requests().len(), 3);
+  }
+
+  #[tokio::test]
+  async fn not_modified_is_not_a_redirect() {
+    let server = MockServer::start(vec![("/feed", vec![Reply::status(304)])]).await;
+    let transport = HttpTransport::new(&FetchConfig::default()).unwrap();
+    let error = transport.get(&server.url("/feed"), None).await.unwrap_err();
+    assert!(matches!(error, FetchError::Status { status: StatusCode::NOT_MODIFIED, .. }));
+  }
+
+  #[tokio::test]
+  async fn passes_on_the_retry_after_header() {
+    let server =
+      MockServer::start(vec![("/feed", vec![Reply::status(429).header("Retry-After", "7")])]).await;
+    let transport = HttpTransport::new(&FetchConfig::default()).unwrap();
+    let error = transport.get(&server.url("/feed"), None).await.unwrap_err();
+    assert!(matches!(
+      error,
+      FetchError::Status { retry_after: Some(retry_after), .. } if retry_after == Duration::from_secs(7)
+    ));
+  }
+
+  #[tokio::test]
+  async fn downloads_files_through_redirects() {
+    let server = MockServer::start(vec![
+      ("/episode.mp3", vec![Reply::redirect(302, "/cdn/episode.mp3")]),
+      ("/cdn/episode.mp3", vec![Reply::ok("audio")]),
+    ])
+    .await;
+    let fetcher = Fetcher::new(FetchConfig::default()).unwrap();
+    let response = fetcher.download(&server.url("/episode.mp3")).await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "audio");
+    let user_agent = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+    assert!(server
+      .requests()
+      .iter()
+      .all(|request| request.user_agent.as_deref() == Some(user_agent)));
+  }
+
+  #[test]
+  fn escapes_mastodon_media_descriptions() {
+    let status = MastodonStatus {
+      uri: "https://social.example/1".to_string(),
+      media_attachments: vec![MastodonMedia {
+        kind: "image".to_string(),
+        url: "https://files.example/a.png\"onclick=\"x".to_string(),
+        description: Some("<script>alert(1)</script> & more".to_string()),
+      }],
+      ..Default::default()
+    };
+    let item = status.into_item(&feed("https://social.example/@someone"));
+    assert!(item.content.ends_with(
+      "<p><a href=\"https://files.example/a.png&quot;onclick=&quot;x\">[image] \
+       &lt;script&gt;alert(1)&lt;/script&gt; &amp; more</a></p>"
+    ));
+  }
+}
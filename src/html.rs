@@ -0,0 +1,101 @@
+use scraper::{ElementRef, Html};
+
+/// Elements whose text is never meant to be read.
+const SKIPPED: [&str; 6] = ["script", "style", "head", "noscript", "template", "iframe"];
+
+/// Elements that start on a new line when rendered, so their text must not run into the
+/// surrounding words.
+const BLOCKS: [&str; 20] = [
+  "address",
+  "article",
+  "blockquote",
+  "br",
+  "dd",
+  "div",
+  "dl",
+  "dt",
+  "figcaption",
+  "figure",
+  "footer",
+  "h1",
+  "h2",
+  "h3",
+  "h4",
+  "h5",
+  "h6",
+  "hr",
+  "li",
+  "p",
+];
+
+/// Turns an HTML fragment, such as a feed item description, into a single line of plain text.
+/// Tags, scripts and images like tracking pixels are dropped, entities are decoded and runs of
+/// whitespace become single spaces. Descriptions escaped twice by their feed are unescaped again.
+pub fn to_plain_text(html: &str) -> String {
+  let text = fragment_text(html);
+  if looks_like_html(&text) {
+    fragment_text(&text)
+  } else {
+    text
+  }
+}
+
+/// Paragraph put in place of what [`truncate`] cuts off.
+const TRUNCATED_MARKER: &str = "<p><em>[Content truncated]</em></p>";
+
+/// The smallest limit [`truncate`] is given, leaving room for some content besides the marker.
+pub const MIN_CONTENT_SIZE: usize = 1024;
+
+/// Cuts `html` down to at most `max_bytes`, marker included, 0 meaning no limit. The cut falls
+/// before any tag it would split, and a marker at the end says something is missing. Returns
+/// whether anything was cut.
+pub fn truncate(html: &mut String, max_bytes: usize) -> bool {
+  if max_bytes == 0 || html.len() <= max_bytes {
+    return false;
+  }
+  let mut end = max_bytes.saturating_sub(TRUNCATED_MARKER.len());
+  while !html.is_char_boundary(end) {
+    end -= 1;
+  }
+  if let Some(open) = html[..end].rfind('<') {
+    if html[open..end].find('>').is_none() {
+      end = open;
+    }
+  }
+  html.truncate(end);
+  html.push_str(TRUNCATED_MARKER);
+  true
+}
+
+fn fragment_text(html: &str) -> String {
+  let fragment = Html::parse_fragment(html);
+  let mut text = String::new();
+  push_text(fragment.root_element(), &mut text);
+  text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn push_text(element: ElementRef, text: &mut String) {
+  for child in element.children() {
+    if let Some(child_text) = child.value().as_text() {
+      text.push_str(child_text);
+    } else if let Some(child) = ElementRef::wrap(child) {
+      let name = child.value().name();
+      if SKIPPED.contains(&name) {
+        continue;
+      }
+      let block = BLOCKS.contains(&name);
+      if block {
+        text.push(' ');
+      }
+      push_text(child, text);
+      if block {
+        text.push(' ');
+      }
+    }
+  }
+}
+
+/// Whether decoded text still holds tags, as in `&lt;p&gt;Hello&lt;/p&gt;`.
+fn looks_like_html(text: &str) -> bool {
+  text.contains("</") || text.contains("/>") || text.contains("<p>") || text.contains("<br")
+}
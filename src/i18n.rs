@@ -0,0 +1,136 @@
+use std::fmt::Display;
+
+/// A language the interface is translated to, picked by the `locale` of the display config.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Language {
+  #[default]
+  English,
+  Afrikaans,
+}
+
+/// A string shown in the interface. Its English text is in the comment, with each `{}` filled in
+/// by an argument to [`Language::format`] in turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+  /// Are you sure you want to quit?
+  QuitPrompt,
+  /// [y]es      [n]o
+  ConfirmKeys,
+  /// All Feeds
+  AllFeeds,
+  /// All Articles
+  AllArticles,
+  /// MAIN
+  ModeMain,
+  /// FEEDS
+  ModeFeeds,
+  /// ARTICLES
+  ModeArticles,
+  /// REFRESHING
+  ModeRefreshing,
+  /// {} unread
+  Unread,
+  /// Refreshing… {} new, {} updated
+  Refreshing,
+  /// , {} failed
+  RefreshingFailed,
+  /// Reading aloud…
+  ReadingAloud,
+  /// A feed failed to refresh, {}
+  FeedFailed,
+  /// {} feeds failed to refresh ({}), see feed health (F)
+  FeedsFailed,
+  /// Failed to open {}: {}
+  OpenFailed,
+  /// just now
+  JustNow,
+  /// {} min ago
+  MinutesAgo,
+  /// {} h ago
+  HoursAgo,
+  /// yesterday
+  Yesterday,
+  /// {} days ago
+  DaysAgo,
+}
+
+impl Language {
+  /// The language of a locale name like `af_ZA` or `af_ZA.UTF-8`, English for any the interface
+  /// is not translated to.
+  pub fn from_locale(name: &str) -> Self {
+    match name.split(['_', '-', '.']).next().unwrap_or_default() {
+      "af" => Language::Afrikaans,
+      _ => Language::English,
+    }
+  }
+
+  pub fn text(self, message: Message) -> &'static str {
+    match self {
+      Language::English => english(message),
+      Language::Afrikaans => afrikaans(message),
+    }
+  }
+
+  /// The text of `message` with its `{}` replaced by `args` in order.
+  pub fn format(self, message: Message, args: &[&dyn Display]) -> String {
+    let mut parts = self.text(message).split("{}");
+    let mut formatted = parts.next().unwrap_or_default().to_string();
+    for (i, part) in parts.enumerate() {
+      if let Some(arg) = args.get(i) {
+        formatted.push_str(&arg.to_string());
+      }
+      formatted.push_str(part);
+    }
+    formatted
+  }
+}
+
+fn english(message: Message) -> &'static str {
+  match message {
+    Message::QuitPrompt => "Are you sure you want to quit?",
+    Message::ConfirmKeys => "[y]es      [n]o",
+    Message::AllFeeds => "All Feeds",
+    Message::AllArticles => "All Articles",
+    Message::ModeMain => "MAIN",
+    Message::ModeFeeds => "FEEDS",
+    Message::ModeArticles => "ARTICLES",
+    Message::ModeRefreshing => "REFRESHING",
+    Message::Unread => "{} unread",
+    Message::Refreshing => "Refreshing… {} new, {} updated",
+    Message::RefreshingFailed => ", {} failed",
+    Message::ReadingAloud => "Reading aloud…",
+    Message::FeedFailed => "A feed failed to refresh, {}",
+    Message::FeedsFailed => "{} feeds failed to refresh ({}), see feed health (F)",
+    Message::OpenFailed => "Failed to open {}: {}",
+    Message::JustNow => "just now",
+    Message::MinutesAgo => "{} min ago",
+    Message::HoursAgo => "{} h ago",
+    Message::Yesterday => "yesterday",
+    Message::DaysAgo => "{} days ago",
+  }
+}
+
+fn afrikaans(message: Message) -> &'static str {
+  match message {
+    Message::QuitPrompt => "Is jy seker jy wil ophou?",
+    Message::ConfirmKeys => "[y] ja      [n] nee",
+    Message::AllFeeds => "Alle Voere",
+    Message::AllArticles => "Alle Artikels",
+    Message::ModeMain => "HOOF",
+    Message::ModeFeeds => "VOERE",
+    Message::ModeArticles => "ARTIKELS",
+    Message::ModeRefreshing => "VERFRIS",
+    Message::Unread => "{} ongelees",
+    Message::Refreshing => "Verfris… {} nuut, {} bygewerk",
+    Message::RefreshingFailed => ", {} misluk",
+    Message::ReadingAloud => "Lees hardop voor…",
+    Message::FeedFailed => "’n Voer kon nie verfris nie, {}",
+    Message::FeedsFailed => "{} voere kon nie verfris nie ({}), sien voergesondheid (F)",
+    Message::OpenFailed => "Kon nie {} oopmaak nie: {}",
+    Message::JustNow => "so pas",
+    Message::MinutesAgo => "{} min gelede",
+    Message::HoursAgo => "{} h gelede",
+    Message::Yesterday => "gister",
+    Message::DaysAgo => "{} dae gelede",
+  }
+}
@@ -0,0 +1,34 @@
+use std::collections::BTreeSet;
+
+/// Shortest title word worth indexing.
+const MIN_WORD_LEN: usize = 4;
+
+/// Common English words that say nothing about what an article is about.
+const STOPWORDS: &str =
+  "about after again against also among announcing another back been before being best between \
+   both could does doing down during each even every first from further have having here into \
+   introducing just last like made make makes many more most much must need never news next only \
+   other over part really same should since some still such take than that their them then there \
+   these they thing things this those through time today under until update using very want ways \
+   week were what when where which while whom will with without would year years your";
+
+/// The keywords an article is indexed under: its categories, prefixed with `#` so they can be
+/// told apart, and the significant words of its title. All are lower case.
+pub fn keywords(title: &str, categories: &[String]) -> BTreeSet<String> {
+  let mut keywords: BTreeSet<String> = categories
+    .iter()
+    .map(|category| category.trim().to_lowercase())
+    .filter(|category| !category.is_empty())
+    .map(|category| format!("#{category}"))
+    .collect();
+  for word in title.split(|c: char| !c.is_alphanumeric()) {
+    let word = word.to_lowercase();
+    if word.chars().count() >= MIN_WORD_LEN
+      && !word.chars().all(|c| c.is_ascii_digit())
+      && !STOPWORDS.split_whitespace().any(|stopword| stopword == word)
+    {
+      keywords.insert(word);
+    }
+  }
+  keywords
+}
@@ -3,22 +3,56 @@
 #![allow(unused_variables)]
 
 pub mod action;
+pub mod alerts;
 pub mod app;
+pub mod browser;
+pub mod cache;
+pub mod catalog;
 pub mod cli;
 pub mod components;
 pub mod config;
 pub mod db;
+pub mod digest;
+pub mod email;
+pub mod export;
+pub mod extract;
+pub mod fetcher;
+pub mod html;
+pub mod i18n;
+pub mod keywords;
+pub mod media;
 pub mod mode;
+pub mod preview;
+pub mod refresh;
+pub mod render_html;
+pub mod secrets;
+pub mod speech;
+pub mod state;
+pub mod summary;
+pub mod switcher;
+pub mod task;
+#[cfg(test)]
+mod testing;
 pub mod tui;
 pub mod utils;
+pub mod viewer;
+
+use std::{
+  collections::HashSet,
+  io::Write,
+  path::{Path, PathBuf},
+};
 
 use clap::Parser;
-use cli::Cli;
-use color_eyre::eyre::Result;
+use cli::{AuthCommand, Cli, Command, DbCommand, StateCommand};
+use color_eyre::eyre::{eyre, Result};
 
 use crate::{
   app::App,
-  utils::{initialize_logging, initialize_panic_handler, version},
+  config::{Config, StartupRefresh},
+  db::{Database, DbProblem},
+  digest::DigestFormat,
+  utils::{get_data_dir, initialize_logging, initialize_panic_handler, version},
 };
 
 async fn tokio_main() -> Result<()> {
@@ -27,12 +61,259 @@ async fn tokio_main() -> Result<()> {
   initialize_panic_handler()?;
 
   let args = Cli::parse();
+  match args.command {
+    Some(Command::PreviewTheme { variant, width, height }) => {
+      return preview::preview_theme(Config::new()?, variant, width, height);
+    },
+    Some(Command::Auth { command }) => return run_auth_command(command),
+    Some(Command::State { command }) => return run_state_command(command).await,
+    Some(Command::Db { command }) => return run_db_command(command).await,
+    Some(Command::Add { urls, from_file, group }) => {
+      return run_add_command(urls, from_file, group).await;
+    },
+    Some(Command::Digest { since, output, format }) => {
+      return run_digest_command(&since, output, format).await;
+    },
+    None => {},
+  }
+  let data_dir = get_data_dir();
+  let rebuild = args.rebuild_db || check_database(&data_dir).await?;
+  if rebuild {
+    if let Some(backup) = db::backup(&data_dir)? {
+      println!("Moved the old database to {}", backup.display());
+    }
+  }
   let mut app = App::new(args.tick_rate, args.frame_rate).await?;
+  if let Some(group) = args.group {
+    app.config.start_group = Some(group);
+    app.config.start_feed = None;
+  }
+  if rebuild {
+    app.config.fetch.on_startup = StartupRefresh::Always;
+  }
   app.run().await?;
 
   Ok(())
 }
 
+/// Makes sure the database opens before the app starts. Returns whether the user chose to rebuild
+/// a corrupt one, and fails if it is locked or they would rather keep it.
+async fn check_database(data_dir: &Path) -> Result<bool> {
+  match Database::check(data_dir.to_str().unwrap()).await? {
+    None => Ok(false),
+    Some(DbProblem::Locked) => {
+      Err(eyre!(
+        "{} is locked by another process, close any other nuuslees and try again",
+        db::db_path(data_dir).display()
+      ))
+    },
+    Some(DbProblem::Corrupt(details)) => {
+      eprintln!("{} is damaged: {details}", db::db_path(data_dir).display());
+      eprint!(
+        "Move it aside and start a new one from the config? Every feed is fetched again, and read \
+         articles, notes and feeds subscribed to in the app are left in the old one. [y/N] "
+      );
+      std::io::stderr().flush()?;
+      let mut answer = String::new();
+      std::io::stdin().read_line(&mut answer)?;
+      if answer.trim().eq_ignore_ascii_case("y") {
+        Ok(true)
+      } else {
+        Err(eyre!("Kept the damaged database, run with --rebuild-db to replace it later"))
+      }
+    },
+  }
+}
+
+fn run_auth_command(command: AuthCommand) -> Result<()> {
+  match command {
+    AuthCommand::Set { name } => {
+      let secret = rpassword::prompt_password(format!("Secret for {name}: "))?;
+      match secrets::set(&name, &secret)? {
+        secrets::Store::Keyring => println!("Stored {name} in the system keyring"),
+        secrets::Store::File => {
+          println!("No system keyring available, stored {name} in the encrypted secrets file")
+        },
+      }
+    },
+  }
+  Ok(())
+}
+
+async fn run_state_command(command: StateCommand) -> Result<()> {
+  let mut db = Database::new(get_data_dir().to_str().unwrap()).await?;
+  db.set_config(Config::new()?);
+  db.init().await?;
+  match command {
+    StateCommand::Export { path } => {
+      let count = state::export(&db, &path)?;
+      println!("Exported the state of {count} articles to {}", path.display());
+    },
+    StateCommand::Import { path } => {
+      let count = state::import(&db, &path)?;
+      println!("Imported the state of {count} articles from {}", path.display());
+    },
+  }
+  Ok(())
+}
+
+async fn run_db_command(command: DbCommand) -> Result<()> {
+  let mut db = Database::new(get_data_dir().to_str().unwrap()).await?;
+  db.set_config(Config::new()?);
+  db.init().await?;
+  match command {
+    DbCommand::Stats { largest } => {
+      let stats = db.get_storage_stats(largest)?;
+      println!("Database   {}", db::db_path(&get_data_dir()).display());
+      println!(
+        "Size       {} ({} free, given back by a vacuum)",
+        media::format_size(stats.file_size),
+        media::format_size(stats.free_size)
+      );
+      println!("Articles   {} and {} earlier revisions", stats.items, stats.revisions);
+      println!();
+      println!("{:>10}  {:>8}  Feed", "Stored", "Articles");
+      for feed in &stats.feeds {
+        println!("{:>10}  {:>8}  {}", media::format_size(feed.bytes), feed.items, feed.name);
+      }
+      if !stats.largest.is_empty() {
+        println!();
+        println!("{:>10}  Largest articles", "Stored");
+        for item in &stats.largest {
+          println!("{:>10}  {} ({})", media::format_size(item.bytes), item.title, item.feed_name);
+          println!("{:>10}  {}", "", item.url);
+        }
+      }
+    },
+    DbCommand::Vacuum => {
+      let maintenance = db.vacuum()?;
+      println!(
+        "Removed {} orphaned articles and freed {}",
+        maintenance.orphans,
+        media::format_size(maintenance.freed)
+      );
+    },
+  }
+  Ok(())
+}
+
+/// Subscribes to the feed behind each link and prints which were added and why the others
+/// were not. The feeds are fetched the next time the app refreshes them.
+async fn run_add_command(
+  mut urls: Vec<String>,
+  from_file: Option<PathBuf>,
+  group: Option<String>,
+) -> Result<()> {
+  if let Some(path) = &from_file {
+    let list = if path == Path::new("-") {
+      std::io::read_to_string(std::io::stdin())?
+    } else {
+      std::fs::read_to_string(path)?
+    };
+    urls.extend(
+      list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from),
+    );
+  }
+  if urls.is_empty() {
+    return Err(eyre!("No links given, pass them as arguments or with --from-file"));
+  }
+  let config = Config::new()?;
+  let group = match group {
+    Some(group) => group,
+    None if from_file.as_deref() == Some(Path::new("-")) => {
+      return Err(eyre!("Pass --group when the links are read from standard input"));
+    },
+    None => prompt_group(&config)?,
+  };
+
+  let mut db = Database::new(get_data_dir().to_str().unwrap()).await?;
+  db.set_config(config.clone());
+  db.init().await?;
+  let fetcher = fetcher::Fetcher::new(config.fetch.clone())?;
+  let mut subscribed: HashSet<String> =
+    db.get_subscriptions()?.into_iter().map(|(_, feed)| feed.link).collect();
+  let found = futures::future::join_all(urls.iter().map(|url| fetcher.discover(url))).await;
+
+  // Rows of outcome, feed and details.
+  let mut rows = Vec::new();
+  let mut added = 0;
+  for (url, found) in urls.iter().zip(found) {
+    match found {
+      Ok(feed) if config.feed(&feed.url).is_some() || subscribed.contains(&feed.url) => {
+        rows.push(("skipped", feed.name, format!("already subscribed to {}", feed.url)));
+      },
+      Ok(feed) => {
+        let feed_config = config::FeedConfig {
+          link: feed.url.clone(),
+          name: Some(feed.name.clone()),
+          ..Default::default()
+        };
+        db.add_subscription(&group, &feed_config)?;
+        subscribed.insert(feed.url.clone());
+        rows.push(("added", feed.name, feed.url));
+        added += 1;
+      },
+      Err(error) => {
+        rows.push(("failed", url.clone(), refresh::RefreshError::from(&error).explained()));
+      },
+    }
+  }
+
+  let width = rows.iter().map(|(_, feed, _)| feed.chars().count()).max().unwrap_or(0).min(40);
+  println!("{:<7}  {:<width$}  Details", "", "Feed");
+  for (outcome, feed, details) in &rows {
+    println!("{outcome:<7}  {feed:<width$}  {details}");
+  }
+  println!();
+  println!("Added {added} of {} feeds to {group}", rows.len());
+  Ok(())
+}
+
+/// Asks which group to add feeds to, listing the configured ones.
+fn prompt_group(config: &Config) -> Result<String> {
+  let groups: Vec<&str> = config.all_groups().iter().map(|group| group.name.as_str()).collect();
+  if !groups.is_empty() {
+    println!("Groups: {}", groups.join(", "));
+  }
+  print!("Group to add the feeds to: ");
+  std::io::stdout().flush()?;
+  let mut group = String::new();
+  std::io::stdin().read_line(&mut group)?;
+  let group = group.trim();
+  if group.is_empty() {
+    return Err(eyre!("No group given"));
+  }
+  Ok(group.to_string())
+}
+
+async fn run_digest_command(
+  since: &str,
+  output: Option<PathBuf>,
+  format: Option<DigestFormat>,
+) -> Result<()> {
+  let since = chrono::Utc::now()
+    .checked_sub_signed(digest::parse_age(since)?)
+    .ok_or_else(|| eyre!("`{since}` reaches back too far"))?;
+  let format =
+    format.or_else(|| output.as_deref().and_then(DigestFormat::from_path)).unwrap_or_default();
+  let mut db = Database::new(get_data_dir().to_str().unwrap()).await?;
+  db.set_config(Config::new()?);
+  db.init().await?;
+  let (digest, count) = digest::digest(&db, since, format)?;
+  match output {
+    Some(path) => {
+      std::fs::write(&path, digest)?;
+      println!("Wrote {count} articles to {}", path.display());
+    },
+    None => print!("{digest}"),
+  }
+  Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
   if let Err(e) = tokio_main().await {
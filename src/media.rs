@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use tokio::{fs, io::AsyncWriteExt};
+
+use crate::{config::MediaConfig, fetcher::Fetcher};
+
+/// A file attached to an article, such as a podcast episode or the photos of a post.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Enclosure {
+  pub url: String,
+  /// A MIME type such as `audio/mpeg`, or just `image`, `video` or `audio` when the feed says no
+  /// more. Empty when it says nothing.
+  pub mime_type: String,
+  /// Size in bytes, if the feed gives it.
+  pub length: Option<u64>,
+}
+
+impl Enclosure {
+  /// The last segment of the URL's path.
+  pub fn file_name(&self) -> &str {
+    let path = self.url.split(['?', '#']).next().unwrap_or_default();
+    path.trim_end_matches('/').rsplit('/').next().filter(|name| !name.contains(':')).unwrap_or("")
+  }
+
+  /// The part of the MIME type before the slash, such as `audio`.
+  pub fn kind(&self) -> &str {
+    match self.mime_type.split('/').next() {
+      Some(kind) if !kind.is_empty() => kind,
+      _ => "file",
+    }
+  }
+}
+
+/// Formats a byte count with the largest unit that keeps it at or above 1, e.g. `54.2 MB`.
+pub fn format_size(bytes: u64) -> String {
+  const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+  if bytes < 1000 {
+    return format!("{bytes} B");
+  }
+  let mut size = bytes as f64 / 1000.0;
+  let mut unit = 0;
+  while size >= 1000.0 && unit < UNITS.len() - 1 {
+    size /= 1000.0;
+    unit += 1;
+  }
+  format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Where downloads go: `media.download_dir`, else the user's download directory, else the
+/// working directory.
+pub fn download_dir(config: &MediaConfig) -> PathBuf {
+  config
+    .download_dir
+    .clone()
+    .or_else(|| {
+      directories::UserDirs::new().and_then(|dirs| dirs.download_dir().map(Path::to_path_buf))
+    })
+    .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Downloads `enclosure` with `fetcher` into `dir` under its own file name, numbered if that is
+/// taken, and returns where it was saved. A failed download leaves no partial file behind.
+pub async fn download(fetcher: &Fetcher, enclosure: &Enclosure, dir: &Path) -> Result<PathBuf> {
+  fs::create_dir_all(dir)
+    .await
+    .wrap_err_with(|| format!("Could not create the download directory {}", dir.display()))?;
+  let path = free_path(dir, enclosure.file_name());
+  let mut response = fetcher
+    .download(&enclosure.url)
+    .await
+    .wrap_err_with(|| format!("Could not download {}", enclosure.url))?;
+  let mut file = fs::File::create(&path)
+    .await
+    .wrap_err_with(|| format!("Could not create {}", path.display()))?;
+  let written = async {
+    while let Some(chunk) = response.chunk().await? {
+      file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    Ok::<_, color_eyre::eyre::Report>(())
+  }
+  .await;
+  if let Err(error) = written {
+    let _ = fs::remove_file(&path).await;
+    return Err(error.wrap_err(format!("Could not download {}", enclosure.url)));
+  }
+  Ok(path)
+}
+
+/// `dir` joined with `name`, or with `name (2)`, `name (3)` and so on before the extension when
+/// that file exists.
+fn free_path(dir: &Path, name: &str) -> PathBuf {
+  let name = if name.is_empty() { "download" } else { name };
+  let path = dir.join(name);
+  if !path.exists() {
+    return path;
+  }
+  let (stem, extension) = match name.rsplit_once('.') {
+    Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+    _ => (name, String::new()),
+  };
+  (2..).map(|n| dir.join(format!("{stem} ({n}){extension}"))).find(|path| !path.exists()).unwrap()
+}
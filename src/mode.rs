@@ -1,12 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-use crate::db::FeedItem;
-
-#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Mode {
   #[default]
   Main,
   FeedList,
-  ViewArticles(Vec<FeedItem>),
+  ViewArticles,
   Refreshing,
 }
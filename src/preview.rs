@@ -0,0 +1,171 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::Result;
+use crossterm::{
+  queue,
+  style::{Attribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+use ratatui::{buffer::Buffer, style::Modifier};
+
+use crate::{
+  action::{Action, TabId},
+  components::{article_view::ArticleView, group_view::GroupView, Component},
+  config::{Config, ThemeVariant},
+  db::{Feed, FeedItem, Group},
+};
+
+/// Prints the group list and an open article drawn with `variant`, or the palette the theme mode
+/// would pick right now, using made-up feeds so it works before anything has been fetched.
+pub fn preview_theme(
+  mut config: Config,
+  variant: Option<ThemeVariant>,
+  width: u16,
+  height: u16,
+) -> Result<()> {
+  config.theme.variant =
+    variant.unwrap_or_else(|| config.theme.preferred_variant(chrono::Local::now().time()));
+
+  let mut stdout = io::stdout();
+  for buffer in render_preview(config, Utc::now(), width, height)? {
+    print_buffer(&mut stdout, &buffer)?;
+  }
+  Ok(())
+}
+
+/// Draws the group list and then the open article of the preview, dated as if it were `now`.
+fn render_preview(
+  config: Config,
+  now: DateTime<Utc>,
+  width: u16,
+  height: u16,
+) -> Result<[Buffer; 2]> {
+  let groups = sample_groups();
+  let tree = groups
+    .iter()
+    .map(|group| (group.clone(), sample_feeds(group, now)))
+    .filter(|(group, _)| group.id != -1)
+    .collect();
+  let mut group_view = GroupView::new(TabId::next());
+  group_view.register_config_handler(config.clone())?;
+  group_view.update(Action::Refresh(groups))?;
+  group_view.update(Action::UpdateFeedTree(tree))?;
+
+  let tab = TabId::next();
+  let mut article_view =
+    ArticleView::new(tab, vec!["News".to_string(), "Example Times".to_string()])
+      .with_feed_names(true);
+  article_view.register_config_handler(config)?;
+  article_view.update(Action::UpdateArticleView(tab, sample_items(now).into()))?;
+  article_view.update(Action::UpdateReader(tab, SAMPLE_ARTICLE.to_string()))?;
+
+  Ok([group_view.render_to_buffer(width, height)?, article_view.render_to_buffer(width, height)?])
+}
+
+/// Writes the cells of `buffer` with their colours, one terminal line per row.
+fn print_buffer(out: &mut impl Write, buffer: &Buffer) -> io::Result<()> {
+  for y in 0..buffer.area.height {
+    for x in 0..buffer.area.width {
+      let cell = buffer.get(x, y);
+      queue!(out, SetAttribute(Attribute::Reset))?;
+      if cell.modifier.contains(Modifier::BOLD) {
+        queue!(out, SetAttribute(Attribute::Bold))?;
+      }
+      if cell.modifier.contains(Modifier::DIM) {
+        queue!(out, SetAttribute(Attribute::Dim))?;
+      }
+      queue!(
+        out,
+        SetForegroundColor(cell.fg.into()),
+        SetBackgroundColor(cell.bg.into()),
+        Print(cell.symbol())
+      )?;
+    }
+    queue!(out, SetAttribute(Attribute::Reset), Print("\n"))?;
+  }
+  out.flush()
+}
+
+const SAMPLE_ARTICLE: &str = "<h1>Local library extends opening hours</h1>\
+  <p>The central library will stay open until <b>nine in the evening</b> on weekdays from next \
+  month, after a survey found most visitors come after work.</p>\
+  <p>Read the <a href=\"https://example.com/survey\">full survey</a> for the details.</p>";
+
+fn sample_groups() -> Vec<Group> {
+  let group = |id: i32, name: &str, desc: &str, parent_id: Option<i32>| {
+    Group { id, name: name.to_string(), desc: desc.to_string(), parent_id }
+  };
+  vec![
+    group(-1, "All Feeds", "Every article from every feed", None),
+    group(1, "News", "Daily headlines", None),
+    group(2, "Tech", "Programming and hardware", None),
+    group(3, "Rust", "Releases and blogs", Some(2)),
+  ]
+}
+
+fn sample_feeds(group: &Group, now: DateTime<Utc>) -> Vec<Feed> {
+  let feed = |id: i32, name: &str, muted: bool| {
+    Feed {
+      id,
+      group_id: group.id,
+      name: name.to_string(),
+      desc: String::new(),
+      url: format!("https://example.com/{id}.xml"),
+      moved_from: None,
+      updated_at: now,
+      muted,
+      dead: false,
+      cadence: None,
+    }
+  };
+  vec![feed(group.id * 10, "Example Times", false), feed(group.id * 10 + 1, "Example Post", true)]
+}
+
+fn sample_items(now: DateTime<Utc>) -> Vec<FeedItem> {
+  let item = |id: i32, title: &str, read: bool, hours_ago: i64| {
+    FeedItem {
+      id,
+      feed_id: 10,
+      feed_name: "Example Times".to_string(),
+      feed_url: "https://example.com/10.xml".to_string(),
+      title: title.to_string(),
+      url: format!("https://example.com/articles/{id}"),
+      desc: "A short summary of the article.".to_string(),
+      read,
+      pub_date: now - Duration::hours(hours_ago),
+      ..FeedItem::default()
+    }
+  };
+  vec![
+    item(1, "Local library extends opening hours", false, 1),
+    item(2, "Rain expected for the weekend", false, 5),
+    item(3, "City council approves new cycle lanes", true, 30),
+  ]
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+  use crate::testing::{assert_snapshot, config};
+
+  /// Renders the preview at noon UTC with dates shown as days only, so the snapshots read the
+  /// same in every time zone from UTC-11 to UTC+11.
+  fn render() -> [Buffer; 2] {
+    let mut config = config();
+    config.display.date_format = "%a %d %b %Y".to_string();
+    let now = Utc.with_ymd_and_hms(2024, 6, 4, 12, 0, 0).unwrap();
+    render_preview(config, now, 120, 14).unwrap()
+  }
+
+  #[test]
+  fn group_view() {
+    assert_snapshot("group_view", &render()[0]);
+  }
+
+  #[test]
+  fn article_view() {
+    assert_snapshot("article_view", &render()[1]);
+  }
+}
@@ -0,0 +1,137 @@
+use std::error::Error as _;
+
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use thiserror::Error;
+
+use crate::{db::DbError, fetcher::FetchError};
+
+/// What kind of trouble refreshing a feed ran into, which decides what can be done about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum FailureKind {
+  /// The feed's host name could not be resolved.
+  Dns,
+  /// No connection could be made to the server, or it broke off.
+  Connection,
+  Timeout,
+  /// The server answered 404 or 410.
+  NotFound,
+  /// The server answered 401 or 403.
+  Denied,
+  /// The server answered 429.
+  RateLimited,
+  /// The server answered with a 5xx status.
+  ServerError,
+  /// Any other unsuccessful status.
+  Http,
+  /// Too many redirects, or one without a valid location.
+  Redirect,
+  /// The document is not a feed that can be read.
+  Parse,
+  /// The feed's config cannot be used as it is, e.g. a secret is missing.
+  Config,
+  /// The feed was fetched but could not be stored.
+  Database,
+}
+
+impl FailureKind {
+  /// What to do about a failure of this kind.
+  pub fn hint(self) -> &'static str {
+    match self {
+      FailureKind::Dns => "check the feed's host name and your connection",
+      FailureKind::Connection => "check your connection, or whether the site is up",
+      FailureKind::Timeout => "the server is slow, raise the feed's `timeout_secs`",
+      FailureKind::NotFound => "the feed is gone, check its link or remove it",
+      FailureKind::Denied => {
+        "the server refused, the feed may need credentials or another of `fetch.user_agents`"
+      },
+      FailureKind::RateLimited => {
+        "too many requests, lower `fetch.host_requests_per_minute` for this host"
+      },
+      FailureKind::ServerError => "the server is failing, it is retried on the next refresh",
+      FailureKind::Http => "the server rejected the request, check the feed's link",
+      FailureKind::Redirect => "the link redirects in a loop or nowhere, check it in a browser",
+      FailureKind::Parse => "the link does not lead to an RSS or Atom feed",
+      FailureKind::Config => "fix the feed in the config",
+      FailureKind::Database => "the database could not store the feed, see the log",
+    }
+  }
+}
+
+/// Why refreshing a feed failed, as shown in the feed health view and the info bar.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[error("{message}")]
+pub struct RefreshError {
+  pub kind: FailureKind,
+  pub message: String,
+}
+
+impl RefreshError {
+  /// The message followed by what to do about it.
+  pub fn explained(&self) -> String {
+    format!("{}: {}", self.message, self.kind.hint())
+  }
+}
+
+impl From<&FetchError> for RefreshError {
+  fn from(error: &FetchError) -> Self {
+    let kind = match error {
+      FetchError::ReqwestError(error) if is_dns(error) => FailureKind::Dns,
+      FetchError::ReqwestError(error) if error.is_timeout() => FailureKind::Timeout,
+      FetchError::ReqwestError(_) => FailureKind::Connection,
+      FetchError::Timeout => FailureKind::Timeout,
+      FetchError::Status { status, .. } => {
+        match *status {
+          StatusCode::NOT_FOUND | StatusCode::GONE => FailureKind::NotFound,
+          StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => FailureKind::Denied,
+          StatusCode::TOO_MANY_REQUESTS => FailureKind::RateLimited,
+          status if status.is_server_error() => FailureKind::ServerError,
+          _ => FailureKind::Http,
+        }
+      },
+      FetchError::RssError(_)
+      | FetchError::AtomError(_)
+      | FetchError::JsonError(_)
+      | FetchError::NoFeedFound => FailureKind::Parse,
+      FetchError::InvalidMastodonLink(_) | FetchError::SecretError(_) => FailureKind::Config,
+      FetchError::TooManyRedirects | FetchError::InvalidRedirect => FailureKind::Redirect,
+    };
+    Self { kind, message: describe(error) }
+  }
+}
+
+impl From<&DbError> for RefreshError {
+  fn from(error: &DbError) -> Self {
+    Self { kind: FailureKind::Database, message: error.to_string() }
+  }
+}
+
+/// The error followed by its causes, which for network errors are where the detail is.
+fn describe(error: &FetchError) -> String {
+  let mut message = error.to_string();
+  let mut source = error.source();
+  while let Some(cause) = source {
+    let cause_text = cause.to_string();
+    // Most errors already show the one they wrap.
+    if !message.contains(&cause_text) {
+      message = format!("{message}: {cause_text}");
+    }
+    source = cause.source();
+  }
+  message
+}
+
+/// Whether a request failed to resolve its host name, as told by the causes reqwest passes on.
+fn is_dns(error: &reqwest::Error) -> bool {
+  let mut source = error.source();
+  while let Some(cause) = source {
+    let text = cause.to_string();
+    if text.contains("dns error") || text.contains("failed to lookup address") {
+      return true;
+    }
+    source = cause.source();
+  }
+  false
+}
@@ -0,0 +1,696 @@
+use std::{io, ops::Range};
+
+use html5ever::{
+  parse_document, tendril::TendrilSink, tree_builder::TreeBuilderOpts, Attribute, ParseOpts,
+};
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use ratatui::{
+  style::{Color, Modifier, Style},
+  text::{Line, Span, Text},
+};
+use reqwest::Url;
+
+/// Indentation of a definition list description under its term, and of code blocks.
+const DESCRIPTION_INDENT: &str = "    ";
+/// Marker of the items of an unordered list.
+const BULLET: &str = "• ";
+/// What separates the cells of a table row.
+const CELL_SEPARATOR: &str = " │ ";
+
+/// Renders the HTML of an article as styled lines of text, unwrapped so that the reader can wrap
+/// them to any width. Footnotes are gathered under a heading at the end and the links are kept
+/// beside the text.
+#[derive(Clone, Copy, Debug)]
+pub struct Renderer {
+  /// Blank lines after each paragraph.
+  paragraph_spacing: u8,
+  /// Style of links and footnote numbers.
+  link_style: Style,
+}
+
+/// An article as rendered by [`Renderer`].
+pub struct Rendered<'a> {
+  pub text: Text<'a>,
+  /// Where each link in the text leads.
+  pub links: Vec<String>,
+  /// Where the links are in the text, in order.
+  pub link_ranges: Vec<LinkRange>,
+}
+
+/// The characters of a line of the text that make up a link, counted leaving out whitespace and
+/// hyphens so that the range holds however the line is wrapped, justified or hyphenated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkRange {
+  pub line: usize,
+  pub chars: Range<usize>,
+  /// The index of the link in [`Rendered::links`].
+  pub link: usize,
+}
+
+impl LinkRange {
+  /// The characters of `text` a range counts.
+  pub fn count(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace() && *c != '-').count()
+  }
+}
+
+impl Default for Renderer {
+  fn default() -> Self {
+    Self { paragraph_spacing: 1, link_style: Style::default().fg(Color::Blue) }
+  }
+}
+
+impl Renderer {
+  pub fn paragraph_spacing(mut self, lines: u8) -> Self {
+    self.paragraph_spacing = lines;
+    self
+  }
+
+  pub fn link_style(mut self, style: Style) -> Self {
+    self.link_style = style;
+    self
+  }
+
+  /// Renders `html`, which fails only if it cannot be read.
+  pub fn render<'a>(&self, html: &str) -> io::Result<Rendered<'a>> {
+    let opts = ParseOpts {
+      tree_builder: TreeBuilderOpts { drop_doctype: true, ..Default::default() },
+      ..Default::default()
+    };
+    let dom = parse_document(RcDom::default(), opts).from_utf8().read_from(&mut html.as_bytes())?;
+
+    let mut text = Text::default();
+    let mut notes = Footnotes::default();
+    let mut links = Vec::new();
+    self.walk(&dom.document, &mut text, &mut vec![], &mut notes, &mut links);
+    notes.append_to(&mut text, self.link_style);
+    let link_ranges = take_link_ranges(&mut text);
+    Ok(Rendered { text, links, link_ranges })
+  }
+
+  fn walk<'a>(
+    &self,
+    handle: &Handle,
+    text: &mut Text<'a>,
+    spans: &mut Vec<Span<'a>>,
+    notes: &mut Footnotes<'a>,
+    links: &mut Vec<String>,
+  ) {
+    match &handle.data {
+      NodeData::Document => {
+        for child in handle.children.borrow().iter() {
+          self.walk(child, text, spans, notes, links);
+        }
+      },
+      NodeData::Text { contents } => {
+        let content = contents.borrow();
+        spans.push(Span::raw(content.to_string()));
+      },
+      NodeData::Element { name, attrs, .. } => {
+        let tag_name = name.local.as_ref();
+        let attrs = attrs.borrow();
+        if is_footnote_list(&attrs) {
+          self.collect_footnotes(handle, notes, links);
+          return;
+        }
+        // A footnote outside of a list recognised as one, found through a reference to it.
+        if let Some(id) = attr(&attrs, "id").filter(|id| tag_name != "a" && notes.is_referenced(id))
+        {
+          let spans = self.footnote_spans(handle, notes, links);
+          notes.define(id, spans);
+          return;
+        }
+
+        match tag_name {
+          "p" => {
+            // Push current spans as a new line if any
+            if !spans.is_empty() {
+              text.lines.push(Line::from(spans.clone()));
+              spans.clear();
+            }
+            // Process children of <p>
+            for child in handle.children.borrow().iter() {
+              self.walk(child, text, spans, notes, links);
+            }
+            // Push a new line after the paragraph
+            if !spans.is_empty() {
+              text.lines.push(Line::from(spans.clone()));
+              spans.clear();
+            }
+            for _ in 0..self.paragraph_spacing {
+              text.lines.push(Line::from(vec![]));
+            }
+          },
+          "br" => {
+            // A line break ends the line even when it is empty, so `<br><br>` leaves a gap.
+            text.lines.push(Line::from(std::mem::take(spans)));
+          },
+          "strong" | "b" | "em" | "i" => {
+            let modifier =
+              if matches!(tag_name, "strong" | "b") { Modifier::BOLD } else { Modifier::ITALIC };
+            let mut styled_spans = vec![];
+            for child in handle.children.borrow().iter() {
+              self.walk(child, text, &mut styled_spans, notes, links);
+            }
+            for span in styled_spans.iter_mut() {
+              span.style = span.style.add_modifier(modifier);
+            }
+            spans.extend(styled_spans);
+          },
+          "dl" => {
+            push_line(text, spans);
+            for child in handle.children.borrow().iter() {
+              self.walk(child, text, spans, notes, links);
+            }
+            push_line(text, spans);
+            for _ in 0..self.paragraph_spacing {
+              text.lines.push(Line::from(vec![]));
+            }
+          },
+          "dt" => {
+            push_line(text, spans);
+            let mut term_spans = vec![];
+            for child in handle.children.borrow().iter() {
+              self.walk(child, text, &mut term_spans, notes, links);
+            }
+            for span in term_spans.iter_mut() {
+              span.style = span.style.add_modifier(Modifier::BOLD);
+            }
+            push_line(text, &mut term_spans);
+          },
+          "dd" => {
+            push_line(text, spans);
+            // The description is indented under its term, paragraphs and all.
+            let mut description = Text::default();
+            let mut description_spans = vec![];
+            for child in handle.children.borrow().iter() {
+              self.walk(child, &mut description, &mut description_spans, notes, links);
+            }
+            push_line(&mut description, &mut description_spans);
+            for mut line in description.lines {
+              if !line.spans.is_empty() {
+                line.spans.insert(0, Span::raw(DESCRIPTION_INDENT));
+              }
+              text.lines.push(line);
+            }
+          },
+          "ul" | "ol" => {
+            push_line(text, spans);
+            // Read as an `i32`, no list counts far enough to overflow.
+            let start = attr(&attrs, "start").and_then(|start| start.parse::<i32>().ok());
+            let start = i64::from(start.unwrap_or(1));
+            let items: Vec<Handle> = handle
+              .children
+              .borrow()
+              .iter()
+              .filter(|child| is_element(child, "li"))
+              .cloned()
+              .collect();
+            // Numbers are right-aligned, so the items line up past the ninth.
+            let number_width = (start + items.len() as i64 - 1).to_string().len();
+            for (number, child) in (start..).zip(&items) {
+              let marker = match tag_name {
+                "ol" => format!("{number:>number_width$}. "),
+                _ => BULLET.to_string(),
+              };
+              let item = self.block_lines(child, notes, links);
+              let indent = " ".repeat(Span::raw(&marker).width());
+              for (i, mut line) in item.into_iter().enumerate() {
+                if !line.spans.is_empty() {
+                  let prefix = if i == 0 { marker.clone() } else { indent.clone() };
+                  line.spans.insert(0, Span::raw(prefix));
+                }
+                text.lines.push(line);
+              }
+            }
+            for _ in 0..self.paragraph_spacing {
+              text.lines.push(Line::from(vec![]));
+            }
+          },
+          "pre" => {
+            push_line(text, spans);
+            let mut code = String::new();
+            push_raw_text(handle, &mut code);
+            for line in code.trim_matches('\n').lines() {
+              let line = line.replace('\t', DESCRIPTION_INDENT);
+              text.lines.push(Line::from(vec![
+                Span::raw(DESCRIPTION_INDENT),
+                Span::styled(line, Style::default().add_modifier(Modifier::DIM)),
+              ]));
+            }
+            for _ in 0..self.paragraph_spacing {
+              text.lines.push(Line::from(vec![]));
+            }
+          },
+          "table" => {
+            push_line(text, spans);
+            self.push_table(handle, text, notes, links);
+            for _ in 0..self.paragraph_spacing {
+              text.lines.push(Line::from(vec![]));
+            }
+          },
+          "h1" | "h2" | "h3" => {
+            for child in handle.children.borrow().iter() {
+              let mut heading_spans = vec![];
+              self.walk(child, text, &mut heading_spans, notes, links);
+              for span in heading_spans.iter_mut() {
+                span.style = Style::default().add_modifier(Modifier::BOLD);
+              }
+              spans.extend(heading_spans);
+            }
+          },
+          "a" if is_footnote_backlink(&attrs) => {},
+          "a" if footnote_target(&attrs).is_some() => {
+            let number = notes.number(footnote_target(&attrs).unwrap_or_default());
+            spans.push(Span::styled(format!("[{number}]"), self.link_style));
+          },
+          "sup" | "sub" => {
+            let mut script_spans = vec![];
+            for child in handle.children.borrow().iter() {
+              self.walk(child, text, &mut script_spans, notes, links);
+            }
+            let superscript = tag_name == "sup";
+            let content: String = script_spans.iter().map(|span| span.content.as_ref()).collect();
+            if to_script(&content, superscript).is_some() {
+              for span in script_spans.iter_mut() {
+                span.content = to_script(&span.content, superscript).unwrap_or_default().into();
+              }
+            } else if !(content.starts_with('[') && content.ends_with(']')) {
+              // Footnote markers and citations are left alone, anything else is set off.
+              script_spans.insert(0, Span::raw(if superscript { "^(" } else { "_(" }));
+              script_spans.push(Span::raw(")"));
+            }
+            spans.extend(script_spans);
+          },
+          "a" => {
+            let url = attr(&attrs, "href").and_then(|href| Url::parse(&href).ok());
+            let style = match url {
+              Some(url) => {
+                links.push(url.into());
+                link_style(self.link_style, links.len() - 1)
+              },
+              None => self.link_style,
+            };
+            for child in handle.children.borrow().iter() {
+              let mut link_spans = vec![];
+              self.walk(child, text, &mut link_spans, notes, links);
+              for span in link_spans.iter_mut() {
+                span.style = style;
+              }
+              spans.extend(link_spans);
+            }
+          },
+          _ => {
+            for child in handle.children.borrow().iter() {
+              self.walk(child, text, spans, notes, links);
+            }
+          },
+        }
+      },
+      _ => {},
+    }
+  }
+}
+
+impl Renderer {
+  /// Takes the items of a footnote list as footnotes instead of showing them in place.
+  fn collect_footnotes<'a>(
+    &self,
+    handle: &Handle,
+    notes: &mut Footnotes<'a>,
+    links: &mut Vec<String>,
+  ) {
+    for child in handle.children.borrow().iter() {
+      match &child.data {
+        NodeData::Element { name, attrs, .. } if name.local.as_ref() == "li" => {
+          let id = attr(&attrs.borrow(), "id").unwrap_or_else(|| format!("#{}", notes.defs.len()));
+          let spans = self.footnote_spans(child, notes, links);
+          notes.define(id, spans);
+        },
+        _ => self.collect_footnotes(child, notes, links),
+      }
+    }
+  }
+
+  /// The lines of a block such as a list item, without the blank lines after its last
+  /// paragraph.
+  fn block_lines<'a>(
+    &self,
+    handle: &Handle,
+    notes: &mut Footnotes<'a>,
+    links: &mut Vec<String>,
+  ) -> Vec<Line<'a>> {
+    let mut text = Text::default();
+    let mut spans = vec![];
+    for child in handle.children.borrow().iter() {
+      self.walk(child, &mut text, &mut spans, notes, links);
+    }
+    push_line(&mut text, &mut spans);
+    let mut lines = text.lines;
+    while lines.last().is_some_and(|line| line.spans.is_empty()) {
+      lines.pop();
+    }
+    lines
+  }
+
+  /// Lays out the rows of a table with each column as wide as its widest cell, the cells of a
+  /// row on a single line. Header cells are bold.
+  fn push_table<'a>(
+    &self,
+    handle: &Handle,
+    text: &mut Text<'a>,
+    notes: &mut Footnotes<'a>,
+    links: &mut Vec<String>,
+  ) {
+    let mut rows = Vec::new();
+    collect_rows(handle, &mut rows);
+    let rows: Vec<Vec<Vec<Span<'a>>>> = rows
+      .iter()
+      .map(|row| {
+        row
+          .children
+          .borrow()
+          .iter()
+          .filter(|cell| is_element(cell, "td") || is_element(cell, "th"))
+          .map(|cell| {
+            let mut spans = self.footnote_spans(cell, notes, links);
+            if is_element(cell, "th") {
+              for span in spans.iter_mut() {
+                span.style = span.style.add_modifier(Modifier::BOLD);
+              }
+            }
+            spans
+          })
+          .collect()
+      })
+      .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+      .map(|column| {
+        rows
+          .iter()
+          .filter_map(|row| row.get(column))
+          .map(|cell| spans_width(cell))
+          .max()
+          .unwrap_or(0)
+      })
+      .collect();
+    for row in rows {
+      let cells = row.len();
+      let mut line = vec![];
+      for (column, cell) in row.into_iter().enumerate() {
+        if column > 0 {
+          line.push(Span::raw(CELL_SEPARATOR));
+        }
+        let padding = widths[column] - spans_width(&cell);
+        line.extend(cell);
+        // The last cell needs no padding, there being nothing after it to line up.
+        if padding > 0 && column + 1 < cells {
+          line.push(Span::raw(" ".repeat(padding)));
+        }
+      }
+      text.lines.push(Line::from(line));
+    }
+  }
+
+  /// The text of a footnote or table cell on a single line, however many paragraphs it has.
+  fn footnote_spans<'a>(
+    &self,
+    handle: &Handle,
+    notes: &mut Footnotes<'a>,
+    links: &mut Vec<String>,
+  ) -> Vec<Span<'a>> {
+    let mut text = Text::default();
+    let mut spans = vec![];
+    for child in handle.children.borrow().iter() {
+      self.walk(child, &mut text, &mut spans, notes, links);
+    }
+    let mut joined = vec![];
+    for line in text.lines.into_iter().map(|line| line.spans).chain([spans]) {
+      if line.iter().all(|span| span.content.trim().is_empty()) {
+        continue;
+      }
+      if !joined.is_empty() {
+        joined.push(Span::raw(" "));
+      }
+      joined.extend(line);
+    }
+    joined
+  }
+}
+
+/// Footnotes found while walking an article, listed together at its end.
+#[derive(Default)]
+struct Footnotes<'a> {
+  /// Ids of the footnotes in the order they are first referenced, numbered from 1.
+  refs: Vec<String>,
+  /// The text of each footnote by id, in the order they were found.
+  defs: Vec<(String, Vec<Span<'a>>)>,
+}
+
+impl<'a> Footnotes<'a> {
+  /// The number of the marker for a reference to the footnote `id`.
+  fn number(&mut self, id: String) -> usize {
+    match self.refs.iter().position(|referenced| *referenced == id) {
+      Some(index) => index + 1,
+      None => {
+        self.refs.push(id);
+        self.refs.len()
+      },
+    }
+  }
+
+  fn is_referenced(&self, id: &str) -> bool {
+    self.refs.iter().any(|referenced| referenced == id)
+  }
+
+  fn define(&mut self, id: String, spans: Vec<Span<'a>>) {
+    if !self.defs.iter().any(|(defined, _)| *defined == id) {
+      self.defs.push((id, spans));
+    }
+  }
+
+  /// Lists the footnotes under their markers, in `number_style`, followed by any that were never
+  /// referenced.
+  fn append_to(self, text: &mut Text<'a>, number_style: Style) {
+    if self.defs.is_empty() {
+      return;
+    }
+    let mut unreferenced = self.refs.len();
+    let mut numbered: Vec<(usize, Vec<Span<'a>>)> = self
+      .defs
+      .into_iter()
+      .map(|(id, spans)| {
+        let number = match self.refs.iter().position(|referenced| *referenced == id) {
+          Some(index) => index + 1,
+          None => {
+            unreferenced += 1;
+            unreferenced
+          },
+        };
+        (number, spans)
+      })
+      .collect();
+    numbered.sort_by_key(|(number, _)| *number);
+
+    text.lines.push(Line::default());
+    text.lines.push(Line::styled("Notes", Style::default().add_modifier(Modifier::BOLD)));
+    for (number, spans) in numbered {
+      let mut line = vec![Span::styled(format!("[{number}] "), number_style)];
+      line.extend(spans);
+      text.lines.push(Line::from(line));
+    }
+  }
+}
+
+fn attr(attrs: &[Attribute], name: &str) -> Option<String> {
+  attrs.iter().find(|attr| attr.name.local.as_ref() == name).map(|attr| attr.value.to_string())
+}
+
+/// Whether an element holds the footnotes of an article, as written by the common Markdown
+/// renderers and blog engines.
+fn is_footnote_list(attrs: &[Attribute]) -> bool {
+  let class = attr(attrs, "class").unwrap_or_default();
+  class.split_whitespace().any(|class| class.starts_with("footnotes"))
+    || attr(attrs, "role").as_deref() == Some("doc-endnotes")
+    || attr(attrs, "data-footnotes").is_some()
+}
+
+/// The id of the footnote a link points to, if it is a footnote reference.
+fn footnote_target(attrs: &[Attribute]) -> Option<String> {
+  let target = attr(attrs, "href")?.strip_prefix('#')?.to_string();
+  let class = attr(attrs, "class").unwrap_or_default();
+  let is_reference = attr(attrs, "role").as_deref() == Some("doc-noteref")
+    || class.contains("footnote")
+    || target.starts_with("fn")
+    || target.contains("footnote");
+  is_reference.then_some(target)
+}
+
+/// `style` marking the link at `index` in the links of the text while it is rendered. The index
+/// rides along in the underline colour, which nothing else in the text uses, until
+/// [`take_link_ranges`] moves it out of the text.
+fn link_style(style: Style, index: usize) -> Style {
+  let color = Color::Rgb((index >> 16) as u8, (index >> 8) as u8, index as u8);
+  style.underline_color(color)
+}
+
+/// The index of the link marked in `style`, if it is one.
+fn link_index(style: Style) -> Option<usize> {
+  match style.underline_color? {
+    Color::Rgb(r, g, b) => Some(usize::from(r) << 16 | usize::from(g) << 8 | usize::from(b)),
+    _ => None,
+  }
+}
+
+/// Takes the marks of the links off the spans of `text`, returning where they were. Only once
+/// the whole text is rendered, with footnotes moved to the end and descriptions indented, are
+/// the places of the links known.
+fn take_link_ranges(text: &mut Text<'_>) -> Vec<LinkRange> {
+  let mut ranges: Vec<LinkRange> = Vec::new();
+  for (line_idx, line) in text.lines.iter_mut().enumerate() {
+    let mut offset = 0;
+    for span in line.spans.iter_mut() {
+      let start = offset;
+      offset += LinkRange::count(&span.content);
+      let Some(link) = link_index(span.style) else {
+        continue;
+      };
+      span.style.underline_color = None;
+      match ranges.last_mut() {
+        // A link broken up by styles inside it is still one link.
+        Some(last) if last.line == line_idx && last.link == link && last.chars.end == start => {
+          last.chars.end = offset;
+        },
+        _ => ranges.push(LinkRange { line: line_idx, chars: start..offset, link }),
+      }
+    }
+  }
+  ranges.retain(|range| !range.chars.is_empty());
+  ranges
+}
+
+/// Whether a link leads from a footnote back to its reference, like the `↩` after most footnotes.
+fn is_footnote_backlink(attrs: &[Attribute]) -> bool {
+  let href = attr(attrs, "href").unwrap_or_default();
+  let class = attr(attrs, "class").unwrap_or_default();
+  attr(attrs, "role").as_deref() == Some("doc-backlink")
+    || href.starts_with("#fnref")
+    || ["backref", "footnote-back", "reversefootnote"].iter().any(|back| class.contains(back))
+}
+
+/// `text` in superscript or subscript characters, if Unicode has one for each.
+fn to_script(text: &str, superscript: bool) -> Option<String> {
+  let (plain, script) = if superscript {
+    ("0123456789+-=()abcdefghijklmnoprstuvwxyz", "⁰¹²³⁴⁵⁶⁷⁸⁹⁺⁻⁼⁽⁾ᵃᵇᶜᵈᵉᶠᵍʰⁱʲᵏˡᵐⁿᵒᵖʳˢᵗᵘᵛʷˣʸᶻ")
+  } else {
+    ("0123456789+-=()aehijklmnoprstuvx", "₀₁₂₃₄₅₆₇₈₉₊₋₌₍₎ₐₑₕᵢⱼₖₗₘₙₒₚᵣₛₜᵤᵥₓ")
+  };
+  if text.is_empty() {
+    return None;
+  }
+  text
+    .chars()
+    .map(|c| plain.chars().position(|p| p == c).and_then(|index| script.chars().nth(index)))
+    .collect()
+}
+
+/// Whether `handle` is an element named `tag`.
+fn is_element(handle: &Handle, tag: &str) -> bool {
+  matches!(&handle.data, NodeData::Element { name, .. } if name.local.as_ref() == tag)
+}
+
+/// Adds the rows of `handle`, a table or one of its sections, to `rows`, leaving out those of any
+/// table nested in a cell.
+fn collect_rows(handle: &Handle, rows: &mut Vec<Handle>) {
+  for child in handle.children.borrow().iter() {
+    if is_element(child, "tr") {
+      rows.push(child.clone());
+    } else if ["thead", "tbody", "tfoot"].iter().any(|section| is_element(child, section)) {
+      collect_rows(child, rows);
+    }
+  }
+}
+
+/// Adds the text in `handle` to `text` as it is, line breaks and all, as in a code block.
+fn push_raw_text(handle: &Handle, text: &mut String) {
+  match &handle.data {
+    NodeData::Text { contents } => text.push_str(&contents.borrow()),
+    NodeData::Element { name, .. } if name.local.as_ref() == "br" => text.push('\n'),
+    _ => {
+      for child in handle.children.borrow().iter() {
+        push_raw_text(child, text);
+      }
+    },
+  }
+}
+
+fn spans_width(spans: &[Span]) -> usize {
+  spans.iter().map(Span::width).sum()
+}
+
+/// Ends the current line, if anything is on it.
+fn push_line<'a>(text: &mut Text<'a>, spans: &mut Vec<Span<'a>>) {
+  if !spans.is_empty() {
+    text.lines.push(Line::from(std::mem::take(spans)));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  /// The lines `html` renders to, with trailing spaces trimmed.
+  fn lines(html: &str) -> Vec<String> {
+    let rendered = Renderer::default().render(html).unwrap();
+    rendered
+      .text
+      .lines
+      .iter()
+      .map(|line| {
+        let line: String = line.spans.iter().map(|span| span.content.as_ref()).collect();
+        line.trim_end().to_string()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn lists_are_marked_and_nested_items_indented() {
+    let html =
+      r#"<ul><li>Tea</li><li>Coffee<ol start="9"><li>Black</li><li>White</li></ol></li></ul>"#;
+    assert_eq!(lines(html), vec!["• Tea", "• Coffee", "   9. Black", "  10. White", ""]);
+  }
+
+  #[test]
+  fn table_columns_line_up() {
+    let html = "<table>
+      <thead><tr><th>Crate</th><th>Stars</th></tr></thead>
+      <tbody><tr><td>ratatui</td><td>9k</td></tr><tr><td>tokio</td><td><p>26k</p></td></tr></tbody>
+    </table>";
+    assert_eq!(lines(html), vec!["Crate   │ Stars", "ratatui │ 9k", "tokio   │ 26k", ""]);
+  }
+
+  #[test]
+  fn code_blocks_keep_their_lines() {
+    let html = "<pre><code>fn main() {\n\tprintln!(\"&lt;hi&gt;\");\n}\n</code></pre><p>After</p>";
+    assert_eq!(lines(html), vec![
+      "    fn main() {",
+      "        println!(\"<hi>\");",
+      "    }",
+      "",
+      "After",
+      ""
+    ]);
+  }
+
+  #[test]
+  fn links_are_found_wherever_they_end_up() {
+    let html = r#"<ul><li>See <a href="https://example.com/">this <b>page</b></a></li></ul>"#;
+    let rendered = Renderer::default().render(html).unwrap();
+    assert_eq!(rendered.links, vec!["https://example.com/"]);
+    assert_eq!(rendered.link_ranges, vec![LinkRange { line: 0, chars: 4..12, link: 0 }]);
+    assert!(rendered.text.lines[0].spans.iter().all(|span| span.style.underline_color.is_none()));
+  }
+}
@@ -0,0 +1,251 @@
+use std::{
+  collections::BTreeMap,
+  fs, io,
+  path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+  aead::{Aead, AeadCore, KeyInit, OsRng},
+  ChaCha20Poly1305, Key, Nonce,
+};
+use keyring::Entry;
+use thiserror::Error;
+
+use crate::{browser::percent_encode, utils::get_data_dir};
+
+const SERVICE: &str = env!("CARGO_PKG_NAME");
+const NONCE_LEN: usize = 12;
+/// Opens a placeholder for a secret in a feed link, as in `{secret:name}`.
+const PLACEHOLDER: &str = "{secret:";
+
+#[derive(Error, Debug)]
+pub enum SecretError {
+  #[error("No secret named `{0}`, set it with `{} auth set {0}`", env!("CARGO_PKG_NAME"))]
+  Missing(String),
+
+  #[error("Keyring error: {0}")]
+  KeyringError(#[from] keyring::Error),
+
+  #[error("IO error: {0}")]
+  IoError(#[from] io::Error),
+
+  #[error("Invalid secrets file: {0}")]
+  JsonError(#[from] serde_json::Error),
+
+  #[error("Secret `{0}` could not be decrypted")]
+  Corrupt(String),
+}
+
+/// Where a secret was stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Store {
+  /// The keychain or secret service of the operating system.
+  Keyring,
+  /// `secrets.json` in the data directory, for systems without a keyring.
+  File,
+}
+
+/// Looks up the secret stored under `name`, in the keyring first and then the encrypted file.
+pub fn get(name: &str) -> Result<String, SecretError> {
+  match Entry::new(SERVICE, name).and_then(|entry| entry.get_password()) {
+    Ok(secret) => return Ok(secret),
+    Err(keyring::Error::NoEntry) => {},
+    Err(error) => log::warn!("Keyring unavailable, reading {name} from the secrets file: {error}"),
+  }
+  SecretFile::open()?.get(name)?.ok_or_else(|| SecretError::Missing(name.to_string()))
+}
+
+/// Stores `secret` under `name` in the keyring, or the encrypted file when there is no keyring.
+pub fn set(name: &str, secret: &str) -> Result<Store, SecretError> {
+  match Entry::new(SERVICE, name).and_then(|entry| entry.set_password(secret)) {
+    Ok(()) => return Ok(Store::Keyring),
+    Err(error) => log::warn!("Keyring unavailable, writing {name} to the secrets file: {error}"),
+  }
+  let mut file = SecretFile::open()?;
+  file.set(name, secret);
+  file.save()?;
+  Ok(Store::File)
+}
+
+/// Whether `link` has secrets to fill in, see [`expand`].
+pub fn has_placeholders(link: &str) -> bool {
+  link.contains(PLACEHOLDER)
+}
+
+/// Fills in each `{secret:name}` placeholder of a feed link with the secret stored under `name`,
+/// percent-encoded for use in a URL. This keeps the tokens of private feeds out of the config.
+pub fn expand(link: &str) -> Result<String, SecretError> {
+  expand_with(link, get)
+}
+
+/// [`expand`] with the secrets looked up by `get`.
+fn expand_with(
+  link: &str,
+  get: impl Fn(&str) -> Result<String, SecretError>,
+) -> Result<String, SecretError> {
+  let mut expanded = String::new();
+  let mut rest = link;
+  while let Some(start) = rest.find(PLACEHOLDER) {
+    let Some(len) = rest[start..].find('}') else {
+      break;
+    };
+    let name = &rest[start + PLACEHOLDER.len()..start + len];
+    expanded.push_str(&rest[..start]);
+    expanded.push_str(&percent_encode(&get(name)?));
+    rest = &rest[start + len + 1..];
+  }
+  expanded.push_str(rest);
+  Ok(expanded)
+}
+
+/// Secrets encrypted with a key kept in its own file, readable only by the user. This keeps them
+/// out of the config, which tends to end up in dotfile repositories, but anyone who can read both
+/// files can read the secrets.
+struct SecretFile {
+  cipher: ChaCha20Poly1305,
+  path: PathBuf,
+  secrets: BTreeMap<String, String>,
+}
+
+impl SecretFile {
+  fn open() -> Result<Self, SecretError> {
+    Self::open_in(&get_data_dir())
+  }
+
+  /// The secrets file in `dir`, with its key next to it.
+  fn open_in(dir: &Path) -> Result<Self, SecretError> {
+    fs::create_dir_all(dir)?;
+    let cipher = ChaCha20Poly1305::new(&load_key(dir.join("secrets.key"))?);
+    let path = dir.join("secrets.json");
+    let secrets = match fs::read_to_string(&path) {
+      Ok(json) => serde_json::from_str(&json)?,
+      Err(error) if error.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+      Err(error) => return Err(error.into()),
+    };
+    Ok(Self { cipher, path, secrets })
+  }
+
+  fn get(&self, name: &str) -> Result<Option<String>, SecretError> {
+    let Some(encoded) = self.secrets.get(name) else {
+      return Ok(None);
+    };
+    let corrupt = || SecretError::Corrupt(name.to_string());
+    let sealed = STANDARD.decode(encoded).map_err(|_| corrupt())?;
+    if sealed.len() < NONCE_LEN {
+      return Err(corrupt());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let secret =
+      self.cipher.decrypt(Nonce::from_slice(nonce), ciphertext).map_err(|_| corrupt())?;
+    String::from_utf8(secret).map(Some).map_err(|_| corrupt())
+  }
+
+  fn set(&mut self, name: &str, secret: &str) {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext =
+      self.cipher.encrypt(&nonce, secret.as_bytes()).expect("encrypting in memory cannot fail");
+    let sealed = [nonce.as_slice(), &ciphertext].concat();
+    self.secrets.insert(name.to_string(), STANDARD.encode(sealed));
+  }
+
+  fn save(&self) -> Result<(), SecretError> {
+    write_private(&self.path, serde_json::to_string_pretty(&self.secrets)?.as_bytes())?;
+    Ok(())
+  }
+}
+
+/// The key for the secrets file, generated the first time it is needed.
+fn load_key(path: PathBuf) -> io::Result<Key> {
+  match fs::read(&path) {
+    Ok(bytes) if bytes.len() == 32 => Ok(*Key::from_slice(&bytes)),
+    Ok(_) => {
+      Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a key", path.display())))
+    },
+    Err(error) if error.kind() == io::ErrorKind::NotFound => {
+      let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+      write_private(&path, &key)?;
+      Ok(key)
+    },
+    Err(error) => Err(error),
+  }
+}
+
+/// Writes a file only its owner can read.
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+  let mut options = fs::OpenOptions::new();
+  options.write(true).create(true).truncate(true);
+  #[cfg(unix)]
+  std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+  io::Write::write_all(&mut options.open(path)?, contents)
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::testing::TempDir;
+
+  #[test]
+  fn secrets_survive_a_round_trip_through_the_file() {
+    let dir = TempDir::new();
+    let mut file = SecretFile::open_in(dir.path()).unwrap();
+    file.set("token", "s3cr3t");
+    file.save().unwrap();
+    assert!(!fs::read_to_string(dir.path().join("secrets.json")).unwrap().contains("s3cr3t"));
+
+    let file = SecretFile::open_in(dir.path()).unwrap();
+    assert_eq!(file.get("token").unwrap(), Some("s3cr3t".to_string()));
+    assert_eq!(file.get("other").unwrap(), None);
+  }
+
+  #[test]
+  fn secrets_do_not_decrypt_with_another_key() {
+    let dir = TempDir::new();
+    let mut file = SecretFile::open_in(dir.path()).unwrap();
+    file.set("token", "s3cr3t");
+    file.save().unwrap();
+    fs::write(dir.path().join("secrets.key"), ChaCha20Poly1305::generate_key(&mut OsRng)).unwrap();
+
+    let file = SecretFile::open_in(dir.path()).unwrap();
+    assert!(matches!(file.get("token"), Err(SecretError::Corrupt(name)) if name == "token"));
+  }
+
+  fn lookup(name: &str) -> Result<String, SecretError> {
+    match name {
+      "token" => Ok("a b&c".to_string()),
+      _ => Err(SecretError::Missing(name.to_string())),
+    }
+  }
+
+  #[test]
+  fn expands_known_placeholders() {
+    assert_eq!(
+      expand_with("https://example.com/feed?key={secret:token}&u={secret:token}", lookup).unwrap(),
+      "https://example.com/feed?key=a%20b%26c&u=a%20b%26c"
+    );
+    assert_eq!(
+      expand_with("https://example.com/feed", lookup).unwrap(),
+      "https://example.com/feed"
+    );
+  }
+
+  #[test]
+  fn an_unknown_placeholder_is_an_error() {
+    let error = expand_with("https://example.com/{secret:other}", lookup).unwrap_err();
+    assert!(matches!(error, SecretError::Missing(name) if name == "other"));
+  }
+
+  #[test]
+  fn a_malformed_placeholder_is_left_as_it_is() {
+    assert_eq!(
+      expand_with("https://example.com/?key={secret:token", lookup).unwrap(),
+      "https://example.com/?key={secret:token"
+    );
+    assert_eq!(
+      expand_with("https://example.com/{token}?key={secret:token", lookup).unwrap(),
+      "https://example.com/{token}?key={secret:token"
+    );
+  }
+}
@@ -0,0 +1,54 @@
+use std::{io, process::Stdio};
+
+use tokio::{io::AsyncWriteExt, process::Command};
+use tokio_util::sync::CancellationToken;
+
+/// Pipes `text` to the text-to-speech `command` and waits until it is done speaking, or kills it
+/// once `token` is cancelled.
+pub async fn speak(command: &str, text: &str, token: CancellationToken) -> io::Result<()> {
+  let mut shell = std::process::Command::new("sh");
+  shell.arg("-c").arg(command).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null());
+  // Its own process group, so stopping takes down what the shell started along with it.
+  #[cfg(unix)]
+  std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+  let mut child = Command::from(shell).kill_on_drop(true).spawn()?;
+  let mut group = ProcessGroup(child.id());
+
+  let mut stdin = child.stdin.take();
+  let speaking = async {
+    if let Some(stdin) = &mut stdin {
+      stdin.write_all(text.as_bytes()).await?;
+    }
+    // Closing stdin tells the command the text is complete.
+    drop(stdin.take());
+    child.wait().await
+  };
+
+  tokio::select! {
+    status = speaking => {
+      group.0 = None;
+      let status = status?;
+      if !status.success() {
+        log::warn!("Text-to-speech command exited with {}", status);
+      }
+    },
+    _ = token.cancelled() => {},
+  }
+  Ok(())
+}
+
+/// Kills the process group led by the command when dropped, unless it has been cleared once the
+/// command exited. Killing only the shell would leave a player it started still speaking.
+struct ProcessGroup(Option<u32>);
+
+impl Drop for ProcessGroup {
+  fn drop(&mut self) {
+    #[cfg(unix)]
+    if let Some(pid) = self.0.and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+      // SAFETY: `killpg` only sends a signal and touches no memory of this process.
+      unsafe {
+        libc::killpg(pid, libc::SIGKILL);
+      }
+    }
+  }
+}
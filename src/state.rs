@@ -0,0 +1,48 @@
+use std::{fs, path::Path};
+
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+
+/// Version of the state file layout, bumped whenever it changes incompatibly.
+const STATE_VERSION: u32 = 1;
+
+/// Whether an article, identified by its URL, has been read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemState {
+  pub url: String,
+  pub read: bool,
+}
+
+/// The reading history written by `state export`, independent of database ids so it can be
+/// imported into a fresh database.
+#[derive(Debug, Serialize, Deserialize)]
+struct StateFile {
+  version: u32,
+  items: Vec<ItemState>,
+}
+
+/// Writes the read state of every article to `path` as JSON.
+pub fn export(db: &Database, path: &Path) -> Result<usize> {
+  let items = db.get_item_states()?;
+  let count = items.len();
+  let file = StateFile { version: STATE_VERSION, items };
+  fs::write(path, serde_json::to_string_pretty(&file)?)?;
+  Ok(count)
+}
+
+/// Restores the read state in `path`. Articles that have not been fetched yet are marked read
+/// when they first arrive.
+pub fn import(db: &Database, path: &Path) -> Result<usize> {
+  let file: StateFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+  if file.version > STATE_VERSION {
+    return Err(eyre!(
+      "{} was written by a newer version of {} (state version {})",
+      path.display(),
+      env!("CARGO_PKG_NAME"),
+      file.version
+    ));
+  }
+  Ok(db.import_item_states(&file.items)?)
+}
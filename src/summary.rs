@@ -0,0 +1,153 @@
+use std::{io, process::Stdio, time::Duration};
+
+use serde_json::json;
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+  config::SummaryConfig,
+  db::FeedItem,
+  fetcher::{FetchError, Fetcher},
+  task::{self, ShellCommand},
+};
+
+#[derive(Error, Debug)]
+pub enum SummaryError {
+  #[error("No summarizer configured, set `summary.command` or `summary.endpoint`")]
+  NotConfigured,
+
+  #[error("Summarizer timed out")]
+  Timeout,
+
+  #[error("Summarizer failed: {0}")]
+  Io(#[from] io::Error),
+
+  #[error("Summarizer failed: {0}")]
+  Http(#[from] FetchError),
+
+  #[error("Summarizer returned nothing")]
+  Empty,
+}
+
+/// Asks the configured summarizer for a summary of `text`, the extracted text of `item`. The
+/// command is preferred over the endpoint, which is posted to with `fetcher`, when both are set.
+pub async fn summarize(
+  config: &SummaryConfig,
+  fetcher: &Fetcher,
+  item: &FeedItem,
+  text: &str,
+) -> Result<String, SummaryError> {
+  let timeout = Duration::from_secs(config.timeout_secs);
+  let summarizing = async {
+    match (&config.command, &config.endpoint) {
+      (Some(command), _) => run_command(&task::command_line(command, item), text).await,
+      (None, Some(endpoint)) => post(fetcher, endpoint, item, text).await,
+      (None, None) => Err(SummaryError::NotConfigured),
+    }
+  };
+  let summary =
+    tokio::time::timeout(timeout, summarizing).await.map_err(|_| SummaryError::Timeout)??;
+  let summary = summary.trim();
+  if summary.is_empty() {
+    return Err(SummaryError::Empty);
+  }
+  Ok(summary.to_string())
+}
+
+/// Pipes `text` to `command` and reads the summary from its stdout.
+async fn run_command(command: &ShellCommand, text: &str) -> Result<String, SummaryError> {
+  let mut child = command
+    .command()
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true)
+    .spawn()?;
+  let stdin = child.stdin.take();
+  // The text is written while the output is read, as a command answering before it has read
+  // everything would otherwise fill its stdout pipe and wait on us while we wait on it.
+  let writing = async move {
+    if let Some(mut stdin) = stdin {
+      stdin.write_all(text.as_bytes()).await?;
+      // Dropping stdin closes it, telling the command the text is complete.
+    }
+    Ok::<_, io::Error>(())
+  };
+  let (written, output) = tokio::join!(writing, child.wait_with_output());
+  let output = output?;
+  match written {
+    // A command may well stop reading once it has what it needs.
+    Err(error) if error.kind() != io::ErrorKind::BrokenPipe => return Err(error.into()),
+    _ => {},
+  }
+  if !output.status.success() {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    return Err(io::Error::other(format!("{} {}", output.status, stderr.trim())).into());
+  }
+  Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Posts the article to `endpoint` as JSON. The answer is the summary, either as plain text or
+/// in the `summary` field of a JSON object.
+async fn post(
+  fetcher: &Fetcher,
+  endpoint: &str,
+  item: &FeedItem,
+  text: &str,
+) -> Result<String, SummaryError> {
+  let body = json!({ "title": item.title, "url": item.url, "text": text });
+  let response = fetcher.post_json(endpoint, &body).await?;
+  let summary = serde_json::from_str::<serde_json::Value>(&response)
+    .ok()
+    .and_then(|value| value.get("summary")?.as_str().map(str::to_string));
+  Ok(summary.unwrap_or(response))
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::{
+    config::FetchConfig,
+    testing::{MockServer, Reply},
+  };
+
+  fn item() -> FeedItem {
+    FeedItem { title: "A long read".to_string(), ..Default::default() }
+  }
+
+  fn config(command: Option<&str>, endpoint: Option<String>) -> SummaryConfig {
+    SummaryConfig { command: command.map(str::to_string), endpoint, ..Default::default() }
+  }
+
+  #[tokio::test]
+  async fn pipes_long_texts_through_the_command() {
+    let fetcher = Fetcher::new(FetchConfig::default()).unwrap();
+    // More than a pipe holds, echoed back before all of it is read.
+    let text = "word ".repeat(200_000);
+    let summary =
+      summarize(&config(Some("cat | wc -w"), None), &fetcher, &item(), &text).await.unwrap();
+    assert_eq!(summary, "200000");
+    let summary = summarize(&config(Some("cat"), None), &fetcher, &item(), &text).await.unwrap();
+    assert_eq!(summary.len(), text.trim().len());
+  }
+
+  #[tokio::test]
+  async fn a_command_may_stop_reading_early() {
+    let fetcher = Fetcher::new(FetchConfig::default()).unwrap();
+    let text = "word ".repeat(200_000);
+    let summary =
+      summarize(&config(Some("head -c 9"), None), &fetcher, &item(), &text).await.unwrap();
+    assert_eq!(summary, "word word");
+  }
+
+  #[tokio::test]
+  async fn reads_the_summary_field_of_the_endpoint() {
+    let server =
+      MockServer::start(vec![("/summarize", vec![Reply::ok(r#"{"summary": "In short."}"#)])]).await;
+    let fetcher = Fetcher::new(FetchConfig::default()).unwrap();
+    let config = config(None, Some(server.url("/summarize")));
+    assert_eq!(summarize(&config, &fetcher, &item(), "Text").await.unwrap(), "In short.");
+  }
+}
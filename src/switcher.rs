@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  action::Action,
+  db::{Database, DbError, Feed, Group, SmartView},
+};
+
+/// A group, smart view or feed the quick switcher can jump to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Destination {
+  pub group: Group,
+  /// The feed within `group`, or `None` for the whole group.
+  pub feed: Option<Feed>,
+  /// Unread articles, unknown for the smart views.
+  pub unread: Option<usize>,
+}
+
+impl Destination {
+  pub fn name(&self) -> &str {
+    self.feed.as_ref().map_or(&self.group.name, |feed| &feed.name)
+  }
+
+  /// The group id and feed id of the destination, which tell the tab showing it apart.
+  pub fn key(&self) -> (i32, Option<i32>) {
+    (self.group.id, self.feed.as_ref().map(|feed| feed.id))
+  }
+
+  /// The action opening the destination's articles in a new tab.
+  pub fn new_tab_action(&self) -> Action {
+    match (&self.feed, self.group.id) {
+      (Some(feed), _) => Action::NewTabArticleViewFeed(self.group.clone(), feed.clone()),
+      (None, -1) => Action::NewTabArticleViewAll,
+      (None, id) => {
+        match SmartView::from_group_id(id) {
+          Some(view) => Action::NewTabArticleViewSmart(view),
+          None => Action::NewTabArticleViewGroup(self.group.clone()),
+        }
+      },
+    }
+  }
+
+  /// How well `query` matches the destination, or `None` if it does not. Feeds also match on
+  /// the name of their group.
+  pub fn score(&self, query: &str) -> Option<i64> {
+    match &self.feed {
+      Some(feed) => {
+        fuzzy_score(query, &feed.name)
+          .or_else(|| fuzzy_score(query, &format!("{} {}", self.group.name, feed.name)))
+      },
+      None => fuzzy_score(query, &self.group.name),
+    }
+  }
+}
+
+/// Every group, smart view and feed, each group followed by its feeds, with unread counts.
+pub fn destinations(db: &Database) -> Result<Vec<Destination>, DbError> {
+  let total_unread = db.count_unread()?;
+  let group_unread = db.count_unread_by_group()?;
+  let feed_unread = db.count_unread_by_feed()?;
+  let feeds = db.get_feeds()?;
+
+  let mut destinations = Vec::new();
+  for group in db.get_groups()? {
+    let unread = match group.id {
+      -1 => Some(total_unread),
+      id if id < 0 => None,
+      id => Some(group_unread.get(&id).copied().unwrap_or(0)),
+    };
+    let group_feeds: Vec<Destination> = feeds
+      .iter()
+      .filter(|feed| feed.group_id == group.id)
+      .map(|feed| {
+        Destination {
+          group: group.clone(),
+          feed: Some(feed.clone()),
+          unread: Some(feed_unread.get(&feed.id).copied().unwrap_or(0)),
+        }
+      })
+      .collect();
+    destinations.push(Destination { group, feed: None, unread });
+    destinations.extend(group_feeds);
+  }
+  Ok(destinations)
+}
+
+/// Scores `text` against `query` typed as an abbreviation, ignoring case: every character of the
+/// query has to appear in order. Characters matched at the start of a word or right after the
+/// previous match score higher, and an empty query matches everything equally.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+  let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+  let mut score = 0;
+  let mut next = 0;
+  let mut previous: Option<usize> = None;
+  for wanted in query.chars().flat_map(char::to_lowercase).filter(|c| !c.is_whitespace()) {
+    let found = next + text[next..].iter().position(|&c| c == wanted)?;
+    score += 1;
+    if found == 0 || !text[found - 1].is_alphanumeric() {
+      score += 8;
+    }
+    if previous.is_some_and(|previous| previous + 1 == found) {
+      score += 4;
+    }
+    // Matches spread far apart are worth less.
+    score -= (found - next) as i64 / 4;
+    previous = Some(found);
+    next = found + 1;
+  }
+  Some(score)
+}
@@ -0,0 +1,162 @@
+use std::{fmt::Write, io, process::Stdio};
+
+use tokio::process::Command;
+
+use crate::{browser::percent_encode, db::FeedItem};
+
+/// A shell command filled in from a template. Its placeholders become positional parameters of
+/// the shell instead of text pasted into the script, so no title or link, whatever it holds, is
+/// ever read as shell code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellCommand {
+  script: String,
+  args: Vec<String>,
+}
+
+impl ShellCommand {
+  /// Fills in `template`, where `%name` stands for the value of each `(name, value)` of `values`.
+  /// A placeholder may stand on its own or inside single or double quotes.
+  pub fn new(template: &str, values: &[(&str, &str)]) -> Self {
+    let names: Vec<&str> = values.iter().map(|(name, _)| *name).collect();
+    let args = values.iter().map(|(_, value)| value.to_string()).collect();
+    Self { script: script(template, &names), args }
+  }
+
+  /// The script passed to `sh -c`, with `$1`, `$2` and so on in place of the placeholders.
+  pub fn script(&self) -> &str {
+    &self.script
+  }
+
+  /// A `sh` process running the command, with the values as its positional parameters.
+  pub fn command(&self) -> Command {
+    let mut command = Command::new("sh");
+    // The first argument after the script is `$0`, the name the script runs under.
+    command.arg("-c").arg(&self.script).arg("sh").args(&self.args);
+    command
+  }
+}
+
+/// `template` with each `%name` of `names` replaced by the positional parameter of its place in
+/// `names`, quoted to fit in wherever it stands.
+fn script(template: &str, names: &[&str]) -> String {
+  let mut script = String::with_capacity(template.len());
+  let mut quote = None;
+  let mut chars = template.char_indices();
+  while let Some((i, c)) = chars.next() {
+    let placeholder = (c == '%')
+      .then(|| names.iter().position(|name| template[i + 1..].starts_with(name)))
+      .flatten();
+    if let Some(idx) = placeholder {
+      let param = idx + 1;
+      let _ = match quote {
+        None => write!(script, "\"${{{param}}}\""),
+        Some('"') => write!(script, "${{{param}}}"),
+        // Nothing expands between single quotes, so they are closed around the parameter.
+        Some(_) => write!(script, "'\"${{{param}}}\"'"),
+      };
+      for _ in 0..names[idx].chars().count() {
+        chars.next();
+      }
+      continue;
+    }
+    script.push(c);
+    match (quote, c) {
+      (Some(open), c) if open == c => quote = None,
+      (None, '\'' | '"') => quote = Some(c),
+      // An escaped character is kept as it is, even a quote or the `%` of a placeholder.
+      (None | Some('"'), '\\') => script.extend(chars.next().map(|(_, c)| c)),
+      _ => {},
+    }
+  }
+  script
+}
+
+/// Fills in the placeholders of a task command: `%title` with the article's title, `%url` with
+/// its link and `%feed` with the name of its feed.
+pub fn command_line(template: &str, item: &FeedItem) -> ShellCommand {
+  ShellCommand::new(template, &[
+    ("title", &item.title),
+    ("url", &item.url),
+    ("feed", &item.feed_name),
+  ])
+}
+
+/// Fills in the placeholders of a share link like those of a task command, URL-encoded instead.
+pub fn share_url(template: &str, item: &FeedItem) -> String {
+  template
+    .replace("%title", &percent_encode(&item.title))
+    .replace("%url", &percent_encode(&item.url))
+    .replace("%feed", &percent_encode(&item.feed_name))
+}
+
+/// Runs the task `command` in the background, off the terminal. A command that fails is
+/// reported with what it wrote to stderr.
+pub async fn capture(command: &ShellCommand) -> io::Result<()> {
+  run_quietly(command.command()).await
+}
+
+/// Shows a desktop notification of the alert `item` with `notify-send`, or with the shell
+/// `command` filled in as a task command when one is configured.
+pub async fn notify(item: &FeedItem, command: Option<&str>) -> io::Result<()> {
+  if let Some(template) = command {
+    return capture(&command_line(template, item)).await;
+  }
+  let mut notify_send = Command::new("notify-send");
+  // After `--`, not even a title starting with a dash is taken for an option.
+  notify_send.arg("--app-name=nuuslees").arg("--").arg(&item.feed_name).arg(&item.title);
+  run_quietly(notify_send).await
+}
+
+/// Runs `command` without input or output, reporting a failure with what it wrote to stderr.
+async fn run_quietly(mut command: Command) -> io::Result<()> {
+  let output =
+    command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped()).output().await?;
+  if output.status.success() {
+    return Ok(());
+  }
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  Err(io::Error::other(format!("{} {}", output.status, stderr.trim())))
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  fn item(title: &str) -> FeedItem {
+    FeedItem {
+      title: title.to_string(),
+      url: "https://example.com/a?b=1&c=2".to_string(),
+      feed_name: "Example".to_string(),
+      ..Default::default()
+    }
+  }
+
+  /// What `template` filled in for `item` prints.
+  async fn run(template: &str, item: &FeedItem) -> String {
+    let output = command_line(template, item).command().output().await.unwrap();
+    String::from_utf8(output.stdout).unwrap()
+  }
+
+  #[test]
+  fn placeholders_become_positional_parameters() {
+    let command = command_line(r#"task add %title '%url' "Read: %feed" \%title"#, &item("x"));
+    assert_eq!(command.script(), r#"task add "${1}" ''"${2}"'' "Read: ${3}" \%title"#);
+  }
+
+  #[tokio::test]
+  async fn titles_are_never_run_as_shell_code() {
+    for title in [
+      "$(echo pwned)",
+      "`echo pwned`",
+      "'; echo pwned; '",
+      "\"; echo pwned; \"",
+      "%url",
+      "Rust's %feed \\ \"release\"",
+    ] {
+      let printed = run(r#"printf '%s|' %title '%title' "%title" %url"#, &item(title)).await;
+      assert_eq!(printed, format!("{title}|{title}|{title}|https://example.com/a?b=1&c=2|"));
+    }
+  }
+}
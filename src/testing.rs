@@ -0,0 +1,344 @@
+//! Helpers for the tests: a local HTTP server answering with canned responses, a harness that
+//! runs the app against it without a terminal, and golden snapshots of what gets drawn.
+
+use std::{
+  collections::HashMap,
+  net::SocketAddr,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+  },
+  time::Duration,
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, Terminal};
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpListener,
+  sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+};
+
+use crate::{
+  action::Action,
+  app::App,
+  cache::ArticleCache,
+  config::{Config, FeedConfig, GroupConfig, StartupRefresh},
+  db::Database,
+  fetcher::Fetcher,
+  tui::Event,
+};
+
+/// A canned response of a [`MockServer`].
+#[derive(Debug, Clone)]
+pub struct Reply {
+  status: u16,
+  headers: Vec<(String, String)>,
+  body: String,
+}
+
+impl Reply {
+  pub fn ok(body: &str) -> Self {
+    Self { status: 200, headers: Vec::new(), body: body.to_string() }
+  }
+
+  pub fn status(status: u16) -> Self {
+    Self { status, headers: Vec::new(), body: String::new() }
+  }
+
+  /// A redirect with `status` to `location`, which can be relative to the server.
+  pub fn redirect(status: u16, location: &str) -> Self {
+    Self::status(status).header("Location", location)
+  }
+
+  pub fn header(mut self, name: &str, value: &str) -> Self {
+    self.headers.push((name.to_string(), value.to_string()));
+    self
+  }
+}
+
+/// A request the server was sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+  pub path: String,
+  pub user_agent: Option<String>,
+}
+
+/// The replies of each path, with how many of them have been served.
+type Routes = HashMap<String, (Vec<Reply>, usize)>;
+
+/// Serves its routes on a free port of the loopback interface until dropped, answering 404 for
+/// any other path. Each route answers with its replies in turn, repeating the last.
+pub struct MockServer {
+  addr: SocketAddr,
+  routes: Arc<Mutex<Routes>>,
+  requests: Arc<Mutex<Vec<Request>>>,
+  task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+  pub async fn start(routes: Vec<(&str, Vec<Reply>)>) -> Self {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let routes: Routes =
+      routes.into_iter().map(|(path, replies)| (path.to_string(), (replies, 0))).collect();
+    let routes = Arc::new(Mutex::new(routes));
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let task = tokio::spawn({
+      let routes = routes.clone();
+      let requests = requests.clone();
+      async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+          let routes = routes.clone();
+          let requests = requests.clone();
+          tokio::spawn(async move {
+            let Some(request) = read_request(&mut stream).await else {
+              return;
+            };
+            let reply = {
+              let mut routes = routes.lock().unwrap();
+              match routes.get_mut(&request.path) {
+                Some((replies, served)) => {
+                  let reply = replies[(*served).min(replies.len() - 1)].clone();
+                  *served += 1;
+                  reply
+                },
+                None => Reply::status(404),
+              }
+            };
+            requests.lock().unwrap().push(request);
+            let _ = stream.write_all(&encode(&reply)).await;
+          });
+        }
+      }
+    });
+    Self { addr, routes, requests, task }
+  }
+
+  /// Adds a route, or replaces one, for replies that need the server's own URL.
+  pub fn route(&self, path: &str, replies: Vec<Reply>) {
+    self.routes.lock().unwrap().insert(path.to_string(), (replies, 0));
+  }
+
+  pub fn url(&self, path: &str) -> String {
+    format!("http://{}{path}", self.addr)
+  }
+
+  /// The requests served so far, in the order they came in.
+  pub fn requests(&self) -> Vec<Request> {
+    self.requests.lock().unwrap().clone()
+  }
+}
+
+impl Drop for MockServer {
+  fn drop(&mut self) {
+    self.task.abort();
+  }
+}
+
+/// Reads the head of a request, which is all a GET has.
+async fn read_request(stream: &mut tokio::net::TcpStream) -> Option<Request> {
+  let mut head = Vec::new();
+  let mut buf = [0; 1024];
+  while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+    let read = stream.read(&mut buf).await.ok()?;
+    if read == 0 {
+      return None;
+    }
+    head.extend_from_slice(&buf[..read]);
+  }
+  let head = String::from_utf8_lossy(&head);
+  let mut lines = head.lines();
+  let path = lines.next()?.split_whitespace().nth(1)?.to_string();
+  let user_agent = lines
+    .filter_map(|line| line.split_once(':'))
+    .find(|(name, _)| name.eq_ignore_ascii_case("user-agent"))
+    .map(|(_, value)| value.trim().to_string());
+  Some(Request { path, user_agent })
+}
+
+fn encode(reply: &Reply) -> Vec<u8> {
+  let mut response = format!("HTTP/1.1 {} Mock\r\n", reply.status);
+  for (name, value) in &reply.headers {
+    response.push_str(&format!("{name}: {value}\r\n"));
+  }
+  response.push_str(&format!(
+    "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+    reply.body.len(),
+    reply.body
+  ));
+  response.into_bytes()
+}
+
+/// A directory of its own under the system's temporary one, removed with everything in it once
+/// dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+  pub fn new() -> Self {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let name = format!(
+      "{}-test-{}-{}",
+      env!("CARGO_PKG_NAME"),
+      std::process::id(),
+      COUNT.fetch_add(1, Ordering::Relaxed)
+    );
+    let path = std::env::temp_dir().join(name);
+    std::fs::create_dir_all(&path).unwrap();
+    Self(path)
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.0
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
+
+/// The config as loaded from an empty file, with every default, quitting without asking and
+/// dates shown as they are, so what is drawn stays the same from day to day.
+pub fn config() -> Config {
+  let mut config: Config = config::Config::builder().build().unwrap().try_deserialize().unwrap();
+  config.confirm_quit = false;
+  config.display.terminal_title = false;
+  config.display.relative_dates = false;
+  config.display.locale = Some("en_US".to_string());
+  config.fetch.on_startup = StartupRefresh::Never;
+  config.fetch.retries = 0;
+  config
+}
+
+/// A group of the config holding the feeds at `links`.
+pub fn group(name: &str, links: &[String]) -> GroupConfig {
+  GroupConfig {
+    name: name.to_string(),
+    desc: format!("{name} for the tests"),
+    feeds: links
+      .iter()
+      .map(|link| FeedConfig { link: link.clone(), ..Default::default() })
+      .collect(),
+    ..Default::default()
+  }
+}
+
+/// The app around `db` with the default config, for calling its action handlers directly. Nothing
+/// is started: no components are registered, no database writer runs and nothing is fetched.
+pub fn app(db: Database) -> App {
+  let config = config();
+  let fetcher = Arc::new(Fetcher::new(config.fetch.clone()).unwrap());
+  App::with_parts(config, db, fetcher, ArticleCache::new(PathBuf::new(), 0)).unwrap()
+}
+
+/// Runs the whole app without a terminal: actions go through the same dispatch as in the event
+/// loop, and frames are drawn onto a `TestBackend`. The database lives in a temporary directory.
+pub struct Harness {
+  pub app: App,
+  tx: UnboundedSender<Action>,
+  rx: UnboundedReceiver<Action>,
+  /// Every action dispatched so far, in order.
+  pub actions: Vec<Action>,
+  terminal: Terminal<TestBackend>,
+  _data_dir: TempDir,
+}
+
+impl Harness {
+  /// Starts the app with `config` on a screen of `width` by `height`, as `App::run` does.
+  pub async fn start(config: Config, width: u16, height: u16) -> Self {
+    let data_dir = TempDir::new();
+    let data_path = data_dir.path().to_str().unwrap().to_string();
+    let mut db = Database::new(&data_path).await.unwrap();
+    db.set_config(config.clone());
+    db.init().await.unwrap();
+    let fetcher = Arc::new(Fetcher::new(config.fetch.clone()).unwrap());
+    let cache = ArticleCache::new(data_dir.path().join("articles"), 0);
+    let mut app = App::with_parts(config, db, fetcher, cache).unwrap();
+    let (tx, rx) = mpsc::unbounded_channel();
+    app.register_components(&tx, Rect::new(0, 0, width, height)).unwrap();
+    app.start_db_writer(Database::new(&data_path).await.unwrap(), &tx);
+    app.start(&tx).unwrap();
+    let terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let mut harness = Self { app, tx, rx, actions: Vec::new(), terminal, _data_dir: data_dir };
+    harness.settle().await;
+    harness
+  }
+
+  /// Queues `action` as a component or background task would.
+  pub fn send(&self, action: Action) {
+    self.tx.send(action).unwrap();
+  }
+
+  /// Presses `code`, without modifiers.
+  pub fn press(&mut self, code: KeyCode) {
+    self.app.handle_event(Event::Key(KeyEvent::from(code)), &self.tx).unwrap();
+  }
+
+  /// Dispatches queued actions until there are none left.
+  pub async fn settle(&mut self) {
+    while let Ok(action) = self.rx.try_recv() {
+      self.dispatch(action).await;
+    }
+  }
+
+  /// Dispatches actions as they come, waiting for background tasks such as a refresh, until one
+  /// matching `done` has been dispatched. Panics if none comes within ten seconds.
+  pub async fn run_until(&mut self, done: impl Fn(&Action) -> bool) {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    loop {
+      let action = tokio::time::timeout_at(deadline, self.rx.recv())
+        .await
+        .expect("timed out waiting for an action")
+        .unwrap();
+      let finished = done(&action);
+      self.dispatch(action).await;
+      if finished {
+        break;
+      }
+    }
+    self.settle().await;
+  }
+
+  async fn dispatch(&mut self, action: Action) {
+    self.actions.push(action.clone());
+    self.app.dispatch(action, None, &self.tx).await.unwrap();
+  }
+
+  /// Draws a frame, returning the screen as lines of text.
+  pub fn render(&mut self) -> String {
+    let Self { app, tx, terminal, .. } = self;
+    let frame = terminal.draw(|f| app.render(f, tx)).unwrap();
+    buffer_text(frame.buffer)
+  }
+}
+
+/// The symbols of `buffer` as lines of text, with trailing spaces trimmed.
+pub fn buffer_text(buffer: &Buffer) -> String {
+  let area = buffer.area;
+  (area.top()..area.bottom())
+    .map(|y| {
+      let line: String = (area.left()..area.right()).map(|x| buffer.get(x, y).symbol()).collect();
+      line.trim_end().to_string()
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Compares the text of `buffer` with `tests/snapshots/{name}.txt`. Run the tests with
+/// `UPDATE_SNAPSHOTS=1` to write the snapshots instead, after a change meant to alter the layout.
+pub fn assert_snapshot(name: &str, buffer: &Buffer) {
+  let path =
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(format!("{name}.txt"));
+  let actual = buffer_text(buffer) + "\n";
+  if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, &actual).unwrap();
+    return;
+  }
+  let expected = std::fs::read_to_string(&path)
+    .unwrap_or_else(|error| panic!("Failed to read {}: {error}", path.display()));
+  pretty_assertions::assert_eq!(expected, actual, "snapshot {name} changed");
+}
@@ -7,8 +7,8 @@ use color_eyre::eyre::Result;
 use crossterm::{
   cursor,
   event::{
-    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event as CrosstermEvent,
-    KeyEvent, KeyEventKind, MouseEvent,
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    Event as CrosstermEvent, KeyEvent, KeyEventKind, MouseEvent,
   },
   terminal::{EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -65,7 +65,17 @@ impl Tui {
     let task = tokio::spawn(async {});
     let mouse = false;
     let paste = false;
-    Ok(Self { terminal, task, cancellation_token, event_rx, event_tx, frame_rate, tick_rate, mouse, paste })
+    Ok(Self {
+      terminal,
+      task,
+      cancellation_token,
+      event_rx,
+      event_tx,
+      frame_rate,
+      tick_rate,
+      mouse,
+      paste,
+    })
   }
 
   pub fn tick_rate(mut self, tick_rate: f64) -> Self {
@@ -185,14 +195,7 @@ impl Tui {
     self.stop()?;
     if crossterm::terminal::is_raw_mode_enabled()? {
       self.flush()?;
-      if self.paste {
-        crossterm::execute!(io(), DisableBracketedPaste)?;
-      }
-      if self.mouse {
-        crossterm::execute!(io(), DisableMouseCapture)?;
-      }
-      crossterm::execute!(io(), LeaveAlternateScreen, cursor::Show)?;
-      crossterm::terminal::disable_raw_mode()?;
+      restore()?;
     }
     Ok(())
   }
@@ -218,6 +221,22 @@ impl Tui {
   }
 }
 
+/// Puts the terminal back the way it was found, whatever `Tui::enter` turned on. It needs no `Tui`,
+/// so the panic hook can call it while the `Tui` is borrowed mid-draw.
+pub fn restore() -> Result<()> {
+  if crossterm::terminal::is_raw_mode_enabled()? {
+    crossterm::execute!(
+      io(),
+      DisableBracketedPaste,
+      DisableMouseCapture,
+      LeaveAlternateScreen,
+      cursor::Show
+    )?;
+    crossterm::terminal::disable_raw_mode()?;
+  }
+  Ok(())
+}
+
 impl Deref for Tui {
   type Target = ratatui::Terminal<Backend<IO>>;
 
@@ -234,6 +253,9 @@ impl DerefMut for Tui {
 
 impl Drop for Tui {
   fn drop(&mut self) {
-    self.exit().unwrap();
+    // Also runs while unwinding, where a second panic would abort before the terminal is restored.
+    if let Err(e) = self.exit() {
+      log::error!("Unable to restore the terminal: {:?}", e);
+    }
   }
 }
@@ -1,42 +1,91 @@
-use std::path::PathBuf;
+use std::{
+  ffi::OsString,
+  path::{Path, PathBuf},
+};
 
-use color_eyre::eyre::Result;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{Result, WrapErr};
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
 use tracing::error;
 use tracing_error::ErrorLayer;
-use tracing_subscriber::{self, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, Layer};
+use tracing_subscriber::{
+  self, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, Layer,
+};
 
-const VERSION_MESSAGE: &str =
-  concat!(env!("CARGO_PKG_VERSION"), "-", env!("VERGEN_GIT_DESCRIBE"), " (", env!("VERGEN_BUILD_DATE"), ")");
+const VERSION_MESSAGE: &str = concat!(
+  env!("CARGO_PKG_VERSION"),
+  "-",
+  env!("VERGEN_GIT_DESCRIBE"),
+  " (",
+  env!("VERGEN_BUILD_DATE"),
+  ")"
+);
 
 lazy_static! {
   pub static ref PROJECT_NAME: String = env!("CARGO_CRATE_NAME").to_uppercase().to_string();
-  pub static ref DATA_FOLDER: Option<PathBuf> =
-    std::env::var(format!("{}_DATA", PROJECT_NAME.clone())).ok().map(PathBuf::from);
-  pub static ref CONFIG_FOLDER: Option<PathBuf> =
-    std::env::var(format!("{}_CONFIG", PROJECT_NAME.clone())).ok().map(PathBuf::from);
+  pub static ref DATA_ENV: String = format!("{}_DATA_DIR", PROJECT_NAME.clone());
+  pub static ref CONFIG_ENV: String = format!("{}_CONFIG_DIR", PROJECT_NAME.clone());
+  // `NUUSLEES_DATA` and `NUUSLEES_CONFIG` are still read, as they were the names before.
+  static ref DATA_ENVS: [String; 2] = [DATA_ENV.clone(), format!("{}_DATA", PROJECT_NAME.clone())];
+  static ref CONFIG_ENVS: [String; 2] =
+    [CONFIG_ENV.clone(), format!("{}_CONFIG", PROJECT_NAME.clone())];
   pub static ref LOG_ENV: String = format!("{}_LOGLEVEL", PROJECT_NAME.clone());
   pub static ref LOG_FILE: String = format!("{}.log", env!("CARGO_PKG_NAME"));
 }
 
+/// Picks the data or config directory, with `var` reading the environment: the directory named
+/// by the first of `overrides` that is set, else this app's directory under the XDG base
+/// directory `xdg`, honoured on every platform, else `default`. Empty variables count as unset,
+/// and the spec has relative XDG paths ignored.
+fn pick_dir(
+  var: impl Fn(&str) -> Option<OsString>,
+  overrides: &[String],
+  xdg: &str,
+  default: PathBuf,
+) -> PathBuf {
+  let env_dir = |name: &str| var(name).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+  overrides
+    .iter()
+    .find_map(|name| env_dir(name))
+    .or_else(|| {
+      env_dir(xdg).filter(|dir| dir.is_absolute()).map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+    })
+    .unwrap_or(default)
+}
+
+/// The FNV-1a hash of `text`. Unlike the standard library's hashers it stays the same across runs
+/// and builds, so what is derived from it, such as a file name or a color, does too.
+pub fn stable_hash(text: &str) -> u64 {
+  text
+    .bytes()
+    .fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// The time `days` days ago, or `None` for a time before any that can be represented, which no
+/// date comes before.
+pub fn days_ago(days: u32) -> Option<DateTime<Utc>> {
+  chrono::Duration::try_days(days.into()).and_then(|age| Utc::now().checked_sub_signed(age))
+}
+
 fn project_directory() -> Option<ProjectDirs> {
   ProjectDirs::from("com", "lukeleppan", env!("CARGO_PKG_NAME"))
 }
 
 pub fn initialize_panic_handler() -> Result<()> {
   let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default()
-    .panic_section(format!("This is a bug. Consider reporting it at {}", env!("CARGO_PKG_REPOSITORY")))
+    .panic_section(format!(
+      "This is a bug. Consider reporting it at {}",
+      env!("CARGO_PKG_REPOSITORY")
+    ))
     .capture_span_trace_by_default(false)
     .display_location_section(false)
     .display_env_section(false)
     .into_hooks();
   eyre_hook.install()?;
   std::panic::set_hook(Box::new(move |panic_info| {
-    if let Ok(mut t) = crate::tui::Tui::new() {
-      if let Err(r) = t.exit() {
-        error!("Unable to exit Terminal: {:?}", r);
-      }
+    if let Err(r) = crate::tui::restore() {
+      error!("Unable to exit Terminal: {:?}", r);
     }
 
     #[cfg(not(debug_assertions))]
@@ -73,30 +122,40 @@ pub fn initialize_panic_handler() -> Result<()> {
 }
 
 pub fn get_data_dir() -> PathBuf {
-  let directory = if let Some(s) = DATA_FOLDER.clone() {
-    s
-  } else if let Some(proj_dirs) = project_directory() {
-    proj_dirs.data_local_dir().to_path_buf()
-  } else {
-    PathBuf::from(".").join(".data")
+  let default = match project_directory() {
+    Some(proj_dirs) => proj_dirs.data_local_dir().to_path_buf(),
+    None => PathBuf::from(".").join(".data"),
   };
-  directory
+  pick_dir(|name| std::env::var_os(name), &*DATA_ENVS, "XDG_DATA_HOME", default)
 }
 
 pub fn get_config_dir() -> PathBuf {
-  let directory = if let Some(s) = CONFIG_FOLDER.clone() {
-    s
-  } else if let Some(proj_dirs) = project_directory() {
-    proj_dirs.config_local_dir().to_path_buf()
-  } else {
-    PathBuf::from(".").join(".config")
+  let default = match project_directory() {
+    Some(proj_dirs) => proj_dirs.config_local_dir().to_path_buf(),
+    None => PathBuf::from(".").join(".config"),
   };
-  directory
+  pick_dir(|name| std::env::var_os(name), &*CONFIG_ENVS, "XDG_CONFIG_HOME", default)
+}
+
+/// Creates the data and config directories if they are missing, saying which one failed and how to
+/// point the app elsewhere.
+pub fn create_dirs() -> Result<()> {
+  create_dir(&get_data_dir(), "data", &DATA_ENV)?;
+  create_dir(&get_config_dir(), "config", &CONFIG_ENV)
+}
+
+fn create_dir(directory: &Path, kind: &str, env: &str) -> Result<()> {
+  std::fs::create_dir_all(directory).wrap_err_with(|| {
+    format!(
+      "Could not create the {kind} directory {}, set {env} to use another",
+      directory.display()
+    )
+  })
 }
 
 pub fn initialize_logging() -> Result<()> {
+  create_dirs()?;
   let directory = get_data_dir();
-  std::fs::create_dir_all(directory.clone())?;
   let log_path = directory.join(LOG_FILE.clone());
   let log_file = std::fs::File::create(log_path)?;
   std::env::set_var(
@@ -159,3 +218,51 @@ Config directory: {config_dir_path}
 Data directory: {data_dir_path}"
   )
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn stable_hash_is_fnv_1a() {
+    assert_eq!(stable_hash(""), 0xcbf29ce484222325);
+    assert_eq!(stable_hash("a"), 0xaf63dc4c8601ec8c);
+    assert_eq!(stable_hash("foobar"), 0x85944171f73967e8);
+  }
+
+  fn pick_data_dir(vars: &[(&str, &str)]) -> PathBuf {
+    let var = |name: &str| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.into());
+    pick_dir(var, &*DATA_ENVS, "XDG_DATA_HOME", PathBuf::from("/default"))
+  }
+
+  #[test]
+  fn the_env_override_comes_before_xdg_and_the_default() {
+    let xdg = ("XDG_DATA_HOME", "/xdg");
+    assert_eq!(pick_data_dir(&[]), PathBuf::from("/default"));
+    assert_eq!(pick_data_dir(&[xdg]), Path::new("/xdg").join(env!("CARGO_PKG_NAME")));
+    assert_eq!(pick_data_dir(&[xdg, (&DATA_ENV, "/override")]), PathBuf::from("/override"));
+    assert_eq!(pick_data_dir(&[xdg, (&DATA_ENVS[1], "/legacy")]), PathBuf::from("/legacy"));
+    // Empty variables and relative XDG paths are passed over.
+    assert_eq!(
+      pick_data_dir(&[xdg, (&DATA_ENV, "")]),
+      Path::new("/xdg").join(env!("CARGO_PKG_NAME"))
+    );
+    assert_eq!(pick_data_dir(&[("XDG_DATA_HOME", "xdg")]), PathBuf::from("/default"));
+  }
+
+  #[test]
+  fn a_directory_that_cannot_be_created_names_the_variable_to_set() {
+    let dir = crate::testing::TempDir::new();
+    let file = dir.path().join("file");
+    std::fs::write(&file, "").unwrap();
+    let error = create_dir(&file.join("data"), "data", &DATA_ENV).unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      format!(
+        "Could not create the data directory {}, set {} to use another",
+        file.join("data").display(),
+        *DATA_ENV
+      )
+    );
+  }
+}
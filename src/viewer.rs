@@ -0,0 +1,30 @@
+use std::{io, path::Path, process::ExitStatus};
+
+use crate::task::ShellCommand;
+
+/// Fills in the placeholders of a viewer command: `%url` with the article's link and `%file` with
+/// the file it was exported to, handed to the shell as arguments.
+pub fn command_line(template: &str, url: &str, file: Option<&Path>) -> ShellCommand {
+  let file = file.map(|file| file.to_string_lossy().into_owned()).unwrap_or_default();
+  ShellCommand::new(template, &[("url", url), ("file", &file)])
+}
+
+/// Runs `command` on the terminal and waits for it to exit.
+pub async fn run(command: &ShellCommand) -> io::Result<ExitStatus> {
+  command.command().status().await
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[tokio::test]
+  async fn a_link_holding_a_placeholder_stays_one_argument() {
+    let url = "https://example.com/?q=%file';echo pwned;'";
+    let command = command_line("printf '%s|' %url %file", url, Some(Path::new("/tmp/a b.md")));
+    let output = command.command().output().await.unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), format!("{url}|/tmp/a b.md|"));
+  }
+}